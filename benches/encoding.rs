@@ -0,0 +1,23 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+use kv_3d_storage::Point3d;
+
+fn fixed_width_encoding(c: &mut Criterion) {
+    let point = Point3d {
+        x: 0x1234_5678u32,
+        y: 0x9abc_def0u32,
+        z: 42u32,
+    };
+    let mut buf = [0; Point3d::<u32, u32, u32>::max_encoding_len_xyz()];
+
+    c.bench_function("encode_xyz (u32, u32, u32)", |b| {
+        b.iter(|| black_box(&point).encode_xyz(black_box(&mut buf)))
+    });
+
+    c.bench_function("encode_xyz_fixed (u32, u32, u32)", |b| {
+        b.iter(|| black_box(&point).encode_xyz_fixed(black_box(&mut buf)))
+    });
+}
+
+criterion_group!(benches, fixed_width_encoding);
+criterion_main!(benches);
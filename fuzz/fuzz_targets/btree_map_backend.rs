@@ -0,0 +1,160 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+use std::collections::BTreeMap;
+use std::convert::Infallible;
+use std::ops::{Bound, RangeBounds};
+
+use futures::StreamExt;
+use pollster::block_on;
+
+use kv_3d_storage::*;
+
+// A backend that implements only the required methods, so that the default implementations get exercised.
+struct RequiredMethodsOnly(BTreeMapBackEnd<u8>);
+
+impl BackEnd<u8> for RequiredMethodsOnly {
+    type Error = Infallible;
+    type Snapshot = BTreeMapSnapshot<u8>;
+
+    async fn get(&self, key: &[u8]) -> Result<Option<u8>, Self::Error> {
+        return self.0.get(key).await;
+    }
+
+    async fn find_lte(&self, key: &[u8]) -> Result<Option<(Vec<u8>, u8)>, Self::Error> {
+        return self.0.find_lte(key).await;
+    }
+
+    async fn find_gte(&self, key: &[u8]) -> Result<Option<(Vec<u8>, u8)>, Self::Error> {
+        return self.0.find_gte(key).await;
+    }
+
+    async fn insert(&mut self, key: &[u8], value: u8) -> Result<Option<u8>, Self::Error> {
+        return self.0.insert(key, value).await;
+    }
+
+    async fn delete(&mut self, key: &[u8]) -> Result<Option<u8>, Self::Error> {
+        return self.0.delete(key).await;
+    }
+
+    async fn snapshot(&self) -> Result<Self::Snapshot, Self::Error> {
+        return self.0.snapshot().await;
+    }
+
+    async fn flush(&mut self) -> Result<(), Self::Error> {
+        return self.0.flush().await;
+    }
+}
+
+fuzz_target!(|data: (
+    BTreeMap<Vec<u8>, u8>,
+    Vec<Vec<u8>>,
+    Vec<(Bound<Vec<u8>>, Bound<Vec<u8>>)>,
+    Vec<Vec<u8>>
+)| {
+    let (map, queries, ranges, mut prefixes) = data;
+    let backend = BTreeMapBackEnd::from(map.clone());
+    let required_only = RequiredMethodsOnly(backend.clone());
+
+    for query in queries {
+        assert_eq!(
+            block_on(backend.get(&query)).unwrap(),
+            map.get(&query).copied()
+        );
+
+        let expected_lte = map
+            .iter()
+            .filter(|(k, _)| **k <= query)
+            .last()
+            .map(|(k, v)| (k.clone(), *v));
+        assert_eq!(block_on(backend.find_lte(&query)).unwrap(), expected_lte);
+
+        let expected_gte = map
+            .iter()
+            .find(|(k, _)| **k >= query)
+            .map(|(k, v)| (k.clone(), *v));
+        assert_eq!(block_on(backend.find_gte(&query)).unwrap(), expected_gte);
+    }
+
+    for (lo, hi) in ranges {
+        let expected: Vec<_> = map
+            .iter()
+            .filter(|(k, _)| (lo.clone(), hi.clone()).contains(*k))
+            .map(|(k, v)| Ok((k.clone(), *v)))
+            .collect();
+
+        let lo = lo.as_ref().map(|k| k.as_slice());
+        let hi = hi.as_ref().map(|k| k.as_slice());
+        assert_eq!(
+            block_on(backend.stream_range(lo, hi).collect::<Vec<_>>()),
+            expected
+        );
+        assert_eq!(
+            block_on(required_only.stream_range(lo, hi).collect::<Vec<_>>()),
+            expected
+        );
+
+        let expected: Vec<_> = expected.into_iter().map(|entry| entry.unwrap()).collect();
+        let expected_rev: Vec<_> = expected.iter().rev().cloned().collect();
+        assert_eq!(
+            block_on(backend.range(lo, hi)).unwrap().collect::<Vec<_>>(),
+            expected
+        );
+        assert_eq!(
+            block_on(backend.range_rev(lo, hi))
+                .unwrap()
+                .collect::<Vec<_>>(),
+            expected_rev
+        );
+        assert_eq!(
+            block_on(required_only.range(lo, hi))
+                .unwrap()
+                .collect::<Vec<_>>(),
+            expected
+        );
+        assert_eq!(
+            block_on(required_only.range_rev(lo, hi))
+                .unwrap()
+                .collect::<Vec<_>>(),
+            expected_rev
+        );
+    }
+
+    // Scan by the rank-like one-byte prefixes of all keys, plus the edge cases without a successor.
+    prefixes.extend(
+        map.keys()
+            .filter_map(|k| k.first())
+            .map(|first| vec![*first]),
+    );
+    prefixes.push(vec![]);
+    prefixes.push(vec![0xff]);
+    prefixes.push(vec![0xff, 0xff]);
+
+    for prefix in prefixes {
+        let expected: Vec<_> = map
+            .iter()
+            .filter(|(k, _)| k.starts_with(&prefix))
+            .map(|(k, v)| (k.clone(), *v))
+            .collect();
+
+        assert_eq!(
+            block_on(backend.scan_prefix(&prefix))
+                .unwrap()
+                .collect::<Vec<_>>(),
+            expected
+        );
+        assert_eq!(
+            block_on(required_only.scan_prefix(&prefix))
+                .unwrap()
+                .collect::<Vec<_>>(),
+            expected
+        );
+
+        if let Some(successor) = prefix_successor(&prefix) {
+            assert!(successor > prefix);
+            assert!(!successor.starts_with(&prefix));
+        } else {
+            assert!(prefix.iter().all(|byte| *byte == 0xff));
+        }
+    }
+});
@@ -1,6 +1,8 @@
 #![no_main]
 use libfuzzer_sys::fuzz_target;
 
+use core::fmt::Debug;
+use core::hash::Hash;
 use std::collections::HashMap;
 
 use kv_3d_storage::*;
@@ -17,14 +19,96 @@ fuzz_target!(|data: HashMap<
     );
 
     tree.assert_tree_invariants();
+    tree.assert_summaries_consistent();
 
-    match tree {
-        ControlNode::Empty => {
-            assert_eq!(data.len(), 0);
-        }
-        ControlNode::NonEmpty { count, summary, .. } => {
-            assert_eq!(count, data.len());
-            assert_eq!(summary, data.len());
-        }
+    assert_eq!(tree.len(), data.len());
+    assert_eq!(tree.is_empty(), data.is_empty());
+
+    let values: HashMap<_, _> = data
+        .iter()
+        .map(|(point, (value, _rank))| (*point, *value))
+        .collect();
+    tree.assert_matches_map(&values);
+
+    assert_kv_tree_keys_work(&tree);
+
+    let pairs: Vec<_> = data
+        .iter()
+        .map(|(point, (value, _rank))| (*point, *value))
+        .collect();
+    assert_eq!(tree.summarize(), usize::lift_all(&pairs));
+    assert_eq!(tree.summary(), tree.summarize());
+
+    if let ControlNode::NonEmpty { summary, .. } = tree {
+        assert_eq!(summary, data.len());
     }
+
+    // The same points, but with variable-width dimensions.
+    let tree_vw: ControlNode<_, _, _, _, usize> =
+        ControlNode::from_iter(data.iter().map(|(point, (value, rank))| {
+            let point = Point3d {
+                x: point.x,
+                y: U8VariableWidth(point.y.0),
+                z: U8VariableWidth(point.z.0),
+            };
+            (point, *value, *rank)
+        }));
+
+    assert_kv_tree_keys_work(&tree_vw);
+
+    // Summaries that depend on the points, not only on their number.
+    let tree_bounds: ControlNode<_, _, _, _, BoundsMonoid<_, _, _>> = ControlNode::from_iter(
+        data.iter()
+            .map(|(point, (value, rank))| (*point, *value, *rank)),
+    );
+    tree_bounds.assert_summaries_consistent();
 });
+
+// For every vertex, check that its kv-tree key (the rank byte followed by the encoding of the point in the ordering of that rank) decodes back to the rank and point of the vertex.
+fn assert_kv_tree_keys_work<X, Y, Z, V, M>(tree: &ControlNode<X, Y, Z, V, M>)
+where
+    X: Dimension + Clone + Debug + Hash,
+    Y: Dimension + Clone + Debug + Hash,
+    Z: Dimension + Clone + Debug + Hash,
+    V: Debug + Clone,
+    M: LiftingCommutativeMonoid<(Point3d<X, Y, Z>, V)> + Debug,
+{
+    if let ControlNode::NonEmpty {
+        key,
+        rank,
+        left,
+        right,
+        ..
+    } = tree
+    {
+        let ordering = ordering_for_rank(*rank);
+
+        let max_len = Point3d::<X, Y, Z>::max_encoding_len_xyz()
+            .max(Point3d::<X, Y, Z>::max_encoding_len_yzx())
+            .max(Point3d::<X, Y, Z>::max_encoding_len_zxy());
+        let mut kv_key = vec![0; 1 + max_len];
+        kv_key[0] = *rank;
+        let len = match ordering {
+            Ordering3d::Xyz => key.encode_xyz(&mut kv_key[1..]),
+            Ordering3d::Yzx => key.encode_yzx(&mut kv_key[1..]),
+            Ordering3d::Zxy => key.encode_zxy(&mut kv_key[1..]),
+        };
+        kv_key.truncate(1 + len);
+
+        let decoded_ordering = ordering_for_rank(kv_key[0]);
+        assert_eq!(decoded_ordering, ordering);
+
+        let (decoded, decoded_len) = match decoded_ordering {
+            Ordering3d::Xyz => Point3d::<X, Y, Z>::decode_xyz(&kv_key[1..]),
+            Ordering3d::Yzx => Point3d::<X, Y, Z>::decode_yzx(&kv_key[1..]),
+            Ordering3d::Zxy => Point3d::<X, Y, Z>::decode_zxy(&kv_key[1..]),
+        }
+        .unwrap();
+
+        assert_eq!(&decoded, key);
+        assert_eq!(decoded_len, len);
+
+        assert_kv_tree_keys_work(left);
+        assert_kv_tree_keys_work(right);
+    }
+}
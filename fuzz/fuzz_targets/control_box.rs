@@ -0,0 +1,57 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+use std::collections::HashMap;
+
+use kv_3d_storage::*;
+use kv_3d_storage_fuzz::*;
+
+type P = Point3d<U8FixedWidth, U8VariableWidth, U8FixedWidth>;
+
+fuzz_target!(
+    |data: (HashMap<P, (u8 /* value */, u8 /* rank */)>, P, P)| {
+        let (points, lo, hi) = data;
+
+        let tree: ControlNode<_, _, _, _, usize> = ControlNode::from_iter(
+            points
+                .iter()
+                .map(|(point, (value, rank))| (*point, *value, *rank)),
+        );
+
+        let min = Point3d::from((U8FixedWidth(0), U8VariableWidth(0), U8FixedWidth(0)));
+        let max = Point3d::from((
+            U8FixedWidth(u8::MAX),
+            U8VariableWidth(u8::MAX),
+            U8FixedWidth(u8::MAX),
+        ));
+
+        // The fuzzed box, the same box with swapped corners (empty unless the corners differ in no dimension), a box that covers everything, and a degenerate box.
+        let mut boxes = vec![(lo, hi), (hi, lo), (min, max), (lo, lo)];
+        // Boxes whose corners are stored points, so that points lie exactly on their boundaries.
+        for point in points.keys().take(2) {
+            boxes.push((*point, *point));
+            boxes.push((lo, *point));
+            boxes.push((*point, hi));
+        }
+
+        for (lo, hi) in boxes {
+            let mut expected: Vec<_> = points
+                .iter()
+                .filter(|(p, _)| {
+                    lo.x <= p.x
+                        && p.x <= hi.x
+                        && lo.y <= p.y
+                        && p.y <= hi.y
+                        && lo.z <= p.z
+                        && p.z <= hi.z
+                })
+                .map(|(p, (value, _))| (p, value))
+                .collect();
+            expected.sort_by(|(p1, _), (p2, _)| p1.cmp_xyz(p2));
+
+            assert_eq!(tree.query_box(&lo, &hi), expected);
+        }
+
+        assert_eq!(tree.query_box(&min, &max).len(), points.len());
+    }
+);
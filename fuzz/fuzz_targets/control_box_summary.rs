@@ -0,0 +1,57 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+use std::collections::HashMap;
+
+use kv_3d_storage::*;
+use kv_3d_storage_fuzz::*;
+
+type P = Point3d<U8FixedWidth, U8VariableWidth, U8FixedWidth>;
+
+fuzz_target!(
+    |data: (HashMap<P, (u8 /* value */, u8 /* rank */)>, P, P)| {
+        let (points, lo, hi) = data;
+
+        let fingerprints: ControlNode<_, _, _, _, XorFingerprint> = ControlNode::from_iter(
+            points
+                .iter()
+                .map(|(point, (value, rank))| (*point, *value, *rank)),
+        );
+        let counts: ControlNode<_, _, _, _, usize> = ControlNode::from_iter(
+            points
+                .iter()
+                .map(|(point, (value, rank))| (*point, *value, *rank)),
+        );
+
+        let min = Point3d::from((U8FixedWidth(0), U8VariableWidth(0), U8FixedWidth(0)));
+        let max = Point3d::from((
+            U8FixedWidth(u8::MAX),
+            U8VariableWidth(u8::MAX),
+            U8FixedWidth(u8::MAX),
+        ));
+
+        let mut boxes = vec![(lo, hi), (hi, lo), (min, max), (lo, max), (min, hi)];
+        for point in points.keys().take(2) {
+            boxes.push((*point, *point));
+        }
+
+        for (lo, hi) in boxes {
+            let entries: Vec<_> = fingerprints
+                .query_box(&lo, &hi)
+                .into_iter()
+                .map(|(point, value)| (*point, *value))
+                .collect();
+
+            assert_eq!(
+                fingerprints.summarize_box(&lo, &hi),
+                XorFingerprint::lift_all(&entries)
+            );
+            assert_eq!(counts.summarize_box(&lo, &hi), entries.len());
+        }
+
+        assert_eq!(
+            fingerprints.summarize_box(&min, &max),
+            fingerprints.summary()
+        );
+    }
+);
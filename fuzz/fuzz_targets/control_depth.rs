@@ -0,0 +1,47 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+use kv_3d_storage::*;
+use kv_3d_storage_fuzz::*;
+
+fuzz_target!(|data: (u64 /* seed */, u16 /* number of points */)| {
+    let (seed, n) = data;
+    let n = (n % 4096) as usize;
+
+    // Pseudo-random points (splitmix64), with ranks derived from hashes.
+    let mut state = seed;
+    let mut next = move || {
+        state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        return z ^ (z >> 31);
+    };
+    let points = (0..n).map(|_| {
+        let bits = next();
+        (Point3d::from((bits as u32, (bits >> 32) as u16, (bits >> 48) as u16)), ())
+    });
+
+    let tree: ControlNode<u32, u16, u16, (), ()> = ControlNode::from_points(points);
+
+    let height = tree.height();
+    let min_leaf_depth = tree.min_leaf_depth();
+    let average_depth = tree.average_depth();
+
+    if tree.is_empty() {
+        assert_eq!((height, min_leaf_depth, average_depth), (0, 0, 0.0));
+    } else {
+        assert!(1 <= min_leaf_depth && min_leaf_depth <= height);
+        assert!(1.0 <= average_depth && average_depth <= height as f64);
+        assert!(height <= tree.len());
+    }
+
+    // The tree is balanced with high probability: its height is logarithmic in the number of points.
+    let log2 = (usize::BITS - tree.len().leading_zeros()) as usize;
+    assert!(
+        height <= 4 * log2 + 8,
+        "height {} for {} points",
+        height,
+        tree.len()
+    );
+});
@@ -0,0 +1,46 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+use core::cmp::Ordering;
+use std::collections::HashMap;
+
+use kv_3d_storage::*;
+use kv_3d_storage_fuzz::*;
+
+type P = Point3d<U8FixedWidth, U8VariableWidth, U8FixedWidth>;
+
+fuzz_target!(|data: (
+    HashMap<P, u8 /* rank */>,
+    HashMap<P, u8 /* rank */>,
+    Vec<(P, u8 /* rank */, u8 /* rank */)> /* points in both trees */
+)| {
+    let (mut a, mut b, shared) = data;
+    for (point, rank_a, rank_b) in shared {
+        a.insert(point, rank_a);
+        b.insert(point, rank_b);
+    }
+
+    let tree_a: ControlNode<_, _, _, _, XorFingerprint> =
+        ControlNode::from_iter(a.iter().map(|(point, rank)| (*point, (), *rank)));
+    let tree_b: ControlNode<_, _, _, _, XorFingerprint> =
+        ControlNode::from_iter(b.iter().map(|(point, rank)| (*point, (), *rank)));
+
+    for ordering in [Ordering3d::Xyz, Ordering3d::Yzx, Ordering3d::Zxy] {
+        let sorted = |mut points: Vec<P>| {
+            points.sort_by(|p1, p2| p1.cmp_by(p2, ordering));
+            points
+        };
+        let only_a = sorted(a.keys().filter(|p| !b.contains_key(p)).copied().collect());
+        let only_b = sorted(b.keys().filter(|p| !a.contains_key(p)).copied().collect());
+
+        assert_eq!(tree_a.diff(&tree_b, ordering), (only_a.clone(), only_b.clone()));
+        assert_eq!(tree_b.diff(&tree_a, ordering), (only_b, only_a));
+        assert_eq!(tree_a.diff(&tree_a, ordering), (vec![], vec![]));
+
+        // Diffing against the empty tree yields all points, in ascending order.
+        let (all, none) = tree_a.diff(&ControlNode::Empty, ordering);
+        assert!(none.is_empty());
+        assert_eq!(all.len(), a.len());
+        assert!(all.windows(2).all(|pair| pair[0].cmp_by(&pair[1], ordering) == Ordering::Less));
+    }
+});
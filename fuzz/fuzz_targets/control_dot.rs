@@ -0,0 +1,36 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+use std::collections::HashMap;
+
+use kv_3d_storage::*;
+use kv_3d_storage_fuzz::*;
+
+fuzz_target!(|data: HashMap<
+    Point3d<U8FixedWidth, U8VariableWidth, U8FixedWidth>,
+    (u8 /* value */, u8 /* rank */),
+>| {
+    let tree: ControlNode<_, _, _, _, BoundsMonoid<_, _, _>> = ControlNode::from_iter(
+        data.iter()
+            .map(|(point, (value, rank))| (*point, *value, *rank)),
+    );
+
+    let dot = tree.to_dot();
+    assert!(dot.starts_with("digraph {\n"));
+    assert!(dot.ends_with("}\n"));
+
+    // One declaration per vertex, and one edge per vertex but the root.
+    let lines: Vec<_> = dot.lines().collect();
+    let edges: Vec<_> = lines.iter().filter(|line| line.contains(" -> ")).collect();
+    let vertices = lines.len() - 2 - edges.len();
+    assert_eq!(vertices, tree.len());
+    assert_eq!(edges.len(), tree.len().saturating_sub(1));
+
+    // Every vertex shows its rank, and every edge its direction.
+    for (point, (_, rank)) in data.iter() {
+        assert!(dot.contains(&format!("label=\"{}\\nrank {}\\n", point, rank)));
+    }
+    for edge in edges {
+        assert!(edge.ends_with("[label=\"left\"];") || edge.ends_with("[label=\"right\"];"));
+    }
+});
@@ -0,0 +1,25 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+use std::collections::HashMap;
+
+use kv_3d_storage::*;
+use kv_3d_storage_fuzz::*;
+
+fuzz_target!(|data: HashMap<Point3d<U8VariableWidth, U8FixedWidth, U8VariableWidth>, u8>| {
+    let tree: ControlNode<_, _, _, _, usize> = ControlNode::from_points(data.clone().drain());
+
+    tree.assert_tree_invariants();
+    assert_eq!(tree.len(), data.len());
+
+    for (point, value) in data.iter() {
+        let rank = rank_from_hash(point, value);
+        assert!(rank <= 64);
+        assert_eq!(rank, rank_from_hash(point, value));
+    }
+
+    // The shape depends only on the point-value pairs, not on the order in which they are supplied.
+    let mut reversed: Vec<_> = data.into_iter().collect();
+    reversed.reverse();
+    assert_eq!(tree, ControlNode::from_points(reversed.into_iter()));
+});
@@ -0,0 +1,31 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+use std::collections::HashMap;
+
+use kv_3d_storage::*;
+use kv_3d_storage_fuzz::*;
+
+type P = Point3d<U8FixedWidth, U8VariableWidth, U8FixedWidth>;
+
+fuzz_target!(
+    |data: (HashMap<P, u8>, Vec<(P, u8)> /* pairs to insert */)| {
+        let (mut points, to_insert) = data;
+
+        let mut tree: ControlNode<_, _, _, _, XorFingerprint> =
+            ControlNode::from_points(points.clone().drain());
+
+        for (point, value) in to_insert {
+            assert_eq!(tree.insert(point, value), points.insert(point, value));
+
+            tree.assert_tree_invariants();
+            tree.assert_summaries_consistent();
+            assert_eq!(tree.len(), points.len());
+            assert_eq!(tree.get(&point), Some(&value));
+        }
+        tree.assert_matches_map(&points);
+
+        // The tree must be exactly the tree we would have built from all points at once.
+        assert_eq!(tree, ControlNode::from_points(points.into_iter()));
+    }
+);
@@ -0,0 +1,36 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+use std::collections::HashMap;
+
+use kv_3d_storage::*;
+use kv_3d_storage_fuzz::*;
+
+fuzz_target!(|data: Vec<(
+    Point3d<U8FixedWidth, U8FixedWidth, U8FixedWidth>,
+    u8, /* value */
+    u8  /* rank */
+)>| {
+    let mut tree: ControlNode<_, _, _, _, usize> =
+        ControlNode::from_iter_last_wins(data.iter().copied());
+    tree.assert_tree_invariants();
+
+    let mut latest = HashMap::new();
+    for (point, value, rank) in data.iter() {
+        latest.insert(*point, (*value, *rank));
+    }
+    assert_eq!(tree.len(), latest.len());
+
+    // Ranks of the last occurrences determine the shape.
+    let expected_tree = ControlNode::from_iter(
+        latest
+            .iter()
+            .map(|(point, (value, rank))| (*point, *value, *rank)),
+    );
+    assert_eq!(tree, expected_tree);
+
+    // Values of the last occurrences are stored.
+    for (point, (value, _)) in latest {
+        assert_eq!(tree.remove(&point), Some(value));
+    }
+});
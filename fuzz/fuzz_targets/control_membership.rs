@@ -0,0 +1,53 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+use kv_3d_storage::*;
+use kv_3d_storage_fuzz::*;
+
+type P = Point3d<U8FixedWidth, U8VariableWidth, U8FixedWidth>;
+
+fuzz_target!(|data: (Vec<(P, u8, u8)>, Vec<P>)| {
+    let (points, queries) = data;
+    let tree: ControlNode<_, _, _, _, usize> =
+        ControlNode::from_iter_last_wins(points.iter().copied());
+
+    for point in points
+        .iter()
+        .map(|(point, _, _)| point)
+        .chain(queries.iter())
+    {
+        match tree.membership_path(point) {
+            None => assert_eq!(tree.rank_of(point), None),
+            Some(path) => {
+                // The path starts at the root and ends at the queried point.
+                let (root_rank, root_key, root_summary) = &path[0];
+                match &tree {
+                    ControlNode::NonEmpty {
+                        key, rank, summary, ..
+                    } => assert_eq!((root_rank, root_key, root_summary), (rank, key, summary)),
+                    ControlNode::Empty => unreachable!(),
+                }
+
+                let (last_rank, last_key, _) = path.last().unwrap();
+                assert_eq!(last_key, point);
+                assert_eq!(tree.rank_of(point), Some(*last_rank));
+
+                for pair in path.windows(2) {
+                    let (parent_rank, parent_key, parent_summary) = &pair[0];
+                    let (child_rank, child_key, child_summary) = &pair[1];
+
+                    // Ranks never increase, and strictly decrease towards left children.
+                    assert!(child_rank <= parent_rank);
+                    let went_left = child_key.cmp_by(parent_key, ordering_for_rank(*parent_rank))
+                        == core::cmp::Ordering::Less;
+                    if went_left {
+                        assert!(child_rank < parent_rank);
+                    }
+
+                    // Subtrees summarize fewer points than their ancestors (the summary is a count).
+                    assert!(child_summary < parent_summary);
+                }
+            }
+        }
+    }
+});
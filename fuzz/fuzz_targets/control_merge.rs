@@ -0,0 +1,68 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+use std::cell::Cell;
+use std::collections::HashMap;
+
+use kv_3d_storage::*;
+use kv_3d_storage_fuzz::*;
+
+type P = Point3d<U8FixedWidth, U8FixedWidth, U8FixedWidth>;
+
+fuzz_target!(|data: (
+    HashMap<P, (u8 /* value */, u8 /* rank */)>,
+    HashMap<P, (u8 /* value */, u8 /* rank */)>,
+    Vec<(u8 /* value */, u8 /* rank */)> /* overrides for points of the first map */
+)| {
+    let (a, mut b) = (data.0, data.1);
+
+    // Random points rarely coincide, so we explicitly add some points of `a` to `b`.
+    for ((point, _), (value, rank)) in a.iter().zip(data.2) {
+        b.insert(*point, (value, rank));
+    }
+
+    assert_merge_works(&a, &b);
+    assert_merge_works(&b, &a);
+    assert_merge_works(&a, &HashMap::new());
+    assert_merge_works(&a, &a);
+});
+
+fn assert_merge_works(a: &HashMap<P, (u8, u8)>, b: &HashMap<P, (u8, u8)>) {
+    let tree_a: ControlNode<_, _, _, _, usize> =
+        ControlNode::from_iter(a.iter().map(|(point, (value, rank))| (*point, *value, *rank)));
+    let tree_b: ControlNode<_, _, _, _, usize> =
+        ControlNode::from_iter(b.iter().map(|(point, (value, rank))| (*point, *value, *rank)));
+
+    let resolver_calls = Cell::new(0);
+    let merged = tree_a.merge(tree_b, |value_a, value_b| {
+        resolver_calls.set(resolver_calls.get() + 1);
+        value_a.wrapping_mul(3).wrapping_add(*value_b)
+    });
+
+    merged.assert_tree_invariants();
+    merged.assert_summaries_consistent();
+
+    // Points in both maps keep the rank from `a` and get the resolved value.
+    let mut expected = b.clone();
+    let mut overlaps = 0;
+    for (point, (value_a, rank_a)) in a {
+        let value = match b.get(point) {
+            Some((value_b, _)) => {
+                overlaps += 1;
+                value_a.wrapping_mul(3).wrapping_add(*value_b)
+            }
+            None => *value_a,
+        };
+        expected.insert(*point, (value, *rank_a));
+    }
+
+    assert_eq!(resolver_calls.get(), overlaps);
+    assert_eq!(merged.len(), expected.len());
+
+    let expected_tree = ControlNode::from_iter(
+        expected
+            .into_iter()
+            .map(|(point, (value, rank))| (point, value, rank)),
+    );
+    assert_eq!(merged, expected_tree);
+}
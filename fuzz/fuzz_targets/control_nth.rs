@@ -0,0 +1,41 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+use std::collections::HashMap;
+
+use kv_3d_storage::*;
+use kv_3d_storage_fuzz::*;
+
+type P = Point3d<U8FixedWidth, U8VariableWidth, U8FixedWidth>;
+
+fuzz_target!(|data: Vec<(P, u8, u8)>| {
+    let tree: ControlNode<_, _, _, _, ()> = ControlNode::from_iter_last_wins(data.iter().copied());
+
+    // The last value of each point wins, just like for the tree.
+    let latest: HashMap<P, u8> = data
+        .iter()
+        .map(|(point, value, _)| (*point, *value))
+        .collect();
+    let mut sorted: Vec<(P, u8)> = latest.into_iter().collect();
+    sorted.sort_by(|(p1, _), (p2, _)| p1.cmp_xyz(p2));
+
+    for (k, (point, value)) in sorted.iter().enumerate() {
+        assert_eq!(tree.nth_xyz(k), Some((point, value)));
+
+        // Ranking inverts selecting.
+        assert_eq!(tree.rank_xyz(tree.nth_xyz(k).unwrap().0), k);
+    }
+    assert_eq!(tree.nth_xyz(sorted.len()), None);
+    assert_eq!(tree.nth_xyz(usize::MAX), None);
+
+    // Points that are not in the tree are ranked by the number of smaller points, too.
+    for (point, _, _) in data.iter() {
+        let mut successor = *point;
+        successor.z = U8FixedWidth(successor.z.0.wrapping_add(1));
+        let expected = sorted
+            .iter()
+            .filter(|(p, _)| p.cmp_xyz(&successor) == core::cmp::Ordering::Less)
+            .count();
+        assert_eq!(tree.rank_xyz(&successor), expected);
+    }
+});
@@ -0,0 +1,37 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+use core::cmp::Ordering;
+use std::collections::HashMap;
+
+use kv_3d_storage::*;
+use kv_3d_storage_fuzz::*;
+
+type P = Point3d<U8FixedWidth, U8FixedWidth, U8FixedWidth>;
+
+fuzz_target!(|data: (
+    HashMap<P, (u8 /* value */, u8 /* rank */)>,
+    Ordering3d,
+    P,
+    P
+)| {
+    let (points, ordering, lo, hi) = data;
+
+    let tree: ControlNode<_, _, _, _, usize> = ControlNode::from_iter(
+        points
+            .clone()
+            .drain()
+            .map(|(point, (value, rank))| (point, value, rank)),
+    );
+
+    let expected = points
+        .keys()
+        .filter(|point| {
+            point.cmp_by(&lo, ordering) != Ordering::Less
+                && point.cmp_by(&hi, ordering) == Ordering::Less
+        })
+        .count();
+
+    assert_eq!(tree.count_range(ordering, &lo, &hi), expected);
+    assert_eq!(tree.summarize_range(ordering, &lo, &hi), expected);
+});
@@ -0,0 +1,33 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+use std::collections::{BTreeMap, HashMap};
+
+use kv_3d_storage::*;
+use kv_3d_storage_fuzz::*;
+
+type P = Point3d<U8FixedWidth, U8VariableWidth, U8FixedWidth>;
+
+fuzz_target!(|data: (HashMap<P, (u8 /* value */, u8 /* rank */)>, Vec<P>)| {
+    let (points, queries) = data;
+
+    let tree: ControlNode<_, _, _, _, usize> = ControlNode::from_iter(
+        points
+            .iter()
+            .map(|(point, (value, rank))| (*point, *value, *rank)),
+    );
+
+    // Every point reports the rank it was inserted with, other points have no rank.
+    for point in queries.iter().chain(points.keys()) {
+        assert_eq!(tree.rank_of(point), points.get(point).map(|(_, rank)| *rank));
+    }
+
+    // The histogram counts exactly the known ranks.
+    let mut expected = BTreeMap::new();
+    for (_, rank) in points.values() {
+        *expected.entry(*rank).or_insert(0) += 1;
+    }
+    let histogram = tree.rank_histogram();
+    assert_eq!(histogram, expected);
+    assert_eq!(histogram.values().sum::<usize>(), tree.len());
+});
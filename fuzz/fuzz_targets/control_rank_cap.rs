@@ -0,0 +1,27 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+use kv_3d_storage::*;
+use kv_3d_storage_fuzz::*;
+
+type P = Point3d<U8FixedWidth, U8VariableWidth, U8FixedWidth>;
+
+fuzz_target!(|data: (Vec<(P, u8)>, u8)| {
+    let (points, max_rank) = data;
+
+    let tree: ControlNode<_, _, _, _, usize> =
+        ControlNode::from_points_capped(points.iter().copied(), max_rank);
+    tree.assert_tree_invariants();
+
+    // No vertex exceeds the cap.
+    if let Some((greatest, _)) = tree.rank_histogram().last_key_value() {
+        assert!(*greatest <= max_rank);
+    }
+
+    // Each point has its hash rank, unless that exceeds the cap.
+    let uncapped: ControlNode<_, _, _, _, usize> = ControlNode::from_points(points.iter().copied());
+    for (point, _) in points.iter() {
+        let rank = tree.rank_of(point).unwrap();
+        assert_eq!(rank, uncapped.rank_of(point).unwrap().min(max_rank));
+    }
+});
@@ -0,0 +1,82 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+use std::collections::{HashMap, VecDeque};
+
+use kv_3d_storage::*;
+use kv_3d_storage_fuzz::*;
+
+type P = Point3d<U8FixedWidth, U8VariableWidth, U8FixedWidth>;
+
+fuzz_target!(|data: (
+    HashMap<P, u8 /* value */>,
+    HashMap<P, u8 /* value */>,
+    Vec<(P, u8 /* value */, u8 /* value */)>, /* points on both sides */
+    Option<(P, P)>                            /* bounds of the reconciled range */
+)| {
+    let (mut a, mut b, shared, bounds) = data;
+    for (point, value_a, value_b) in shared {
+        a.insert(point, value_a);
+        b.insert(point, value_b);
+    }
+
+    for ordering in [Ordering3d::Xyz, Ordering3d::Yzx, Ordering3d::Zxy] {
+        let range = match bounds.as_ref() {
+            None => ReconRange::full(),
+            Some((lo, hi)) => ReconRange {
+                lo: Some(*lo),
+                hi: Some(*hi),
+            },
+        };
+
+        let mut alice = ReconSession::new(
+            ControlNode::from_points(a.iter().map(|(point, value)| (*point, *value))),
+            ordering,
+        );
+        let mut bob = ReconSession::new(
+            ControlNode::from_points(b.iter().map(|(point, value)| (*point, *value))),
+            ordering,
+        );
+
+        // Messages in flight, and whether they go to Alice.
+        let mut queue = VecDeque::new();
+        queue.push_back((true, bob.respond(&alice.initiate(range.clone()))));
+
+        while let Some((to_alice, message)) = queue.pop_front() {
+            let answers = if to_alice {
+                alice.receive(message)
+            } else {
+                bob.receive(message)
+            };
+            queue.extend(answers.into_iter().map(|answer| (!to_alice, answer)));
+        }
+
+        // Both sides now have the union of the points in the range, and keep their own values. Points outside of the range are unaffected.
+        let mut expected_a = a.clone();
+        let mut expected_b = b.clone();
+        for (point, value) in b
+            .iter()
+            .filter(|(point, _)| range.contains(point, ordering))
+        {
+            expected_a.entry(*point).or_insert(*value);
+        }
+        for (point, value) in a
+            .iter()
+            .filter(|(point, _)| range.contains(point, ordering))
+        {
+            expected_b.entry(*point).or_insert(*value);
+        }
+
+        for (session, expected) in [(alice, expected_a), (bob, expected_b)] {
+            let tree = session.into_tree();
+            tree.assert_tree_invariants();
+            tree.assert_summaries_consistent();
+
+            assert_eq!(tree.len(), expected.len());
+            for (point, value) in expected.iter() {
+                assert!(tree.rank_of(point).is_some());
+                assert_eq!(tree.nth_xyz(tree.rank_xyz(point)), Some((point, value)));
+            }
+        }
+    }
+});
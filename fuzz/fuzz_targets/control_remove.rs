@@ -0,0 +1,41 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+use std::collections::HashMap;
+
+use kv_3d_storage::*;
+use kv_3d_storage_fuzz::*;
+
+type P = Point3d<U8FixedWidth, U8FixedWidth, U8FixedWidth>;
+
+fuzz_target!(|data: (
+    HashMap<P, (u8 /* value */, u8 /* rank */)>,
+    Vec<P> /* points to remove */
+)| {
+    let (mut points, to_remove) = data;
+
+    let mut tree: ControlNode<_, _, _, _, usize> = ControlNode::from_iter(
+        points
+            .clone()
+            .drain()
+            .map(|(point, (value, rank))| (point, value, rank)),
+    );
+
+    for point in to_remove {
+        let expected = points.remove(&point).map(|(value, _)| value);
+        assert_eq!(tree.remove(&point), expected);
+
+        tree.assert_tree_invariants();
+        tree.assert_summaries_consistent();
+        assert_eq!(tree.len(), points.len());
+    }
+
+    // The tree must be exactly the tree we would have built from the remaining points.
+    let expected_tree = ControlNode::from_iter(
+        points
+            .clone()
+            .drain()
+            .map(|(point, (value, rank))| (point, value, rank)),
+    );
+    assert_eq!(tree, expected_tree);
+});
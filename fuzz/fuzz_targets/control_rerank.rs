@@ -0,0 +1,45 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+use std::collections::HashMap;
+
+use kv_3d_storage::*;
+use kv_3d_storage_fuzz::*;
+
+type P = Point3d<U8FixedWidth, U8VariableWidth, U8FixedWidth>;
+
+fuzz_target!(|data: (Vec<(P, u8, u8)>, Vec<u8>)| {
+    let (points, other_ranks) = data;
+
+    // Deduplicate, so that both trees below contain the same point-value pairs.
+    let unique: HashMap<P, u8> = points
+        .iter()
+        .map(|(point, value, _)| (*point, *value))
+        .collect();
+    let entries: Vec<(P, u8, u8)> = points
+        .iter()
+        .filter(|(point, value, _)| unique[point] == *value)
+        .copied()
+        .collect();
+
+    // Build two trees of the same point-value pairs, inserted in different orders and with unrelated ranks.
+    let tree1: ControlNode<_, _, _, _, usize> = ControlNode::from_iter(entries.iter().copied());
+    let tree2: ControlNode<_, _, _, _, usize> = ControlNode::from_iter(
+        entries
+            .iter()
+            .rev()
+            .zip(other_ranks.iter().chain(core::iter::repeat(&0)))
+            .map(|((point, value, _), rank)| (*point, *value, *rank)),
+    );
+
+    let reranked1 = tree1.rerank();
+    let reranked2 = tree2.rerank();
+    reranked1.assert_tree_invariants();
+
+    // Both yield the same structure, which is the one derived from hashes from the start.
+    assert_eq!(reranked1, reranked2);
+    assert_eq!(reranked1, ControlNode::from_points(unique.into_iter()));
+
+    // Reranking a canonical tree changes nothing.
+    assert_eq!(reranked1.clone().rerank(), reranked1);
+});
@@ -0,0 +1,70 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+use std::collections::HashMap;
+
+use kv_3d_storage::*;
+use kv_3d_storage_fuzz::*;
+
+type P = Point3d<U8FixedWidth, U8FixedWidth, U8FixedWidth>;
+
+fuzz_target!(|data: (
+    HashMap<P, (u8 /* value */, u8 /* rank */)>,
+    Vec<(P, u8)> /* points and new values */
+)| {
+    let (mut points, updates) = data;
+
+    // The values themselves are summarized, so that summaries change when values do.
+    let mut tree: ControlNode<_, _, _, _, ValueSum> = ControlNode::from_iter(
+        points
+            .iter()
+            .map(|(point, (value, rank))| (*point, *value, *rank)),
+    );
+
+    // Also update points that are in the tree, not only random ones.
+    let existing: Vec<P> = points.keys().copied().collect();
+    let updates = updates.into_iter().enumerate().map(|(i, (point, value))| {
+        if i % 2 == 0 && !existing.is_empty() {
+            (existing[i % existing.len()], value)
+        } else {
+            (point, value)
+        }
+    });
+
+    for (point, value) in updates {
+        let expected = points
+            .get_mut(&point)
+            .map(|(old, _)| std::mem::replace(old, value));
+        assert_eq!(tree.set_value(&point, value), expected);
+        assert_eq!(tree.len(), points.len());
+    }
+
+    tree.assert_tree_invariants();
+    tree.assert_summaries_consistent();
+
+    // The tree (including all summaries) must be exactly the tree we would have built from the updated points.
+    let expected_tree = ControlNode::from_iter(
+        points
+            .iter()
+            .map(|(point, (value, rank))| (*point, *value, *rank)),
+    );
+    assert_eq!(tree, expected_tree);
+});
+
+// Sums up all values.
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+struct ValueSum(u64);
+
+impl CommutativeMonoid for ValueSum {
+    const NEUTRAL: Self = ValueSum(0);
+
+    fn combine(a: &Self, b: &Self) -> Self {
+        return ValueSum(a.0 + b.0);
+    }
+}
+
+impl LiftingCommutativeMonoid<(P, u8)> for ValueSum {
+    fn lift(val: &(P, u8)) -> Self {
+        return ValueSum(val.1 as u64);
+    }
+}
@@ -0,0 +1,47 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+use core::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+
+use kv_3d_storage::*;
+use kv_3d_storage_fuzz::*;
+
+fuzz_target!(|data: HashMap<
+    Point3d<U8FixedWidth, U8FixedWidth, U8FixedWidth>,
+    (u8 /* value */, u8 /* rank */),
+>| {
+    let triples: Vec<_> = data
+        .iter()
+        .map(|(point, (value, rank))| (*point, *value, *rank))
+        .collect();
+
+    // The default comparator yields the same tree as `from_iter`.
+    let tree: ControlNode<_, _, _, _, usize> = ControlNode::from_iter(triples.iter().copied());
+    let tree_default: ControlNode<_, _, _, _, usize> =
+        ControlNode::from_iter_with_cmp(triples.iter().copied(), node_sort_key);
+    assert_eq!(tree_default, tree);
+
+    // Descending rank, but descending xyz order within each rank.
+    let tree_custom: ControlNode<_, _, _, _, usize> = ControlNode::from_iter_with_cmp(
+        triples.iter().copied(),
+        |(p1, _, rank1), (p2, _, rank2)| match rank2.cmp(rank1) {
+            Ordering::Equal => return p2.cmp_xyz(p1),
+            _ => return rank2.cmp(rank1),
+        },
+    );
+
+    // The tree is a valid search tree over the same points.
+    assert_eq!(tree_custom.len(), tree.len());
+    assert_eq!(tree_custom.summary(), tree.summary());
+    tree_custom.assert_summaries_consistent();
+    for (point, (_, rank)) in data.iter() {
+        assert_eq!(tree_custom.rank_of(point), Some(*rank));
+    }
+
+    // When no two points share a rank, the order within a rank does not matter.
+    let mut ranks = HashSet::new();
+    if data.values().all(|(_, rank)| ranks.insert(*rank)) {
+        assert_eq!(tree_custom, tree);
+    }
+});
@@ -0,0 +1,52 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+use std::collections::HashMap;
+
+use kv_3d_storage::*;
+use kv_3d_storage_fuzz::*;
+
+type P = Point3d<U8FixedWidth, U8FixedWidth, U8FixedWidth>;
+
+fuzz_target!(|data: (
+    HashMap<P, (u8 /* value */, u8 /* rank */)>,
+    Ordering3d,
+    P,
+    P,
+    u8
+)| {
+    let (points, ordering, lo, hi, k) = data;
+    let k = k as usize + 1;
+
+    let tree: ControlNode<_, _, _, _, usize> = ControlNode::from_iter(
+        points
+            .clone()
+            .drain()
+            .map(|(point, (value, rank))| (point, value, rank)),
+    );
+
+    let count = tree.count_range(ordering, &lo, &hi);
+    let parts = tree.split_range(ordering, &lo, &hi, k);
+
+    assert!(parts.len() >= 1);
+    assert!(parts.len() <= k);
+
+    // The sub-ranges are contiguous and cover the whole range.
+    assert_eq!(parts[0].0, lo);
+    assert_eq!(parts[parts.len() - 1].1, hi);
+    for window in parts.windows(2) {
+        assert_eq!(window[0].1, window[1].0);
+    }
+
+    // The summaries combine to the summary of the whole range, and are balanced.
+    let mut total = 0;
+    for (sub_lo, sub_hi, summary) in parts.iter() {
+        assert_eq!(*summary, tree.summarize_range(ordering, sub_lo, sub_hi));
+        if count > 0 {
+            assert!(*summary >= count / parts.len());
+            assert!(*summary <= count / parts.len() + 1);
+        }
+        total += summary;
+    }
+    assert_eq!(total, count);
+});
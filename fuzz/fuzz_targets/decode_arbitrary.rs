@@ -0,0 +1,59 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+use core::fmt::Debug;
+
+use kv_3d_storage::*;
+use kv_3d_storage_fuzz::*;
+
+fuzz_target!(|data: &[u8]| {
+    // All fixed-width
+    assert_decoding_is_robust::<U8FixedWidth, U8FixedWidth, U8FixedWidth>(data);
+
+    // All variable-width
+    assert_decoding_is_robust::<U8VariableWidth, U8VariableWidth, U8VariableWidth>(data);
+
+    // One fixed-width, two variable width.
+    assert_decoding_is_robust::<U8FixedWidth, U8VariableWidth, U8VariableWidth>(data);
+
+    // Two fixed-width, one variable width.
+    assert_decoding_is_robust::<U8FixedWidth, U8FixedWidth, U8VariableWidth>(data);
+
+    // Dimensions of this crate.
+    assert_decoding_is_robust::<Rev<u16>, PadFixed<U8VariableWidth, 4>, i32>(data);
+});
+
+// Decode arbitrary bytes in every ordering. Decoding must not panic, and everything that decodes successfully must be the encoding of the decoded point.
+fn assert_decoding_is_robust<X, Y, Z>(data: &[u8])
+where
+    X: Dimension + Debug,
+    Y: Dimension + Debug,
+    Z: Dimension + Debug,
+{
+    let mut buf = vec![0; Point3d::<X, Y, Z>::max_encoding_len_xyz_length_prefixed() + 1];
+
+    if let Ok((point, len)) = Point3d::<X, Y, Z>::decode_xyz(data) {
+        assert_eq!(point.encode_xyz(&mut buf), len);
+        assert_eq!(&buf[..len], &data[..len]);
+    }
+    if let Ok((point, len)) = Point3d::<X, Y, Z>::decode_yzx(data) {
+        assert_eq!(point.encode_yzx(&mut buf), len);
+        assert_eq!(&buf[..len], &data[..len]);
+    }
+    if let Ok((point, len)) = Point3d::<X, Y, Z>::decode_zxy(data) {
+        assert_eq!(point.encode_zxy(&mut buf), len);
+        assert_eq!(&buf[..len], &data[..len]);
+    }
+
+    if let Ok(point) = Point3d::<X, Y, Z>::decode_xyz_exact(data) {
+        assert_eq!(point.encode_xyz(&mut buf), data.len());
+    }
+    if let Ok((point, len)) = Point3d::<X, Y, Z>::decode_xyz_length_prefixed(data) {
+        assert_eq!(point.encode_xyz_length_prefixed(&mut buf), len);
+        assert_eq!(&buf[..len], &data[..len]);
+    }
+    if let Ok((point, len)) = Point3d::<X, Y, Z>::decode_xyz_versioned(data) {
+        assert_eq!(point.encode_xyz_versioned(POINT_ENCODING_VERSION, &mut buf), len);
+        assert_eq!(&buf[..len], &data[..len]);
+    }
+}
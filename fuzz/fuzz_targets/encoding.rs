@@ -2,12 +2,26 @@
 use libfuzzer_sys::fuzz_target;
 
 use core::fmt::Debug;
+use std::convert::TryFrom;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use kv_3d_storage::*;
 use kv_3d_storage_fuzz::*;
 
-fuzz_target!(|data: (u8, u8, u8, u8, u8, u8)| {
-    let (x1, y1, z1, x2, y2, z2) = data;
+// `Point3d` implements `Arbitrary` (via the `arbitrary` feature), so targets can take structured points directly.
+fuzz_target!(|data: (Point3d<u8, u8, u8>, Point3d<u8, u8, u8>)| {
+    let (
+        Point3d {
+            x: x1,
+            y: y1,
+            z: z1,
+        },
+        Point3d {
+            x: x2,
+            y: y2,
+            z: z2,
+        },
+    ) = data;
 
     let x1_fw = U8FixedWidth(x1);
     let y1_fw = U8FixedWidth(y1);
@@ -40,6 +54,83 @@ fuzz_target!(|data: (u8, u8, u8, u8, u8, u8)| {
         },
     );
 
+    // The fixed-width fast path agrees with the general encoders.
+    let point_fw = Point3d {
+        x: x1_fw,
+        y: y1_fw,
+        z: z1_fw,
+    };
+    let mut buf = [0; 3];
+    let mut buf_fixed = [0; 3];
+    assert_eq!(point_fw.encode_xyz(&mut buf), point_fw.encode_xyz_fixed(&mut buf_fixed));
+    assert_eq!(buf, buf_fixed);
+    assert_eq!(point_fw.encode_yzx(&mut buf), point_fw.encode_yzx_fixed(&mut buf_fixed));
+    assert_eq!(buf, buf_fixed);
+    assert_eq!(point_fw.encode_zxy(&mut buf), point_fw.encode_zxy_fixed(&mut buf_fixed));
+    assert_eq!(buf, buf_fixed);
+
+    // Integer dimensions
+    assert_dimension_works(&(x1 as i8), &(x2 as i8));
+    assert_dimension_works(&(y1 as u16 * 257), &(y2 as u16 * 257));
+    assert_dimension_works(&(z1 as i64 * -1_000_000_007), &(z2 as i64 * -1_000_000_007));
+    assert_point3d_works(
+        &Point3d {
+            x: x1 as i8,
+            y: y1 as u16 * 257,
+            z: z1 as i64 * -1_000_000_007,
+        },
+        &Point3d {
+            x: x2 as i8,
+            y: y2 as u16 * 257,
+            z: z2 as i64 * -1_000_000_007,
+        },
+    );
+
+    // Fixed-point numbers compare exactly like their stored integers.
+    assert_dimension_works(&Fixed::<2>(z1 as i64 * -1_000_000_007), &Fixed::<2>(z2 as i64 * -1_000_000_007));
+
+    // Reversed dimensions.
+    assert_dimension_works(&Rev(y1 as u16 * 257), &Rev(y2 as u16 * 257));
+    assert_dimension_works(&Rev(x1_fw), &Rev(x2_fw));
+
+    // Variable-width dimensions padded to a fixed width sort identically, and make points fixed-width.
+    type Padded = PadFixed<U8VariableWidth, 256>;
+    assert_dimension_works(&PadFixed::<_, 256>(x1_vw), &PadFixed::<_, 256>(x2_vw));
+    assert_eq!(PadFixed::<_, 256>(x1_vw).cmp(&PadFixed(x2_vw)), x1_vw.cmp(&x2_vw));
+    assert!(Point3d::<Padded, Padded, U8FixedWidth>::IS_FIXED_WIDTH_ENCODING);
+    assert_point3d_works(
+        &Point3d {
+            x: PadFixed::<_, 256>(x1_vw),
+            y: PadFixed::<_, 256>(y1_vw),
+            z: z1_fw,
+        },
+        &Point3d {
+            x: PadFixed::<_, 256>(x2_vw),
+            y: PadFixed::<_, 256>(y2_vw),
+            z: z2_fw,
+        },
+    );
+
+    // Timestamps, including the epoch and the far future.
+    let t1 = Timestamp(u64::from_be_bytes([x1, y1, z1, x2, y2, z2, x1, y1]));
+    let t2 = Timestamp(u64::from_be_bytes([x2, y2, z2, x1, y1, z1, x2, y2]));
+    for t in [t1, t2, Timestamp(0), Timestamp(u64::MAX)] {
+        assert_dimension_works(&t, &t1);
+        assert_dimension_works(&t, &t2);
+
+        let time = SystemTime::try_from(t).unwrap();
+        assert_eq!(Timestamp::try_from(time), Ok(t));
+    }
+    assert_eq!(SystemTime::try_from(Timestamp(0)), Ok(UNIX_EPOCH));
+    assert_eq!(
+        Timestamp::try_from(UNIX_EPOCH - Duration::from_micros(1)),
+        Err(TimestampError::BeforeEpoch)
+    );
+    assert_eq!(
+        Timestamp::try_from(UNIX_EPOCH + Duration::from_micros(u64::MAX) + Duration::from_micros(1)),
+        Err(TimestampError::Overflow)
+    );
+
     // All variable-width
     assert_point3d_works(
         &Point3d {
@@ -83,107 +174,6 @@ fuzz_target!(|data: (u8, u8, u8, u8, u8, u8)| {
     );
 });
 
-/// Check that the encodings of two values of a dimension do not violate the contracts of the Dimension trait.
-pub fn assert_dimension_works<D: Dimension + Debug>(v1: &D, v2: &D) {
-    let mut v1_buf = vec![];
-    v1_buf.resize(D::HOMOMORPHIC_ENCODING_MAX_LENGTH, 0);
-
-    let v1_encoding_len = v1.homomorphic_encode(&mut v1_buf);
-
-    if D::IS_FIXED_WIDTH_ENCODING {
-        assert_eq!(
-            v1_encoding_len,
-            D::HOMOMORPHIC_ENCODING_MAX_LENGTH,
-            "\n\nDimension claims to produce fixed-width encodings, but got an encoding of length other than the claimed fixed width.
-value: {:?}
-encoding: {:?}
-actual encoding length: {:?}
-claimed fixed width (HOMOMORPHIC_ENCODING_MAX_LENGTH): {:?}\n\n", v1, &v1_buf[0..v1_encoding_len], v1_encoding_len, D::HOMOMORPHIC_ENCODING_MAX_LENGTH
-        );
-    } else {
-        assert!(
-            v1_encoding_len <= D::HOMOMORPHIC_ENCODING_MAX_LENGTH,
-            "\n\nOverlong encoding.
-value: {:?}
-encoding: {:?}
-encoding length: {:?}
-claimed maximum length (HOMOMORPHIC_ENCODING_MAX_LENGTH): {:?}\n\n",
-            v1,
-            &v1_buf[0..v1_encoding_len],
-            v1_encoding_len,
-            D::HOMOMORPHIC_ENCODING_MAX_LENGTH
-        );
-
-        for i in 0..v1_encoding_len {
-            if i > 0 && v1_buf[i] == 0 && v1_buf[i - 1] == 0 {
-                panic!(
-                    "A variable-width encoding must not contain consecutive zero bytes.
-value: {:?}
-encoding: {:?}
-index of first of the consecutive zero bytes: {:?}\n\n",
-                    v1,
-                    &v1_buf[0..v1_encoding_len],
-                    i - 1
-                );
-            }
-        }
-    }
-
-    let (v1_decoded, v1_num_decoded_bytes) = D::homomorphic_decode(&v1_buf).unwrap();
-
-    assert_eq!(
-        &v1_decoded,
-        v1,
-        "\n\nDecoding the encoding did not yield the original value.
-value: {:?}
-encoding: {:?}
-decoded: {:?}
-number of decoded bytes by the decoding function: {:?}\n\n",
-        v1,
-        &v1_buf[0..v1_encoding_len],
-        v1_decoded,
-        v1_num_decoded_bytes
-    );
-
-    assert_eq!(
-        v1_num_decoded_bytes,
-        v1_encoding_len,
-        "\n\nDecoding reported a different length than the encoding process.
-value: {:?}
-encoding: {:?}
-encoding length as reported by the encoding function: {:?}
-number of decoded bytes by the decoding function: {:?}\n\n",
-        v1,
-        &v1_buf[0..v1_encoding_len],
-        v1_encoding_len,
-        v1_num_decoded_bytes
-    );
-
-    let mut v2_buf = vec![];
-    v2_buf.resize(D::HOMOMORPHIC_ENCODING_MAX_LENGTH, 0);
-
-    let v2_encoding_len = v2.homomorphic_encode(&mut v2_buf);
-
-    // Test that the encoding is homomorphic.
-    assert_eq!(
-        v1.cmp(&v2),
-        v1_buf[0..v1_encoding_len].cmp(&v2_buf[0..v2_encoding_len]),
-        "\n\nEncoding is not homomorphic:
-v1: {:?}
-v2: {:?}
-v1.cmp(v2): {:?}
-encoding of v1: {:?}
-encoding of v2: {:?}
-v1_enc.cmp(v2.enc): {:?}\n\n",
-        v1,
-        v2,
-        v1.cmp(&v2),
-        &v1_buf[0..v1_encoding_len],
-        &v2_buf[0..v2_encoding_len],
-        v1_buf[0..v1_encoding_len].cmp(&v2_buf[0..v2_encoding_len])
-    );
-}
-
 // Check that the encodings of two 3d points work and are homomorphic.
 pub fn assert_point3d_works<X: Dimension + Debug, Y: Dimension + Debug, Z: Dimension + Debug>(
     v1: &Point3d<X, Y, Z>,
@@ -196,6 +186,7 @@ pub fn assert_point3d_works<X: Dimension + Debug, Y: Dimension + Debug, Z: Dimen
     v1_xyz_buf.resize(Point3d::<X, Y, Z>::max_encoding_len_xyz(), 0);
 
     let v1_xyz_encoding_len = v1.encode_xyz(&mut v1_xyz_buf);
+    assert_eq!(v1.encoded_len_xyz(), v1_xyz_encoding_len);
 
     if X::IS_FIXED_WIDTH_ENCODING && Y::IS_FIXED_WIDTH_ENCODING && Z::IS_FIXED_WIDTH_ENCODING {
         assert_eq!(
@@ -253,6 +244,15 @@ number of decoded bytes by the decoding function: {:?}\n\n",
         v1_xyz_num_decoded_bytes
     );
 
+    // Exact decoding accepts the encoding itself, but not the encoding followed by junk.
+    assert_eq!(
+        Point3d::<X, Y, Z>::decode_xyz_exact(&v1_xyz_buf[..v1_xyz_encoding_len]).as_ref(),
+        Ok(v1)
+    );
+    let mut v1_xyz_junk = v1_xyz_buf[..v1_xyz_encoding_len].to_vec();
+    v1_xyz_junk.push(0);
+    assert!(Point3d::<X, Y, Z>::decode_xyz_exact(&v1_xyz_junk).is_err());
+
     let mut v2_xyz_buf = vec![];
     v2_xyz_buf.resize(Point3d::<X, Y, Z>::max_encoding_len_xyz(), 0);
 
@@ -277,6 +277,55 @@ v1_xyz_enc.cmp(v2.enc): {:?}\n\n",
         v1_xyz_buf[0..v1_xyz_encoding_len].cmp(&v2_xyz_buf[0..v2_xyz_encoding_len])
     );
 
+    // Comparing via the encodings without allocating agrees with comparing the points.
+    assert_eq!(v1.cmp_xyz_via_encoding(&v2), v1.cmp_xyz(&v2));
+
+    /*
+     * Test the length-prefixed xyz encoding, with two points back to back.
+     */
+    let mut prefixed_buf = vec![0; 2 * Point3d::<X, Y, Z>::max_encoding_len_xyz_length_prefixed()];
+
+    let v1_prefixed_len = v1.encode_xyz_length_prefixed(&mut prefixed_buf);
+    let v2_prefixed_len = v2.encode_xyz_length_prefixed(&mut prefixed_buf[v1_prefixed_len..]);
+    assert!(v1_prefixed_len <= Point3d::<X, Y, Z>::max_encoding_len_xyz_length_prefixed());
+    assert_eq!(
+        &prefixed_buf[v1_prefixed_len - v1_xyz_encoding_len..v1_prefixed_len],
+        &v1_xyz_buf[0..v1_xyz_encoding_len]
+    );
+
+    let (v1_prefixed_decoded, v1_prefixed_decoded_len) =
+        Point3d::<X, Y, Z>::decode_xyz_length_prefixed(&prefixed_buf).unwrap();
+    assert_eq!(&v1_prefixed_decoded, v1);
+    assert_eq!(v1_prefixed_decoded_len, v1_prefixed_len);
+
+    let (v2_prefixed_decoded, v2_prefixed_decoded_len) =
+        Point3d::<X, Y, Z>::decode_xyz_length_prefixed(&prefixed_buf[v1_prefixed_len..]).unwrap();
+    assert_eq!(&v2_prefixed_decoded, v2);
+    assert_eq!(v2_prefixed_decoded_len, v2_prefixed_len);
+
+    // Truncated encodings are rejected.
+    assert!(Point3d::<X, Y, Z>::decode_xyz_length_prefixed(&prefixed_buf[..v1_prefixed_len - 1]).is_err());
+
+    /*
+     * Test the versioned xyz encoding.
+     */
+    let mut versioned_buf = vec![0; Point3d::<X, Y, Z>::max_encoding_len_xyz_versioned()];
+
+    let v1_versioned_len = v1.encode_xyz_versioned(POINT_ENCODING_VERSION, &mut versioned_buf);
+    assert_eq!(v1_versioned_len, 1 + v1_xyz_encoding_len);
+    assert_eq!(&versioned_buf[1..v1_versioned_len], &v1_xyz_buf[0..v1_xyz_encoding_len]);
+    let (v1_versioned_decoded, v1_versioned_decoded_len) =
+        Point3d::<X, Y, Z>::decode_xyz_versioned(&versioned_buf[..v1_versioned_len]).unwrap();
+    assert_eq!(&v1_versioned_decoded, v1);
+    assert_eq!(v1_versioned_decoded_len, v1_versioned_len);
+
+    // Other versions are rejected.
+    versioned_buf[0] = POINT_ENCODING_VERSION.wrapping_add(1);
+    assert!(matches!(
+        Point3d::<X, Y, Z>::decode_xyz_versioned(&versioned_buf[..v1_versioned_len]),
+        Err(VersionedDecodeError::UnsupportedVersion(version)) if version == POINT_ENCODING_VERSION.wrapping_add(1)
+    ));
+
     /*
      * Test yzx ordering.
      */
@@ -284,6 +333,7 @@ v1_xyz_enc.cmp(v2.enc): {:?}\n\n",
     v1_yzx_buf.resize(Point3d::<X, Y, Z>::max_encoding_len_yzx(), 0);
 
     let v1_yzx_encoding_len = v1.encode_yzx(&mut v1_yzx_buf);
+    assert_eq!(v1.encoded_len_yzx(), v1_yzx_encoding_len);
 
     if X::IS_FIXED_WIDTH_ENCODING && Y::IS_FIXED_WIDTH_ENCODING && Z::IS_FIXED_WIDTH_ENCODING {
         assert_eq!(
@@ -341,6 +391,15 @@ number of decoded bytes by the decoding function: {:?}\n\n",
         v1_yzx_num_decoded_bytes
     );
 
+    // Exact decoding accepts the encoding itself, but not the encoding followed by junk.
+    assert_eq!(
+        Point3d::<X, Y, Z>::decode_yzx_exact(&v1_yzx_buf[..v1_yzx_encoding_len]).as_ref(),
+        Ok(v1)
+    );
+    let mut v1_yzx_junk = v1_yzx_buf[..v1_yzx_encoding_len].to_vec();
+    v1_yzx_junk.push(0);
+    assert!(Point3d::<X, Y, Z>::decode_yzx_exact(&v1_yzx_junk).is_err());
+
     let mut v2_yzx_buf = vec![];
     v2_yzx_buf.resize(Point3d::<X, Y, Z>::max_encoding_len_yzx(), 0);
 
@@ -372,6 +431,7 @@ v1_yzx_enc.cmp(v2.enc): {:?}\n\n",
     v1_zxy_buf.resize(Point3d::<X, Y, Z>::max_encoding_len_zxy(), 0);
 
     let v1_zxy_encoding_len = v1.encode_zxy(&mut v1_zxy_buf);
+    assert_eq!(v1.encoded_len_zxy(), v1_zxy_encoding_len);
 
     if X::IS_FIXED_WIDTH_ENCODING && Y::IS_FIXED_WIDTH_ENCODING && Z::IS_FIXED_WIDTH_ENCODING {
         assert_eq!(
@@ -429,6 +489,15 @@ number of decoded bytes by the decoding function: {:?}\n\n",
         v1_zxy_num_decoded_bytes
     );
 
+    // Exact decoding accepts the encoding itself, but not the encoding followed by junk.
+    assert_eq!(
+        Point3d::<X, Y, Z>::decode_zxy_exact(&v1_zxy_buf[..v1_zxy_encoding_len]).as_ref(),
+        Ok(v1)
+    );
+    let mut v1_zxy_junk = v1_zxy_buf[..v1_zxy_encoding_len].to_vec();
+    v1_zxy_junk.push(0);
+    assert!(Point3d::<X, Y, Z>::decode_zxy_exact(&v1_zxy_junk).is_err());
+
     let mut v2_zxy_buf = vec![];
     v2_zxy_buf.resize(Point3d::<X, Y, Z>::max_encoding_len_zxy(), 0);
 
@@ -451,5 +520,13 @@ v1_zxy_enc.cmp(v2.enc): {:?}\n\n",
         &v1_zxy_buf[0..v1_zxy_encoding_len],
         &v2_zxy_buf[0..v2_zxy_encoding_len],
         v1_zxy_buf[0..v1_zxy_encoding_len].cmp(&v2_zxy_buf[0..v2_zxy_encoding_len])
-    );    
+    );
+
+    /*
+     * Test computing all encodings at once.
+     */
+    let all = v1.encode_all();
+    assert_eq!(&all.xyz[..], &v1_xyz_buf[0..v1_xyz_encoding_len]);
+    assert_eq!(&all.yzx[..], &v1_yzx_buf[0..v1_yzx_encoding_len]);
+    assert_eq!(&all.zxy[..], &v1_zxy_buf[0..v1_zxy_encoding_len]);
 }
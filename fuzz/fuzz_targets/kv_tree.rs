@@ -0,0 +1,70 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+use std::collections::HashMap;
+
+use futures::StreamExt;
+use pollster::block_on;
+
+use kv_3d_storage::*;
+use kv_3d_storage_fuzz::*;
+
+type P = Point3d<U8FixedWidth, U8VariableWidth, U8FixedWidth>;
+
+fuzz_target!(|data: (Vec<(P, u8 /* value */, u8 /* rank */)>, Vec<P>)| {
+    let (items, queries) = data;
+
+    // Rank 255 is reserved for missing children.
+    let items: Vec<_> = items
+        .into_iter()
+        .map(|(point, value, rank)| (point, value, Rank::new(rank).unwrap_or(Rank::MAX)))
+        .collect();
+
+    let bulk: KvTree<_, _, _, _, _, usize> =
+        block_on(KvTree::bulk_load(BTreeMapBackEnd::new(), items.iter().cloned())).unwrap();
+
+    let mut incremental: KvTree<_, _, _, _, _, usize> = KvTree::new(BTreeMapBackEnd::new());
+    let mut expected = HashMap::new();
+    for (point, value, rank) in items.iter() {
+        assert_eq!(
+            block_on(incremental.insert(*point, *value, *rank)).unwrap(),
+            expected.insert(*point, *value)
+        );
+    }
+
+    for point in queries.iter().chain(items.iter().map(|(point, _, _)| point)) {
+        let expected = expected.get(point).copied();
+        assert_eq!(block_on(bulk.get(point)).unwrap(), expected);
+        assert_eq!(block_on(incremental.get(point)).unwrap(), expected);
+    }
+
+    // Iterating by rank yields every point exactly once, with non-decreasing ranks.
+    for tree in [&bulk, &incremental] {
+        let by_rank: Vec<_> = block_on(tree.iter_by_rank().collect::<Vec<_>>())
+            .into_iter()
+            .map(|entry| entry.unwrap())
+            .collect();
+
+        assert_eq!(by_rank.len(), expected.len());
+        assert!(by_rank.windows(2).all(|pair| pair[0].0 <= pair[1].0));
+
+        let yielded: HashMap<_, _> = by_rank
+            .iter()
+            .map(|(_, point, value)| (*point, *value))
+            .collect();
+        assert_eq!(yielded, expected);
+    }
+
+    // Both trees have exactly the shape of the control tree.
+    let control: ControlNode<_, _, _, _, usize> = ControlNode::from_iter_last_wins(
+        items
+            .iter()
+            .map(|(point, value, rank)| (*point, *value, rank.get())),
+    );
+
+    let root = bulk.root_key().map(|key| key.to_vec());
+    control.assert_matches_kv_nodes(&bulk.into_backend().into_map(), root.as_deref());
+
+    let root = incremental.root_key().map(|key| key.to_vec());
+    control.assert_matches_kv_nodes(&incremental.into_backend().into_map(), root.as_deref());
+});
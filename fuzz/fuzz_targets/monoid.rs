@@ -0,0 +1,69 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+use kv_3d_storage::*;
+use kv_3d_storage_fuzz::*;
+
+type P = Point3d<U8FixedWidth, U8VariableWidth, U8FixedWidth>;
+
+fuzz_target!(|data: (Vec<u8>, Vec<(P, u8, u8)>)| {
+    let (data, points) = data;
+
+    // The counting monoid counts.
+    let lifted: Vec<usize> = data
+        .iter()
+        .map(<usize as LiftingCommutativeMonoid<u8>>::lift)
+        .collect();
+    assert_eq!(
+        <usize as CommutativeMonoid>::combine_all(&lifted),
+        data.len()
+    );
+    assert_eq!(
+        <usize as LiftingCommutativeMonoid<u8>>::lift_all(&data),
+        data.len()
+    );
+
+    // Combining nothing yields the neutral element.
+    assert_eq!(
+        <usize as CommutativeMonoid>::combine_all(&[]),
+        <usize as CommutativeMonoid>::NEUTRAL
+    );
+
+    // The trivial monoid does nothing.
+    assert_eq!(<() as LiftingCommutativeMonoid<u8>>::lift_all(&data), ());
+
+    // The bounds monoid finds the least and greatest points.
+    let pairs: Vec<(P, u8)> = points
+        .iter()
+        .map(|(point, value, _rank)| (*point, *value))
+        .collect();
+    let expected = match (
+        pairs.iter().map(|(point, _)| Xyz(*point)).min(),
+        pairs.iter().map(|(point, _)| Xyz(*point)).max(),
+    ) {
+        (Some(min), Some(max)) => BoundsMonoid::Bounds {
+            min: min.0,
+            max: max.0,
+        },
+        _ => BoundsMonoid::Empty,
+    };
+    assert_eq!(BoundsMonoid::lift_all(&pairs), expected);
+
+    // Also when used as the summary of a tree.
+    let tree: ControlNode<_, _, _, _, BoundsMonoid<_, _, _>> = ControlNode::from_iter_last_wins(
+        points.iter().map(|(point, value, rank)| (*point, *value, *rank)),
+    );
+    assert_eq!(tree.summary(), expected);
+
+    // The xor fingerprint ignores values, and does not depend on the order of the points.
+    let fingerprint = XorFingerprint::lift_all(&pairs);
+    let mut reversed: Vec<(P, ())> = pairs.iter().rev().map(|(point, _)| (*point, ())).collect();
+    assert_eq!(XorFingerprint::lift_all(&reversed), fingerprint);
+
+    // Each point that occurs twice cancels itself out.
+    reversed.extend(reversed.clone());
+    assert_eq!(
+        XorFingerprint::lift_all(&reversed),
+        <XorFingerprint as CommutativeMonoid>::NEUTRAL
+    );
+});
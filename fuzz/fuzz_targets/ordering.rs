@@ -0,0 +1,164 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+use core::cmp::Ordering;
+use core::fmt::Debug;
+use core::hash::{Hash, Hasher};
+use std::collections::hash_map::DefaultHasher;
+
+use kv_3d_storage::*;
+use kv_3d_storage_fuzz::*;
+
+fuzz_target!(|data: ([u8; 3], [u8; 3], [u8; 3])| {
+    let (a, b, c) = data;
+
+    // All fixed-width
+    let fw = |[x, y, z]: [u8; 3]| Point3d::from((U8FixedWidth(x), U8FixedWidth(y), U8FixedWidth(z)));
+    assert_orderings_work(&fw(a), &fw(b), &fw(c));
+
+    // All variable-width
+    let vw = |[x, y, z]: [u8; 3]| Point3d::from((U8VariableWidth(x), U8VariableWidth(y), U8VariableWidth(z)));
+    assert_orderings_work(&vw(a), &vw(b), &vw(c));
+
+    // One fixed-width, two variable width.
+    let fvv = |[x, y, z]: [u8; 3]| Point3d::from((U8FixedWidth(x), U8VariableWidth(y), U8VariableWidth(z)));
+    assert_orderings_work(&fvv(a), &fvv(b), &fvv(c));
+
+    // Two fixed-width, one variable width.
+    let ffv = |[x, y, z]: [u8; 3]| Point3d::from((U8FixedWidth(x), U8FixedWidth(y), U8VariableWidth(z)));
+    assert_orderings_work(&ffv(a), &ffv(b), &ffv(c));
+});
+
+// Check that each of the three orderings is a total order on the given points.
+fn assert_orderings_work<X, Y, Z>(a: &Point3d<X, Y, Z>, b: &Point3d<X, Y, Z>, c: &Point3d<X, Y, Z>)
+where
+    X: Dimension + Debug + Clone,
+    Y: Dimension + Debug + Clone,
+    Z: Dimension + Debug + Clone,
+{
+    for ordering in [Ordering3d::Xyz, Ordering3d::Yzx, Ordering3d::Zxy] {
+        let points = [a, b, c];
+
+        for p in points {
+            for q in points {
+                // Antisymmetry
+                assert_eq!(
+                    p.cmp_by(q, ordering),
+                    q.cmp_by(p, ordering).reverse(),
+                    "\n\nComparison is not antisymmetric in the {:?} ordering.\np: {:?}\nq: {:?}\n\n",
+                    ordering,
+                    p,
+                    q
+                );
+
+                // Consistency with equality
+                assert_eq!(
+                    p.cmp_by(q, ordering) == Ordering::Equal,
+                    p == q,
+                    "\n\nComparison is not consistent with equality in the {:?} ordering.\np: {:?}\nq: {:?}\n\n",
+                    ordering,
+                    p,
+                    q
+                );
+
+                // Points that are equal according to the ordering hash identically, and the hash is that of the encoding.
+                if p.cmp_by(q, ordering) == Ordering::Equal {
+                    assert_eq!(hash_encoded(p, ordering), hash_encoded(q, ordering));
+                }
+
+                // Clamping into the interval spanned by the other two points
+                for r in points {
+                    let (lo, hi) = if q.cmp_by(r, ordering) == Ordering::Greater {
+                        (r, q)
+                    } else {
+                        (q, r)
+                    };
+
+                    let clamped = clamp_by(p.clone(), lo, hi, ordering);
+                    if p.cmp_by(lo, ordering) == Ordering::Less {
+                        assert_eq!(&clamped, lo);
+                    } else if p.cmp_by(hi, ordering) == Ordering::Greater {
+                        assert_eq!(&clamped, hi);
+                    } else {
+                        assert_eq!(&clamped, p);
+                    }
+                }
+
+                // Containment in the half-open interval between the other two points
+                for r in points {
+                    let contained = p.cmp_by(q, ordering) != Ordering::Less
+                        && p.cmp_by(r, ordering) == Ordering::Less;
+                    assert_eq!(p.in_range_by(q, r, ordering), contained);
+
+                    // The lower boundary is included, the upper boundary is excluded.
+                    assert_eq!(p.in_range_by(p, r, ordering), p.cmp_by(r, ordering) == Ordering::Less);
+                    assert!(!p.in_range_by(q, p, ordering));
+
+                    // Empty (or wrapping) intervals contain nothing.
+                    if q.cmp_by(r, ordering) != Ordering::Less {
+                        assert!(!p.in_range_by(q, r, ordering));
+                    }
+                }
+
+                // Transitivity
+                for r in points {
+                    if p.cmp_by(q, ordering) != Ordering::Greater
+                        && q.cmp_by(r, ordering) != Ordering::Greater
+                    {
+                        assert_ne!(
+                            p.cmp_by(r, ordering),
+                            Ordering::Greater,
+                            "\n\nComparison is not transitive in the {:?} ordering.\np: {:?}\nq: {:?}\nr: {:?}\n\n",
+                            ordering,
+                            p,
+                            q,
+                            r
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn clamp_by<X, Y, Z>(
+    p: Point3d<X, Y, Z>,
+    lo: &Point3d<X, Y, Z>,
+    hi: &Point3d<X, Y, Z>,
+    ordering: Ordering3d,
+) -> Point3d<X, Y, Z>
+where
+    X: Dimension + Clone,
+    Y: Dimension + Clone,
+    Z: Dimension + Clone,
+{
+    match ordering {
+        Ordering3d::Xyz => return p.clamp_xyz(lo, hi),
+        Ordering3d::Yzx => return p.clamp_yzx(lo, hi),
+        Ordering3d::Zxy => return p.clamp_zxy(lo, hi),
+    }
+}
+
+fn hash_encoded<X, Y, Z>(p: &Point3d<X, Y, Z>, ordering: Ordering3d) -> u64
+where
+    X: Dimension,
+    Y: Dimension,
+    Z: Dimension,
+{
+    let mut hasher = DefaultHasher::new();
+    p.hash_encoded(&mut hasher, ordering);
+    let hash = hasher.finish();
+
+    // The hash is the hash of the encoding, as a byte slice.
+    let mut buf = vec![0; 1000];
+    let len = match ordering {
+        Ordering3d::Xyz => p.encode_xyz(&mut buf),
+        Ordering3d::Yzx => p.encode_yzx(&mut buf),
+        Ordering3d::Zxy => p.encode_zxy(&mut buf),
+    };
+    let mut hasher = DefaultHasher::new();
+    buf[..len].hash(&mut hasher);
+    assert_eq!(hash, hasher.finish());
+
+    return hash;
+}
@@ -0,0 +1,62 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+use std::collections::BTreeMap;
+use std::ops::{Bound, RangeBounds};
+
+use arbitrary::Arbitrary;
+use pollster::block_on;
+
+use kv_3d_storage::*;
+use kv_3d_storage_fuzz::*;
+
+type P = Point3d<U8FixedWidth, U8VariableWidth, U8VariableWidth>;
+
+#[derive(Debug, Arbitrary)]
+enum Operation {
+    Get(P),
+    Insert(P, u8),
+    Delete(P),
+    RangeXyz(Bound<P>, Bound<P>),
+}
+
+fuzz_target!(|data: Vec<Operation>| {
+    let mut store = PointStore::new(BTreeMapBackEnd::new());
+    let mut control = BTreeMap::new();
+
+    for op in data {
+        match op {
+            Operation::Get(point) => {
+                assert_eq!(
+                    block_on(store.get(&point)).unwrap(),
+                    control.get(&Xyz(point)).copied()
+                );
+            }
+            Operation::Insert(point, value) => {
+                assert_eq!(
+                    block_on(store.insert(&point, value)).unwrap(),
+                    control.insert(Xyz(point), value)
+                );
+            }
+            Operation::Delete(point) => {
+                assert_eq!(
+                    block_on(store.delete(&point)).unwrap(),
+                    control.remove(&Xyz(point))
+                );
+            }
+            Operation::RangeXyz(lo, hi) => {
+                let bounds = (lo.clone().map(Xyz), hi.clone().map(Xyz));
+                let expected: Vec<_> = control
+                    .iter()
+                    .filter(|(point, _)| bounds.contains(*point))
+                    .map(|(point, value)| (point.0, *value))
+                    .collect();
+
+                assert_eq!(
+                    block_on(store.range_xyz(lo.as_ref(), hi.as_ref())).unwrap(),
+                    expected
+                );
+            }
+        }
+    }
+});
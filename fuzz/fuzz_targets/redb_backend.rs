@@ -0,0 +1,135 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+use std::collections::BTreeMap;
+use std::ops::Bound;
+
+use arbitrary::Arbitrary;
+use futures::StreamExt;
+use pollster::block_on;
+use redb::{backends::InMemoryBackend, Database};
+
+use kv_3d_storage::*;
+
+#[derive(Debug, Arbitrary)]
+enum Operation {
+    Get(Vec<u8>),
+    FindLte(Vec<u8>),
+    FindGte(Vec<u8>),
+    StreamRange(Bound<Vec<u8>>, Bound<Vec<u8>>),
+    Range(Bound<Vec<u8>>, Bound<Vec<u8>>),
+    ScanPrefix(Vec<u8>),
+    Insert(Vec<u8>, Vec<u8>),
+    Delete(Vec<u8>),
+    // Append a byte to the current value, or delete the key if there is no byte.
+    Update(Vec<u8>, Option<u8>),
+    Flush,
+    // Drop all unflushed mutations by reopening the database.
+    Reopen,
+}
+
+fuzz_target!(|data: Vec<Operation>| {
+    let db = Database::builder()
+        .create_with_backend(InMemoryBackend::new())
+        .unwrap();
+    let mut redb = RedbBackEnd::<Vec<u8>>::new(db).unwrap();
+
+    // The state including unflushed mutations.
+    let mut control = BTreeMapBackEnd::new();
+    // The state as of the latest flush.
+    let mut durable = BTreeMap::new();
+
+    for op in data {
+        match op {
+            Operation::Get(key) => {
+                assert_eq!(
+                    block_on(redb.get(&key)).unwrap(),
+                    block_on(control.get(&key)).unwrap()
+                );
+            }
+            Operation::FindLte(key) => {
+                assert_eq!(
+                    block_on(redb.find_lte(&key)).unwrap(),
+                    block_on(control.find_lte(&key)).unwrap()
+                );
+            }
+            Operation::FindGte(key) => {
+                assert_eq!(
+                    block_on(redb.find_gte(&key)).unwrap(),
+                    block_on(control.find_gte(&key)).unwrap()
+                );
+            }
+            Operation::StreamRange(lo, hi) => {
+                let lo = lo.as_ref().map(|k| k.as_slice());
+                let hi = hi.as_ref().map(|k| k.as_slice());
+                let expected: Vec<_> = block_on(control.stream_range(lo, hi).collect::<Vec<_>>())
+                    .into_iter()
+                    .map(|entry| entry.unwrap())
+                    .collect();
+                let actual: Vec<_> = block_on(redb.stream_range(lo, hi).collect::<Vec<_>>())
+                    .into_iter()
+                    .map(|entry| entry.unwrap())
+                    .collect();
+                assert_eq!(actual, expected);
+            }
+            Operation::Range(lo, hi) => {
+                let lo = lo.as_ref().map(|k| k.as_slice());
+                let hi = hi.as_ref().map(|k| k.as_slice());
+                assert_eq!(
+                    block_on(redb.range(lo, hi)).unwrap().collect::<Vec<_>>(),
+                    block_on(control.range(lo, hi)).unwrap().collect::<Vec<_>>()
+                );
+                assert_eq!(
+                    block_on(redb.range_rev(lo, hi)).unwrap().collect::<Vec<_>>(),
+                    block_on(control.range_rev(lo, hi)).unwrap().collect::<Vec<_>>()
+                );
+            }
+            Operation::ScanPrefix(prefix) => {
+                assert_eq!(
+                    block_on(redb.scan_prefix(&prefix)).unwrap().collect::<Vec<_>>(),
+                    block_on(control.scan_prefix(&prefix)).unwrap().collect::<Vec<_>>()
+                );
+            }
+            Operation::Insert(key, value) => {
+                assert_eq!(
+                    block_on(redb.insert(&key, value.clone())).unwrap(),
+                    block_on(control.insert(&key, value)).unwrap()
+                );
+            }
+            Operation::Delete(key) => {
+                assert_eq!(
+                    block_on(redb.delete(&key)).unwrap(),
+                    block_on(control.delete(&key)).unwrap()
+                );
+            }
+            Operation::Update(key, byte) => {
+                let append = |old: Option<Vec<u8>>| {
+                    byte.map(|byte| {
+                        let mut new = old.unwrap_or_default();
+                        new.push(byte);
+                        new
+                    })
+                };
+                assert_eq!(
+                    block_on(redb.update_with(&key, append)).unwrap(),
+                    block_on(control.update_with(&key, append)).unwrap()
+                );
+            }
+            Operation::Flush => {
+                block_on(redb.flush()).unwrap();
+                durable = control.clone().into_map();
+            }
+            Operation::Reopen => {
+                redb = RedbBackEnd::new(redb.into_database()).unwrap();
+                control = BTreeMapBackEnd::from(durable.clone());
+            }
+        }
+    }
+
+    // Everything that was flushed survives reopening, nothing else does.
+    block_on(redb.flush()).unwrap();
+    let redb = RedbBackEnd::<Vec<u8>>::new(redb.into_database()).unwrap();
+    for (key, value) in control.into_map() {
+        assert_eq!(block_on(redb.get(&key)).unwrap(), Some(value));
+    }
+});
@@ -0,0 +1,25 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+use kv_3d_storage::*;
+use kv_3d_storage_fuzz::*;
+
+fuzz_target!(|data: Vec<Point3d<U8FixedWidth, U8VariableWidth, U8FixedWidth>>| {
+    let mut expected = data.clone();
+    expected.sort_by(|p1, p2| p1.cmp_xyz(p2));
+    let mut actual: Vec<_> = data.iter().copied().map(Xyz).collect();
+    actual.sort();
+    assert_eq!(actual.into_iter().map(|p| p.0).collect::<Vec<_>>(), expected);
+
+    let mut expected = data.clone();
+    expected.sort_by(|p1, p2| p1.cmp_yzx(p2));
+    let mut actual: Vec<_> = data.iter().copied().map(Yzx).collect();
+    actual.sort();
+    assert_eq!(actual.into_iter().map(|p| p.0).collect::<Vec<_>>(), expected);
+
+    let mut expected = data.clone();
+    expected.sort_by(|p1, p2| p1.cmp_zxy(p2));
+    let mut actual: Vec<_> = data.iter().copied().map(Zxy).collect();
+    actual.sort();
+    assert_eq!(actual.into_iter().map(|p| p.0).collect::<Vec<_>>(), expected);
+});
@@ -0,0 +1,129 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+use core::fmt::Debug;
+use std::io::Cursor;
+
+use kv_3d_storage::*;
+use kv_3d_storage_fuzz::*;
+
+fuzz_target!(|data: Vec<(u8, u8, u8)>| {
+    // All fixed-width
+    assert_streaming_works(
+        &data
+            .iter()
+            .map(|(x, y, z)| Point3d {
+                x: U8FixedWidth(*x),
+                y: U8FixedWidth(*y),
+                z: U8FixedWidth(*z),
+            })
+            .collect::<Vec<_>>(),
+    );
+
+    // All variable-width
+    assert_streaming_works(
+        &data
+            .iter()
+            .map(|(x, y, z)| Point3d {
+                x: U8VariableWidth(*x),
+                y: U8VariableWidth(*y),
+                z: U8VariableWidth(*z),
+            })
+            .collect::<Vec<_>>(),
+    );
+
+    // One fixed-width, two variable width.
+    assert_streaming_works(
+        &data
+            .iter()
+            .map(|(x, y, z)| Point3d {
+                x: U8FixedWidth(*x),
+                y: U8VariableWidth(*y),
+                z: U8VariableWidth(*z),
+            })
+            .collect::<Vec<_>>(),
+    );
+
+    // Two fixed-width, one variable width.
+    assert_streaming_works(
+        &data
+            .iter()
+            .map(|(x, y, z)| Point3d {
+                x: U8FixedWidth(*x),
+                y: U8FixedWidth(*y),
+                z: U8VariableWidth(*z),
+            })
+            .collect::<Vec<_>>(),
+    );
+});
+
+// Check that writing many points back-to-back and reading them back yields the original points, and the same bytes as the slice-based encodings.
+fn assert_streaming_works<X: Dimension + Debug, Y: Dimension + Debug, Z: Dimension + Debug>(
+    points: &[Point3d<X, Y, Z>],
+) {
+    /*
+     * Test xyz ordering.
+     */
+    let mut xyz_stream = vec![];
+    let mut xyz_expected = vec![];
+    for point in points {
+        let len = point.encode_xyz_to_writer(&mut xyz_stream).unwrap();
+
+        let mut buf = vec![0; Point3d::<X, Y, Z>::max_encoding_len_xyz()];
+        let expected_len = point.encode_xyz(&mut buf);
+        assert_eq!(len, expected_len);
+        xyz_expected.extend_from_slice(&buf[..expected_len]);
+    }
+    assert_eq!(xyz_stream, xyz_expected);
+
+    let mut cursor = Cursor::new(&xyz_stream);
+    for point in points {
+        let (decoded, _) = Point3d::<X, Y, Z>::decode_xyz_from_reader(&mut cursor).unwrap();
+        assert_eq!(&decoded, point);
+    }
+    assert_eq!(cursor.position() as usize, xyz_stream.len());
+
+    /*
+     * Test yzx ordering.
+     */
+    let mut yzx_stream = vec![];
+    let mut yzx_expected = vec![];
+    for point in points {
+        let len = point.encode_yzx_to_writer(&mut yzx_stream).unwrap();
+
+        let mut buf = vec![0; Point3d::<X, Y, Z>::max_encoding_len_yzx()];
+        let expected_len = point.encode_yzx(&mut buf);
+        assert_eq!(len, expected_len);
+        yzx_expected.extend_from_slice(&buf[..expected_len]);
+    }
+    assert_eq!(yzx_stream, yzx_expected);
+
+    let mut cursor = Cursor::new(&yzx_stream);
+    for point in points {
+        let (decoded, _) = Point3d::<X, Y, Z>::decode_yzx_from_reader(&mut cursor).unwrap();
+        assert_eq!(&decoded, point);
+    }
+    assert_eq!(cursor.position() as usize, yzx_stream.len());
+
+    /*
+     * Test zxy ordering.
+     */
+    let mut zxy_stream = vec![];
+    let mut zxy_expected = vec![];
+    for point in points {
+        let len = point.encode_zxy_to_writer(&mut zxy_stream).unwrap();
+
+        let mut buf = vec![0; Point3d::<X, Y, Z>::max_encoding_len_zxy()];
+        let expected_len = point.encode_zxy(&mut buf);
+        assert_eq!(len, expected_len);
+        zxy_expected.extend_from_slice(&buf[..expected_len]);
+    }
+    assert_eq!(zxy_stream, zxy_expected);
+
+    let mut cursor = Cursor::new(&zxy_stream);
+    for point in points {
+        let (decoded, _) = Point3d::<X, Y, Z>::decode_zxy_from_reader(&mut cursor).unwrap();
+        assert_eq!(&decoded, point);
+    }
+    assert_eq!(cursor.position() as usize, zxy_stream.len());
+}
@@ -1,7 +1,8 @@
 use core::cmp::Ordering;
-use core::fmt::Debug;
+use core::fmt::{self, Debug, Display};
 use core::hash::Hash;
-use std::collections::HashSet;
+use core::mem;
+use std::collections::{BTreeMap, HashMap, HashSet};
 
 use arbitrary::Arbitrary;
 
@@ -21,15 +22,21 @@ impl Dimension for U8FixedWidth {
         return 1;
     }
 
-    fn homomorphic_decode(buf: &[u8]) -> Result<(Self, usize), ()> {
+    fn homomorphic_decode(buf: &[u8]) -> Result<(Self, usize), DecodeError> {
         if buf.len() == 0 {
-            return Err(());
+            return Err(DecodeError::UnexpectedEof);
         } else {
             return Ok((U8FixedWidth(buf[0]), 1));
         }
     }
 }
 
+impl Display for U8FixedWidth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return write!(f, "{}", self.0);
+    }
+}
+
 /// A `u8` that uses a variable-width homomorphic encoding.
 ///
 /// The encoding of a `u8` `n` consists of `n` times the byte `0x02`, followed by the single byte `0x01`.
@@ -41,6 +48,8 @@ impl Dimension for U8VariableWidth {
 
     const IS_FIXED_WIDTH_ENCODING: bool = false;
 
+    const IS_PREFIX_FREE: bool = true;
+
     fn homomorphic_encode(&self, buf: &mut [u8]) -> usize {
         let n = self.0 as usize;
         for i in 0..n {
@@ -51,22 +60,36 @@ impl Dimension for U8VariableWidth {
         return n + 1;
     }
 
-    fn homomorphic_decode(buf: &[u8]) -> Result<(Self, usize), ()> {
+    fn homomorphic_decode(buf: &[u8]) -> Result<(Self, usize), DecodeError> {
         let mut i = 0;
-        while buf[i] != 1 {
-            if i >= 256 {
-                return Err(());
+        loop {
+            if i >= buf.len() {
+                return Err(DecodeError::UnexpectedEof);
+            } else if i >= 256 {
+                return Err(DecodeError::InvalidValue);
             }
 
-            if buf[i] == 2 {
+            if buf[i] == 1 {
+                break;
+            } else if buf[i] == 2 {
                 i += 1;
             } else {
-                return Err(());
+                return Err(DecodeError::InvalidValue);
             }
         }
 
         return Ok((Self(i as u8), i + 1));
     }
+
+    fn encoded_len(&self) -> usize {
+        return self.0 as usize + 1;
+    }
+}
+
+impl Display for U8VariableWidth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return write!(f, "{}", self.0);
+    }
 }
 
 /// An in-memory control implementation of a 3d-ish-zip-tree.
@@ -98,85 +121,1180 @@ where
     },
 }
 
-impl<X, Y, Z, V, M> ControlNode<X, Y, Z, V, M>
-where
-    X: Dimension + Clone + Debug + Hash,
-    Y: Dimension + Clone + Debug + Hash,
-    Z: Dimension + Clone + Debug + Hash,
-    V: Debug + Clone,
-    M: LiftingCommutativeMonoid<(Point3d<X, Y, Z>, V)> + Debug,
-{
-    /// Create a control tree from a set of points, associated values, and desired ranks.
-    /// In case of duplicate points, will ignore all but one of them. Which one will be preserved
-    /// is unspecified -> ensure to only use this without duplicate points.
-    pub fn from_iter<I: Iterator<Item = (Point3d<X, Y, Z>, V, u8)>>(iter: I) -> Self {
-        let mut sorted: Vec<_> = iter.collect();
+/// A vertex on a path as returned by [`ControlNode::membership_path`]: its rank, its key, and its summary.
+pub type PathVertex<X, Y, Z, M> = (u8, Point3d<X, Y, Z>, M);
+
+/// A sub-range `[lo, hi)` as returned by [`ControlNode::split_range`]: its lower bound, its upper bound, and its monoidal summary.
+pub type SubRange<X, Y, Z, M> = (Point3d<X, Y, Z>, Point3d<X, Y, Z>, M);
+
+impl<X, Y, Z, V, M> ControlNode<X, Y, Z, V, M>
+where
+    X: Dimension + Clone + Debug + Hash,
+    Y: Dimension + Clone + Debug + Hash,
+    Z: Dimension + Clone + Debug + Hash,
+    V: Debug + Clone,
+    M: LiftingCommutativeMonoid<(Point3d<X, Y, Z>, V)> + Debug,
+{
+    /// Create a control tree from a set of points, associated values, and desired ranks.
+    /// In case of duplicate points, will ignore all but one of them. Which one will be preserved
+    /// is unspecified -> ensure to only use this without duplicate points.
+    pub fn from_iter<I: Iterator<Item = (Point3d<X, Y, Z>, V, u8)>>(iter: I) -> Self {
+        return Self::from_iter_with_cmp(iter, node_sort_key);
+    }
+
+    /// Create a control tree from a set of points, associated values, and desired ranks, inserting them in the order given by `cmp`.
+    /// In case of duplicate points, will ignore all but one of them, just like [`from_iter`](Self::from_iter).
+    ///
+    /// The comparator must sort by descending rank first, so that every vertex is inserted before its descendants. Within a rank, it may order the points arbitrarily, for example to match an external canonical form. The resulting tree is always a valid search tree, but it is the unique 3d-ish-zip-tree on the given points and ranks only if no vertex ends up with a left child of equal rank. Sorting each rank ascendingly, as [`node_sort_key`] does, guarantees this.
+    pub fn from_iter_with_cmp<I, C>(iter: I, cmp: C) -> Self
+    where
+        I: Iterator<Item = (Point3d<X, Y, Z>, V, u8)>,
+        C: FnMut(&(Point3d<X, Y, Z>, V, u8), &(Point3d<X, Y, Z>, V, u8)) -> Ordering,
+    {
+        let mut sorted: Vec<_> = iter.collect();
+
+        // Before we sort, remove all but the first occurence of each point.
+        let mut uniques = HashSet::new();
+        sorted.retain(|(point, _, _)| uniques.insert(point.clone()));
+
+        sorted.sort_by(cmp);
+
+        let mut tree = ControlNode::Empty;
+        for (point, value, rank) in sorted {
+            tree.insert_no_balance(point, value, rank);
+        }
+
+        return tree;
+    }
+
+    /// Create a control tree from a sequence of points, associated values, and desired ranks.
+    /// In case of duplicate points, the last occurrence (with its value and rank) wins.
+    pub fn from_iter_last_wins<I: Iterator<Item = (Point3d<X, Y, Z>, V, u8)>>(iter: I) -> Self {
+        let mut latest = HashMap::new();
+        for (point, value, rank) in iter {
+            latest.insert(point, (value, rank));
+        }
+
+        return Self::from_iter(
+            latest
+                .into_iter()
+                .map(|(point, (value, rank))| (point, value, rank)),
+        );
+    }
+
+    /// Create a control tree from a set of points and associated values, deriving the rank of each point-value pair via [`rank_from_hash`].
+    /// In case of duplicate points, will ignore all but one of them, just like [`from_iter`](Self::from_iter).
+    pub fn from_points<I: Iterator<Item = (Point3d<X, Y, Z>, V)>>(iter: I) -> Self
+    where
+        V: Hash,
+    {
+        return Self::from_iter(iter.map(|(point, value)| {
+            let rank = rank_from_hash(&point, &value);
+            (point, value, rank)
+        }));
+    }
+
+    /// Create a control tree from a set of points and associated values, deriving the rank of each point-value pair via [`rank_from_hash_capped`] with the given maximum rank.
+    /// In case of duplicate points, will ignore all but one of them, just like [`from_iter`](Self::from_iter).
+    pub fn from_points_capped<I: Iterator<Item = (Point3d<X, Y, Z>, V)>>(iter: I, max_rank: u8) -> Self
+    where
+        V: Hash,
+    {
+        return Self::from_iter(iter.map(|(point, value)| {
+            let rank = rank_from_hash_capped(&point, &value, max_rank);
+            (point, value, rank)
+        }));
+    }
+
+    // Insert point-value pair without rebalancing.
+    fn insert_no_balance(&mut self, point: Point3d<X, Y, Z>, value: V, rank: u8) {
+        let kv_pair = (point, value);
+        let summary = M::lift(&kv_pair);
+        let (point, value) = kv_pair;
+
+        match self {
+            ControlNode::Empty => {
+                *self = ControlNode::NonEmpty {
+                    key: point,
+                    rank: rank,
+                    left: Box::new(ControlNode::Empty),
+                    right: Box::new(ControlNode::Empty),
+                    value: value,
+                    count: 1,
+                    summary: summary,
+                }
+            }
+            ControlNode::NonEmpty {
+                key: parent_key,
+                rank: parent_rank,
+                left,
+                right,
+                ref mut count,
+                summary: parent_summary,
+                ..
+            } => {
+                match cmp_points_at_rank(*parent_rank, parent_key, &point) {
+                    Ordering::Equal => {
+                        panic!("Do not insert duplicate points into a control tree.")
+                    }
+                    Ordering::Less => {
+                        right.insert_no_balance(point, value, rank);
+                    }
+                    Ordering::Greater => {
+                        left.insert_no_balance(point, value, rank);
+                    }
+                }
+
+                *count = *count + 1;
+                *parent_summary = M::combine(parent_summary, &summary);
+            }
+        }
+    }
+
+    /// Insert a point-value pair, deriving its rank via [`rank_from_hash`], and return the value the point was previously mapped to, if any.
+    ///
+    /// The result is again the unique 3d-ish-zip-tree on all points and ranks, so inserting point-value pairs one by one yields the same tree as [`from_points`](Self::from_points). Since the rank depends on the value, a point that is already present is removed first, and then inserted with the rank of its new value.
+    pub fn insert(&mut self, point: Point3d<X, Y, Z>, value: V) -> Option<V>
+    where
+        V: Hash,
+    {
+        let old = self.remove(&point);
+        let rank = rank_from_hash(&point, &value);
+        self.insert_with_rank(point, value, rank);
+        return old;
+    }
+
+    // Insert a point that is not in the tree yet with the given rank: descend to where the new vertex takes precedence over the root of the subtree (just like in `union`), unzip that subtree along the point, and splice in the new vertex with the two parts as its children.
+    fn insert_with_rank(&mut self, point: Point3d<X, Y, Z>, value: V, rank: u8) {
+        let new_wins = match self {
+            ControlNode::Empty => true,
+            ControlNode::NonEmpty { key, rank: root_rank, .. } => match rank.cmp(root_rank) {
+                Ordering::Equal => cmp_points_at_rank(rank, &point, key) == Ordering::Less,
+                rank_cmp => rank_cmp == Ordering::Greater,
+            },
+        };
+
+        if new_wins {
+            let (less, greater) = mem::replace(self, ControlNode::Empty).split(&point, ordering_for_rank(rank));
+            *self = Self::new_node(point, rank, value, less, greater);
+            return;
+        }
+
+        match self {
+            ControlNode::NonEmpty {
+                key,
+                rank: root_rank,
+                left,
+                right,
+                ..
+            } => {
+                if cmp_points_at_rank(*root_rank, key, &point) == Ordering::Less {
+                    right.insert_with_rank(point, value, rank);
+                } else {
+                    left.insert_with_rank(point, value, rank);
+                }
+            }
+            ControlNode::Empty => unreachable!(),
+        }
+        self.recompute_aggregates();
+    }
+
+    /// Remove a point from the tree, and return its value if it was present.
+    ///
+    /// The children of the removed vertex are merged such that the result is again the unique 3d-ish-zip-tree on the remaining points and ranks.
+    pub fn remove(&mut self, point: &Point3d<X, Y, Z>) -> Option<V> {
+        let removed = match self {
+            ControlNode::Empty => return None,
+            ControlNode::NonEmpty {
+                key,
+                rank,
+                left,
+                right,
+                ..
+            } => match cmp_points_at_rank(*rank, key, point) {
+                Ordering::Less => right.remove(point),
+                Ordering::Greater => left.remove(point),
+                Ordering::Equal => {
+                    let left = mem::replace(left.as_mut(), ControlNode::Empty);
+                    let right = mem::replace(right.as_mut(), ControlNode::Empty);
+
+                    match mem::replace(self, Self::union(left, right)) {
+                        ControlNode::NonEmpty { value, .. } => return Some(value),
+                        ControlNode::Empty => unreachable!(),
+                    }
+                }
+            },
+        };
+
+        if removed.is_some() {
+            self.recompute_aggregates();
+        }
+
+        return removed;
+    }
+
+    /// Merge two trees into the tree over the union of their points.
+    ///
+    /// Points that occur in only one of the trees keep their values and ranks. A point that occurs in both trees keeps its rank from `self`, and is mapped to `resolve(value_in_self, value_in_other)`.
+    pub fn merge(self, mut other: Self, resolve: impl Fn(&V, &V) -> V) -> Self {
+        let mut merged = self;
+
+        let mut points = Vec::with_capacity(merged.len());
+        merged.collect_points(&mut points);
+
+        // Take the points that occur in both trees out of both trees, so that the remaining trees are disjoint.
+        let mut resolved = ControlNode::Empty;
+        for (point, rank) in points {
+            if let Some(other_value) = other.remove(&point) {
+                let own_value = merged.remove(&point).unwrap();
+                let value = resolve(&own_value, &other_value);
+
+                let singleton =
+                    Self::new_node(point, rank, value, ControlNode::Empty, ControlNode::Empty);
+                resolved = Self::union(resolved, singleton);
+            }
+        }
+
+        return Self::union(Self::union(merged, other), resolved);
+    }
+
+    // Push all points of the tree and their ranks onto `out`, in no particular order.
+    fn collect_points(&self, out: &mut Vec<(Point3d<X, Y, Z>, u8)>) {
+        if let ControlNode::NonEmpty {
+            key,
+            rank,
+            left,
+            right,
+            ..
+        } = self
+        {
+            out.push((key.clone(), *rank));
+            left.collect_points(out);
+            right.collect_points(out);
+        }
+    }
+
+    /// Rebuild the tree on the same point-value pairs, but with each rank derived via [`rank_from_hash`] instead.
+    ///
+    /// The result depends only on the point-value pairs, not on the ranks or on the history of the tree, so this turns a tree with externally specified ranks into the canonical one that [`from_points`](Self::from_points) would build.
+    pub fn rerank(self) -> Self
+    where
+        V: Hash,
+    {
+        let mut entries = Vec::with_capacity(self.len());
+        self.collect_entries(&mut entries);
+        return Self::from_points(entries.into_iter());
+    }
+
+    // Move all point-value pairs of the tree onto `out`, in no particular order.
+    fn collect_entries(self, out: &mut Vec<(Point3d<X, Y, Z>, V)>) {
+        if let ControlNode::NonEmpty {
+            key,
+            left,
+            right,
+            value,
+            ..
+        } = self
+        {
+            out.push((key, value));
+            left.collect_entries(out);
+            right.collect_entries(out);
+        }
+    }
+
+    /// Replace the value associated with a point, and return the old value. Does nothing and returns `None` if the point is not in the tree.
+    ///
+    /// The shape of the tree does not depend on values, so only the summaries along the path from the root to the point change. A monoid offers no way to remove the old value from a summary, so instead each summary on the path is recomputed from the vertex itself and the (unchanged or already updated) summaries of its two children, bottom-up.
+    pub fn set_value(&mut self, point: &Point3d<X, Y, Z>, new_value: V) -> Option<V> {
+        let old = match self {
+            ControlNode::Empty => return None,
+            ControlNode::NonEmpty {
+                key,
+                rank,
+                left,
+                right,
+                value,
+                ..
+            } => match cmp_points_at_rank(*rank, key, point) {
+                Ordering::Less => right.set_value(point, new_value),
+                Ordering::Greater => left.set_value(point, new_value),
+                Ordering::Equal => Some(mem::replace(value, new_value)),
+            },
+        };
+
+        if old.is_some() {
+            self.recompute_aggregates();
+        }
+
+        return old;
+    }
+
+    // Create a non-empty tree from a vertex and its children, computing count and summary.
+    fn new_node(key: Point3d<X, Y, Z>, rank: u8, value: V, left: Self, right: Self) -> Self {
+        let mut node = ControlNode::NonEmpty {
+            key,
+            rank,
+            left: Box::new(left),
+            right: Box::new(right),
+            value,
+            count: 0,
+            summary: M::NEUTRAL,
+        };
+        node.recompute_aggregates();
+
+        return node;
+    }
+
+    // Recompute count and summary of the root from the root vertex and the (correct) aggregates of its children.
+    fn recompute_aggregates(&mut self) {
+        if let ControlNode::NonEmpty {
+            key,
+            left,
+            right,
+            value,
+            count,
+            summary,
+            ..
+        } = self
+        {
+            let (left_count, left_summary) = left.aggregates();
+            let (right_count, right_summary) = right.aggregates();
+
+            *count = 1 + left_count + right_count;
+            let own = M::lift(&(key.clone(), value.clone()));
+            *summary = M::combine_all([&own, &left_summary, &right_summary]);
+        }
+    }
+
+    fn aggregates(&self) -> (usize, M) {
+        match self {
+            ControlNode::Empty => return (0, M::NEUTRAL),
+            ControlNode::NonEmpty { count, summary, .. } => {
+                // Combining with the neutral element gives us an owned summary without requiring `M: Clone`.
+                return (*count, M::combine(summary, &M::NEUTRAL));
+            }
+        }
+    }
+
+    // Combine two trees over disjoint sets of points into the 3d-ish-zip-tree over the union of their points.
+    //
+    // The root of the union is the root of higher rank (or, for equal ranks, the lesser root according to the ordering of that rank). The other tree is split by that root, and the parts are recursively combined with the children of the root.
+    fn union(a: Self, b: Self) -> Self {
+        match (a, b) {
+            (ControlNode::Empty, b) => return b,
+            (a, ControlNode::Empty) => return a,
+            (a, b) => {
+                let a_wins = match (&a, &b) {
+                    (
+                        ControlNode::NonEmpty {
+                            key: a_key,
+                            rank: a_rank,
+                            ..
+                        },
+                        ControlNode::NonEmpty {
+                            key: b_key,
+                            rank: b_rank,
+                            ..
+                        },
+                    ) => match a_rank.cmp(b_rank) {
+                        Ordering::Equal => {
+                            cmp_points_at_rank(*a_rank, a_key, b_key) == Ordering::Less
+                        }
+                        rank_cmp => rank_cmp == Ordering::Greater,
+                    },
+                    _ => unreachable!(),
+                };
+
+                let (winner, loser) = if a_wins { (a, b) } else { (b, a) };
+
+                match winner {
+                    ControlNode::NonEmpty {
+                        key,
+                        rank,
+                        left,
+                        right,
+                        value,
+                        ..
+                    } => {
+                        let (loser_less, loser_greater) =
+                            loser.split(&key, ordering_for_rank(rank));
+
+                        return Self::new_node(
+                            key,
+                            rank,
+                            value,
+                            Self::union(*left, loser_less),
+                            Self::union(*right, loser_greater),
+                        );
+                    }
+                    ControlNode::Empty => unreachable!(),
+                }
+            }
+        }
+    }
+
+    // Split a tree that does not contain `point` into the trees of all points less than `point` and all points greater than `point`, according to the given ordering.
+    fn split(self, point: &Point3d<X, Y, Z>, ordering: Ordering3d) -> (Self, Self) {
+        match self {
+            ControlNode::Empty => return (ControlNode::Empty, ControlNode::Empty),
+            ControlNode::NonEmpty {
+                key,
+                rank,
+                left,
+                right,
+                value,
+                ..
+            } => {
+                let key_is_less = key.cmp_by(point, ordering) == Ordering::Less;
+
+                if ordering_for_rank(rank) == ordering {
+                    // The key separates the subtrees according to the ordering of the split, so only one subtree needs splitting.
+                    if key_is_less {
+                        let (right_less, right_greater) = right.split(point, ordering);
+                        return (
+                            Self::new_node(key, rank, value, *left, right_less),
+                            right_greater,
+                        );
+                    } else {
+                        let (left_less, left_greater) = left.split(point, ordering);
+                        return (
+                            left_less,
+                            Self::new_node(key, rank, value, left_greater, *right),
+                        );
+                    }
+                } else {
+                    let (left_less, left_greater) = left.split(point, ordering);
+                    let (right_less, right_greater) = right.split(point, ordering);
+
+                    if key_is_less {
+                        return (
+                            Self::new_node(key, rank, value, left_less, right_less),
+                            Self::union(left_greater, right_greater),
+                        );
+                    } else {
+                        return (
+                            Self::union(left_less, right_less),
+                            Self::new_node(key, rank, value, left_greater, right_greater),
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    /// Return the number of points in the tree.
+    pub fn len(&self) -> usize {
+        match self {
+            ControlNode::Empty => return 0,
+            ControlNode::NonEmpty { count, .. } => return *count,
+        }
+    }
+
+    /// Return whether the tree contains no points.
+    pub fn is_empty(&self) -> bool {
+        return self.len() == 0;
+    }
+
+    /// Return the value associated with the given point, or `None` if the point is not in the tree.
+    pub fn get(&self, point: &Point3d<X, Y, Z>) -> Option<&V> {
+        match self {
+            ControlNode::Empty => return None,
+            ControlNode::NonEmpty {
+                key,
+                rank,
+                left,
+                right,
+                value,
+                ..
+            } => match cmp_points_at_rank(*rank, key, point) {
+                Ordering::Less => return right.get(point),
+                Ordering::Greater => return left.get(point),
+                Ordering::Equal => return Some(value),
+            },
+        }
+    }
+
+    /// Return the rank of the vertex of the given point, or `None` if the point is not in the tree.
+    pub fn rank_of(&self, point: &Point3d<X, Y, Z>) -> Option<u8> {
+        match self {
+            ControlNode::Empty => return None,
+            ControlNode::NonEmpty {
+                key,
+                rank,
+                left,
+                right,
+                ..
+            } => match cmp_points_at_rank(*rank, key, point) {
+                Ordering::Less => return right.rank_of(point),
+                Ordering::Greater => return left.rank_of(point),
+                Ordering::Equal => return Some(*rank),
+            },
+        }
+    }
+
+    /// Return the rank, key, and summary of every vertex on the path from the root to the vertex of the given point (inclusive), or `None` if the point is not in the tree.
+    ///
+    /// Together with the summaries of the siblings along the path, this lets a verifier recompute the summary of the root, which is the first piece of authenticating query results. Ranks never increase along the path, and they strictly decrease wherever the path descends to a left child.
+    pub fn membership_path(&self, point: &Point3d<X, Y, Z>) -> Option<Vec<PathVertex<X, Y, Z, M>>>
+    where
+        M: Clone,
+    {
+        let mut path = vec![];
+        let mut node = self;
+
+        loop {
+            match node {
+                ControlNode::Empty => return None,
+                ControlNode::NonEmpty {
+                    key,
+                    rank,
+                    left,
+                    right,
+                    summary,
+                    ..
+                } => {
+                    path.push((*rank, key.clone(), summary.clone()));
+
+                    match cmp_points_at_rank(*rank, key, point) {
+                        Ordering::Less => node = right,
+                        Ordering::Greater => node = left,
+                        Ordering::Equal => return Some(path),
+                    }
+                }
+            }
+        }
+    }
+
+    /// Return the point-value pair whose point is the `k`-th smallest (counting from zero) according to the [xyz ordering](Point3d::cmp_xyz), or `None` if the tree contains at most `k` points.
+    ///
+    /// Vertices whose rank calls for the xyz ordering partition their subtree accordingly, so the search descends through them via the `count` fields. Below any other vertex, the points of the subtree are not partitioned by the xyz ordering, so the search collects them and selects the `k`-th one in linear time.
+    pub fn nth_xyz(&self, k: usize) -> Option<(&Point3d<X, Y, Z>, &V)> {
+        match self {
+            ControlNode::Empty => return None,
+            ControlNode::NonEmpty {
+                key,
+                rank,
+                left,
+                right,
+                value,
+                count,
+                ..
+            } => {
+                if k >= *count {
+                    return None;
+                }
+
+                if ordering_for_rank(*rank) == Ordering3d::Xyz {
+                    match k.cmp(&left.len()) {
+                        Ordering::Less => return left.nth_xyz(k),
+                        Ordering::Equal => return Some((key, value)),
+                        Ordering::Greater => return right.nth_xyz(k - left.len() - 1),
+                    }
+                } else {
+                    let mut entries = Vec::with_capacity(*count);
+                    self.collect_entry_refs(&mut entries);
+                    let (_, nth, _) =
+                        entries.select_nth_unstable_by(k, |(p1, _), (p2, _)| p1.cmp_xyz(p2));
+                    return Some(*nth);
+                }
+            }
+        }
+    }
+
+    /// Return the number of points in the tree that are strictly less than `point` according to the [xyz ordering](Point3d::cmp_xyz). This is the inverse of [`nth_xyz`](Self::nth_xyz), and not to be confused with the ranks of the zip tree.
+    ///
+    /// Below vertices whose rank calls for the xyz ordering, only one child needs to be searched, since the other one lies entirely on one side of `point`. Below any other vertex, both children need to be searched.
+    pub fn rank_xyz(&self, point: &Point3d<X, Y, Z>) -> usize {
+        match self {
+            ControlNode::Empty => return 0,
+            ControlNode::NonEmpty {
+                key,
+                rank,
+                left,
+                right,
+                ..
+            } => {
+                let key_is_less = key.cmp_xyz(point) == Ordering::Less;
+
+                if ordering_for_rank(*rank) == Ordering3d::Xyz {
+                    if key_is_less {
+                        return left.len() + 1 + right.rank_xyz(point);
+                    } else {
+                        return left.rank_xyz(point);
+                    }
+                } else {
+                    return key_is_less as usize + left.rank_xyz(point) + right.rank_xyz(point);
+                }
+            }
+        }
+    }
+
+    // Push references to all point-value pairs of the tree onto `out`, in no particular order.
+    fn collect_entry_refs<'a>(&'a self, out: &mut Vec<(&'a Point3d<X, Y, Z>, &'a V)>) {
+        if let ControlNode::NonEmpty {
+            key,
+            left,
+            right,
+            value,
+            ..
+        } = self
+        {
+            out.push((key, value));
+            left.collect_entry_refs(out);
+            right.collect_entry_refs(out);
+        }
+    }
+
+    /// Return how many vertices of each rank the tree contains. Ranks without any vertices do not appear in the map.
+    ///
+    /// For trees whose ranks come from [`rank_from_hash`], about half of all vertices should have rank zero, a quarter rank one, and so on.
+    pub fn rank_histogram(&self) -> BTreeMap<u8, usize> {
+        let mut histogram = BTreeMap::new();
+        self.add_to_rank_histogram(&mut histogram);
+        return histogram;
+    }
+
+    // Count the vertices of this tree into the histogram.
+    fn add_to_rank_histogram(&self, histogram: &mut BTreeMap<u8, usize>) {
+        if let ControlNode::NonEmpty {
+            rank, left, right, ..
+        } = self
+        {
+            *histogram.entry(*rank).or_insert(0) += 1;
+            left.add_to_rank_histogram(histogram);
+            right.add_to_rank_histogram(histogram);
+        }
+    }
+
+    /// Return the number of vertices on the longest path from the root to a leaf, or zero if the tree is empty.
+    pub fn height(&self) -> usize {
+        match self {
+            ControlNode::Empty => return 0,
+            ControlNode::NonEmpty { left, right, .. } => return 1 + left.height().max(right.height()),
+        }
+    }
+
+    /// Return the number of vertices on the shortest path from the root to a leaf (a vertex without children), or zero if the tree is empty.
+    pub fn min_leaf_depth(&self) -> usize {
+        match self {
+            ControlNode::Empty => return 0,
+            ControlNode::NonEmpty { left, right, .. } => match (left.is_empty(), right.is_empty()) {
+                (true, true) => return 1,
+                (false, true) => return 1 + left.min_leaf_depth(),
+                (true, false) => return 1 + right.min_leaf_depth(),
+                (false, false) => return 1 + left.min_leaf_depth().min(right.min_leaf_depth()),
+            },
+        }
+    }
+
+    /// Return the average over all vertices of the number of vertices on the path from the root to that vertex (so the root has depth one), or zero if the tree is empty.
+    pub fn average_depth(&self) -> f64 {
+        if self.is_empty() {
+            return 0.0;
+        } else {
+            return self.depth_sum() as f64 / self.len() as f64;
+        }
+    }
+
+    // Return the sum of the depths of all vertices, relative to this vertex.
+    fn depth_sum(&self) -> usize {
+        match self {
+            ControlNode::Empty => return 0,
+            ControlNode::NonEmpty {
+                left, right, count, ..
+            } => return count + left.depth_sum() + right.depth_sum(),
+        }
+    }
+
+    /// Render the tree as a [Graphviz](https://graphviz.org) graph in the DOT language. Every vertex is labeled with its point, rank, and summary, and every edge with the direction (`left` or `right`) of the child it leads to.
+    ///
+    /// Pipe the output into `dot -Tsvg` to draw the tree, e.g., to inspect the tree of a failing fuzz input.
+    pub fn to_dot(&self) -> String
+    where
+        X: Display,
+        Y: Display,
+        Z: Display,
+    {
+        let mut out = String::from("digraph {\n");
+        let mut next_id = 0;
+        self.write_dot(&mut out, &mut next_id);
+        out.push_str("}\n");
+
+        return out;
+    }
+
+    // Append the vertices and edges of this tree to `out`, numbering the vertices in preorder starting at `next_id`. Return the number of the root, if any.
+    fn write_dot(&self, out: &mut String, next_id: &mut usize) -> Option<usize>
+    where
+        X: Display,
+        Y: Display,
+        Z: Display,
+    {
+        match self {
+            ControlNode::Empty => return None,
+            ControlNode::NonEmpty {
+                key,
+                rank,
+                left,
+                right,
+                summary,
+                ..
+            } => {
+                let id = *next_id;
+                *next_id += 1;
+
+                let label = format!("{}\nrank {}\nsummary {:?}", key, rank, summary);
+                out.push_str(&format!("    n{} [label=\"{}\"];\n", id, escape_dot(&label)));
+
+                for (child, direction) in [(left, "left"), (right, "right")] {
+                    if let Some(child_id) = child.write_dot(out, next_id) {
+                        out.push_str(&format!(
+                            "    n{} -> n{} [label=\"{}\"];\n",
+                            id, child_id, direction
+                        ));
+                    }
+                }
+
+                return Some(id);
+            }
+        }
+    }
+
+    /// Return the accumulated monoidal value over all point-value pairs in the tree.
+    pub fn summary(&self) -> M
+    where
+        M: Clone,
+    {
+        match self {
+            ControlNode::Empty => return M::NEUTRAL,
+            ControlNode::NonEmpty { summary, .. } => return summary.clone(),
+        }
+    }
+
+    /// Return the accumulated monoidal value over all point-value pairs in the tree. Same as [`summary`](Self::summary).
+    pub fn summarize(&self) -> M
+    where
+        M: Clone,
+    {
+        return self.summary();
+    }
+
+    /// Return the accumulated monoidal value over all point-value pairs whose point lies in the half-open range `[lo, hi)` according to the given ordering.
+    pub fn summarize_range(&self, ordering: Ordering3d, lo: &Point3d<X, Y, Z>, hi: &Point3d<X, Y, Z>) -> M
+    where
+        M: Clone,
+    {
+        return self.fold_range(ordering, lo, hi, false, false, &Self::summary);
+    }
+
+    /// Return the number of points that lie in the half-open range `[lo, hi)` according to the given ordering.
+    pub fn count_range(&self, ordering: Ordering3d, lo: &Point3d<X, Y, Z>, hi: &Point3d<X, Y, Z>) -> usize {
+        return self.fold_range(ordering, lo, hi, false, false, &|node| node.len());
+    }
+
+    /// Partition the half-open range `[lo, hi)` according to the given ordering into at most `k` contiguous sub-ranges that contain roughly equal numbers of points, and annotate each sub-range with its monoidal summary.
+    ///
+    /// The first sub-range starts at `lo`, the last one ends at `hi`, and each other sub-range starts where its predecessor ends. If the range contains fewer than `k` points, every sub-range contains exactly one point. If it contains no points at all, the result is the whole range. Panics if `k` is zero.
+    pub fn split_range(
+        &self,
+        ordering: Ordering3d,
+        lo: &Point3d<X, Y, Z>,
+        hi: &Point3d<X, Y, Z>,
+        k: usize,
+    ) -> Vec<SubRange<X, Y, Z, M>>
+    where
+        M: Clone,
+    {
+        assert!(k > 0, "Cannot split a range into zero sub-ranges.");
+
+        let count = self.count_range(ordering, lo, hi);
+        let parts = k.min(count);
+        if parts <= 1 {
+            return vec![(lo.clone(), hi.clone(), self.summarize_range(ordering, lo, hi))];
+        }
+
+        // The i-th sub-range starts at the point with index `i * count / parts` (except for the first one, which starts at `lo`).
+        let mut boundaries = Vec::with_capacity(parts + 1);
+        boundaries.push(lo.clone());
+        for i in 1..parts {
+            let start = self.select_range(ordering, lo, hi, i * count / parts);
+            boundaries.push(start.clone());
+        }
+        boundaries.push(hi.clone());
+
+        return boundaries
+            .windows(2)
+            .map(|bounds| {
+                let summary = self.summarize_range(ordering, &bounds[0], &bounds[1]);
+                (bounds[0].clone(), bounds[1].clone(), summary)
+            })
+            .collect();
+    }
+
+    // Return the point with the given index among the points in the range `[lo, hi)`, sorted according to the given ordering. Panics if the index is out of bounds.
+    //
+    // Descends through a forest of subtrees that together contain all candidates, using the `count` fields (via `count_range`) to decide on which side of each visited key the point lies. The forest starts as the whole tree; visiting a key replaces its subtree with its children. Where the rank of a vertex governs a different ordering than that of the range, both children may contain candidates, which is why a forest is needed rather than a single path.
+    fn select_range(
+        &self,
+        ordering: Ordering3d,
+        lo: &Point3d<X, Y, Z>,
+        hi: &Point3d<X, Y, Z>,
+        mut index: usize,
+    ) -> &Point3d<X, Y, Z> {
+        let mut lo = lo.clone();
+        let mut hi = hi.clone();
+        let mut forest = vec![self];
+
+        while let Some(tree) = forest.pop() {
+            if let ControlNode::NonEmpty { key, left, right, .. } = tree {
+                forest.push(left.as_ref());
+                forest.push(right.as_ref());
+
+                let key_ge_lo = key.cmp_by(&lo, ordering) != Ordering::Less;
+                let key_lt_hi = key.cmp_by(&hi, ordering) == Ordering::Less;
+
+                if key_ge_lo && key_lt_hi {
+                    // The number of candidates that are less than the key.
+                    let less: usize = forest
+                        .iter()
+                        .map(|candidates| candidates.count_range(ordering, &lo, key))
+                        .sum();
+
+                    match index.cmp(&less) {
+                        Ordering::Equal => return key,
+                        Ordering::Less => hi = key.clone(),
+                        Ordering::Greater => {
+                            // The key itself is no candidate anymore, so `key` may serve as the inclusive lower bound.
+                            index -= less + 1;
+                            lo = key.clone();
+                        }
+                    }
+                }
+
+                forest.retain(|candidates| candidates.count_range(ordering, &lo, &hi) > 0);
+            }
+        }
+
+        panic!("Index out of bounds of the range.");
+    }
+
+    // Push all point-value pairs whose point lies in the range `[lo, hi)` onto `out`, in no particular order. A missing bound does not restrict the range.
+    fn collect_range<'a>(
+        &'a self,
+        ordering: Ordering3d,
+        lo: Option<&Point3d<X, Y, Z>>,
+        hi: Option<&Point3d<X, Y, Z>>,
+        out: &mut Vec<(&'a Point3d<X, Y, Z>, &'a V)>,
+    ) {
+        if let ControlNode::NonEmpty {
+            key,
+            rank,
+            left,
+            right,
+            value,
+            ..
+        } = self
+        {
+            let key_ge_lo = lo.is_none_or(|lo| key.cmp_by(lo, ordering) != Ordering::Less);
+            let key_lt_hi = hi.is_none_or(|hi| key.cmp_by(hi, ordering) == Ordering::Less);
+
+            if key_ge_lo && key_lt_hi {
+                out.push((key, value));
+            }
+
+            // If the key separates the subtrees according to the ordering of the range, we can prune.
+            let prune = ordering_for_rank(*rank) == ordering;
+            if key_ge_lo || !prune {
+                left.collect_range(ordering, lo, hi, out);
+            }
+            if key_lt_hi || !prune {
+                right.collect_range(ordering, lo, hi, out);
+            }
+        }
+    }
+
+    // Accumulate a monoid over all point-value pairs in the range `[lo, hi)`, using `whole` to obtain the monoidal value of an entire subtree once it is known to lie within the range.
+    // `lo_ok` and `hi_ok` indicate that all points in this subtree are already known to be greater than or equal to `lo` or strictly less than `hi` respectively.
+    fn fold_range<A: LiftingCommutativeMonoid<(Point3d<X, Y, Z>, V)>>(
+        &self,
+        ordering: Ordering3d,
+        lo: &Point3d<X, Y, Z>,
+        hi: &Point3d<X, Y, Z>,
+        lo_ok: bool,
+        hi_ok: bool,
+        whole: &impl Fn(&Self) -> A,
+    ) -> A {
+        match self {
+            ControlNode::Empty => return A::NEUTRAL,
+            ControlNode::NonEmpty {
+                key,
+                rank,
+                left,
+                right,
+                value,
+                ..
+            } => {
+                if lo_ok && hi_ok {
+                    return whole(self);
+                }
+
+                let key_ge_lo = lo_ok || key.cmp_by(lo, ordering) != Ordering::Less;
+                let key_vs_hi = if hi_ok {
+                    Ordering::Less
+                } else {
+                    key.cmp_by(hi, ordering)
+                };
+                let key_lt_hi = key_vs_hi == Ordering::Less;
+
+                let own = if key_ge_lo && key_lt_hi {
+                    A::lift(&(key.clone(), value.clone()))
+                } else {
+                    A::NEUTRAL
+                };
+
+                if ordering_for_rank(*rank) == ordering {
+                    // The key separates the subtrees according to the ordering of the range, so we can prune.
+                    // All points in the left subtree are less than the key, all points in the right subtree are greater than the key.
+                    let left_summary = if key_ge_lo {
+                        let key_le_hi = key_vs_hi != Ordering::Greater;
+                        left.fold_range(ordering, lo, hi, lo_ok, key_le_hi, whole)
+                    } else {
+                        A::NEUTRAL
+                    };
+                    let right_summary = if key_lt_hi {
+                        right.fold_range(ordering, lo, hi, key_ge_lo, hi_ok, whole)
+                    } else {
+                        A::NEUTRAL
+                    };
 
-        // Before we sort, remove all but the first occurence of each point.
-        let mut uniques = HashSet::new();
-        sorted.retain(|(point, _, _)| uniques.insert(point.clone()));
+                    return A::combine_all([&own, &left_summary, &right_summary]);
+                } else {
+                    let left_summary = left.fold_range(ordering, lo, hi, lo_ok, hi_ok, whole);
+                    let right_summary = right.fold_range(ordering, lo, hi, lo_ok, hi_ok, whole);
 
-        // Sort by descending rank, and ascending according to the rank-appropriate order within each rank.
-        sorted.sort_by(|(p1, _, rank1), (p2, _, rank2)| {
-            match rank2.cmp(rank1) {
-                Ordering::Equal => return cmp_points_at_rank(*rank1, p1, p2),
-                _ => return rank2.cmp(rank1), // The unintuitive ordering results in *descending* sorting.
+                    return A::combine_all([&own, &left_summary, &right_summary]);
+                }
             }
-        });
-
-        let mut tree = ControlNode::Empty;
-        for (point, value, rank) in sorted {
-            tree.insert_no_balance(point, value, rank);
         }
+    }
 
-        return tree;
+    /// Return all point-value pairs whose point lies in the closed axis-aligned box spanned by `lo` and `hi`, that is, all points `p` with `lo.x <= p.x <= hi.x`, `lo.y <= p.y <= hi.y`, and `lo.z <= p.z <= hi.z`. The pairs are sorted by their points in xyz order.
+    pub fn query_box(&self, lo: &Point3d<X, Y, Z>, hi: &Point3d<X, Y, Z>) -> Vec<(&Point3d<X, Y, Z>, &V)> {
+        let mut out = vec![];
+        self.collect_box(lo, hi, &mut out);
+        out.sort_by(|(p1, _), (p2, _)| p1.cmp_xyz(p2));
+        return out;
     }
 
-    // Insert point-value pair without rebalancing.
-    fn insert_no_balance(&mut self, point: Point3d<X, Y, Z>, value: V, rank: u8) {
-        let kv_pair = (point, value);
-        let summary = M::lift(&kv_pair);
-        let (point, value) = kv_pair;
+    // Push all point-value pairs in the box spanned by `lo` and `hi` onto `out`, in no particular order.
+    fn collect_box<'a>(
+        &'a self,
+        lo: &Point3d<X, Y, Z>,
+        hi: &Point3d<X, Y, Z>,
+        out: &mut Vec<(&'a Point3d<X, Y, Z>, &'a V)>,
+    ) {
+        if let ControlNode::NonEmpty {
+            key,
+            rank,
+            left,
+            right,
+            value,
+            ..
+        } = self
+        {
+            let in_box = lo.x <= key.x
+                && key.x <= hi.x
+                && lo.y <= key.y
+                && key.y <= hi.y
+                && lo.z <= key.z
+                && key.z <= hi.z;
+            if in_box {
+                out.push((key, value));
+            }
 
-        match self {
-            ControlNode::Empty => {
-                *self = ControlNode::NonEmpty {
-                    key: point,
-                    rank: rank,
-                    left: Box::new(ControlNode::Empty),
-                    right: Box::new(ControlNode::Empty),
-                    value: value,
-                    count: 1,
-                    summary: summary,
-                }
+            // In every ordering, `lo` is less than or equal to all points in the box, and `hi` is greater than or equal to all of them.
+            // So if the key is not greater than `lo` according to the ordering of its rank, no point of the left subtree can lie in the box, and likewise for `hi` and the right subtree.
+            let ordering = ordering_for_rank(*rank);
+            if key.cmp_by(lo, ordering) == Ordering::Greater {
+                left.collect_box(lo, hi, out);
             }
+            if key.cmp_by(hi, ordering) == Ordering::Less {
+                right.collect_box(lo, hi, out);
+            }
+        }
+    }
+
+    /// Return the accumulated monoidal value over all point-value pairs whose point lies in the closed axis-aligned box spanned by `lo` and `hi`, see [`query_box`](Self::query_box).
+    pub fn summarize_box(&self, lo: &Point3d<X, Y, Z>, hi: &Point3d<X, Y, Z>) -> M
+    where
+        M: Clone,
+    {
+        return self.fold_box(lo, hi, DimensionBounds::NONE, DimensionBounds::NONE, DimensionBounds::NONE);
+    }
+
+    // Accumulate the monoid over all point-value pairs in the box spanned by `lo` and `hi`.
+    // The `DimensionBounds` are what the ancestors of this subtree guarantee about the dimensions of its points. Once they show that the subtree lies entirely inside the box, we use its summary instead of descending further.
+    fn fold_box<'a>(
+        &'a self,
+        lo: &Point3d<X, Y, Z>,
+        hi: &Point3d<X, Y, Z>,
+        x: DimensionBounds<&'a X>,
+        y: DimensionBounds<&'a Y>,
+        z: DimensionBounds<&'a Z>,
+    ) -> M
+    where
+        M: Clone,
+    {
+        match self {
+            ControlNode::Empty => return M::NEUTRAL,
             ControlNode::NonEmpty {
-                key: parent_key,
-                rank: parent_rank,
+                key,
+                rank,
                 left,
                 right,
-                ref mut count,
-                summary: parent_summary,
+                value,
+                summary,
                 ..
             } => {
-                match cmp_points_at_rank(*parent_rank, parent_key, &point) {
-                    Ordering::Equal => {
-                        panic!("Do not insert duplicate points into a control tree.")
-                    }
-                    Ordering::Less => {
-                        right.insert_no_balance(point, value, rank);
+                if x.within(&lo.x, &hi.x) && y.within(&lo.y, &hi.y) && z.within(&lo.z, &hi.z) {
+                    return summary.clone();
+                }
+
+                let in_box = lo.x <= key.x
+                    && key.x <= hi.x
+                    && lo.y <= key.y
+                    && key.y <= hi.y
+                    && lo.z <= key.z
+                    && key.z <= hi.z;
+                let own = if in_box {
+                    M::lift(&(key.clone(), value.clone()))
+                } else {
+                    M::NEUTRAL
+                };
+
+                // Prune just like `collect_box`. Only the leading dimension of the ordering of the rank bounds the dimensions of the points in the subtrees.
+                let ordering = ordering_for_rank(*rank);
+                let left_summary = if key.cmp_by(lo, ordering) == Ordering::Greater {
+                    match ordering {
+                        Ordering3d::Xyz => left.fold_box(lo, hi, x.at_most(&key.x), y, z),
+                        Ordering3d::Yzx => left.fold_box(lo, hi, x, y.at_most(&key.y), z),
+                        Ordering3d::Zxy => left.fold_box(lo, hi, x, y, z.at_most(&key.z)),
                     }
-                    Ordering::Greater => {
-                        left.insert_no_balance(point, value, rank);
+                } else {
+                    M::NEUTRAL
+                };
+                let right_summary = if key.cmp_by(hi, ordering) == Ordering::Less {
+                    match ordering {
+                        Ordering3d::Xyz => right.fold_box(lo, hi, x.at_least(&key.x), y, z),
+                        Ordering3d::Yzx => right.fold_box(lo, hi, x, y.at_least(&key.y), z),
+                        Ordering3d::Zxy => right.fold_box(lo, hi, x, y, z.at_least(&key.z)),
                     }
-                }
+                } else {
+                    M::NEUTRAL
+                };
 
-                *count = *count + 1;
-                *parent_summary = M::combine(parent_summary, &summary);
+                return M::combine_all([&own, &left_summary, &right_summary]);
+            }
+        }
+    }
+
+    /// Panic if the points of this tree are not exactly the keys of the given map, or if any point is associated with a different value than in the map.
+    pub fn assert_matches_map(&self, map: &HashMap<Point3d<X, Y, Z>, V>)
+    where
+        V: PartialEq,
+    {
+        for (point, expected) in map.iter() {
+            match self.get(point) {
+                None => panic!("tree lacks the point {:?} of the map", point),
+                Some(actual) => assert_eq!(actual, expected, "value of the point {:?}", point),
+            }
+        }
+
+        // The tree contains every key of the map, so it contains no other points iff the sizes match.
+        assert_eq!(
+            self.len(),
+            map.len(),
+            "tree contains points that are not in the map"
+        );
+    }
+
+    /// Panic if the given kv-tree vertices, with the given root key, do not represent exactly this tree.
+    pub fn assert_matches_kv_nodes(
+        &self,
+        nodes: &BTreeMap<Vec<u8>, KvNode<V, M>>,
+        root: Option<&[u8]>,
+    ) where
+        V: PartialEq,
+    {
+        assert_eq!(self.len(), nodes.len());
+        self.do_assert_matches_kv_nodes(nodes, root);
+    }
+
+    fn do_assert_matches_kv_nodes(
+        &self,
+        nodes: &BTreeMap<Vec<u8>, KvNode<V, M>>,
+        root: Option<&[u8]>,
+    ) where
+        V: PartialEq,
+    {
+        match self {
+            ControlNode::Empty => assert_eq!(root, None),
+            ControlNode::NonEmpty {
+                key,
+                rank,
+                left,
+                right,
+                value,
+                count,
+                summary,
+            } => {
+                let root = root.expect("kv-tree lacks a vertex");
+                let rank = Rank::new(*rank).expect("control tree has the reserved rank");
+                assert_eq!(root, encode_tree_key(rank, key).as_slice());
+
+                let node = &nodes[root];
+                assert_eq!(node.rank, rank);
+                assert_eq!(&node.value, value);
+                assert_eq!(&node.count, count);
+                assert_eq!(&node.summary, summary);
+
+                left.do_assert_matches_kv_nodes(nodes, node.left_key().as_deref());
+                right.do_assert_matches_kv_nodes(nodes, node.right_key().as_deref());
             }
         }
     }
 
+    /// Panic if the count or summary stored at any vertex differs from the count or summary recomputed from scratch, by folding [`lift`](LiftingCommutativeMonoid::lift) over all point-value pairs in the subtree of the vertex.
+    ///
+    /// This takes quadratic time in the worst case, as every vertex folds over its whole subtree.
+    pub fn assert_summaries_consistent(&self)
+    where
+        M: PartialEq,
+    {
+        if let ControlNode::NonEmpty {
+            key,
+            left,
+            right,
+            count,
+            summary,
+            ..
+        } = self
+        {
+            let mut entries = vec![];
+            self.collect_entry_refs(&mut entries);
+            let pairs: Vec<_> = entries
+                .into_iter()
+                .map(|(point, value)| (point.clone(), value.clone()))
+                .collect();
+
+            assert_eq!(*count, pairs.len(), "count of the subtree at {:?}", key);
+            assert_eq!(*summary, M::lift_all(&pairs), "summary of the subtree at {:?}", key);
+
+            left.assert_summaries_consistent();
+            right.assert_summaries_consistent();
+        }
+    }
+
     /// Panic if self is not a valid 3d-ish-zip-tree.
     /// This is for testing purposes, and *should* never panic...
     pub fn assert_tree_invariants(&self) {
@@ -343,6 +1461,442 @@ where
     }
 }
 
+// Ranges with at most this many points in both trees are diffed by comparing their points directly.
+const DIFF_LEAF_SIZE: usize = 8;
+
+/// The result of [`ControlNode::diff`]: the points that are only in the first tree, and the points that are only in the second tree.
+pub type Difference<X, Y, Z> = (Vec<Point3d<X, Y, Z>>, Vec<Point3d<X, Y, Z>>);
+
+// The least and the greatest point of a tree according to some ordering.
+type Extremes<'a, X, Y, Z> = (&'a Point3d<X, Y, Z>, &'a Point3d<X, Y, Z>);
+
+impl<X, Y, Z, V> ControlNode<X, Y, Z, V, XorFingerprint>
+where
+    X: Dimension + Clone + Debug + Hash,
+    Y: Dimension + Clone + Debug + Hash,
+    Z: Dimension + Clone + Debug + Hash,
+    V: Debug + Clone,
+{
+    /// Return the points that are only in `self`, and the points that are only in `other`, each sorted according to the given ordering.
+    ///
+    /// Compares the fingerprints of both trees over matching ranges of the given ordering, and only descends into ranges whose fingerprints differ, splitting them in half (by the number of points of the larger tree) each time. Ranges with few points are compared point by point. Ranges with equal fingerprints are assumed to contain the same points, see [`XorFingerprint`] for the probability of that assumption being wrong.
+    pub fn diff(&self, other: &Self, ordering: Ordering3d) -> Difference<X, Y, Z> {
+        let mut only_self = vec![];
+        let mut only_other = vec![];
+
+        let (lo, hi) = match (self.extremes(ordering), other.extremes(ordering)) {
+            (None, None) => return (only_self, only_other),
+            (Some(extremes), None) | (None, Some(extremes)) => extremes,
+            (Some((lo1, hi1)), Some((lo2, hi2))) => (
+                min_by(lo1, lo2, ordering),
+                max_by(hi1, hi2, ordering),
+            ),
+        };
+
+        self.diff_range(other, ordering, lo, hi, &mut only_self, &mut only_other);
+
+        // The ranges are half-open, so the greatest point needs to be compared separately.
+        match (self.rank_of(hi).is_some(), other.rank_of(hi).is_some()) {
+            (true, false) => only_self.push(hi.clone()),
+            (false, true) => only_other.push(hi.clone()),
+            _ => {}
+        }
+
+        return (only_self, only_other);
+    }
+
+    // Append the points in `[lo, hi)` that are only in `self` and only in `other` to the respective vector, in ascending order.
+    fn diff_range(
+        &self,
+        other: &Self,
+        ordering: Ordering3d,
+        lo: &Point3d<X, Y, Z>,
+        hi: &Point3d<X, Y, Z>,
+        only_self: &mut Vec<Point3d<X, Y, Z>>,
+        only_other: &mut Vec<Point3d<X, Y, Z>>,
+    ) {
+        if self.summarize_range(ordering, lo, hi) == other.summarize_range(ordering, lo, hi) {
+            return;
+        }
+
+        let self_count = self.count_range(ordering, lo, hi);
+        let other_count = other.count_range(ordering, lo, hi);
+
+        if self_count.max(other_count) <= DIFF_LEAF_SIZE {
+            let mut mine = vec![];
+            self.collect_range(ordering, Some(lo), Some(hi), &mut mine);
+            mine.sort_by(|(p1, _), (p2, _)| p1.cmp_by(p2, ordering));
+
+            let mut theirs = vec![];
+            other.collect_range(ordering, Some(lo), Some(hi), &mut theirs);
+            theirs.sort_by(|(p1, _), (p2, _)| p1.cmp_by(p2, ordering));
+
+            // Merge the two sorted lists, keeping the points that occur in only one of them.
+            let (mut i, mut j) = (0, 0);
+            while i < mine.len() || j < theirs.len() {
+                let cmp = match (mine.get(i), theirs.get(j)) {
+                    (Some((p1, _)), Some((p2, _))) => p1.cmp_by(p2, ordering),
+                    (Some(_), None) => Ordering::Less,
+                    _ => Ordering::Greater,
+                };
+
+                match cmp {
+                    Ordering::Less => {
+                        only_self.push(mine[i].0.clone());
+                        i += 1;
+                    }
+                    Ordering::Greater => {
+                        only_other.push(theirs[j].0.clone());
+                        j += 1;
+                    }
+                    Ordering::Equal => {
+                        i += 1;
+                        j += 1;
+                    }
+                }
+            }
+
+            return;
+        }
+
+        // Each half contains fewer points of the larger tree than the whole range, so this terminates.
+        let larger = if self_count >= other_count { self } else { other };
+        for (sub_lo, sub_hi, _) in larger.split_range(ordering, lo, hi, 2) {
+            self.diff_range(other, ordering, &sub_lo, &sub_hi, only_self, only_other);
+        }
+    }
+
+    // Return the least and the greatest point of the tree according to the given ordering, or `None` if the tree is empty.
+    fn extremes(&self, ordering: Ordering3d) -> Option<Extremes<'_, X, Y, Z>> {
+        match self {
+            ControlNode::Empty => return None,
+            ControlNode::NonEmpty { key, left, right, .. } => {
+                let mut extremes = (key, key);
+                for child in [left, right] {
+                    if let Some((lo, hi)) = child.extremes(ordering) {
+                        extremes = (min_by(extremes.0, lo, ordering), max_by(extremes.1, hi, ordering));
+                    }
+                }
+
+                return Some(extremes);
+            }
+        }
+    }
+}
+
+// Ranges with at most this many points of the responding side are answered by sending the points instead of splitting the range.
+const RECON_LEAF_SIZE: usize = 8;
+
+/// A range of points according to the ordering of a [`ReconSession`]: all points that are greater than or equal to `lo`, and less than `hi`. `None` stands for a missing bound.
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub struct ReconRange<X, Y, Z>
+where
+    X: Dimension,
+    Y: Dimension,
+    Z: Dimension,
+{
+    pub lo: Option<Point3d<X, Y, Z>>,
+    pub hi: Option<Point3d<X, Y, Z>>,
+}
+
+impl<X, Y, Z> ReconRange<X, Y, Z>
+where
+    X: Dimension,
+    Y: Dimension,
+    Z: Dimension,
+{
+    /// The range of all points.
+    pub fn full() -> Self {
+        return ReconRange { lo: None, hi: None };
+    }
+
+    /// Return whether the range contains the given point, according to the given ordering.
+    pub fn contains(&self, point: &Point3d<X, Y, Z>, ordering: Ordering3d) -> bool {
+        let above_lo = self
+            .lo
+            .as_ref()
+            .is_none_or(|lo| point.cmp_by(lo, ordering) != Ordering::Less);
+        let below_hi = self
+            .hi
+            .as_ref()
+            .is_none_or(|hi| point.cmp_by(hi, ordering) == Ordering::Less);
+        return above_lo && below_hi;
+    }
+}
+
+/// The fingerprint of the points that one side of a reconciliation has in some range.
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub struct RangeFingerprint<X, Y, Z>
+where
+    X: Dimension,
+    Y: Dimension,
+    Z: Dimension,
+{
+    pub range: ReconRange<X, Y, Z>,
+    pub fingerprint: XorFingerprint,
+}
+
+/// A message of range-based set reconciliation, sent by a [`ReconSession`] in response to a [`RangeFingerprint`] or to another message.
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub enum ReconMessage<X, Y, Z, V>
+where
+    X: Dimension,
+    Y: Dimension,
+    Z: Dimension,
+{
+    /// Both sides have the same points in the range, nothing more needs to be done for it.
+    RangesMatch(ReconRange<X, Y, Z>),
+    /// All point-value pairs that the sender has in the range. If `reply` is `true`, the receiver answers with the pairs in the range whose points the sender lacks.
+    Items {
+        range: ReconRange<X, Y, Z>,
+        items: Vec<(Point3d<X, Y, Z>, V)>,
+        reply: bool,
+    },
+    /// The fingerprints of the range differ, so the sender has split it into sub-ranges, and sends its fingerprints for them.
+    Split(Vec<RangeFingerprint<X, Y, Z>>),
+}
+
+/// One side of a [range-based set reconciliation](https://github.com/AljoschaMeyer/rbsr_short/blob/main/main.pdf) of the points of two [`ControlNode`]s. All ranges are given in a fixed ordering.
+///
+/// One side [initiates](Self::initiate) a reconciliation by sending the fingerprint of some range, to which the other side [responds](Self::respond). From then on, both sides pass every message they receive to [`receive`](Self::receive), and send the messages it returns, until no messages remain. Afterwards, both trees contain the union of the points that either tree contained in the range. Where both trees contain a point, each keeps its own value.
+#[derive(Debug, Clone)]
+pub struct ReconSession<X, Y, Z, V>
+where
+    X: Dimension + Clone + Debug,
+    Y: Dimension + Clone + Debug,
+    Z: Dimension + Clone + Debug,
+    V: Debug + Clone,
+{
+    tree: ControlNode<X, Y, Z, V, XorFingerprint>,
+    ordering: Ordering3d,
+}
+
+impl<X, Y, Z, V> ReconSession<X, Y, Z, V>
+where
+    X: Dimension + Clone + Debug + Hash,
+    Y: Dimension + Clone + Debug + Hash,
+    Z: Dimension + Clone + Debug + Hash,
+    V: Debug + Clone + Hash,
+{
+    /// Create a session that reconciles the given tree, with ranges in the given ordering. Points received from the other side are inserted at the ranks given by [`rank_from_hash`].
+    pub fn new(tree: ControlNode<X, Y, Z, V, XorFingerprint>, ordering: Ordering3d) -> Self {
+        return ReconSession { tree, ordering };
+    }
+
+    /// Return the local tree, including all points received so far.
+    pub fn tree(&self) -> &ControlNode<X, Y, Z, V, XorFingerprint> {
+        return &self.tree;
+    }
+
+    /// Consume the session and return the local tree.
+    pub fn into_tree(self) -> ControlNode<X, Y, Z, V, XorFingerprint> {
+        return self.tree;
+    }
+
+    /// Start reconciling the given range, by computing the fingerprint to send to the other side.
+    pub fn initiate(&self, range: ReconRange<X, Y, Z>) -> RangeFingerprint<X, Y, Z> {
+        let fingerprint = self.fingerprint(&range);
+        return RangeFingerprint { range, fingerprint };
+    }
+
+    /// Compare the fingerprint of the other side to the local one for the same range, and compute the answer: that the ranges match, all local points of the range if there are few of them, or fingerprints for two halves of the range (by the number of local points) otherwise.
+    pub fn respond(&self, theirs: &RangeFingerprint<X, Y, Z>) -> ReconMessage<X, Y, Z, V> {
+        let range = theirs.range.clone();
+        if self.fingerprint(&range) == theirs.fingerprint {
+            return ReconMessage::RangesMatch(range);
+        }
+
+        let mut items = self.items(&range);
+        if items.len() <= RECON_LEAF_SIZE {
+            return ReconMessage::Items {
+                range,
+                items,
+                reply: true,
+            };
+        }
+
+        // Both halves contain fewer points of the union of both sides than the whole range, so the recursion terminates.
+        let middle = items.swap_remove(items.len() / 2).0;
+        let halves = vec![
+            ReconRange {
+                lo: range.lo,
+                hi: Some(middle.clone()),
+            },
+            ReconRange {
+                lo: Some(middle),
+                hi: range.hi,
+            },
+        ];
+
+        return ReconMessage::Split(halves.into_iter().map(|half| self.initiate(half)).collect());
+    }
+
+    /// Process a message from the other side, inserting all points it contains that are missing locally, and return the messages to send back.
+    pub fn receive(&mut self, message: ReconMessage<X, Y, Z, V>) -> Vec<ReconMessage<X, Y, Z, V>> {
+        match message {
+            ReconMessage::RangesMatch(_) => return vec![],
+            ReconMessage::Split(fingerprints) => {
+                return fingerprints.iter().map(|theirs| self.respond(theirs)).collect();
+            }
+            ReconMessage::Items {
+                range,
+                items,
+                reply,
+            } => {
+                let mut answers = vec![];
+                if reply {
+                    let theirs: HashSet<&Point3d<X, Y, Z>> = items.iter().map(|(point, _)| point).collect();
+                    let missing: Vec<_> = self
+                        .items(&range)
+                        .into_iter()
+                        .filter(|(point, _)| !theirs.contains(point))
+                        .collect();
+
+                    if !missing.is_empty() {
+                        answers.push(ReconMessage::Items {
+                            range,
+                            items: missing,
+                            reply: false,
+                        });
+                    }
+                }
+
+                let new: Vec<_> = items
+                    .into_iter()
+                    .filter(|(point, _)| self.tree.rank_of(point).is_none())
+                    .collect();
+                let tree = mem::replace(&mut self.tree, ControlNode::Empty);
+                // The new points are disjoint from the local ones, so the values never need resolving.
+                self.tree = tree.merge(ControlNode::from_points(new.into_iter()), |own, _| own.clone());
+
+                return answers;
+            }
+        }
+    }
+
+    // Compute the local fingerprint of the range.
+    fn fingerprint(&self, range: &ReconRange<X, Y, Z>) -> XorFingerprint {
+        // A bound that is already known to hold is never compared against, so any point can stand in for a missing bound.
+        match (range.lo.as_ref(), range.hi.as_ref()) {
+            (None, None) => return self.tree.summary(),
+            (Some(lo), None) => {
+                return self
+                    .tree
+                    .fold_range(self.ordering, lo, lo, false, true, &ControlNode::summary)
+            }
+            (None, Some(hi)) => {
+                return self
+                    .tree
+                    .fold_range(self.ordering, hi, hi, true, false, &ControlNode::summary)
+            }
+            (Some(lo), Some(hi)) => return self.tree.summarize_range(self.ordering, lo, hi),
+        }
+    }
+
+    // Return all local point-value pairs in the range, sorted by the ordering of the session.
+    fn items(&self, range: &ReconRange<X, Y, Z>) -> Vec<(Point3d<X, Y, Z>, V)> {
+        let mut entries = vec![];
+        self.tree
+            .collect_range(self.ordering, range.lo.as_ref(), range.hi.as_ref(), &mut entries);
+
+        let mut items: Vec<_> = entries
+            .into_iter()
+            .map(|(point, value)| (point.clone(), value.clone()))
+            .collect();
+        items.sort_by(|(p1, _), (p2, _)| p1.cmp_by(p2, self.ordering));
+
+        return items;
+    }
+}
+
+// Escape a string for use within a quoted DOT label, turning line breaks into DOT line breaks.
+fn escape_dot(label: &str) -> String {
+    let mut escaped = String::with_capacity(label.len());
+    for c in label.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            c => escaped.push(c),
+        }
+    }
+
+    return escaped;
+}
+
+// Inclusive bounds on one dimension of all points in some subtree, as established by its ancestors. `None` stands for a missing bound.
+// Generic over the reference type rather than the dimension, so that the derived `Copy` does not require the dimension to be `Copy`.
+#[derive(Clone, Copy)]
+struct DimensionBounds<R> {
+    lo: Option<R>,
+    hi: Option<R>,
+}
+
+impl<'a, T: Ord> DimensionBounds<&'a T> {
+    const NONE: Self = DimensionBounds { lo: None, hi: None };
+
+    // Add the bound that all values are less than or equal to `hi`.
+    fn at_most(self, hi: &'a T) -> Self {
+        return DimensionBounds {
+            lo: self.lo,
+            hi: Some(self.hi.map_or(hi, |old| old.min(hi))),
+        };
+    }
+
+    // Add the bound that all values are greater than or equal to `lo`.
+    fn at_least(self, lo: &'a T) -> Self {
+        return DimensionBounds {
+            lo: Some(self.lo.map_or(lo, |old| old.max(lo))),
+            hi: self.hi,
+        };
+    }
+
+    // Do the bounds show that all values lie in the closed interval from `lo` to `hi`?
+    fn within(&self, lo: &T, hi: &T) -> bool {
+        match (self.lo, self.hi) {
+            (Some(self_lo), Some(self_hi)) => return lo <= self_lo && self_hi <= hi,
+            _ => return false,
+        }
+    }
+}
+
+// Return the lesser of two points according to the given ordering.
+fn min_by<'a, X: Dimension, Y: Dimension, Z: Dimension>(
+    p1: &'a Point3d<X, Y, Z>,
+    p2: &'a Point3d<X, Y, Z>,
+    ordering: Ordering3d,
+) -> &'a Point3d<X, Y, Z> {
+    if p2.cmp_by(p1, ordering) == Ordering::Less {
+        return p2;
+    } else {
+        return p1;
+    }
+}
+
+// Return the greater of two points according to the given ordering.
+fn max_by<'a, X: Dimension, Y: Dimension, Z: Dimension>(
+    p1: &'a Point3d<X, Y, Z>,
+    p2: &'a Point3d<X, Y, Z>,
+    ordering: Ordering3d,
+) -> &'a Point3d<X, Y, Z> {
+    if p2.cmp_by(p1, ordering) == Ordering::Greater {
+        return p2;
+    } else {
+        return p1;
+    }
+}
+
+/// The order in which [`ControlNode::from_iter`] inserts point-value pairs with their ranks: by descending rank, and ascending according to the rank-appropriate order within each rank.
+pub fn node_sort_key<X: Dimension, Y: Dimension, Z: Dimension, V>(
+    (p1, _, rank1): &(Point3d<X, Y, Z>, V, u8),
+    (p2, _, rank2): &(Point3d<X, Y, Z>, V, u8),
+) -> Ordering {
+    match rank2.cmp(rank1) {
+        Ordering::Equal => return cmp_points_at_rank(*rank1, p1, p2),
+        _ => return rank2.cmp(rank1), // The unintuitive ordering results in *descending* sorting.
+    }
+}
+
 fn cmp_points_at_rank<X: Dimension, Y: Dimension, Z: Dimension>(
     rank: u8,
     p1: &Point3d<X, Y, Z>,
@@ -1,37 +1,392 @@
+use alloc::vec::Vec;
 use core::future::Future;
+use core::ops::Bound::{self, Excluded, Included, Unbounded};
+use core::pin::pin;
+
+use futures::{stream, Stream, StreamExt};
 
 /// A persistent storage backend that maps bytestrings keys to values of some type `V`, and allows for efficient access based on the lexicographic ordering of the keys.
 pub trait BackEnd<V> {
     /// Type of errors that can occur when interacting with the backend.
     type Error;
 
+    /// A frozen, read-only view of the backend, see [`snapshot`](Self::snapshot).
+    type Snapshot: ReadBackEnd<V, Error = Self::Error>;
+
     /// Get the value associated with the given key, if there is any.
     fn get(&self, key: &[u8]) -> impl Future<Output = Result<Option<V>, Self::Error>>;
 
     /// Get the greatest kv pair whose key is less than or equal to the given key, if there is any.
-    fn find_lte(&self, key: &[u8])
-        -> impl Future<Output = Result<Option<(&[u8], V)>, Self::Error>>;
+    ///
+    /// The key is returned as an owned bytestring, so that backends which need to decode or copy their keys (as is typical for on-disk stores) can implement this.
+    fn find_lte(
+        &self,
+        key: &[u8],
+    ) -> impl Future<Output = Result<Option<(Vec<u8>, V)>, Self::Error>>;
 
     /// Get the least kv pair whose key is greater than or equal to the given key, if there is any.
-    fn find_gte(&self, key: &[u8])
-        -> impl Future<Output = Result<Option<(&[u8], V)>, Self::Error>>;
-
-    // /// Insert a kv pair. Returns the old value for that key, if there was any.
-    // ///
-    // /// This need not be persisted to disk immediately, persistence may be delayed until [`flush`](Self::flush) is called. All subsequent method calls must incorporat the insertion though, even if it has not been persisted yet.
-    // fn insert(
-    //     &mut self,
-    //     key: &[u8],
-    //     value: V,
-    // ) -> impl Future<Output = Result<Option<V>, Self::Error>>;
-
-    // /// Delete a kv pair. Returns the old value for that key, if there was any.
-    // ///
-    // /// This need not be persisted to disk immediately, persistence may be delayed until [`flush`](Self::flush) is called. All subsequent method calls must incorporat the deletion though, even if it has not been persisted yet.
-    // fn delete(&mut self, key: &[u8]) -> impl Future<Output = Result<Option<V>, Self::Error>>;
-
-    // /// Commit all mutations that have been performed so far to disk. When the Future is done, the changes are guaranteed to be persisted.
-    // fn flush(&mut self) -> impl Future<Output = Result<(), Self::Error>>;
+    ///
+    /// The key is returned as an owned bytestring, so that backends which need to decode or copy their keys (as is typical for on-disk stores) can implement this.
+    fn find_gte(
+        &self,
+        key: &[u8],
+    ) -> impl Future<Output = Result<Option<(Vec<u8>, V)>, Self::Error>>;
+
+    /// Insert a kv pair. Returns the old value for that key, if there was any.
+    ///
+    /// This need not be persisted to disk immediately, persistence may be delayed until [`flush`](Self::flush) is called. All subsequent method calls must incorporate the insertion though, even if it has not been persisted yet.
+    fn insert(
+        &mut self,
+        key: &[u8],
+        value: V,
+    ) -> impl Future<Output = Result<Option<V>, Self::Error>>;
+
+    /// Delete a kv pair. Returns the old value for that key, if there was any.
+    ///
+    /// This need not be persisted to disk immediately, persistence may be delayed until [`flush`](Self::flush) is called. All subsequent method calls must incorporate the deletion though, even if it has not been persisted yet.
+    fn delete(&mut self, key: &[u8]) -> impl Future<Output = Result<Option<V>, Self::Error>>;
+
+    /// Get the values associated with each of the given keys, if there are any. The results are in the same order as the keys.
+    ///
+    /// The default implementation performs one [`get`](Self::get) per key. Backends with a per-call overhead should override it, for example by looking up all keys within a single read transaction, which also makes the results consistent with each other.
+    fn get_many(&self, keys: &[&[u8]]) -> impl Future<Output = Result<Vec<Option<V>>, Self::Error>> {
+        async move {
+            let mut values = Vec::with_capacity(keys.len());
+            for key in keys {
+                values.push(self.get(key).await?);
+            }
+
+            return Ok(values);
+        }
+    }
+
+    /// Get the kv pair whose key is closest to the given key, if there is any.
+    ///
+    /// The candidates are the results of [`find_lte`](Self::find_lte) and [`find_gte`](Self::find_gte). Of those, the closer one is the one whose key has the longer common prefix with the given key. If both common prefixes have the same length, the result of `find_lte` is returned. In particular, if the key itself is present, its kv pair is returned.
+    fn find_nearest(
+        &self,
+        key: &[u8],
+    ) -> impl Future<Output = Result<Option<(Vec<u8>, V)>, Self::Error>> {
+        async move {
+            let lte = self.find_lte(key).await?;
+            let gte = self.find_gte(key).await?;
+
+            match (lte, gte) {
+                (None, gte) => return Ok(gte),
+                (lte, None) => return Ok(lte),
+                (Some(lte), Some(gte)) => {
+                    if common_prefix_len(&gte.0, key) > common_prefix_len(&lte.0, key) {
+                        return Ok(Some(gte));
+                    } else {
+                        return Ok(Some(lte));
+                    }
+                }
+            }
+        }
+    }
+
+    /// Update the value associated with the given key, by mapping the current value (if any) to a new value (if any). Returns the new value.
+    ///
+    /// `f` sees the current value including all mutations that have not been [flushed](Self::flush) yet. Returning `None` from `f` deletes the key.
+    ///
+    /// The default implementation performs a [`get`](Self::get), followed by an [`insert`](Self::insert) or a [`delete`](Self::delete).
+    fn update_with<F: FnOnce(Option<V>) -> Option<V>>(
+        &mut self,
+        key: &[u8],
+        f: F,
+    ) -> impl Future<Output = Result<Option<V>, Self::Error>>
+    where
+        V: Clone,
+    {
+        async move {
+            let old = self.get(key).await?;
+
+            match f(old) {
+                Some(new) => {
+                    self.insert(key, new.clone()).await?;
+                    return Ok(Some(new));
+                }
+                None => {
+                    self.delete(key).await?;
+                    return Ok(None);
+                }
+            }
+        }
+    }
+
+    /// Get the value associated with the given key if there is one, otherwise insert the value returned by `f` and return it. `f` is only called if the key is absent.
+    ///
+    /// Whether the key is present takes all mutations into account that have not been [flushed](Self::flush) yet, and the insertion need not be persisted before the next flush either.
+    ///
+    /// The default implementation performs a [`get`](Self::get), followed by an [`insert`](Self::insert) if the key is absent.
+    fn get_or_insert_with<F: FnOnce() -> V>(
+        &mut self,
+        key: &[u8],
+        f: F,
+    ) -> impl Future<Output = Result<V, Self::Error>>
+    where
+        V: Clone,
+    {
+        async move {
+            if let Some(value) = self.get(key).await? {
+                return Ok(value);
+            }
+
+            let value = f();
+            self.insert(key, value.clone()).await?;
+            return Ok(value);
+        }
+    }
+
+    /// Delete all kv pairs whose keys lie within the given bounds, and return how many kv pairs were deleted. Bounds that describe an empty range delete nothing.
+    ///
+    /// Just like with [`delete`](Self::delete), the deletions need not be persisted before the next [flush](Self::flush).
+    ///
+    /// The default implementation collects the keys of the [`range`](Self::range), and then [deletes](Self::delete) them one by one.
+    fn delete_range(
+        &mut self,
+        lo: Bound<&[u8]>,
+        hi: Bound<&[u8]>,
+    ) -> impl Future<Output = Result<usize, Self::Error>> {
+        async move {
+            let keys: Vec<Vec<u8>> = self.range(lo, hi).await?.map(|(key, _)| key).collect();
+
+            for key in keys.iter() {
+                self.delete(key).await?;
+            }
+            return Ok(keys.len());
+        }
+    }
+
+    /// Get all kv pairs whose keys lie within the given bounds, in ascending order of keys.
+    ///
+    /// The default implementation collects [`stream_range`](Self::stream_range) into a vector.
+    fn range<'s>(
+        &'s self,
+        lo: Bound<&'s [u8]>,
+        hi: Bound<&'s [u8]>,
+    ) -> impl Future<Output = Result<impl Iterator<Item = (Vec<u8>, V)>, Self::Error>> {
+        async move {
+            let mut entries = Vec::new();
+            let mut stream = pin!(self.stream_range(lo, hi));
+
+            while let Some(entry) = stream.next().await {
+                entries.push(entry?);
+            }
+
+            return Ok(entries.into_iter());
+        }
+    }
+
+    /// Get all kv pairs whose keys lie within the given bounds, in descending order of keys. The bounds have the same semantics as for [`range`](Self::range), so this yields exactly the reverse of `range`.
+    ///
+    /// The default implementation reverses the kv pairs of [`range`](Self::range), which takes memory linear in their number. Backends that can iterate backwards should override it.
+    fn range_rev<'s>(
+        &'s self,
+        lo: Bound<&'s [u8]>,
+        hi: Bound<&'s [u8]>,
+    ) -> impl Future<Output = Result<impl Iterator<Item = (Vec<u8>, V)>, Self::Error>> {
+        async move {
+            let entries: Vec<_> = self.range(lo, hi).await?.collect();
+            return Ok(entries.into_iter().rev());
+        }
+    }
+
+    /// Get all kv pairs whose keys start with the given prefix, in ascending order of keys.
+    ///
+    /// The default implementation delegates to [`range`](Self::range), using the [`prefix_successor`] of the prefix as the (exclusive) upper bound, and collects the results.
+    fn scan_prefix<'s>(
+        &'s self,
+        prefix: &'s [u8],
+    ) -> impl Future<Output = Result<impl Iterator<Item = (Vec<u8>, V)>, Self::Error>> {
+        async move {
+            let successor = prefix_successor(prefix);
+            let hi = match successor.as_ref() {
+                Some(successor) => Excluded(successor.as_slice()),
+                None => Unbounded,
+            };
+
+            let entries: Vec<_> = self.range(Included(prefix), hi).await?.collect();
+            return Ok(entries.into_iter());
+        }
+    }
+
+    /// Get up to `limit` kv pairs whose keys are strictly greater than `after` (or all kv pairs, if `after` is `None`), in ascending order of keys.
+    ///
+    /// This serves paginated reads: passing the last key of one page as `after` yields the next page. The default implementation takes the first `limit` kv pairs of [`stream_range`](Self::stream_range), so it does not read past the end of the page.
+    fn page_after<'s>(
+        &'s self,
+        after: Option<&'s [u8]>,
+        limit: usize,
+    ) -> impl Future<Output = Result<Vec<(Vec<u8>, V)>, Self::Error>> {
+        async move {
+            let lo = match after {
+                Some(after) => Excluded(after),
+                None => Unbounded,
+            };
+
+            let mut page = Vec::new();
+            let mut stream = pin!(self.stream_range(lo, Unbounded).take(limit));
+
+            while let Some(entry) = stream.next().await {
+                page.push(entry?);
+            }
+
+            return Ok(page);
+        }
+    }
+
+    /// Lazily iterate over all kv pairs whose keys lie within the given bounds, in ascending order of keys. The stream ends after the first error.
+    ///
+    /// The default implementation performs one [`find_gte`](Self::find_gte) per kv pair. Backends that can iterate more efficiently (e.g., via a cursor) should override it.
+    ///
+    /// Iterating in descending order is not supported yet, use [`range_rev`](Self::range_rev) instead.
+    fn stream_range<'s>(
+        &'s self,
+        lo: Bound<&'s [u8]>,
+        hi: Bound<&'s [u8]>,
+    ) -> impl Stream<Item = Result<(Vec<u8>, V), Self::Error>> + 's {
+        // The state is the lower bound for the next kv pair, or `None` once the stream is done.
+        let start = Some(lo.map(|key| key.to_vec()));
+
+        return stream::unfold(start, move |state| async move {
+            let next = match state? {
+                Included(key) => self.find_gte(&key).await,
+                // The least key greater than `key` is `key` with a zero byte appended.
+                Excluded(mut key) => {
+                    key.push(0);
+                    self.find_gte(&key).await
+                }
+                Unbounded => self.find_gte(&[]).await,
+            };
+
+            match next {
+                Err(err) => return Some((Err(err), None)),
+                Ok(None) => return None,
+                Ok(Some((key, value))) => {
+                    if !is_below(&key, hi) {
+                        return None;
+                    }
+
+                    let state = Some(Excluded(key.clone()));
+                    return Some((Ok((key, value)), state));
+                }
+            }
+        });
+    }
+
+    /// Take a snapshot of the current state of the backend, including all mutations that have not been [flushed](Self::flush) yet.
+    ///
+    /// Reads through the snapshot are isolated from all later mutations of the backend, so long-running scans see a consistent state instead of a torn one.
+    fn snapshot(&self) -> impl Future<Output = Result<Self::Snapshot, Self::Error>>;
+
+    /// Commit all mutations that have been performed so far to disk. When the Future is done, the changes are guaranteed to be persisted.
+    ///
+    /// The Future must be cancellation-safe: if it is dropped before it is done, the backend must remain consistent. Each mutation is then either persisted or still pending (disk backends should persist all of them or none, by committing them in a single transaction), all subsequent method calls must still incorporate every mutation, and a later `flush` must persist whatever was not persisted yet. In particular, a backend must not discard its record of pending mutations before they have been committed. See [`assert_flush_cancel_safe`](crate::assert_flush_cancel_safe) (with the `testing` feature) for a check.
+    fn flush(&mut self) -> impl Future<Output = Result<(), Self::Error>>;
+}
+
+/// Read access to a frozen view of a [`BackEnd`], as taken by [`BackEnd::snapshot`]. All reads reflect the state of the backend at the time the snapshot was taken.
+///
+/// All methods have the same semantics as the corresponding methods of [`BackEnd`].
+pub trait ReadBackEnd<V> {
+    /// Type of errors that can occur when reading from the snapshot.
+    type Error;
+
+    /// Get the value associated with the given key, if there is any.
+    fn get(&self, key: &[u8]) -> impl Future<Output = Result<Option<V>, Self::Error>>;
+
+    /// Get the greatest kv pair whose key is less than or equal to the given key, if there is any.
+    fn find_lte(
+        &self,
+        key: &[u8],
+    ) -> impl Future<Output = Result<Option<(Vec<u8>, V)>, Self::Error>>;
+
+    /// Get the least kv pair whose key is greater than or equal to the given key, if there is any.
+    fn find_gte(
+        &self,
+        key: &[u8],
+    ) -> impl Future<Output = Result<Option<(Vec<u8>, V)>, Self::Error>>;
+
+    /// Get all kv pairs whose keys lie within the given bounds, in ascending order of keys.
+    fn range<'s>(
+        &'s self,
+        lo: Bound<&'s [u8]>,
+        hi: Bound<&'s [u8]>,
+    ) -> impl Future<Output = Result<impl Iterator<Item = (Vec<u8>, V)>, Self::Error>>;
+}
+
+/// A [`BackEnd`]-like storage backend whose mutating methods take `&self` instead of `&mut self`, so that it can be shared between concurrent tasks (e.g., behind an `Arc`). Implementations use interior mutability to coordinate the mutations.
+///
+/// All methods have the same semantics as the corresponding methods of [`BackEnd`]. In particular, every read must incorporate all mutations that have completed before, even if they have not been flushed yet.
+pub trait SharedBackEnd<V> {
+    /// Type of errors that can occur when interacting with the backend.
+    type Error;
+
+    /// Get the value associated with the given key, if there is any.
+    fn get(&self, key: &[u8]) -> impl Future<Output = Result<Option<V>, Self::Error>>;
+
+    /// Get the greatest kv pair whose key is less than or equal to the given key, if there is any.
+    fn find_lte(
+        &self,
+        key: &[u8],
+    ) -> impl Future<Output = Result<Option<(Vec<u8>, V)>, Self::Error>>;
+
+    /// Get the least kv pair whose key is greater than or equal to the given key, if there is any.
+    fn find_gte(
+        &self,
+        key: &[u8],
+    ) -> impl Future<Output = Result<Option<(Vec<u8>, V)>, Self::Error>>;
+
+    /// Insert a kv pair. Returns the old value for that key, if there was any.
+    fn insert(&self, key: &[u8], value: V) -> impl Future<Output = Result<Option<V>, Self::Error>>;
+
+    /// Delete a kv pair. Returns the old value for that key, if there was any.
+    fn delete(&self, key: &[u8]) -> impl Future<Output = Result<Option<V>, Self::Error>>;
+
+    /// Commit all mutations that have been performed so far to disk. When the Future is done, the changes are guaranteed to be persisted.
+    ///
+    /// Just like for [`BackEnd::flush`], the Future must be cancellation-safe.
+    fn flush(&self) -> impl Future<Output = Result<(), Self::Error>>;
 }
 
 // TODO batch/transaction
+
+/// Return the least bytestring that is greater than all bytestrings starting with the given prefix, or `None` if there is no such bytestring (i.e., if the prefix is empty or consists only of `0xff` bytes).
+pub fn prefix_successor(prefix: &[u8]) -> Option<Vec<u8>> {
+    let mut successor = prefix.to_vec();
+
+    while let Some(last) = successor.pop() {
+        if last < 0xff {
+            successor.push(last + 1);
+            return Some(successor);
+        }
+    }
+
+    return None;
+}
+
+// Return the length of the longest common prefix of two bytestrings.
+fn common_prefix_len(a: &[u8], b: &[u8]) -> usize {
+    return a.iter().zip(b).take_while(|(a, b)| a == b).count();
+}
+
+// Return whether `key` satisfies the upper bound `hi`.
+fn is_below(key: &[u8], hi: Bound<&[u8]>) -> bool {
+    match hi {
+        Included(hi) => return key <= hi,
+        Excluded(hi) => return key < hi,
+        Unbounded => return true,
+    }
+}
+
+// Return whether the given bounds are known to contain no key. This includes all bounds on which `BTreeMap::range` panics, so backends should check this first.
+pub(crate) fn is_empty_range(lo: Bound<&[u8]>, hi: Bound<&[u8]>) -> bool {
+    match (lo, hi) {
+        (Included(lo), Included(hi)) => return lo > hi,
+        (Included(lo), Excluded(hi)) | (Excluded(lo), Included(hi)) | (Excluded(lo), Excluded(hi)) => {
+            return lo >= hi
+        }
+        _ => return false,
+    }
+}
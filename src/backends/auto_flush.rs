@@ -0,0 +1,165 @@
+use alloc::vec::Vec;
+use core::future::Future;
+use core::ops::Bound;
+
+use futures::Stream;
+
+use crate::BackEnd;
+
+/// A [`BackEnd`] that wraps another backend and [flushes](BackEnd::flush) it automatically after every `threshold` mutations, so that callers need not batch their flushes themselves.
+///
+/// Every [`insert`](BackEnd::insert) and [`delete`](BackEnd::delete) counts as a mutation, whether it changed anything or not. Explicit flushes (via [`force_flush`](Self::force_flush) or [`flush`](BackEnd::flush)) reset the count. Mutations since the last flush are not persisted until the count reaches the threshold, so call [`force_flush`](Self::force_flush) before dropping the wrapper.
+///
+/// If an automatic flush fails, the [`insert`](BackEnd::insert) or [`delete`](BackEnd::delete) that triggered it returns the error of the flush, even though the mutation itself has taken effect. The count is not reset in that case, so the next mutation tries to flush again.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AutoFlush<B> {
+    inner: B,
+    dirty: usize,
+    threshold: usize,
+}
+
+impl<B> AutoFlush<B> {
+    /// Wrap a backend, flushing it after every `threshold` mutations. A threshold of zero or one flushes after every mutation.
+    pub fn new(inner: B, threshold: usize) -> Self {
+        return AutoFlush {
+            inner,
+            dirty: 0,
+            threshold,
+        };
+    }
+
+    /// The number of mutations since the last flush.
+    pub fn dirty(&self) -> usize {
+        return self.dirty;
+    }
+
+    /// The number of mutations after which the wrapped backend is flushed.
+    pub fn threshold(&self) -> usize {
+        return self.threshold;
+    }
+
+    /// Get a reference to the wrapped backend.
+    pub fn inner(&self) -> &B {
+        return &self.inner;
+    }
+
+    /// Consume the wrapper and return the wrapped backend, without flushing it.
+    pub fn into_inner(self) -> B {
+        return self.inner;
+    }
+
+    /// Flush the wrapped backend right away, regardless of the number of mutations since the last flush.
+    pub async fn force_flush<V>(&mut self) -> Result<(), B::Error>
+    where
+        B: BackEnd<V>,
+    {
+        self.inner.flush().await?;
+        self.dirty = 0;
+        return Ok(());
+    }
+
+    // Count a mutation, and flush if that reaches the threshold.
+    async fn mutated<V>(&mut self) -> Result<(), B::Error>
+    where
+        B: BackEnd<V>,
+    {
+        self.dirty += 1;
+        if self.dirty >= self.threshold {
+            self.force_flush().await?;
+        }
+        return Ok(());
+    }
+}
+
+impl<V, B: BackEnd<V>> BackEnd<V> for AutoFlush<B> {
+    type Error = B::Error;
+    type Snapshot = B::Snapshot;
+
+    fn get(&self, key: &[u8]) -> impl Future<Output = Result<Option<V>, Self::Error>> {
+        return self.inner.get(key);
+    }
+
+    fn find_lte(
+        &self,
+        key: &[u8],
+    ) -> impl Future<Output = Result<Option<(Vec<u8>, V)>, Self::Error>> {
+        return self.inner.find_lte(key);
+    }
+
+    fn find_gte(
+        &self,
+        key: &[u8],
+    ) -> impl Future<Output = Result<Option<(Vec<u8>, V)>, Self::Error>> {
+        return self.inner.find_gte(key);
+    }
+
+    fn get_many(&self, keys: &[&[u8]]) -> impl Future<Output = Result<Vec<Option<V>>, Self::Error>> {
+        return self.inner.get_many(keys);
+    }
+
+    async fn insert(&mut self, key: &[u8], value: V) -> Result<Option<V>, Self::Error> {
+        let old = self.inner.insert(key, value).await?;
+        self.mutated().await?;
+        return Ok(old);
+    }
+
+    async fn delete(&mut self, key: &[u8]) -> Result<Option<V>, Self::Error> {
+        let old = self.inner.delete(key).await?;
+        self.mutated().await?;
+        return Ok(old);
+    }
+
+    fn find_nearest(
+        &self,
+        key: &[u8],
+    ) -> impl Future<Output = Result<Option<(Vec<u8>, V)>, Self::Error>> {
+        return self.inner.find_nearest(key);
+    }
+
+    async fn range<'s>(
+        &'s self,
+        lo: Bound<&'s [u8]>,
+        hi: Bound<&'s [u8]>,
+    ) -> Result<impl Iterator<Item = (Vec<u8>, V)>, Self::Error> {
+        return self.inner.range(lo, hi).await;
+    }
+
+    async fn range_rev<'s>(
+        &'s self,
+        lo: Bound<&'s [u8]>,
+        hi: Bound<&'s [u8]>,
+    ) -> Result<impl Iterator<Item = (Vec<u8>, V)>, Self::Error> {
+        return self.inner.range_rev(lo, hi).await;
+    }
+
+    async fn scan_prefix<'s>(
+        &'s self,
+        prefix: &'s [u8],
+    ) -> Result<impl Iterator<Item = (Vec<u8>, V)>, Self::Error> {
+        return self.inner.scan_prefix(prefix).await;
+    }
+
+    fn page_after<'s>(
+        &'s self,
+        after: Option<&'s [u8]>,
+        limit: usize,
+    ) -> impl Future<Output = Result<Vec<(Vec<u8>, V)>, Self::Error>> {
+        return self.inner.page_after(after, limit);
+    }
+
+    fn stream_range<'s>(
+        &'s self,
+        lo: Bound<&'s [u8]>,
+        hi: Bound<&'s [u8]>,
+    ) -> impl Stream<Item = Result<(Vec<u8>, V), Self::Error>> + 's {
+        return self.inner.stream_range(lo, hi);
+    }
+
+    fn snapshot(&self) -> impl Future<Output = Result<Self::Snapshot, Self::Error>> {
+        return self.inner.snapshot();
+    }
+
+    async fn flush(&mut self) -> Result<(), Self::Error> {
+        return self.force_flush().await;
+    }
+}
@@ -0,0 +1,207 @@
+use alloc::collections::{btree_map, BTreeMap};
+use alloc::vec::Vec;
+use core::convert::Infallible;
+use core::ops::Bound::{self, Excluded, Included, Unbounded};
+
+use futures::{stream, Stream};
+
+use crate::backend::is_empty_range;
+use crate::{prefix_successor, BackEnd, ReadBackEnd};
+
+/// An in-memory [`BackEnd`], backed by a [`BTreeMap`]. Nothing is ever persisted, so this is mostly useful for testing, or for data that need not outlive the process.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BTreeMapBackEnd<V> {
+    map: BTreeMap<Vec<u8>, V>,
+}
+
+impl<V> BTreeMapBackEnd<V> {
+    /// Create an empty backend.
+    pub fn new() -> Self {
+        return BTreeMapBackEnd {
+            map: BTreeMap::new(),
+        };
+    }
+
+    /// Consume the backend and return the map of all its kv pairs.
+    pub fn into_map(self) -> BTreeMap<Vec<u8>, V> {
+        return self.map;
+    }
+}
+
+impl<V: Clone> BTreeMapBackEnd<V> {
+    // Iterate over clones of all kv pairs within the given bounds.
+    fn range_entries<'s>(
+        &'s self,
+        lo: Bound<&[u8]>,
+        hi: Bound<&[u8]>,
+    ) -> impl DoubleEndedIterator<Item = (Vec<u8>, V)> + 's {
+        let range = if is_empty_range(lo, hi) {
+            btree_map::Range::default()
+        } else {
+            self.map.range::<[u8], _>((lo, hi))
+        };
+
+        return range.map(|(k, v)| (k.clone(), v.clone()));
+    }
+}
+
+impl<V> Default for BTreeMapBackEnd<V> {
+    fn default() -> Self {
+        return Self::new();
+    }
+}
+
+impl<V> From<BTreeMap<Vec<u8>, V>> for BTreeMapBackEnd<V> {
+    fn from(map: BTreeMap<Vec<u8>, V>) -> Self {
+        return BTreeMapBackEnd { map };
+    }
+}
+
+impl<V: Clone> BackEnd<V> for BTreeMapBackEnd<V> {
+    type Error = Infallible;
+    type Snapshot = BTreeMapSnapshot<V>;
+
+    async fn get(&self, key: &[u8]) -> Result<Option<V>, Self::Error> {
+        return Ok(self.map.get(key).cloned());
+    }
+
+    async fn find_lte(&self, key: &[u8]) -> Result<Option<(Vec<u8>, V)>, Self::Error> {
+        return Ok(self
+            .map
+            .range::<[u8], _>((Unbounded, Included(key)))
+            .next_back()
+            .map(|(k, v)| (k.clone(), v.clone())));
+    }
+
+    async fn find_gte(&self, key: &[u8]) -> Result<Option<(Vec<u8>, V)>, Self::Error> {
+        return Ok(self
+            .map
+            .range::<[u8], _>((Included(key), Unbounded))
+            .next()
+            .map(|(k, v)| (k.clone(), v.clone())));
+    }
+
+    async fn insert(&mut self, key: &[u8], value: V) -> Result<Option<V>, Self::Error> {
+        return Ok(self.map.insert(key.to_vec(), value));
+    }
+
+    async fn delete(&mut self, key: &[u8]) -> Result<Option<V>, Self::Error> {
+        return Ok(self.map.remove(key));
+    }
+
+    async fn get_many(&self, keys: &[&[u8]]) -> Result<Vec<Option<V>>, Self::Error> {
+        return Ok(keys.iter().map(|key| self.map.get(*key).cloned()).collect());
+    }
+
+    async fn update_with<F: FnOnce(Option<V>) -> Option<V>>(
+        &mut self,
+        key: &[u8],
+        f: F,
+    ) -> Result<Option<V>, Self::Error> {
+        match f(self.map.remove(key)) {
+            Some(new) => {
+                self.map.insert(key.to_vec(), new.clone());
+                return Ok(Some(new));
+            }
+            None => return Ok(None),
+        }
+    }
+
+    async fn delete_range(&mut self, lo: Bound<&[u8]>, hi: Bound<&[u8]>) -> Result<usize, Self::Error> {
+        if is_empty_range(lo, hi) {
+            return Ok(0);
+        }
+
+        let keys: Vec<Vec<u8>> = self
+            .map
+            .range::<[u8], _>((lo, hi))
+            .map(|(key, _)| key.clone())
+            .collect();
+
+        for key in keys.iter() {
+            self.map.remove(key);
+        }
+        return Ok(keys.len());
+    }
+
+    async fn range<'s>(
+        &'s self,
+        lo: Bound<&'s [u8]>,
+        hi: Bound<&'s [u8]>,
+    ) -> Result<impl Iterator<Item = (Vec<u8>, V)>, Self::Error> {
+        return Ok(self.range_entries(lo, hi));
+    }
+
+    async fn range_rev<'s>(
+        &'s self,
+        lo: Bound<&'s [u8]>,
+        hi: Bound<&'s [u8]>,
+    ) -> Result<impl Iterator<Item = (Vec<u8>, V)>, Self::Error> {
+        return Ok(self.range_entries(lo, hi).rev());
+    }
+
+    async fn scan_prefix<'s>(
+        &'s self,
+        prefix: &'s [u8],
+    ) -> Result<impl Iterator<Item = (Vec<u8>, V)>, Self::Error> {
+        let successor = prefix_successor(prefix);
+        let hi = match successor.as_ref() {
+            Some(successor) => Excluded(successor.as_slice()),
+            None => Unbounded,
+        };
+
+        return Ok(self.range_entries(Included(prefix), hi));
+    }
+
+    fn stream_range<'s>(
+        &'s self,
+        lo: Bound<&'s [u8]>,
+        hi: Bound<&'s [u8]>,
+    ) -> impl Stream<Item = Result<(Vec<u8>, V), Self::Error>> + 's {
+        return stream::iter(self.range_entries(lo, hi).map(Ok));
+    }
+
+    /// Clones the map, which takes time and memory linear in the number of kv pairs.
+    async fn snapshot(&self) -> Result<Self::Snapshot, Self::Error> {
+        return Ok(BTreeMapSnapshot(self.clone()));
+    }
+
+    /// Nothing to do here, an in-memory backend never persists anything. Hence, cancelling a flush trivially leaves the backend consistent.
+    async fn flush(&mut self) -> Result<(), Self::Error> {
+        return Ok(());
+    }
+}
+
+/// A [snapshot](BackEnd::snapshot) of an in-memory backend: a copy of all its kv pairs at the time the snapshot was taken.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BTreeMapSnapshot<V>(BTreeMapBackEnd<V>);
+
+impl<V> From<BTreeMap<Vec<u8>, V>> for BTreeMapSnapshot<V> {
+    fn from(map: BTreeMap<Vec<u8>, V>) -> Self {
+        return BTreeMapSnapshot(BTreeMapBackEnd::from(map));
+    }
+}
+
+impl<V: Clone> ReadBackEnd<V> for BTreeMapSnapshot<V> {
+    type Error = Infallible;
+
+    async fn get(&self, key: &[u8]) -> Result<Option<V>, Self::Error> {
+        return BackEnd::get(&self.0, key).await;
+    }
+
+    async fn find_lte(&self, key: &[u8]) -> Result<Option<(Vec<u8>, V)>, Self::Error> {
+        return BackEnd::find_lte(&self.0, key).await;
+    }
+
+    async fn find_gte(&self, key: &[u8]) -> Result<Option<(Vec<u8>, V)>, Self::Error> {
+        return BackEnd::find_gte(&self.0, key).await;
+    }
+
+    async fn range<'s>(
+        &'s self,
+        lo: Bound<&'s [u8]>,
+        hi: Bound<&'s [u8]>,
+    ) -> Result<impl Iterator<Item = (Vec<u8>, V)>, Self::Error> {
+        return Ok(self.0.range_entries(lo, hi));
+    }
+}
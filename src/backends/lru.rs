@@ -0,0 +1,213 @@
+use core::future::Future;
+use core::num::NonZeroUsize;
+use core::ops::{Bound, RangeBounds};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use std::vec::Vec;
+
+use futures::Stream;
+use lru::LruCache;
+
+use crate::BackEnd;
+
+/// A [`BackEnd`] that wraps another backend and keeps the values of recently read keys in memory, for read-heavy workloads on backends whose reads are expensive (e.g., because they go to disk).
+///
+/// The cache holds at most `capacity` kv pairs, and evicts the least recently used ones first. [`get`](BackEnd::get) and [`get_many`](BackEnd::get_many) are answered from the cache where possible, and populate it otherwise. Absent keys are not cached. All other reads, such as searches and ranges, bypass the cache. Every mutation through the wrapper invalidates the cached values of the keys it affects, but mutations of the wrapped backend that bypass the wrapper are not noticed.
+#[derive(Debug)]
+pub struct LruCached<B, V> {
+    inner: B,
+    cache: Mutex<LruCache<Vec<u8>, V>>,
+}
+
+impl<B, V> LruCached<B, V> {
+    /// Wrap a backend, caching the values of up to `capacity` keys.
+    pub fn new(inner: B, capacity: NonZeroUsize) -> Self {
+        return LruCached {
+            inner,
+            cache: Mutex::new(LruCache::new(capacity)),
+        };
+    }
+
+    /// The number of kv pairs that are currently cached.
+    pub fn cached(&self) -> usize {
+        return self.cache().len();
+    }
+
+    /// The maximum number of kv pairs that are cached at any time.
+    pub fn capacity(&self) -> NonZeroUsize {
+        return self.cache().cap();
+    }
+
+    /// Drop all cached kv pairs.
+    pub fn clear_cache(&mut self) {
+        self.cache_mut().clear();
+    }
+
+    /// Get a reference to the wrapped backend.
+    pub fn inner(&self) -> &B {
+        return &self.inner;
+    }
+
+    /// Consume the wrapper and return the wrapped backend.
+    pub fn into_inner(self) -> B {
+        return self.inner;
+    }
+
+    // A panic while holding the lock cannot leave the cache in an inconsistent state, so we ignore poisoning.
+    fn cache(&self) -> MutexGuard<'_, LruCache<Vec<u8>, V>> {
+        return self.cache.lock().unwrap_or_else(PoisonError::into_inner);
+    }
+
+    fn cache_mut(&mut self) -> &mut LruCache<Vec<u8>, V> {
+        return self.cache.get_mut().unwrap_or_else(PoisonError::into_inner);
+    }
+}
+
+impl<V: Clone, B: BackEnd<V>> BackEnd<V> for LruCached<B, V> {
+    type Error = B::Error;
+    type Snapshot = B::Snapshot;
+
+    async fn get(&self, key: &[u8]) -> Result<Option<V>, Self::Error> {
+        if let Some(value) = self.cache().get(key) {
+            return Ok(Some(value.clone()));
+        }
+
+        let value = self.inner.get(key).await?;
+        if let Some(value) = value.as_ref() {
+            self.cache().put(key.to_vec(), value.clone());
+        }
+        return Ok(value);
+    }
+
+    fn find_lte(
+        &self,
+        key: &[u8],
+    ) -> impl Future<Output = Result<Option<(Vec<u8>, V)>, Self::Error>> {
+        return self.inner.find_lte(key);
+    }
+
+    fn find_gte(
+        &self,
+        key: &[u8],
+    ) -> impl Future<Output = Result<Option<(Vec<u8>, V)>, Self::Error>> {
+        return self.inner.find_gte(key);
+    }
+
+    async fn get_many(&self, keys: &[&[u8]]) -> Result<Vec<Option<V>>, Self::Error> {
+        let mut values: Vec<Option<V>> = {
+            let mut cache = self.cache();
+            keys.iter().map(|key| cache.get(*key).cloned()).collect()
+        };
+
+        // Fetch all keys that were not cached with a single call to the wrapped backend.
+        let missing: Vec<&[u8]> = keys
+            .iter()
+            .zip(values.iter())
+            .filter(|(_, value)| value.is_none())
+            .map(|(key, _)| *key)
+            .collect();
+        if missing.is_empty() {
+            return Ok(values);
+        }
+        let mut fetched = self.inner.get_many(&missing).await?.into_iter();
+
+        let mut cache = self.cache();
+        for (key, value) in keys.iter().zip(values.iter_mut()) {
+            if value.is_none() {
+                *value = fetched.next().flatten();
+                if let Some(value) = value.as_ref() {
+                    cache.put(key.to_vec(), value.clone());
+                }
+            }
+        }
+        return Ok(values);
+    }
+
+    async fn insert(&mut self, key: &[u8], value: V) -> Result<Option<V>, Self::Error> {
+        // Invalidate before forwarding, so that the cache stays correct even if this Future is dropped after the wrapped backend applied the mutation.
+        self.cache_mut().pop(key);
+        return self.inner.insert(key, value).await;
+    }
+
+    async fn delete(&mut self, key: &[u8]) -> Result<Option<V>, Self::Error> {
+        self.cache_mut().pop(key);
+        return self.inner.delete(key).await;
+    }
+
+    async fn update_with<F: FnOnce(Option<V>) -> Option<V>>(
+        &mut self,
+        key: &[u8],
+        f: F,
+    ) -> Result<Option<V>, Self::Error> {
+        self.cache_mut().pop(key);
+        return self.inner.update_with(key, f).await;
+    }
+
+    async fn delete_range(&mut self, lo: Bound<&[u8]>, hi: Bound<&[u8]>) -> Result<usize, Self::Error> {
+        let cache = self.cache_mut();
+        let stale: Vec<Vec<u8>> = cache
+            .iter()
+            .map(|(key, _)| key)
+            .filter(|key| (lo, hi).contains::<[u8]>(key))
+            .cloned()
+            .collect();
+        for key in stale.iter() {
+            cache.pop(key);
+        }
+
+        return self.inner.delete_range(lo, hi).await;
+    }
+
+    fn find_nearest(
+        &self,
+        key: &[u8],
+    ) -> impl Future<Output = Result<Option<(Vec<u8>, V)>, Self::Error>> {
+        return self.inner.find_nearest(key);
+    }
+
+    async fn range<'s>(
+        &'s self,
+        lo: Bound<&'s [u8]>,
+        hi: Bound<&'s [u8]>,
+    ) -> Result<impl Iterator<Item = (Vec<u8>, V)>, Self::Error> {
+        return self.inner.range(lo, hi).await;
+    }
+
+    async fn range_rev<'s>(
+        &'s self,
+        lo: Bound<&'s [u8]>,
+        hi: Bound<&'s [u8]>,
+    ) -> Result<impl Iterator<Item = (Vec<u8>, V)>, Self::Error> {
+        return self.inner.range_rev(lo, hi).await;
+    }
+
+    async fn scan_prefix<'s>(
+        &'s self,
+        prefix: &'s [u8],
+    ) -> Result<impl Iterator<Item = (Vec<u8>, V)>, Self::Error> {
+        return self.inner.scan_prefix(prefix).await;
+    }
+
+    fn page_after<'s>(
+        &'s self,
+        after: Option<&'s [u8]>,
+        limit: usize,
+    ) -> impl Future<Output = Result<Vec<(Vec<u8>, V)>, Self::Error>> {
+        return self.inner.page_after(after, limit);
+    }
+
+    fn stream_range<'s>(
+        &'s self,
+        lo: Bound<&'s [u8]>,
+        hi: Bound<&'s [u8]>,
+    ) -> impl Stream<Item = Result<(Vec<u8>, V), Self::Error>> + 's {
+        return self.inner.stream_range(lo, hi);
+    }
+
+    fn snapshot(&self) -> impl Future<Output = Result<Self::Snapshot, Self::Error>> {
+        return self.inner.snapshot();
+    }
+
+    fn flush(&mut self) -> impl Future<Output = Result<(), Self::Error>> {
+        return self.inner.flush();
+    }
+}
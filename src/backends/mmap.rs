@@ -0,0 +1,524 @@
+use core::fmt;
+use core::marker::PhantomData;
+use core::ops::Bound::{self, Excluded, Included, Unbounded};
+use std::ffi::OsString;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use futures::{stream, Stream};
+use memmap2::Mmap;
+
+use crate::{BackEnd, BytesCodec, ReadBackEnd, ValueCodec};
+
+/// A read-only [`BackEnd`] over a memory-mapped file of kv pairs sorted by key, as written by [`build_from_sorted`](MmapSortedBackEnd::build_from_sorted).
+///
+/// All reads binary-search the mapped bytes, so the backend needs no memory per kv pair and opening a file takes constant time. This suits static, read-heavy data sets. All mutations fail with [`MmapError::ReadOnly`].
+///
+/// The file consists of the kv pairs in ascending order of keys, each stored as the length of the key (four bytes, little-endian), the key, the length of the encoded value (four bytes, little-endian), and the encoded value. These records are followed by the offsets of all records (eight bytes each, little-endian), and finally by the number of records (eight bytes, little-endian).
+///
+/// Values are turned into bytes and back by a [`ValueCodec`]. By default, this is the [`BytesCodec`], which stores values that already are bytes as they are. Malformed files and stored bytes that the codec fails to decode are reported as [`Corrupted`](MmapError::Corrupted) errors.
+///
+/// The file must not be modified while it is mapped, which is why all constructors are `unsafe`.
+#[derive(Debug, Clone)]
+pub struct MmapSortedBackEnd<V, C = BytesCodec> {
+    file: SortedFile,
+    codec: C,
+    phantom: PhantomData<fn() -> V>,
+}
+
+/// The error type of the [`MmapSortedBackEnd`].
+#[derive(Debug)]
+pub enum MmapError {
+    /// A mutation was attempted, but the backend is read-only.
+    ReadOnly,
+    /// Reading or writing the file failed.
+    Io(io::Error),
+    /// The file is malformed, or the codec failed to decode a stored value.
+    Corrupted(String),
+}
+
+impl fmt::Display for MmapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MmapError::ReadOnly => return write!(f, "the backend is read-only"),
+            MmapError::Io(err) => return write!(f, "I/O error: {}", err),
+            MmapError::Corrupted(message) => return write!(f, "corrupted file: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for MmapError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            MmapError::Io(err) => return Some(err),
+            _ => return None,
+        }
+    }
+}
+
+impl From<io::Error> for MmapError {
+    fn from(err: io::Error) -> Self {
+        return MmapError::Io(err);
+    }
+}
+
+impl<V> MmapSortedBackEnd<V> {
+    /// Map the sorted file at the given path. Values are stored with the [`BytesCodec`].
+    ///
+    /// # Safety
+    ///
+    /// See [`open_with_codec`](MmapSortedBackEnd::open_with_codec).
+    pub unsafe fn open<P: AsRef<Path>>(path: P) -> Result<Self, MmapError> {
+        return Self::open_with_codec(path, BytesCodec);
+    }
+
+    /// Write the given kv pairs to a new sorted file at the given path (replacing any existing file), and map it. Values are stored with the [`BytesCodec`].
+    ///
+    /// # Safety
+    ///
+    /// See [`open_with_codec`](MmapSortedBackEnd::open_with_codec).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the keys are not strictly ascending. Any existing file at the given path is then left untouched.
+    pub unsafe fn build_from_sorted<P, K, I>(path: P, entries: I) -> Result<Self, MmapError>
+    where
+        P: AsRef<Path>,
+        K: AsRef<[u8]>,
+        I: IntoIterator<Item = (K, V)>,
+        BytesCodec: ValueCodec<V>,
+    {
+        return Self::build_from_sorted_with_codec(path, entries, BytesCodec);
+    }
+}
+
+impl<V, C> MmapSortedBackEnd<V, C> {
+    /// Map the sorted file at the given path. Values are stored with the given codec.
+    ///
+    /// # Safety
+    ///
+    /// The file must not be modified or truncated, by this or any other process, for as long as the backend or any of its snapshots exist. Otherwise, reads observe the changes, which is undefined behavior.
+    pub unsafe fn open_with_codec<P: AsRef<Path>>(path: P, codec: C) -> Result<Self, MmapError> {
+        return Ok(MmapSortedBackEnd {
+            file: SortedFile::open(&File::open(path)?)?,
+            codec,
+            phantom: PhantomData,
+        });
+    }
+
+    /// Write the given kv pairs to a new sorted file at the given path (replacing any existing file), and map it. Values are stored with the given codec.
+    ///
+    /// The kv pairs are first written to a temporary file next to the given path, which then replaces any existing file. If writing fails or panics, the temporary file is removed, and any existing file stays as it was.
+    ///
+    /// # Safety
+    ///
+    /// See [`open_with_codec`](MmapSortedBackEnd::open_with_codec).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the keys are not strictly ascending.
+    pub unsafe fn build_from_sorted_with_codec<P, K, I>(
+        path: P,
+        entries: I,
+        codec: C,
+    ) -> Result<Self, MmapError>
+    where
+        P: AsRef<Path>,
+        K: AsRef<[u8]>,
+        I: IntoIterator<Item = (K, V)>,
+        C: ValueCodec<V>,
+    {
+        let path = path.as_ref();
+        let temp = TempFile::next_to(path);
+        let mut out = BufWriter::new(File::create(&temp.path)?);
+
+        let mut offsets: Vec<u64> = Vec::new();
+        let mut offset: u64 = 0;
+        let mut previous: Option<Vec<u8>> = None;
+
+        for (key, value) in entries {
+            let key = key.as_ref();
+            if let Some(previous) = previous.as_ref() {
+                assert!(previous.as_slice() < key, "keys must be strictly ascending");
+            }
+
+            let value = codec.encode(&value);
+            offsets.push(offset);
+            for bytes in [key, value.as_slice()] {
+                out.write_all(&length_prefix(bytes)?)?;
+                out.write_all(bytes)?;
+                offset += (LENGTH_PREFIX_LEN + bytes.len()) as u64;
+            }
+
+            previous = Some(key.to_vec());
+        }
+
+        for offset in offsets.iter() {
+            out.write_all(&offset.to_le_bytes())?;
+        }
+        out.write_all(&(offsets.len() as u64).to_le_bytes())?;
+
+        out.into_inner()
+            .map_err(|err| err.into_error())?
+            .sync_all()?;
+        temp.persist(path)?;
+
+        return Self::open_with_codec(path, codec);
+    }
+
+    /// Return the codec with which values are stored.
+    pub fn codec(&self) -> &C {
+        return &self.codec;
+    }
+
+    /// Return the number of kv pairs in the file.
+    pub fn len(&self) -> usize {
+        return self.file.count;
+    }
+
+    /// Return whether the file contains no kv pairs.
+    pub fn is_empty(&self) -> bool {
+        return self.file.count == 0;
+    }
+}
+
+impl<V, C> BackEnd<V> for MmapSortedBackEnd<V, C>
+where
+    C: ValueCodec<V> + Clone,
+{
+    type Error = MmapError;
+    type Snapshot = MmapSortedSnapshot<V, C>;
+
+    async fn get(&self, key: &[u8]) -> Result<Option<V>, Self::Error> {
+        return self.file.get(key, &self.codec);
+    }
+
+    async fn find_lte(&self, key: &[u8]) -> Result<Option<(Vec<u8>, V)>, Self::Error> {
+        return self.file.find_lte(key, &self.codec);
+    }
+
+    async fn find_gte(&self, key: &[u8]) -> Result<Option<(Vec<u8>, V)>, Self::Error> {
+        return self.file.find_gte(key, &self.codec);
+    }
+
+    /// Always fails with [`MmapError::ReadOnly`].
+    async fn insert(&mut self, _key: &[u8], _value: V) -> Result<Option<V>, Self::Error> {
+        return Err(MmapError::ReadOnly);
+    }
+
+    /// Always fails with [`MmapError::ReadOnly`].
+    async fn delete(&mut self, _key: &[u8]) -> Result<Option<V>, Self::Error> {
+        return Err(MmapError::ReadOnly);
+    }
+
+    async fn range<'s>(
+        &'s self,
+        lo: Bound<&'s [u8]>,
+        hi: Bound<&'s [u8]>,
+    ) -> Result<impl Iterator<Item = (Vec<u8>, V)>, Self::Error> {
+        return self.file.range(lo, hi, false, &self.codec);
+    }
+
+    async fn range_rev<'s>(
+        &'s self,
+        lo: Bound<&'s [u8]>,
+        hi: Bound<&'s [u8]>,
+    ) -> Result<impl Iterator<Item = (Vec<u8>, V)>, Self::Error> {
+        return self.file.range(lo, hi, true, &self.codec);
+    }
+
+    fn stream_range<'s>(
+        &'s self,
+        lo: Bound<&'s [u8]>,
+        hi: Bound<&'s [u8]>,
+    ) -> impl Stream<Item = Result<(Vec<u8>, V), Self::Error>> + 's {
+        let (error, indices) = match self.file.indices(lo, hi) {
+            Ok(indices) => (None, indices),
+            Err(err) => (Some(Err(err)), 0..0),
+        };
+        let entries = indices.map(move |i| self.file.entry(i, &self.codec));
+
+        return stream::iter(error.into_iter().chain(entries));
+    }
+
+    /// The file never changes, so the snapshot merely shares the mapping.
+    async fn snapshot(&self) -> Result<Self::Snapshot, Self::Error> {
+        return Ok(MmapSortedSnapshot {
+            file: self.file.clone(),
+            codec: self.codec.clone(),
+            phantom: PhantomData,
+        });
+    }
+
+    /// There never are any mutations to persist, so this always succeeds.
+    async fn flush(&mut self) -> Result<(), Self::Error> {
+        return Ok(());
+    }
+}
+
+/// A [snapshot](BackEnd::snapshot) of a [`MmapSortedBackEnd`], which shares the mapping of its file.
+#[derive(Debug, Clone)]
+pub struct MmapSortedSnapshot<V, C = BytesCodec> {
+    file: SortedFile,
+    codec: C,
+    phantom: PhantomData<fn() -> V>,
+}
+
+impl<V, C> ReadBackEnd<V> for MmapSortedSnapshot<V, C>
+where
+    C: ValueCodec<V>,
+{
+    type Error = MmapError;
+
+    async fn get(&self, key: &[u8]) -> Result<Option<V>, Self::Error> {
+        return self.file.get(key, &self.codec);
+    }
+
+    async fn find_lte(&self, key: &[u8]) -> Result<Option<(Vec<u8>, V)>, Self::Error> {
+        return self.file.find_lte(key, &self.codec);
+    }
+
+    async fn find_gte(&self, key: &[u8]) -> Result<Option<(Vec<u8>, V)>, Self::Error> {
+        return self.file.find_gte(key, &self.codec);
+    }
+
+    async fn range<'s>(
+        &'s self,
+        lo: Bound<&'s [u8]>,
+        hi: Bound<&'s [u8]>,
+    ) -> Result<impl Iterator<Item = (Vec<u8>, V)>, Self::Error> {
+        return self.file.range(lo, hi, false, &self.codec);
+    }
+}
+
+// The number of bytes of the length prefixes of keys and values.
+const LENGTH_PREFIX_LEN: usize = 4;
+
+// The number of bytes of a record offset, and of the record count at the end of the file.
+const WORD_LEN: usize = 8;
+
+fn length_prefix(bytes: &[u8]) -> Result<[u8; LENGTH_PREFIX_LEN], io::Error> {
+    return u32::try_from(bytes.len())
+        .map(u32::to_le_bytes)
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "key or value too long"));
+}
+
+// A temporary file, which is removed when dropped unless it has been persisted.
+struct TempFile {
+    path: PathBuf,
+}
+
+impl TempFile {
+    // The temporary file for building the file at the given path: the same path with `.tmp` appended.
+    fn next_to(path: &Path) -> TempFile {
+        let mut temp = OsString::from(path.as_os_str());
+        temp.push(".tmp");
+        return TempFile {
+            path: PathBuf::from(temp),
+        };
+    }
+
+    // Move the temporary file to the given path, replacing any file there.
+    fn persist(mut self, path: &Path) -> Result<(), io::Error> {
+        fs::rename(&self.path, path)?;
+        self.path = PathBuf::new();
+        return Ok(());
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        if !self.path.as_os_str().is_empty() {
+            let _ = fs::remove_file(&self.path);
+        }
+    }
+}
+
+// A mapped sorted file, with the location of its offset table.
+#[derive(Debug, Clone)]
+struct SortedFile {
+    mmap: Arc<Mmap>,
+    // The number of records.
+    count: usize,
+    // Where the offset table starts, which is also where the records end.
+    index_start: usize,
+}
+
+impl SortedFile {
+    // Safety: the file must not be modified while it is mapped, see `MmapSortedBackEnd::open_with_codec`.
+    unsafe fn open(file: &File) -> Result<Self, MmapError> {
+        let mmap = Mmap::map(file)?;
+
+        let count_start = mmap
+            .len()
+            .checked_sub(WORD_LEN)
+            .ok_or_else(|| corrupted("file too short"))?;
+        let count = read_word(&mmap, count_start)?;
+        let index_start = count
+            .checked_mul(WORD_LEN)
+            .and_then(|index_len| count_start.checked_sub(index_len))
+            .ok_or_else(|| corrupted("record count exceeds file size"))?;
+
+        return Ok(SortedFile {
+            mmap: Arc::new(mmap),
+            count,
+            index_start,
+        });
+    }
+
+    // The key and the encoded value of the `i`-th record.
+    fn record(&self, i: usize) -> Result<(&[u8], &[u8]), MmapError> {
+        let offset = read_word(&self.mmap, self.index_start + i * WORD_LEN)?;
+        let records = &self.mmap[..self.index_start];
+
+        let (key, value_start) = read_prefixed(records, offset)?;
+        let (value, _) = read_prefixed(records, value_start)?;
+        return Ok((key, value));
+    }
+
+    fn key(&self, i: usize) -> Result<&[u8], MmapError> {
+        return Ok(self.record(i)?.0);
+    }
+
+    fn entry<V, C: ValueCodec<V>>(&self, i: usize, codec: &C) -> Result<(Vec<u8>, V), MmapError> {
+        let (key, value) = self.record(i)?;
+        return Ok((key.to_vec(), decode_value(codec, value)?));
+    }
+
+    // The number of records whose key is less than the given key (or less than or equal to it, if `inclusive` is `true`).
+    fn count_below(&self, key: &[u8], inclusive: bool) -> Result<usize, MmapError> {
+        let mut lo = 0;
+        let mut hi = self.count;
+
+        while lo < hi {
+            let mid = lo + (hi - lo) / 2;
+            let below = if inclusive {
+                self.key(mid)? <= key
+            } else {
+                self.key(mid)? < key
+            };
+
+            if below {
+                lo = mid + 1;
+            } else {
+                hi = mid;
+            }
+        }
+
+        return Ok(lo);
+    }
+
+    fn get<V, C: ValueCodec<V>>(&self, key: &[u8], codec: &C) -> Result<Option<V>, MmapError> {
+        let i = self.count_below(key, false)?;
+        if i == self.count {
+            return Ok(None);
+        }
+
+        let (found, value) = self.record(i)?;
+        if found != key {
+            return Ok(None);
+        }
+        return Ok(Some(decode_value(codec, value)?));
+    }
+
+    fn find_lte<V, C: ValueCodec<V>>(
+        &self,
+        key: &[u8],
+        codec: &C,
+    ) -> Result<Option<(Vec<u8>, V)>, MmapError> {
+        match self.count_below(key, true)? {
+            0 => return Ok(None),
+            i => return Ok(Some(self.entry(i - 1, codec)?)),
+        }
+    }
+
+    fn find_gte<V, C: ValueCodec<V>>(
+        &self,
+        key: &[u8],
+        codec: &C,
+    ) -> Result<Option<(Vec<u8>, V)>, MmapError> {
+        let i = self.count_below(key, false)?;
+        if i == self.count {
+            return Ok(None);
+        }
+        return Ok(Some(self.entry(i, codec)?));
+    }
+
+    // The indices of all records whose keys lie within the given bounds.
+    fn indices(
+        &self,
+        lo: Bound<&[u8]>,
+        hi: Bound<&[u8]>,
+    ) -> Result<core::ops::Range<usize>, MmapError> {
+        let start = match lo {
+            Included(key) => self.count_below(key, false)?,
+            Excluded(key) => self.count_below(key, true)?,
+            Unbounded => 0,
+        };
+        let end = match hi {
+            Included(key) => self.count_below(key, true)?,
+            Excluded(key) => self.count_below(key, false)?,
+            Unbounded => self.count,
+        };
+
+        // The bounds may describe an empty range with `lo` above `hi`.
+        return Ok(start..end.max(start));
+    }
+
+    // All kv pairs within the given bounds, in descending order of keys if `rev` is `true`, in ascending order otherwise.
+    fn range<V, C: ValueCodec<V>>(
+        &self,
+        lo: Bound<&[u8]>,
+        hi: Bound<&[u8]>,
+        rev: bool,
+        codec: &C,
+    ) -> Result<std::vec::IntoIter<(Vec<u8>, V)>, MmapError> {
+        let indices = self.indices(lo, hi)?;
+
+        // Decoding can fail, so we decode eagerly to report errors.
+        let entries: Result<Vec<_>, _> = if rev {
+            indices.rev().map(|i| self.entry(i, codec)).collect()
+        } else {
+            indices.map(|i| self.entry(i, codec)).collect()
+        };
+        return Ok(entries?.into_iter());
+    }
+}
+
+// Read the little-endian word at the given position.
+fn read_word(bytes: &[u8], start: usize) -> Result<usize, MmapError> {
+    let word = bytes
+        .get(start..start + WORD_LEN)
+        .ok_or_else(|| corrupted("offset table out of bounds"))?;
+    let word = u64::from_le_bytes(word.try_into().unwrap());
+    return usize::try_from(word).map_err(|_| corrupted("offset exceeds address space"));
+}
+
+// Read the length-prefixed bytes at the given position, and return them together with the position right after them.
+fn read_prefixed(bytes: &[u8], start: usize) -> Result<(&[u8], usize), MmapError> {
+    let prefix = start
+        .checked_add(LENGTH_PREFIX_LEN)
+        .and_then(|end| bytes.get(start..end))
+        .ok_or_else(|| corrupted("record out of bounds"))?;
+    let len = u32::from_le_bytes(prefix.try_into().unwrap()) as usize;
+
+    let data_start = start + LENGTH_PREFIX_LEN;
+    let data = data_start
+        .checked_add(len)
+        .and_then(|end| bytes.get(data_start..end))
+        .ok_or_else(|| corrupted("record out of bounds"))?;
+    return Ok((data, data_start + len));
+}
+
+fn corrupted(message: &str) -> MmapError {
+    return MmapError::Corrupted(message.to_string());
+}
+
+// Decode a value stored in the file, reporting bytes that the codec rejects as corruption.
+fn decode_value<V, C: ValueCodec<V>>(codec: &C, bytes: &[u8]) -> Result<V, MmapError> {
+    return codec
+        .decode(bytes)
+        .map_err(|err| MmapError::Corrupted(err.to_string()));
+}
@@ -0,0 +1,30 @@
+//! Implementations of the [`BackEnd`](crate::BackEnd) and [`SharedBackEnd`](crate::SharedBackEnd) traits.
+
+mod auto_flush;
+pub use auto_flush::*;
+
+mod btree_map;
+pub use btree_map::*;
+
+mod size_tracked;
+pub use size_tracked::*;
+
+#[cfg(feature = "std")]
+mod shared_btree_map;
+#[cfg(feature = "std")]
+pub use shared_btree_map::*;
+
+#[cfg(feature = "lru")]
+mod lru;
+#[cfg(feature = "lru")]
+pub use self::lru::*;
+
+#[cfg(feature = "mmap")]
+mod mmap;
+#[cfg(feature = "mmap")]
+pub use mmap::*;
+
+#[cfg(feature = "redb-backend")]
+mod redb;
+#[cfg(feature = "redb-backend")]
+pub use self::redb::*;
@@ -0,0 +1,461 @@
+// `redb::Error` is large, but it is what redb gives us.
+#![allow(clippy::result_large_err)]
+
+use core::cmp::Ordering;
+use core::ops::Bound::{self, Included, Unbounded};
+use std::collections::{btree_map, BTreeMap};
+
+use ::redb::{Database, Range, ReadOnlyTable, TableDefinition};
+use futures::{stream, Stream};
+
+use crate::backend::is_empty_range;
+use crate::{BackEnd, BytesCodec, ReadBackEnd, ValueCodec};
+
+// The single table in which we store all kv pairs.
+const TABLE: TableDefinition<&[u8], &[u8]> = TableDefinition::new("kv_3d_storage");
+
+/// A [`BackEnd`] that persists its kv pairs in a single table of a [redb](https://docs.rs/redb) [`Database`].
+///
+/// Mutations are buffered in memory until [`flush`](BackEnd::flush) is called, which applies all of them in a single write transaction. All reads take the buffered mutations into account.
+///
+/// Values are turned into bytes and back by a [`ValueCodec`]. By default, this is the [`BytesCodec`], which stores values that already are bytes as they are. Stored bytes that the codec fails to decode are reported as [`Corrupted`](::redb::Error::Corrupted) errors.
+#[derive(Debug)]
+pub struct RedbBackEnd<V, C = BytesCodec> {
+    db: Database,
+    // Mutations that have not been flushed yet, `None` denotes a deletion.
+    pending: BTreeMap<Vec<u8>, Option<V>>,
+    codec: C,
+}
+
+impl<V> RedbBackEnd<V> {
+    /// Wrap a redb [`Database`], creating the table this backend uses if it does not exist yet. Values are stored with the [`BytesCodec`].
+    pub fn new(db: Database) -> Result<Self, ::redb::Error> {
+        return Self::with_codec(db, BytesCodec);
+    }
+}
+
+impl<V, C> RedbBackEnd<V, C> {
+    /// Wrap a redb [`Database`], creating the table this backend uses if it does not exist yet. Values are stored with the given codec.
+    pub fn with_codec(db: Database, codec: C) -> Result<Self, ::redb::Error> {
+        let txn = db.begin_write()?;
+        txn.open_table(TABLE)?;
+        txn.commit()?;
+
+        return Ok(RedbBackEnd {
+            db,
+            pending: BTreeMap::new(),
+            codec,
+        });
+    }
+
+    /// Return the codec with which values are stored.
+    pub fn codec(&self) -> &C {
+        return &self.codec;
+    }
+
+    /// Return the underlying [`Database`], discarding all mutations that have not been [flushed](BackEnd::flush).
+    pub fn into_database(self) -> Database {
+        return self.db;
+    }
+}
+
+impl<V, C> RedbBackEnd<V, C> {
+    // Open the table in a new read transaction. The table keeps the transaction alive.
+    fn read_table(&self) -> Result<ReadTable, ::redb::Error> {
+        return Ok(self.db.begin_read()?.open_table(TABLE)?);
+    }
+
+    // A view of the given table together with the pending mutations.
+    fn view<'t>(&self, table: &'t ReadTable) -> View<'t, '_, V, C> {
+        return View {
+            table,
+            pending: &self.pending,
+            codec: &self.codec,
+        };
+    }
+}
+
+// The flushed table as of some read transaction, the pending mutations, and the codec: everything needed to answer reads.
+type ReadTable = ReadOnlyTable<&'static [u8], &'static [u8]>;
+
+struct View<'t, 's, V, C> {
+    table: &'t ReadTable,
+    // Mutations that have not been flushed yet, `None` denotes a deletion.
+    pending: &'s BTreeMap<Vec<u8>, Option<V>>,
+    codec: &'s C,
+}
+
+impl<'s, V, C> View<'_, 's, V, C>
+where
+    V: Clone,
+    C: ValueCodec<V>,
+{
+    fn get(&self, key: &[u8]) -> Result<Option<V>, ::redb::Error> {
+        match self.pending.get(key) {
+            Some(pending) => return Ok(pending.clone()),
+            None => match self.table.get(key)? {
+                None => return Ok(None),
+                Some(v) => return Ok(Some(decode_value(self.codec, v.value())?)),
+            },
+        }
+    }
+
+    fn find_lte(&self, key: &[u8]) -> Result<Option<(Vec<u8>, V)>, ::redb::Error> {
+        let pending = self
+            .pending
+            .range::<[u8], _>((Unbounded, Included(key)))
+            .rev()
+            .find_map(|(k, v)| v.as_ref().map(|v| (k.clone(), v.clone())));
+        let flushed = self.find_flushed(Unbounded, Included(key), true)?;
+
+        // The two candidates never have the same key, since flushed entries with pending mutations are skipped.
+        match (pending, flushed) {
+            (Some(p), Some(f)) => return Ok(Some(if p.0 > f.0 { p } else { f })),
+            (p, f) => return Ok(p.or(f)),
+        }
+    }
+
+    fn find_gte(&self, key: &[u8]) -> Result<Option<(Vec<u8>, V)>, ::redb::Error> {
+        let pending = self
+            .pending
+            .range::<[u8], _>((Included(key), Unbounded))
+            .find_map(|(k, v)| v.as_ref().map(|v| (k.clone(), v.clone())));
+        let flushed = self.find_flushed(Included(key), Unbounded, false)?;
+
+        // The two candidates never have the same key, since flushed entries with pending mutations are skipped.
+        match (pending, flushed) {
+            (Some(p), Some(f)) => return Ok(Some(if p.0 < f.0 { p } else { f })),
+            (p, f) => return Ok(p.or(f)),
+        }
+    }
+
+    // Get the first entry of the table within the given bounds (the last one if `rev` is `true`) whose key has no pending mutation.
+    fn find_flushed(
+        &self,
+        lower: Bound<&[u8]>,
+        upper: Bound<&[u8]>,
+        rev: bool,
+    ) -> Result<Option<(Vec<u8>, V)>, ::redb::Error> {
+        let mut range = self.table.range::<&[u8]>((lower, upper))?;
+
+        loop {
+            let entry = if rev { range.next_back() } else { range.next() };
+
+            match entry {
+                None => return Ok(None),
+                Some(entry) => {
+                    let (k, v) = entry?;
+                    if !self.pending.contains_key(k.value()) {
+                        return Ok(Some((k.value().to_vec(), decode_value(self.codec, v.value())?)));
+                    }
+                }
+            }
+        }
+    }
+
+    // Iterate over all entries within the given bounds, taking pending mutations into account.
+    fn merged_range(
+        &self,
+        lower: Bound<&[u8]>,
+        upper: Bound<&[u8]>,
+        rev: bool,
+    ) -> Result<MergedRange<'s, V, C>, ::redb::Error> {
+        let mut merged = MergedRange::empty(self.codec, rev);
+
+        if !is_empty_range(lower, upper) {
+            // The cursor keeps its read transaction alive.
+            merged.flushed = Some(self.table.range::<&[u8]>((lower, upper))?);
+            merged.pending = self.pending.range::<[u8], _>((lower, upper));
+        }
+
+        return Ok(merged);
+    }
+}
+
+// Iterator over the entries within some bounds, merging the flushed entries with the pending mutations. Yields in descending order of keys if `rev` is `true`, in ascending order otherwise.
+struct MergedRange<'s, V, C> {
+    codec: &'s C,
+    pending: btree_map::Range<'s, Vec<u8>, Option<V>>,
+    // `None` once the cursor has been exhausted or has produced an error.
+    flushed: Option<Range<'static, &'static [u8], &'static [u8]>>,
+    // The entries most recently taken from `pending` and `flushed` but not yet yielded.
+    next_pending: Option<(&'s Vec<u8>, &'s Option<V>)>,
+    next_flushed: Option<(Vec<u8>, V)>,
+    rev: bool,
+}
+
+impl<'s, V, C> MergedRange<'s, V, C> {
+    // A `MergedRange` that yields nothing.
+    fn empty(codec: &'s C, rev: bool) -> Self {
+        return MergedRange {
+            codec,
+            pending: btree_map::Range::default(),
+            flushed: None,
+            next_pending: None,
+            next_flushed: None,
+            rev,
+        };
+    }
+}
+
+impl<V, C> Iterator for MergedRange<'_, V, C>
+where
+    V: Clone,
+    C: ValueCodec<V>,
+{
+    type Item = Result<(Vec<u8>, V), ::redb::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if self.next_pending.is_none() {
+                self.next_pending = if self.rev {
+                    self.pending.next_back()
+                } else {
+                    self.pending.next()
+                };
+            }
+
+            if self.next_flushed.is_none() {
+                if let Some(flushed) = self.flushed.as_mut() {
+                    let entry = if self.rev {
+                        flushed.next_back()
+                    } else {
+                        flushed.next()
+                    };
+
+                    match entry {
+                        None => self.flushed = None,
+                        Some(Err(err)) => {
+                            *self = MergedRange::empty(self.codec, self.rev);
+                            return Some(Err(err.into()));
+                        }
+                        Some(Ok((k, v))) => match decode_value(self.codec, v.value()) {
+                            Err(err) => {
+                                *self = MergedRange::empty(self.codec, self.rev);
+                                return Some(Err(err));
+                            }
+                            Ok(value) => self.next_flushed = Some((k.value().to_vec(), value)),
+                        },
+                    }
+                }
+            }
+
+            // `Less` if the pending entry comes first in iteration order.
+            let order = match (self.next_pending, self.next_flushed.as_ref()) {
+                (None, None) => return None,
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (Some((pending_key, _)), Some((flushed_key, _))) => {
+                    if self.rev {
+                        flushed_key.cmp(pending_key)
+                    } else {
+                        pending_key.cmp(flushed_key)
+                    }
+                }
+            };
+
+            if order == Ordering::Greater {
+                return self.next_flushed.take().map(Ok);
+            }
+
+            // Pending mutations take precedence over flushed entries of the same key.
+            if order == Ordering::Equal {
+                self.next_flushed = None;
+            }
+
+            if let Some((key, Some(value))) = self.next_pending.take() {
+                return Some(Ok((key.clone(), value.clone())));
+            }
+        }
+    }
+}
+
+impl<V, C> BackEnd<V> for RedbBackEnd<V, C>
+where
+    V: Clone,
+    C: ValueCodec<V> + Clone,
+{
+    type Error = ::redb::Error;
+    type Snapshot = RedbSnapshot<V, C>;
+
+    async fn get(&self, key: &[u8]) -> Result<Option<V>, Self::Error> {
+        match self.pending.get(key) {
+            Some(pending) => return Ok(pending.clone()),
+            None => return self.view(&self.read_table()?).get(key),
+        }
+    }
+
+    /// Looks up all keys without pending mutations within a single read transaction, so the results reflect a single state of the database.
+    async fn get_many(&self, keys: &[&[u8]]) -> Result<Vec<Option<V>>, Self::Error> {
+        let table = self.read_table()?;
+        let view = self.view(&table);
+
+        let mut values = Vec::with_capacity(keys.len());
+        for key in keys {
+            values.push(view.get(key)?);
+        }
+
+        return Ok(values);
+    }
+
+    async fn find_lte(&self, key: &[u8]) -> Result<Option<(Vec<u8>, V)>, Self::Error> {
+        return self.view(&self.read_table()?).find_lte(key);
+    }
+
+    async fn find_gte(&self, key: &[u8]) -> Result<Option<(Vec<u8>, V)>, Self::Error> {
+        return self.view(&self.read_table()?).find_gte(key);
+    }
+
+    async fn insert(&mut self, key: &[u8], value: V) -> Result<Option<V>, Self::Error> {
+        let old = self.get(key).await?;
+        self.pending.insert(key.to_vec(), Some(value));
+        return Ok(old);
+    }
+
+    async fn delete(&mut self, key: &[u8]) -> Result<Option<V>, Self::Error> {
+        let old = self.get(key).await?;
+        self.pending.insert(key.to_vec(), None);
+        return Ok(old);
+    }
+
+    /// Reads the keys to delete within a single read transaction and records their deletions as pending mutations, so that the next flush removes them in the same write transaction as all other pending mutations.
+    async fn delete_range(&mut self, lo: Bound<&[u8]>, hi: Bound<&[u8]>) -> Result<usize, Self::Error> {
+        let keys = self
+            .view(&self.read_table()?)
+            .merged_range(lo, hi, false)?
+            .map(|entry| entry.map(|(key, _)| key))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let deleted = keys.len();
+        for key in keys {
+            self.pending.insert(key, None);
+        }
+        return Ok(deleted);
+    }
+
+    async fn range_rev<'s>(
+        &'s self,
+        lo: Bound<&'s [u8]>,
+        hi: Bound<&'s [u8]>,
+    ) -> Result<impl Iterator<Item = (Vec<u8>, V)>, Self::Error> {
+        // Errors can occur during iteration, so we have to iterate eagerly to report them.
+        let entries = self
+            .view(&self.read_table()?)
+            .merged_range(lo, hi, true)?
+            .collect::<Result<Vec<_>, _>>()?;
+        return Ok(entries.into_iter());
+    }
+
+    fn stream_range<'s>(
+        &'s self,
+        lo: Bound<&'s [u8]>,
+        hi: Bound<&'s [u8]>,
+    ) -> impl Stream<Item = Result<(Vec<u8>, V), Self::Error>> + 's {
+        let merged = self
+            .read_table()
+            .and_then(|table| self.view(&table).merged_range(lo, hi, false));
+        let (error, merged) = match merged {
+            Ok(merged) => (None, merged),
+            Err(err) => (Some(Err(err)), MergedRange::empty(&self.codec, false)),
+        };
+
+        return stream::iter(error.into_iter().chain(merged));
+    }
+
+    /// Opens a read transaction, which redb isolates from all later write transactions, and copies the pending mutations.
+    async fn snapshot(&self) -> Result<Self::Snapshot, Self::Error> {
+        return Ok(RedbSnapshot {
+            table: self.read_table()?,
+            pending: self.pending.clone(),
+            codec: self.codec.clone(),
+        });
+    }
+
+    /// Commits all pending mutations in a single write transaction. The pending mutations are only discarded after the commit succeeded, so cancelling a flush (or a failing flush) leaves them pending for the next one.
+    async fn flush(&mut self) -> Result<(), Self::Error> {
+        if self.pending.is_empty() {
+            return Ok(());
+        }
+
+        let txn = self.db.begin_write()?;
+        {
+            let mut table = txn.open_table(TABLE)?;
+            for (key, value) in self.pending.iter() {
+                match value {
+                    Some(value) => {
+                        table.insert(key.as_slice(), self.codec.encode(value).as_slice())?;
+                    }
+                    None => {
+                        table.remove(key.as_slice())?;
+                    }
+                }
+            }
+        }
+        txn.commit()?;
+
+        self.pending.clear();
+        return Ok(());
+    }
+}
+
+/// A [snapshot](BackEnd::snapshot) of a [`RedbBackEnd`]: a read transaction on its database, together with a copy of the mutations that were pending when the snapshot was taken.
+///
+/// The snapshot keeps its read transaction alive until it is dropped, so redb cannot reclaim pages that have been freed since it was taken.
+#[derive(Debug)]
+pub struct RedbSnapshot<V, C = BytesCodec> {
+    table: ReadTable,
+    // Mutations that had not been flushed when the snapshot was taken, `None` denotes a deletion.
+    pending: BTreeMap<Vec<u8>, Option<V>>,
+    codec: C,
+}
+
+impl<V, C> RedbSnapshot<V, C> {
+    // A view of the snapshotted table together with the snapshotted pending mutations.
+    fn view(&self) -> View<'_, '_, V, C> {
+        return View {
+            table: &self.table,
+            pending: &self.pending,
+            codec: &self.codec,
+        };
+    }
+}
+
+impl<V, C> ReadBackEnd<V> for RedbSnapshot<V, C>
+where
+    V: Clone,
+    C: ValueCodec<V>,
+{
+    type Error = ::redb::Error;
+
+    async fn get(&self, key: &[u8]) -> Result<Option<V>, Self::Error> {
+        return self.view().get(key);
+    }
+
+    async fn find_lte(&self, key: &[u8]) -> Result<Option<(Vec<u8>, V)>, Self::Error> {
+        return self.view().find_lte(key);
+    }
+
+    async fn find_gte(&self, key: &[u8]) -> Result<Option<(Vec<u8>, V)>, Self::Error> {
+        return self.view().find_gte(key);
+    }
+
+    async fn range<'s>(
+        &'s self,
+        lo: Bound<&'s [u8]>,
+        hi: Bound<&'s [u8]>,
+    ) -> Result<impl Iterator<Item = (Vec<u8>, V)>, Self::Error> {
+        // Errors can occur during iteration, so we have to iterate eagerly to report them.
+        let entries = self
+            .view()
+            .merged_range(lo, hi, false)?
+            .collect::<Result<Vec<_>, _>>()?;
+        return Ok(entries.into_iter());
+    }
+}
+
+// Decode a value stored in the table, reporting bytes that the codec rejects as corruption.
+fn decode_value<V, C: ValueCodec<V>>(codec: &C, bytes: &[u8]) -> Result<V, ::redb::Error> {
+    return codec
+        .decode(bytes)
+        .map_err(|err| ::redb::Error::Corrupted(err.to_string()));
+}
@@ -0,0 +1,123 @@
+use core::convert::Infallible;
+use core::ops::Bound::{Included, Unbounded};
+use std::collections::BTreeMap;
+use std::sync::{PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard};
+use std::vec::Vec;
+
+use crate::{BTreeMapSnapshot, BackEnd, SharedBackEnd};
+
+/// An in-memory [`SharedBackEnd`], backed by a [`BTreeMap`] behind a [`RwLock`]. Like the [`BTreeMapBackEnd`](crate::BTreeMapBackEnd), nothing is ever persisted.
+///
+/// This also implements [`BackEnd`], so it can be used wherever an exclusively owned backend is expected.
+#[derive(Debug)]
+pub struct SharedBTreeBackEnd<V> {
+    map: RwLock<BTreeMap<Vec<u8>, V>>,
+}
+
+impl<V> SharedBTreeBackEnd<V> {
+    /// Create an empty backend.
+    pub fn new() -> Self {
+        return SharedBTreeBackEnd {
+            map: RwLock::new(BTreeMap::new()),
+        };
+    }
+
+    /// Consume the backend and return the map of all its kv pairs.
+    pub fn into_map(self) -> BTreeMap<Vec<u8>, V> {
+        return self.map.into_inner().unwrap_or_else(PoisonError::into_inner);
+    }
+
+    // A panic while holding the lock cannot leave the map in an inconsistent state, so we ignore poisoning.
+    fn read(&self) -> RwLockReadGuard<'_, BTreeMap<Vec<u8>, V>> {
+        return self.map.read().unwrap_or_else(PoisonError::into_inner);
+    }
+
+    fn write(&self) -> RwLockWriteGuard<'_, BTreeMap<Vec<u8>, V>> {
+        return self.map.write().unwrap_or_else(PoisonError::into_inner);
+    }
+}
+
+impl<V> Default for SharedBTreeBackEnd<V> {
+    fn default() -> Self {
+        return Self::new();
+    }
+}
+
+impl<V> From<BTreeMap<Vec<u8>, V>> for SharedBTreeBackEnd<V> {
+    fn from(map: BTreeMap<Vec<u8>, V>) -> Self {
+        return SharedBTreeBackEnd {
+            map: RwLock::new(map),
+        };
+    }
+}
+
+impl<V: Clone> SharedBackEnd<V> for SharedBTreeBackEnd<V> {
+    type Error = Infallible;
+
+    async fn get(&self, key: &[u8]) -> Result<Option<V>, Self::Error> {
+        return Ok(self.read().get(key).cloned());
+    }
+
+    async fn find_lte(&self, key: &[u8]) -> Result<Option<(Vec<u8>, V)>, Self::Error> {
+        return Ok(self
+            .read()
+            .range::<[u8], _>((Unbounded, Included(key)))
+            .next_back()
+            .map(|(k, v)| (k.clone(), v.clone())));
+    }
+
+    async fn find_gte(&self, key: &[u8]) -> Result<Option<(Vec<u8>, V)>, Self::Error> {
+        return Ok(self
+            .read()
+            .range::<[u8], _>((Included(key), Unbounded))
+            .next()
+            .map(|(k, v)| (k.clone(), v.clone())));
+    }
+
+    async fn insert(&self, key: &[u8], value: V) -> Result<Option<V>, Self::Error> {
+        return Ok(self.write().insert(key.to_vec(), value));
+    }
+
+    async fn delete(&self, key: &[u8]) -> Result<Option<V>, Self::Error> {
+        return Ok(self.write().remove(key));
+    }
+
+    /// Nothing to do here, an in-memory backend never persists anything.
+    async fn flush(&self) -> Result<(), Self::Error> {
+        return Ok(());
+    }
+}
+
+impl<V: Clone> BackEnd<V> for SharedBTreeBackEnd<V> {
+    type Error = Infallible;
+    type Snapshot = BTreeMapSnapshot<V>;
+
+    async fn get(&self, key: &[u8]) -> Result<Option<V>, Self::Error> {
+        return SharedBackEnd::get(self, key).await;
+    }
+
+    async fn find_lte(&self, key: &[u8]) -> Result<Option<(Vec<u8>, V)>, Self::Error> {
+        return SharedBackEnd::find_lte(self, key).await;
+    }
+
+    async fn find_gte(&self, key: &[u8]) -> Result<Option<(Vec<u8>, V)>, Self::Error> {
+        return SharedBackEnd::find_gte(self, key).await;
+    }
+
+    async fn insert(&mut self, key: &[u8], value: V) -> Result<Option<V>, Self::Error> {
+        return SharedBackEnd::insert(&*self, key, value).await;
+    }
+
+    async fn delete(&mut self, key: &[u8]) -> Result<Option<V>, Self::Error> {
+        return SharedBackEnd::delete(&*self, key).await;
+    }
+
+    /// Clones the map while holding the read lock, which takes time and memory linear in the number of kv pairs.
+    async fn snapshot(&self) -> Result<Self::Snapshot, Self::Error> {
+        return Ok(BTreeMapSnapshot::from(self.read().clone()));
+    }
+
+    async fn flush(&mut self) -> Result<(), Self::Error> {
+        return SharedBackEnd::flush(&*self).await;
+    }
+}
@@ -0,0 +1,186 @@
+use alloc::vec::Vec;
+use core::future::Future;
+use core::ops::Bound;
+
+use futures::Stream;
+
+use crate::BackEnd;
+
+/// Values whose size in bytes (when serialized) can be computed cheaply, so that [`SizeTracked`] can estimate how much space a backend uses.
+pub trait ByteSized {
+    /// The number of bytes this value takes up when serialized.
+    fn byte_size(&self) -> usize;
+}
+
+macro_rules! impl_byte_sized_for_int {
+    ($($t:ty),*) => {
+        $(
+            impl ByteSized for $t {
+                fn byte_size(&self) -> usize {
+                    return core::mem::size_of::<$t>();
+                }
+            }
+        )*
+    };
+}
+
+impl_byte_sized_for_int!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128);
+
+impl ByteSized for () {
+    fn byte_size(&self) -> usize {
+        return 0;
+    }
+}
+
+impl ByteSized for Vec<u8> {
+    fn byte_size(&self) -> usize {
+        return self.len();
+    }
+}
+
+impl<const N: usize> ByteSized for [u8; N] {
+    fn byte_size(&self) -> usize {
+        return N;
+    }
+}
+
+/// A [`BackEnd`] that wraps another backend and keeps track of the approximate number of bytes it stores: the sum over all kv pairs of the length of the key plus the [`byte_size`](ByteSized::byte_size) of the value.
+///
+/// Only mutations performed through the wrapper are tracked, so the count starts at zero even if the wrapped backend already contains kv pairs. Overheads of the wrapped backend (e.g., indexes or page fragmentation) are not accounted for.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct SizeTracked<B> {
+    inner: B,
+    approx_bytes: usize,
+}
+
+impl<B> SizeTracked<B> {
+    /// Wrap a backend, starting with a tracked size of zero.
+    pub fn new(inner: B) -> Self {
+        return SizeTracked {
+            inner,
+            approx_bytes: 0,
+        };
+    }
+
+    /// The approximate number of bytes stored in the wrapped backend.
+    pub fn approx_size(&self) -> usize {
+        return self.approx_bytes;
+    }
+
+    /// Get a reference to the wrapped backend.
+    pub fn inner(&self) -> &B {
+        return &self.inner;
+    }
+
+    /// Consume the wrapper and return the wrapped backend.
+    pub fn into_inner(self) -> B {
+        return self.inner;
+    }
+
+    // Account for a kv pair that is no longer stored.
+    fn untrack<V: ByteSized>(&mut self, key: &[u8], old: &Option<V>) {
+        if let Some(old) = old {
+            self.approx_bytes = self
+                .approx_bytes
+                .saturating_sub(key.len() + old.byte_size());
+        }
+    }
+}
+
+impl<V: ByteSized, B: BackEnd<V>> BackEnd<V> for SizeTracked<B> {
+    type Error = B::Error;
+    type Snapshot = B::Snapshot;
+
+    fn get(&self, key: &[u8]) -> impl Future<Output = Result<Option<V>, Self::Error>> {
+        return self.inner.get(key);
+    }
+
+    fn find_lte(
+        &self,
+        key: &[u8],
+    ) -> impl Future<Output = Result<Option<(Vec<u8>, V)>, Self::Error>> {
+        return self.inner.find_lte(key);
+    }
+
+    fn find_gte(
+        &self,
+        key: &[u8],
+    ) -> impl Future<Output = Result<Option<(Vec<u8>, V)>, Self::Error>> {
+        return self.inner.find_gte(key);
+    }
+
+    fn get_many(&self, keys: &[&[u8]]) -> impl Future<Output = Result<Vec<Option<V>>, Self::Error>> {
+        return self.inner.get_many(keys);
+    }
+
+    async fn insert(&mut self, key: &[u8], value: V) -> Result<Option<V>, Self::Error> {
+        let added = key.len() + value.byte_size();
+        let old = self.inner.insert(key, value).await?;
+
+        self.untrack(key, &old);
+        self.approx_bytes += added;
+
+        return Ok(old);
+    }
+
+    async fn delete(&mut self, key: &[u8]) -> Result<Option<V>, Self::Error> {
+        let old = self.inner.delete(key).await?;
+        self.untrack(key, &old);
+
+        return Ok(old);
+    }
+
+    fn find_nearest(
+        &self,
+        key: &[u8],
+    ) -> impl Future<Output = Result<Option<(Vec<u8>, V)>, Self::Error>> {
+        return self.inner.find_nearest(key);
+    }
+
+    async fn range<'s>(
+        &'s self,
+        lo: Bound<&'s [u8]>,
+        hi: Bound<&'s [u8]>,
+    ) -> Result<impl Iterator<Item = (Vec<u8>, V)>, Self::Error> {
+        return self.inner.range(lo, hi).await;
+    }
+
+    async fn range_rev<'s>(
+        &'s self,
+        lo: Bound<&'s [u8]>,
+        hi: Bound<&'s [u8]>,
+    ) -> Result<impl Iterator<Item = (Vec<u8>, V)>, Self::Error> {
+        return self.inner.range_rev(lo, hi).await;
+    }
+
+    async fn scan_prefix<'s>(
+        &'s self,
+        prefix: &'s [u8],
+    ) -> Result<impl Iterator<Item = (Vec<u8>, V)>, Self::Error> {
+        return self.inner.scan_prefix(prefix).await;
+    }
+
+    fn page_after<'s>(
+        &'s self,
+        after: Option<&'s [u8]>,
+        limit: usize,
+    ) -> impl Future<Output = Result<Vec<(Vec<u8>, V)>, Self::Error>> {
+        return self.inner.page_after(after, limit);
+    }
+
+    fn stream_range<'s>(
+        &'s self,
+        lo: Bound<&'s [u8]>,
+        hi: Bound<&'s [u8]>,
+    ) -> impl Stream<Item = Result<(Vec<u8>, V), Self::Error>> + 's {
+        return self.inner.stream_range(lo, hi);
+    }
+
+    fn snapshot(&self) -> impl Future<Output = Result<Self::Snapshot, Self::Error>> {
+        return self.inner.snapshot();
+    }
+
+    fn flush(&mut self) -> impl Future<Output = Result<(), Self::Error>> {
+        return self.inner.flush();
+    }
+}
@@ -0,0 +1,101 @@
+//! Conversions between values and the bytes that persistent [backends](crate::BackEnd) store.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+
+/// A way of turning values of type `V` into bytes and back, so that persistent backends can store values without committing to a particular serialization format.
+pub trait ValueCodec<V> {
+    /// Serialize a value.
+    fn encode(&self, v: &V) -> Vec<u8>;
+
+    /// Deserialize a value from the bytes produced by [`encode`](Self::encode).
+    fn decode(&self, bytes: &[u8]) -> Result<V, CodecError>;
+}
+
+/// The error of a [`ValueCodec`] that failed to decode some bytes.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CodecError {
+    message: String,
+}
+
+impl CodecError {
+    /// Create an error with a human-readable description of what went wrong.
+    pub fn new(message: impl Into<String>) -> Self {
+        return CodecError {
+            message: message.into(),
+        };
+    }
+
+    /// Return the human-readable description of what went wrong.
+    pub fn message(&self) -> &str {
+        return &self.message;
+    }
+}
+
+impl fmt::Display for CodecError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return write!(f, "failed to decode a value: {}", self.message);
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for CodecError {}
+
+/// A [`ValueCodec`] for values that already are bytes: values are encoded as the bytes given by their `AsRef<[u8]>` implementation, and decoded via their `From<Vec<u8>>` implementation. Decoding never fails.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct BytesCodec;
+
+impl<V: AsRef<[u8]> + From<Vec<u8>>> ValueCodec<V> for BytesCodec {
+    fn encode(&self, v: &V) -> Vec<u8> {
+        return v.as_ref().to_vec();
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<V, CodecError> {
+        return Ok(V::from(bytes.to_vec()));
+    }
+}
+
+/// A [`ValueCodec`] for [serde](https://docs.rs/serde) types that uses the [bincode](https://docs.rs/bincode) format.
+///
+/// Encoding panics if serde fails to serialize the value, which only happens for types whose `Serialize` implementation reports errors itself.
+#[cfg(feature = "bincode-codec")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct BincodeCodec;
+
+#[cfg(feature = "bincode-codec")]
+impl<V> ValueCodec<V> for BincodeCodec
+where
+    V: serde::Serialize + serde::de::DeserializeOwned,
+{
+    fn encode(&self, v: &V) -> Vec<u8> {
+        return bincode::serialize(v).expect("failed to serialize a value with bincode");
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<V, CodecError> {
+        return bincode::deserialize(bytes).map_err(|err| CodecError::new(err.to_string()));
+    }
+}
+
+/// A [`ValueCodec`] for [serde](https://docs.rs/serde) types that uses the [CBOR](https://cbor.io) format, via [ciborium](https://docs.rs/ciborium).
+///
+/// Encoding panics if serde fails to serialize the value, which only happens for types whose `Serialize` implementation reports errors itself.
+#[cfg(feature = "cbor-codec")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct CborCodec;
+
+#[cfg(feature = "cbor-codec")]
+impl<V> ValueCodec<V> for CborCodec
+where
+    V: serde::Serialize + serde::de::DeserializeOwned,
+{
+    fn encode(&self, v: &V) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        ciborium::into_writer(v, &mut bytes).expect("failed to serialize a value with CBOR");
+        return bytes;
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<V, CodecError> {
+        return ciborium::from_reader(bytes).map_err(|err| CodecError::new(err.to_string()));
+    }
+}
@@ -0,0 +1,757 @@
+//! Implementations of the [`Dimension`] trait for commonly used types.
+
+#[cfg(feature = "std")]
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+#[cfg(feature = "arbitrary")]
+use arbitrary::Arbitrary;
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::hash::{Hash, Hasher};
+use core::net::{Ipv4Addr, Ipv6Addr};
+
+use crate::{BoundedDimension, DecodeError, Dimension, DiscreteDimension};
+
+macro_rules! impl_dimension_for_unsigned {
+    ($t:ty) => {
+        /// Unsigned integers are encoded as their fixed-width big-endian representation.
+        impl Dimension for $t {
+            const HOMOMORPHIC_ENCODING_MAX_LENGTH: usize = core::mem::size_of::<$t>();
+
+            const IS_FIXED_WIDTH_ENCODING: bool = true;
+
+            fn homomorphic_encode(&self, buf: &mut [u8]) -> usize {
+                buf[..Self::HOMOMORPHIC_ENCODING_MAX_LENGTH].copy_from_slice(&self.to_be_bytes());
+                return Self::HOMOMORPHIC_ENCODING_MAX_LENGTH;
+            }
+
+            fn homomorphic_decode(buf: &[u8]) -> Result<(Self, usize), DecodeError> {
+                match buf.get(..Self::HOMOMORPHIC_ENCODING_MAX_LENGTH) {
+                    None => return Err(DecodeError::UnexpectedEof),
+                    Some(bytes) => {
+                        return Ok((
+                            <$t>::from_be_bytes(bytes.try_into().unwrap()),
+                            Self::HOMOMORPHIC_ENCODING_MAX_LENGTH,
+                        ))
+                    }
+                }
+            }
+        }
+
+        impl BoundedDimension for $t {
+            const MIN: Self = <$t>::MIN;
+
+            const MAX: Self = <$t>::MAX;
+        }
+
+        impl DiscreteDimension for $t {
+            fn successor(&self) -> Option<Self> {
+                return self.checked_add(1);
+            }
+        }
+    };
+}
+
+impl_dimension_for_unsigned!(u8);
+impl_dimension_for_unsigned!(u16);
+impl_dimension_for_unsigned!(u32);
+impl_dimension_for_unsigned!(u64);
+impl_dimension_for_unsigned!(u128);
+
+macro_rules! impl_dimension_for_signed {
+    ($t:ty, $unsigned:ty) => {
+        /// Signed integers are encoded as the fixed-width big-endian representation of their two's complement with a flipped sign bit. This maps the least value to all zero bytes and the greatest value to all one bytes, preserving the order.
+        impl Dimension for $t {
+            const HOMOMORPHIC_ENCODING_MAX_LENGTH: usize = core::mem::size_of::<$t>();
+
+            const IS_FIXED_WIDTH_ENCODING: bool = true;
+
+            fn homomorphic_encode(&self, buf: &mut [u8]) -> usize {
+                let flipped = (*self as $unsigned) ^ (1 << (<$unsigned>::BITS - 1));
+                buf[..Self::HOMOMORPHIC_ENCODING_MAX_LENGTH].copy_from_slice(&flipped.to_be_bytes());
+                return Self::HOMOMORPHIC_ENCODING_MAX_LENGTH;
+            }
+
+            fn homomorphic_decode(buf: &[u8]) -> Result<(Self, usize), DecodeError> {
+                match buf.get(..Self::HOMOMORPHIC_ENCODING_MAX_LENGTH) {
+                    None => return Err(DecodeError::UnexpectedEof),
+                    Some(bytes) => {
+                        let flipped = <$unsigned>::from_be_bytes(bytes.try_into().unwrap());
+                        return Ok((
+                            (flipped ^ (1 << (<$unsigned>::BITS - 1))) as $t,
+                            Self::HOMOMORPHIC_ENCODING_MAX_LENGTH,
+                        ));
+                    }
+                }
+            }
+        }
+
+        impl BoundedDimension for $t {
+            const MIN: Self = <$t>::MIN;
+
+            const MAX: Self = <$t>::MAX;
+        }
+
+        impl DiscreteDimension for $t {
+            fn successor(&self) -> Option<Self> {
+                return self.checked_add(1);
+            }
+        }
+    };
+}
+
+impl_dimension_for_signed!(i8, u8);
+impl_dimension_for_signed!(i16, u16);
+impl_dimension_for_signed!(i32, u32);
+impl_dimension_for_signed!(i64, u64);
+impl_dimension_for_signed!(i128, u128);
+
+/// The unit type is a dimension with a single value and an empty encoding. Use it as a constant dimension, e.g., `Point3d<X, Y, ()>` is a 2d point whose encodings are those of its `x` and `y` coordinates.
+impl Dimension for () {
+    const HOMOMORPHIC_ENCODING_MAX_LENGTH: usize = 0;
+
+    const IS_FIXED_WIDTH_ENCODING: bool = true;
+
+    fn homomorphic_encode(&self, _buf: &mut [u8]) -> usize {
+        return 0;
+    }
+
+    fn homomorphic_decode(_buf: &[u8]) -> Result<(Self, usize), DecodeError> {
+        return Ok(((), 0));
+    }
+}
+
+impl BoundedDimension for () {
+    const MIN: Self = ();
+
+    const MAX: Self = ();
+}
+
+impl DiscreteDimension for () {
+    fn successor(&self) -> Option<Self> {
+        return None;
+    }
+}
+
+/// Pairs of dimensions are ordered lexicographically, and encoded as the concatenation of the encodings of their components.
+///
+/// If both components are fixed-width, so is the pair, and the encodings are concatenated as they are. Otherwise, the components are separated like the dimensions of a [`Point3d`](crate::Point3d), but the separator must not introduce consecutive zero bytes into the encoding of the pair. So every zero byte in the encodings of the components is escaped as `0x00 0x02`, and the components are separated by `0x00 0x01`, which is less than any escaped encoding that continues instead. This doubles the maximum length of the encoding.
+#[cfg(feature = "alloc")]
+impl<A: Dimension, B: Dimension> Dimension for (A, B) {
+    const HOMOMORPHIC_ENCODING_MAX_LENGTH: usize = if Self::IS_FIXED_WIDTH_ENCODING {
+        A::HOMOMORPHIC_ENCODING_MAX_LENGTH + B::HOMOMORPHIC_ENCODING_MAX_LENGTH
+    } else {
+        2 * A::HOMOMORPHIC_ENCODING_MAX_LENGTH + PAIR_SEPARATOR.len() + 2 * B::HOMOMORPHIC_ENCODING_MAX_LENGTH
+    };
+
+    const IS_FIXED_WIDTH_ENCODING: bool = A::IS_FIXED_WIDTH_ENCODING && B::IS_FIXED_WIDTH_ENCODING;
+
+    fn homomorphic_encode(&self, buf: &mut [u8]) -> usize {
+        if Self::IS_FIXED_WIDTH_ENCODING {
+            let len = self.0.homomorphic_encode(buf);
+            return len + self.1.homomorphic_encode(&mut buf[len..]);
+        }
+
+        let mut encoding = Vec::new();
+        push_escaped(&mut encoding, &self.0);
+        encoding.extend_from_slice(&PAIR_SEPARATOR);
+        push_escaped(&mut encoding, &self.1);
+
+        buf[..encoding.len()].copy_from_slice(&encoding);
+        return encoding.len();
+    }
+
+    fn homomorphic_decode(buf: &[u8]) -> Result<(Self, usize), DecodeError> {
+        if Self::IS_FIXED_WIDTH_ENCODING {
+            let (a, a_len) = A::homomorphic_decode(buf)?;
+            let (b, b_len) = B::homomorphic_decode(buf.get(a_len..).ok_or(DecodeError::UnexpectedEof)?)?;
+            return Ok(((a, b), a_len + b_len));
+        }
+
+        let (a_encoding, a_end) = unescape(buf);
+        match buf.get(a_end..a_end + PAIR_SEPARATOR.len()) {
+            None => return Err(DecodeError::UnexpectedEof),
+            Some(separator) if separator != PAIR_SEPARATOR => return Err(DecodeError::BadSeparator),
+            Some(_) => {}
+        }
+
+        let (a, a_len) = A::homomorphic_decode(&a_encoding)?;
+        if a_len != a_encoding.len() {
+            return Err(DecodeError::InvalidValue);
+        }
+
+        // The encoding of the second component may be followed by further bytes, so only count the escaped length of what was actually decoded.
+        let b_start = a_end + PAIR_SEPARATOR.len();
+        let (b_encoding, _) = unescape(&buf[b_start..]);
+        let (b, b_len) = B::homomorphic_decode(&b_encoding)?;
+        let zeros = b_encoding[..b_len].iter().filter(|byte| **byte == 0).count();
+
+        return Ok(((a, b), b_start + b_len + zeros));
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<A: BoundedDimension, B: BoundedDimension> BoundedDimension for (A, B) {
+    const MIN: Self = (A::MIN, B::MIN);
+
+    const MAX: Self = (A::MAX, B::MAX);
+}
+
+// Separates the escaped components of a pair of dimensions that is not fixed-width.
+#[cfg(feature = "alloc")]
+const PAIR_SEPARATOR: [u8; 2] = [0, 1];
+
+// A zero byte in the encoding of a component of a pair of dimensions is followed by this byte.
+#[cfg(feature = "alloc")]
+const ESCAPED_ZERO: u8 = 2;
+
+// Append the encoding of a dimension, with every zero byte escaped.
+#[cfg(feature = "alloc")]
+fn push_escaped<D: Dimension>(out: &mut Vec<u8>, d: &D) {
+    let mut encoding = alloc::vec![0; D::HOMOMORPHIC_ENCODING_MAX_LENGTH];
+    let len = d.homomorphic_encode(&mut encoding);
+
+    for byte in &encoding[..len] {
+        out.push(*byte);
+        if *byte == 0 {
+            out.push(ESCAPED_ZERO);
+        }
+    }
+}
+
+// Undo the escaping of zero bytes up to the first zero byte that is not escaped (or the end of the slice). Return the unescaped bytes, and the number of escaped bytes that were read.
+#[cfg(feature = "alloc")]
+fn unescape(buf: &[u8]) -> (Vec<u8>, usize) {
+    let mut unescaped = Vec::new();
+    let mut i = 0;
+
+    while let Some(byte) = buf.get(i) {
+        if *byte != 0 {
+            unescaped.push(*byte);
+            i += 1;
+        } else if buf.get(i + 1) == Some(&ESCAPED_ZERO) {
+            unescaped.push(0);
+            i += 2;
+        } else {
+            break;
+        }
+    }
+
+    return (unescaped, i);
+}
+
+/// A point in time, given as the number of microseconds since the Unix epoch (1970-01-01 00:00:00 UTC).
+///
+/// Timestamps are encoded like the underlying `u64`, as eight big-endian bytes. We do not implement [`Dimension`] for [`SystemTime`](std::time::SystemTime) directly, because its internal representation (and thus its range and precision) is platform-specific and not guaranteed. Use the `TryFrom` implementations to convert between the two.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug, Hash, Default)]
+#[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
+pub struct Timestamp(pub u64);
+
+impl Dimension for Timestamp {
+    const HOMOMORPHIC_ENCODING_MAX_LENGTH: usize = u64::HOMOMORPHIC_ENCODING_MAX_LENGTH;
+
+    const IS_FIXED_WIDTH_ENCODING: bool = true;
+
+    fn homomorphic_encode(&self, buf: &mut [u8]) -> usize {
+        return self.0.homomorphic_encode(buf);
+    }
+
+    fn homomorphic_decode(buf: &[u8]) -> Result<(Self, usize), DecodeError> {
+        let (micros, len) = u64::homomorphic_decode(buf)?;
+        return Ok((Timestamp(micros), len));
+    }
+}
+
+impl BoundedDimension for Timestamp {
+    const MIN: Self = Timestamp(u64::MIN);
+
+    const MAX: Self = Timestamp(u64::MAX);
+}
+
+impl DiscreteDimension for Timestamp {
+    fn successor(&self) -> Option<Self> {
+        return self.0.successor().map(Timestamp);
+    }
+}
+
+/// The reasons why a conversion between a [`Timestamp`] and a [`SystemTime`](std::time::SystemTime) can fail.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Hash)]
+pub enum TimestampError {
+    /// The `SystemTime` lies before the Unix epoch.
+    BeforeEpoch,
+    /// The time cannot be represented by the target type.
+    Overflow,
+}
+
+impl core::fmt::Display for TimestampError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            TimestampError::BeforeEpoch => return write!(f, "time lies before the Unix epoch"),
+            TimestampError::Overflow => return write!(f, "time is out of the representable range"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for TimestampError {}
+
+/// Sub-microsecond precision is truncated.
+#[cfg(feature = "std")]
+impl TryFrom<SystemTime> for Timestamp {
+    type Error = TimestampError;
+
+    fn try_from(time: SystemTime) -> Result<Self, Self::Error> {
+        let since_epoch = time
+            .duration_since(UNIX_EPOCH)
+            .map_err(|_| TimestampError::BeforeEpoch)?;
+        let micros =
+            u64::try_from(since_epoch.as_micros()).map_err(|_| TimestampError::Overflow)?;
+        return Ok(Timestamp(micros));
+    }
+}
+
+#[cfg(feature = "std")]
+impl TryFrom<Timestamp> for SystemTime {
+    type Error = TimestampError;
+
+    fn try_from(timestamp: Timestamp) -> Result<Self, Self::Error> {
+        return UNIX_EPOCH
+            .checked_add(Duration::from_micros(timestamp.0))
+            .ok_or(TimestampError::Overflow);
+    }
+}
+
+/// A fixed-point decimal number, representing the value `self.0 / 10^SCALE`. For example, `Fixed::<2>(150)` represents `1.5`.
+///
+/// Fixed-point numbers are encoded like the underlying `i64`, as eight bytes with a flipped sign bit. Comparisons are exact: they compare the stored `i64`s, with no floating-point rounding involved. `SCALE` must be at most 18, so that `10^SCALE` fits into an `i64`.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug, Hash, Default)]
+#[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
+pub struct Fixed<const SCALE: u32>(pub i64);
+
+impl<const SCALE: u32> Fixed<SCALE> {
+    /// The stored `i64` that represents `1`.
+    pub const ONE: i64 = {
+        assert!(SCALE <= 18, "the SCALE of a Fixed must be at most 18");
+        10i64.pow(SCALE)
+    };
+
+    /// Return the fixed-point number closest to the given float, rounding halfway cases away from zero. Returns `None` if the float is not finite or out of range.
+    #[cfg(feature = "std")]
+    pub fn from_f64_rounded(value: f64) -> Option<Self> {
+        let scaled = (value * Self::ONE as f64).round();
+
+        // `i64::MAX as f64` rounds up to 2^63, which is out of range already.
+        if scaled.is_finite() && scaled >= i64::MIN as f64 && scaled < i64::MAX as f64 {
+            return Some(Fixed(scaled as i64));
+        } else {
+            return None;
+        }
+    }
+
+    /// Return the float closest to this fixed-point number.
+    pub fn to_f64(self) -> f64 {
+        return self.0 as f64 / Self::ONE as f64;
+    }
+}
+
+impl<const SCALE: u32> Dimension for Fixed<SCALE> {
+    const HOMOMORPHIC_ENCODING_MAX_LENGTH: usize = i64::HOMOMORPHIC_ENCODING_MAX_LENGTH;
+
+    const IS_FIXED_WIDTH_ENCODING: bool = true;
+
+    fn homomorphic_encode(&self, buf: &mut [u8]) -> usize {
+        return self.0.homomorphic_encode(buf);
+    }
+
+    fn homomorphic_decode(buf: &[u8]) -> Result<(Self, usize), DecodeError> {
+        let (scaled, len) = i64::homomorphic_decode(buf)?;
+        return Ok((Fixed(scaled), len));
+    }
+}
+
+impl<const SCALE: u32> BoundedDimension for Fixed<SCALE> {
+    const MIN: Self = Fixed(i64::MIN);
+
+    const MAX: Self = Fixed(i64::MAX);
+}
+
+/// The successor is one unit of the last decimal place greater, i.e., `10^-SCALE` greater.
+impl<const SCALE: u32> DiscreteDimension for Fixed<SCALE> {
+    fn successor(&self) -> Option<Self> {
+        return self.0.successor().map(Fixed);
+    }
+}
+
+/// A dimension whose values are ordered in reverse, for coordinates that should sort descending (e.g., newest timestamps first).
+///
+/// The encoding is the encoding of the wrapped value with every byte complemented, which reverses the lexicographic order. The complemented encoding of a variable-width dimension could contain consecutive zero bytes, so `Rev` only supports fixed-width dimensions: using it with a variable-width `D` fails to compile.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Hash, Default)]
+#[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
+pub struct Rev<D>(pub D);
+
+impl<D: Ord> PartialOrd for Rev<D> {
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+        return Some(self.cmp(other));
+    }
+}
+
+impl<D: Ord> Ord for Rev<D> {
+    fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+        return other.0.cmp(&self.0);
+    }
+}
+
+impl<D: Dimension> Dimension for Rev<D> {
+    const HOMOMORPHIC_ENCODING_MAX_LENGTH: usize = D::HOMOMORPHIC_ENCODING_MAX_LENGTH;
+
+    const IS_FIXED_WIDTH_ENCODING: bool = {
+        assert!(D::IS_FIXED_WIDTH_ENCODING, "Rev only supports fixed-width dimensions");
+        true
+    };
+
+    fn homomorphic_encode(&self, buf: &mut [u8]) -> usize {
+        let len = self.0.homomorphic_encode(buf);
+        complement(&mut buf[..len]);
+
+        return len;
+    }
+
+    /// Without the `alloc` feature, decoding a `Rev<D>` whose encodings are longer than 256 bytes fails to compile.
+    fn homomorphic_decode(buf: &[u8]) -> Result<(Self, usize), DecodeError> {
+        let len = Self::HOMOMORPHIC_ENCODING_MAX_LENGTH;
+        let encoding = buf.get(..len).ok_or(DecodeError::UnexpectedEof)?;
+
+        #[cfg(feature = "alloc")]
+        let mut scratch = alloc::vec![0; len];
+        #[cfg(not(feature = "alloc"))]
+        let mut scratch = {
+            const {
+                assert!(
+                    D::HOMOMORPHIC_ENCODING_MAX_LENGTH <= 256,
+                    "Rev can only decode encodings of at most 256 bytes without the alloc feature"
+                )
+            };
+            [0; 256]
+        };
+
+        scratch[..len].copy_from_slice(encoding);
+        complement(&mut scratch[..len]);
+
+        let (value, value_len) = D::homomorphic_decode(&scratch[..len])?;
+        if value_len != len {
+            return Err(DecodeError::InvalidValue);
+        } else {
+            return Ok((Rev(value), len));
+        }
+    }
+}
+
+/// The order is reversed, so the least value wraps the greatest value of `D` and vice versa.
+impl<D: BoundedDimension> BoundedDimension for Rev<D> {
+    const MIN: Self = Rev(D::MAX);
+
+    const MAX: Self = Rev(D::MIN);
+}
+
+/// A dimension that pads the encoding of a variable-width dimension `D` to exactly `N` bytes, making it fixed-width. This removes the two-byte separators that [`Point3d`](crate::Point3d) encodings place after variable-width dimensions, and if all dimensions are fixed-width, all encodings of all points have the same length.
+///
+/// The encoding of `D` is padded with zero bytes on the high-significance side, i.e., at the front. This preserves the order of the encodings only if `D` satisfies two additional requirements: no encoding of `D` starts with a zero byte, and shorter encodings of `D` are always less than longer ones. The latter holds, for example, for typical variable-length encodings of unsigned integers.
+///
+/// Encoding panics if the encoding of the wrapped value is longer than `N` bytes, so `N` must be large enough for all values that occur.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug, Hash, Default)]
+#[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
+pub struct PadFixed<D, const N: usize>(pub D);
+
+impl<D: Dimension, const N: usize> Dimension for PadFixed<D, N> {
+    const HOMOMORPHIC_ENCODING_MAX_LENGTH: usize = N;
+
+    const IS_FIXED_WIDTH_ENCODING: bool = true;
+
+    fn homomorphic_encode(&self, buf: &mut [u8]) -> usize {
+        let len = self.0.encoded_len();
+        assert!(
+            len <= N,
+            "the encoding of a value of a PadFixed dimension is {} bytes long, more than the {} bytes to pad to",
+            len,
+            N
+        );
+
+        let padding = N - len;
+        buf[..padding].fill(0);
+        self.0.homomorphic_encode(&mut buf[padding..N]);
+
+        return N;
+    }
+
+    fn homomorphic_decode(buf: &[u8]) -> Result<(Self, usize), DecodeError> {
+        let padded = buf.get(..N).ok_or(DecodeError::UnexpectedEof)?;
+
+        // No encoding of `D` starts with a zero byte, so all leading zero bytes are padding.
+        let padding = padded.iter().take_while(|byte| **byte == 0).count();
+        let (value, len) = D::homomorphic_decode(&padded[padding..])?;
+
+        if padding + len == N {
+            return Ok((PadFixed(value), N));
+        } else {
+            return Err(DecodeError::InvalidValue);
+        }
+    }
+}
+
+// Complement every byte of the slice.
+fn complement(bytes: &mut [u8]) {
+    for byte in bytes.iter_mut() {
+        *byte = !*byte;
+    }
+}
+
+macro_rules! ordered_float {
+    ($(#[$attr:meta])* $name:ident, $float:ty, $bits:ty) => {
+        $(#[$attr])*
+        ///
+        #[doc = concat!("The order is the IEEE 754 total order (see [`total_cmp`](", stringify!($float), "::total_cmp)), except that all NaNs are equal to each other and greater than all other values, including positive infinity. In particular, `-0.0` is less than `0.0`.")]
+        ///
+        /// Values are encoded as the big-endian bytes of their bits, transformed so that the encodings compare like the values: the sign bit is flipped for positive values, and all bits are flipped for negative values. All NaNs are encoded as the same canonical quiet NaN, and decoding rejects the encodings of other NaNs.
+        #[derive(Clone, Copy, Debug, Default)]
+        #[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
+        pub struct $name(pub $float);
+
+        impl $name {
+            const SIGN_BIT: $bits = 1 << (<$bits>::BITS - 1);
+
+            // The bits of the positive quiet NaN without payload.
+            const CANONICAL_NAN_BITS: $bits = <$float>::INFINITY.to_bits() | (1 << (<$float>::MANTISSA_DIGITS - 2));
+
+            // The bits of the value, transformed such that they compare like the values.
+            fn ordered_bits(&self) -> $bits {
+                let bits = if self.0.is_nan() {
+                    Self::CANONICAL_NAN_BITS
+                } else {
+                    self.0.to_bits()
+                };
+
+                if bits & Self::SIGN_BIT == 0 {
+                    return bits | Self::SIGN_BIT;
+                } else {
+                    return !bits;
+                }
+            }
+        }
+
+        impl PartialEq for $name {
+            fn eq(&self, other: &Self) -> bool {
+                return self.cmp(other) == Ordering::Equal;
+            }
+        }
+
+        impl Eq for $name {}
+
+        impl PartialOrd for $name {
+            fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+                return Some(self.cmp(other));
+            }
+        }
+
+        impl Ord for $name {
+            fn cmp(&self, other: &Self) -> Ordering {
+                return self.ordered_bits().cmp(&other.ordered_bits());
+            }
+        }
+
+        impl Hash for $name {
+            fn hash<H: Hasher>(&self, state: &mut H) {
+                self.ordered_bits().hash(state);
+            }
+        }
+
+        impl Dimension for $name {
+            const HOMOMORPHIC_ENCODING_MAX_LENGTH: usize = <$bits>::HOMOMORPHIC_ENCODING_MAX_LENGTH;
+
+            const IS_FIXED_WIDTH_ENCODING: bool = true;
+
+            fn homomorphic_encode(&self, buf: &mut [u8]) -> usize {
+                return self.ordered_bits().homomorphic_encode(buf);
+            }
+
+            fn homomorphic_decode(buf: &[u8]) -> Result<(Self, usize), DecodeError> {
+                let (ordered, len) = <$bits>::homomorphic_decode(buf)?;
+
+                let bits = if ordered & Self::SIGN_BIT == 0 {
+                    !ordered
+                } else {
+                    ordered ^ Self::SIGN_BIT
+                };
+
+                let value = <$float>::from_bits(bits);
+                if value.is_nan() && bits != Self::CANONICAL_NAN_BITS {
+                    return Err(DecodeError::InvalidValue);
+                } else {
+                    return Ok(($name(value), len));
+                }
+            }
+        }
+
+        impl BoundedDimension for $name {
+            const MIN: Self = $name(<$float>::NEG_INFINITY);
+
+            const MAX: Self = $name(<$float>::NAN);
+        }
+    };
+}
+
+ordered_float!(
+    /// An `f32` with a total order, so that it can be used as a [`Dimension`].
+    OrderedF32,
+    f32,
+    u32
+);
+
+ordered_float!(
+    /// An `f64` with a total order, so that it can be used as a [`Dimension`].
+    OrderedF64,
+    f64,
+    u64
+);
+
+/// A byte string that is encoded as its raw bytes, without any escaping or length prefix, for the last dimension of a point. Byte strings compare lexicographically, so the raw bytes are an order-homomorphic encoding.
+///
+/// The raw bytes may contain consecutive zero bytes, and nothing marks where they end: decoding consumes the whole slice. Hence, `TailBytes` is only safe as the last component of an encoding, which is why it sets [`MUST_BE_LAST`](Dimension::MUST_BE_LAST). Use it only as the `Z` dimension of points that you encode with the [xyz encoding](crate::Point3d::encode_xyz) alone. In particular, kv-trees use all three orderings, so they cannot store points with a `TailBytes` dimension. Encoding such a point in the yzx or zxy ordering panics in debug builds, and yields encodings that decode incorrectly in release builds.
+///
+/// Further, the whole slice passed to decoding must belong to the encoding of the point, so use exact decoding (such as [`decode_xyz_exact`](crate::Point3d::decode_xyz_exact)) or length-prefixed encodings. Reading a point from an `io::Read` does not compile, see [`decode_xyz_from_reader`](crate::Point3d::decode_xyz_from_reader).
+///
+/// Encodings are at most 256 bytes long, encoding panics for longer byte strings.
+#[cfg(feature = "alloc")]
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Debug, Hash, Default)]
+#[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
+pub struct TailBytes(pub Vec<u8>);
+
+#[cfg(feature = "alloc")]
+impl Dimension for TailBytes {
+    const HOMOMORPHIC_ENCODING_MAX_LENGTH: usize = 256;
+
+    const IS_FIXED_WIDTH_ENCODING: bool = false;
+
+    const MUST_BE_LAST: bool = true;
+
+    fn homomorphic_encode(&self, buf: &mut [u8]) -> usize {
+        assert!(
+            self.0.len() <= Self::HOMOMORPHIC_ENCODING_MAX_LENGTH,
+            "a TailBytes dimension holds {} bytes, more than the maximum of {}",
+            self.0.len(),
+            Self::HOMOMORPHIC_ENCODING_MAX_LENGTH
+        );
+
+        buf[..self.0.len()].copy_from_slice(&self.0);
+        return self.0.len();
+    }
+
+    fn homomorphic_decode(buf: &[u8]) -> Result<(Self, usize), DecodeError> {
+        if buf.len() > Self::HOMOMORPHIC_ENCODING_MAX_LENGTH {
+            return Err(DecodeError::InvalidValue);
+        } else {
+            return Ok((TailBytes(buf.to_vec()), buf.len()));
+        }
+    }
+
+    fn encoded_len(&self) -> usize {
+        return self.0.len();
+    }
+}
+
+/// IPv4 addresses are encoded as their four [octets](Ipv4Addr::octets), which is the order in which `Ipv4Addr`s compare.
+impl Dimension for Ipv4Addr {
+    const HOMOMORPHIC_ENCODING_MAX_LENGTH: usize = 4;
+
+    const IS_FIXED_WIDTH_ENCODING: bool = true;
+
+    fn homomorphic_encode(&self, buf: &mut [u8]) -> usize {
+        buf[..4].copy_from_slice(&self.octets());
+        return 4;
+    }
+
+    fn homomorphic_decode(buf: &[u8]) -> Result<(Self, usize), DecodeError> {
+        match buf.get(..4) {
+            None => return Err(DecodeError::UnexpectedEof),
+            Some(bytes) => return Ok((Ipv4Addr::from(<[u8; 4]>::try_from(bytes).unwrap()), 4)),
+        }
+    }
+}
+
+impl BoundedDimension for Ipv4Addr {
+    const MIN: Self = Ipv4Addr::UNSPECIFIED;
+
+    const MAX: Self = Ipv4Addr::BROADCAST;
+}
+
+impl DiscreteDimension for Ipv4Addr {
+    fn successor(&self) -> Option<Self> {
+        return self.to_bits().successor().map(Ipv4Addr::from_bits);
+    }
+}
+
+/// IPv6 addresses are encoded as their sixteen [octets](Ipv6Addr::octets), which is the order in which `Ipv6Addr`s compare.
+impl Dimension for Ipv6Addr {
+    const HOMOMORPHIC_ENCODING_MAX_LENGTH: usize = 16;
+
+    const IS_FIXED_WIDTH_ENCODING: bool = true;
+
+    fn homomorphic_encode(&self, buf: &mut [u8]) -> usize {
+        buf[..16].copy_from_slice(&self.octets());
+        return 16;
+    }
+
+    fn homomorphic_decode(buf: &[u8]) -> Result<(Self, usize), DecodeError> {
+        match buf.get(..16) {
+            None => return Err(DecodeError::UnexpectedEof),
+            Some(bytes) => return Ok((Ipv6Addr::from(<[u8; 16]>::try_from(bytes).unwrap()), 16)),
+        }
+    }
+}
+
+impl BoundedDimension for Ipv6Addr {
+    const MIN: Self = Ipv6Addr::UNSPECIFIED;
+
+    const MAX: Self = Ipv6Addr::new(0xffff, 0xffff, 0xffff, 0xffff, 0xffff, 0xffff, 0xffff, 0xffff);
+}
+
+impl DiscreteDimension for Ipv6Addr {
+    fn successor(&self) -> Option<Self> {
+        return self.to_bits().successor().map(Ipv6Addr::from_bits);
+    }
+}
+
+/// UUIDs are encoded as their 16 bytes in big-endian order (as given by [`as_bytes`](uuid::Uuid::as_bytes)), which is the order in which `Uuid`s compare.
+#[cfg(feature = "uuid")]
+impl Dimension for uuid::Uuid {
+    const HOMOMORPHIC_ENCODING_MAX_LENGTH: usize = 16;
+
+    const IS_FIXED_WIDTH_ENCODING: bool = true;
+
+    fn homomorphic_encode(&self, buf: &mut [u8]) -> usize {
+        buf[..16].copy_from_slice(self.as_bytes());
+        return 16;
+    }
+
+    fn homomorphic_decode(buf: &[u8]) -> Result<(Self, usize), DecodeError> {
+        match buf.get(..16) {
+            None => return Err(DecodeError::UnexpectedEof),
+            Some(bytes) => return Ok((uuid::Uuid::from_bytes(bytes.try_into().unwrap()), 16)),
+        }
+    }
+}
+
+#[cfg(feature = "uuid")]
+impl BoundedDimension for uuid::Uuid {
+    const MIN: Self = uuid::Uuid::nil();
+
+    const MAX: Self = uuid::Uuid::from_bytes([0xff; 16]);
+}
+
+#[cfg(feature = "uuid")]
+impl DiscreteDimension for uuid::Uuid {
+    fn successor(&self) -> Option<Self> {
+        return self.as_u128().successor().map(uuid::Uuid::from_u128);
+    }
+}
@@ -0,0 +1,71 @@
+use alloc::{vec, vec::Vec};
+
+use crate::{DecodeError, Dimension, Ordering3d, Point3d, Rank};
+
+/// Return the key under which a kv-tree stores the vertex of the given rank and point: the rank as a single byte, followed by the encoding of the point in the [ordering for that rank](Rank::ordering).
+pub fn encode_tree_key<X, Y, Z>(rank: Rank, point: &Point3d<X, Y, Z>) -> Vec<u8>
+where
+    X: Dimension,
+    Y: Dimension,
+    Z: Dimension,
+{
+    let ordering = rank.ordering();
+    let max_len = match ordering {
+        Ordering3d::Xyz => Point3d::<X, Y, Z>::max_encoding_len_xyz(),
+        Ordering3d::Yzx => Point3d::<X, Y, Z>::max_encoding_len_yzx(),
+        Ordering3d::Zxy => Point3d::<X, Y, Z>::max_encoding_len_zxy(),
+    };
+
+    let mut key = vec![0; 1 + max_len];
+    key[0] = rank.get();
+    let len = match ordering {
+        Ordering3d::Xyz => point.encode_xyz(&mut key[1..]),
+        Ordering3d::Yzx => point.encode_yzx(&mut key[1..]),
+        Ordering3d::Zxy => point.encode_zxy(&mut key[1..]),
+    };
+    key.truncate(1 + len);
+
+    return key;
+}
+
+/// Decode a key produced by [`encode_tree_key`] into the rank and the point it was created from. Fails if the key is not exactly such an encoding, in particular if it starts with the reserved [`EMPTY_CHILD`](crate::EMPTY_CHILD).
+pub fn decode_tree_key<X, Y, Z>(key: &[u8]) -> Result<(Rank, Point3d<X, Y, Z>), DecodeError>
+where
+    X: Dimension,
+    Y: Dimension,
+    Z: Dimension,
+{
+    let (rank, encoding) = key.split_first().ok_or(DecodeError::UnexpectedEof)?;
+    let rank = Rank::new(*rank).ok_or(DecodeError::InvalidValue)?;
+
+    let point = match rank.ordering() {
+        Ordering3d::Xyz => Point3d::decode_xyz_exact(encoding)?,
+        Ordering3d::Yzx => Point3d::decode_yzx_exact(encoding)?,
+        Ordering3d::Zxy => Point3d::decode_zxy_exact(encoding)?,
+    };
+
+    return Ok((rank, point));
+}
+
+/// Return the byte keys that bound the vertices of the given rank whose points lie in `[lo, hi)` (in the [ordering for that rank](Rank::ordering)): all keys of such vertices are greater than or equal to the first key, and less than the second one. Feed them to [`BackEnd::range`](crate::BackEnd::range) as an inclusive lower and an exclusive upper bound to scan these vertices.
+///
+/// If `hi` is `None`, the range has no upper bound, and the second key is the least key of any vertex of the next rank.
+pub fn tree_key_bounds<X, Y, Z>(
+    rank: Rank,
+    lo: &Point3d<X, Y, Z>,
+    hi: Option<&Point3d<X, Y, Z>>,
+) -> (Vec<u8>, Vec<u8>)
+where
+    X: Dimension,
+    Y: Dimension,
+    Z: Dimension,
+{
+    let lower = encode_tree_key(rank, lo);
+    let upper = match hi {
+        Some(hi) => encode_tree_key(rank, hi),
+        // Every key of the given rank starts with the rank byte, so the key consisting only of the next byte exceeds all of them.
+        None => vec![rank.next_byte()],
+    };
+
+    return (lower, upper);
+}
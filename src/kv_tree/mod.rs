@@ -0,0 +1,94 @@
+//! This module defines how to represent monoid-3d-ish-zip-trees on a kv store, in the form of a *kv-tree*.
+//! 
+//! For any zip-tree vertex for point `p`, rank `r`, value `v`, and monoidal summary data `s`, we add to the kv-store an entry consisting of
+//! 
+//! - the key, the concatenation of the `r` (encoded as a single byte) and the appropriate homomorphic encoding of `p`:
+//!     - xyz-encoding if `r % 3 == 2`
+//!     - yzx-encoding if `r % 3 == 1`
+//!     - zxy-encoding if `r % 3 == 0`
+//! - the value (a [`KvNode`]), which consists of
+//!     - `r`, `v`, `s`, and the number of vertices in the subtree rooted at the vertex,
+//!     - the rank of the left child of the vertex, or 255 if there is no left child, and the homomorphic encoding of the point of the left child for that rank
+//!     - the rank of the right child of the vertex, or 255 if there is no right child, and the homomorphic encoding of the point of the right child for that rank
+//! 
+//! With this information, we can efficiently find the left or right child of any given vertex.
+//! 
+//! To find the left child: given a zip-tree vertex with left-child-rank `lr`, let `enc` be the stored encoding of the point of the left child, i.e., its homomorphic encoding for the rank `lr` (**not the rank of the vertex**). The concatenation of `lr` and `enc` is the key of the left child.
+//! 
+//! To find the right child: given a zip-tree vertex with right-child-rank `rr`, let `enc` be the stored encoding of the point of the right child, i.e., its homomorphic encoding for the rank `rr` (**not the rank of the vertex**). The concatenation of `rr` and `enc` is the key of the right child.
+//! 
+//! Backends that store bytes rather than arbitrary values can use the [`KvNodeCodec`], whose [`encode_kv_node`] and [`decode_kv_node`] place the rank of a vertex and the ranks of its children in a fixed-size header before everything else. Traversals can thus read those ranks with [`peek_child_ranks`], without decoding the value or the summary.
+//! 
+//! The root of a kv-tree is the vertex of greatest rank (and, among those, the one with the least key). A [`KvTree`] keeps track of its key, and [`KvTree::open`] recovers it from the backend alone, so no root pointer needs to be stored.
+
+use crate::Ordering3d;
+#[cfg(feature = "alloc")]
+use crate::Rank;
+
+#[cfg(feature = "alloc")]
+mod keys;
+#[cfg(feature = "alloc")]
+pub use keys::*;
+
+#[cfg(feature = "alloc")]
+mod node_encoding;
+#[cfg(feature = "alloc")]
+pub use node_encoding::*;
+
+#[cfg(feature = "alloc")]
+mod tree;
+#[cfg(feature = "alloc")]
+pub use tree::*;
+
+/// The byte that stands for a missing child wherever a kv-tree stores the rank of a child instead of a [`ChildRef`]. Consequently, `255` is reserved and cannot be the rank of a vertex; [`rank_from_hash`](crate::rank_from_hash) never produces it.
+pub const EMPTY_CHILD: u8 = 255;
+
+/// A reference to a child of a kv-tree vertex, given by its rank, as it is stored in a [`KvNode`] and in the header of an [encoded vertex](crate::encode_kv_node).
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    any(feature = "bincode-codec", feature = "cbor-codec"),
+    derive(serde::Serialize, serde::Deserialize)
+)]
+pub enum ChildRef {
+    /// There is no child.
+    None,
+    /// There is a child of the given rank.
+    Child(Rank),
+}
+
+#[cfg(feature = "alloc")]
+impl ChildRef {
+    /// Encode as a single byte: the rank of the child, or [`EMPTY_CHILD`] if there is none.
+    pub fn encode(&self) -> u8 {
+        match self {
+            ChildRef::None => return EMPTY_CHILD,
+            ChildRef::Child(rank) => return rank.get(),
+        }
+    }
+
+    /// Decode a byte produced by [`encode`](Self::encode).
+    pub fn decode(byte: u8) -> ChildRef {
+        match Rank::new(byte) {
+            None => return ChildRef::None,
+            Some(rank) => return ChildRef::Child(rank),
+        }
+    }
+
+    /// Return the rank of the child, if there is one.
+    pub fn rank(&self) -> Option<Rank> {
+        match self {
+            ChildRef::None => return None,
+            ChildRef::Child(rank) => return Some(*rank),
+        }
+    }
+}
+
+/// Return the ordering that governs zip-tree vertices of the given rank: xyz if `rank % 3 == 2`, yzx if `rank % 3 == 1`, and zxy if `rank % 3 == 0`.
+pub fn ordering_for_rank(rank: u8) -> Ordering3d {
+    match rank % 3 {
+        2 => return Ordering3d::Xyz,
+        1 => return Ordering3d::Yzx,
+        _ => return Ordering3d::Zxy,
+    }
+}
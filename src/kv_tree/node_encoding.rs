@@ -0,0 +1,172 @@
+use alloc::{vec, vec::Vec};
+
+use crate::{decode_varint, encode_varint, ChildRef, CodecError, KvNode, Rank, ValueCodec};
+
+// The number of bytes before the variable-length part of an encoded vertex: the rank, and the `ChildRef`s of the left and the right child.
+const HEADER_LEN: usize = 3;
+
+/// Encode a [`KvNode`] into the bytes that a persistent backend can store as the value of the vertex, using the given codecs for the value and the summary.
+///
+/// The encoding starts with a fixed-size header, so that traversals can learn the ranks of a vertex and its children via [`peek_child_ranks`] without decoding the value or the summary:
+///
+/// - the rank of the vertex,
+/// - the [encoded](ChildRef::encode) reference to the left child, and
+/// - the [encoded](ChildRef::encode) reference to the right child.
+///
+/// After the header follow the point encodings of the left and right child (if present), each prefixed by its length as a varint, then the count as a varint, then the encoded value prefixed by its length as a varint, and finally the encoded summary.
+pub fn encode_kv_node<V, M, CV, CM>(
+    node: &KvNode<V, M>,
+    value_codec: &CV,
+    summary_codec: &CM,
+) -> Vec<u8>
+where
+    CV: ValueCodec<V>,
+    CM: ValueCodec<M>,
+{
+    let mut out = vec![
+        node.rank.get(),
+        node.left.encode(),
+        node.right.encode(),
+    ];
+
+    if node.left != ChildRef::None {
+        push_length_prefixed(&mut out, &node.left_point);
+    }
+    if node.right != ChildRef::None {
+        push_length_prefixed(&mut out, &node.right_point);
+    }
+
+    push_varint(&mut out, node.count);
+    push_length_prefixed(&mut out, &value_codec.encode(&node.value));
+    out.extend_from_slice(&summary_codec.encode(&node.summary));
+
+    return out;
+}
+
+/// Decode the bytes produced by [`encode_kv_node`] into a [`KvNode`], using the given codecs for the value and the summary.
+pub fn decode_kv_node<V, M, CV, CM>(
+    value_bytes: &[u8],
+    value_codec: &CV,
+    summary_codec: &CM,
+) -> Result<KvNode<V, M>, CodecError>
+where
+    CV: ValueCodec<V>,
+    CM: ValueCodec<M>,
+{
+    let (rank, left_rank, right_rank) = peek_child_ranks(value_bytes)?;
+
+    let mut rest = &value_bytes[HEADER_LEN..];
+
+    let mut left_point = vec![];
+    if left_rank.is_some() {
+        left_point = take_child_point(&mut rest)?;
+    }
+    let mut right_point = vec![];
+    if right_rank.is_some() {
+        right_point = take_child_point(&mut rest)?;
+    }
+
+    let count = take_varint(&mut rest)?;
+    let value_len = take_varint(&mut rest)?;
+    if rest.len() < value_len {
+        return Err(CodecError::new("kv-tree vertex value is truncated"));
+    }
+    let value = value_codec.decode(&rest[..value_len])?;
+    let summary = summary_codec.decode(&rest[value_len..])?;
+
+    return Ok(KvNode {
+        rank,
+        value,
+        left: ChildRef::decode(value_bytes[1]),
+        left_point,
+        right: ChildRef::decode(value_bytes[2]),
+        right_point,
+        count,
+        summary,
+    });
+}
+
+/// Read the rank of a vertex, and the ranks of its left and right child (if any), from the bytes produced by [`encode_kv_node`], without decoding anything else.
+///
+/// Fails if the bytes are shorter than the three-byte header of the encoding, or if the rank of the vertex is the reserved [`EMPTY_CHILD`](crate::EMPTY_CHILD).
+pub fn peek_child_ranks(
+    value_bytes: &[u8],
+) -> Result<(Rank, Option<Rank>, Option<Rank>), CodecError> {
+    match value_bytes {
+        [rank, left, right, ..] => {
+            let rank = Rank::new(*rank)
+                .ok_or_else(|| CodecError::new("kv-tree vertex has the reserved rank"))?;
+            return Ok((
+                rank,
+                ChildRef::decode(*left).rank(),
+                ChildRef::decode(*right).rank(),
+            ));
+        }
+        _ => return Err(CodecError::new("kv-tree vertex is shorter than its header")),
+    }
+}
+
+/// A [`ValueCodec`] for [`KvNode`]s that uses [`encode_kv_node`] and [`decode_kv_node`], with the given codecs for the values and the summaries. Persistent backends that store kv-trees with it let traversals read the ranks of a vertex and its children with [`peek_child_ranks`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct KvNodeCodec<CV, CM> {
+    value_codec: CV,
+    summary_codec: CM,
+}
+
+impl<CV, CM> KvNodeCodec<CV, CM> {
+    /// Create a codec that encodes values with `value_codec`, and summaries with `summary_codec`.
+    pub fn new(value_codec: CV, summary_codec: CM) -> Self {
+        return KvNodeCodec {
+            value_codec,
+            summary_codec,
+        };
+    }
+}
+
+impl<V, M, CV, CM> ValueCodec<KvNode<V, M>> for KvNodeCodec<CV, CM>
+where
+    CV: ValueCodec<V>,
+    CM: ValueCodec<M>,
+{
+    fn encode(&self, node: &KvNode<V, M>) -> Vec<u8> {
+        return encode_kv_node(node, &self.value_codec, &self.summary_codec);
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<KvNode<V, M>, CodecError> {
+        return decode_kv_node(bytes, &self.value_codec, &self.summary_codec);
+    }
+}
+
+// Append `n` as a varint.
+fn push_varint(out: &mut Vec<u8>, n: usize) {
+    let mut buf = [0; 10];
+    let len = encode_varint(n, &mut buf);
+    out.extend_from_slice(&buf[..len]);
+}
+
+// Append the bytes, prefixed by their length as a varint.
+fn push_length_prefixed(out: &mut Vec<u8>, bytes: &[u8]) {
+    push_varint(out, bytes.len());
+    out.extend_from_slice(bytes);
+}
+
+// Read a varint from the start of `rest`, and advance `rest` past it.
+fn take_varint(rest: &mut &[u8]) -> Result<usize, CodecError> {
+    let (n, len) = decode_varint(rest)
+        .map_err(|_| CodecError::new("kv-tree vertex has an invalid varint"))?;
+    *rest = &rest[len..];
+    return Ok(n);
+}
+
+// Read a length-prefixed child point encoding from the start of `rest`, and advance `rest` past it.
+fn take_child_point(rest: &mut &[u8]) -> Result<Vec<u8>, CodecError> {
+    let len = take_varint(rest)?;
+    if rest.len() < len {
+        return Err(CodecError::new("kv-tree vertex child point is truncated"));
+    }
+
+    let point = rest[..len].to_vec();
+    *rest = &rest[len..];
+
+    return Ok(point);
+}
@@ -0,0 +1,816 @@
+use alloc::{boxed::Box, vec, vec::Vec};
+use core::cmp::Ordering;
+use core::marker::PhantomData;
+use core::ops::Bound::Unbounded;
+use core::pin::pin;
+
+use futures::{Stream, StreamExt};
+
+use crate::{
+    decode_tree_key, encode_tree_key, BackEnd, CappedHashRank, ChildRef, Dimension, HashRank,
+    LiftingCommutativeMonoid, Ordering3d, Point3d, Rank, RankStrategy, EMPTY_CHILD,
+};
+
+/// The value that a [`KvTree`] stores for each vertex, under the key created by [`encode_tree_key`] from the rank and point of the vertex.
+///
+/// Persistent backends can store it with the [`KvNodeCodec`](crate::KvNodeCodec), which puts the ranks of the vertex and its children in front. With the `bincode-codec` or `cbor-codec` feature, this also implements the serde traits, so that persistent backends can store it with the corresponding [`ValueCodec`](crate::ValueCodec).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    any(feature = "bincode-codec", feature = "cbor-codec"),
+    derive(serde::Serialize, serde::Deserialize)
+)]
+pub struct KvNode<V, M> {
+    /// The rank of the vertex, which is also the first byte of its key.
+    pub rank: Rank,
+    /// The value associated with the point of the vertex.
+    pub value: V,
+    /// The rank of the left child of the vertex, if it has one.
+    pub left: ChildRef,
+    /// The encoding of the point of the left child in the [ordering of its rank](crate::ordering_for_rank), or no bytes if there is no left child.
+    pub left_point: Vec<u8>,
+    /// The rank of the right child of the vertex, if it has one.
+    pub right: ChildRef,
+    /// The encoding of the point of the right child in the [ordering of its rank](crate::ordering_for_rank), or no bytes if there is no right child.
+    pub right_point: Vec<u8>,
+    /// The number of vertices in the subtree rooted at the vertex.
+    pub count: usize,
+    /// The monoidal summary of all point-value pairs in the subtree rooted at the vertex.
+    pub summary: M,
+}
+
+impl<V, M> KvNode<V, M> {
+    /// Return the key of the left child of the vertex, if it has one: its rank, followed by the encoding of its point.
+    pub fn left_key(&self) -> Option<Vec<u8>> {
+        return child_key(self.left, &self.left_point);
+    }
+
+    /// Return the key of the right child of the vertex, if it has one: its rank, followed by the encoding of its point.
+    pub fn right_key(&self) -> Option<Vec<u8>> {
+        return child_key(self.right, &self.right_point);
+    }
+}
+
+/// A monoid-3d-ish-zip-tree, stored as a kv-tree in a [`BackEnd`].
+///
+/// The tree assumes that it is the only user of the backend.
+///
+/// The tree chooses the ranks of the points inserted via [`insert_with_strategy`](Self::insert_with_strategy) with a [`RankStrategy`], by default the [`HashRank`] strategy. Use a [`KvTreeBuilder`] to create a tree with a different strategy.
+#[derive(Debug)]
+pub struct KvTree<B, X, Y, Z, V, M, R = HashRank> {
+    backend: B,
+    // The key of the root vertex, or `None` if the tree is empty.
+    root: Option<Vec<u8>>,
+    rank_strategy: R,
+    phantom: PhantomData<(X, Y, Z, V, M)>,
+}
+
+/// Configures and creates an empty [`KvTree`].
+///
+/// ```
+/// use kv_3d_storage::{BTreeMapBackEnd, KvNode, KvTree, KvTreeBuilder, Point3d, Rank};
+///
+/// let tree: KvTree<BTreeMapBackEnd<KvNode<u8, usize>>, u8, u8, u8, u8, usize, _> =
+///     KvTreeBuilder::new(BTreeMapBackEnd::new())
+///         .with_rank_strategy(|_: &Point3d<u8, u8, u8>, value: &u8| {
+///             Rank::new(value.trailing_zeros() as u8).unwrap()
+///         })
+///         .build();
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct KvTreeBuilder<B, R = HashRank> {
+    backend: B,
+    rank_strategy: R,
+}
+
+impl<B> KvTreeBuilder<B> {
+    /// Start configuring a tree that is stored in a backend that must not contain any kv pairs, and that uses the [`HashRank`] strategy.
+    pub fn new(backend: B) -> Self {
+        return KvTreeBuilder {
+            backend,
+            rank_strategy: HashRank,
+        };
+    }
+}
+
+impl<B, R> KvTreeBuilder<B, R> {
+    /// Use the given [`RankStrategy`] for [`KvTree::insert_with_strategy`].
+    pub fn with_rank_strategy<R2>(self, rank_strategy: R2) -> KvTreeBuilder<B, R2> {
+        return KvTreeBuilder {
+            backend: self.backend,
+            rank_strategy,
+        };
+    }
+
+    /// Use the [`CappedHashRank`] strategy with the given maximum rank for [`KvTree::insert_with_strategy`], replacing the current strategy. See [`rank_from_hash_capped`](crate::rank_from_hash_capped) for the tradeoffs of capping ranks.
+    pub fn with_max_rank(self, max_rank: Rank) -> KvTreeBuilder<B, CappedHashRank> {
+        return self.with_rank_strategy(CappedHashRank { max_rank });
+    }
+
+    /// Create the configured, empty tree.
+    pub fn build<X, Y, Z, V, M>(self) -> KvTree<B, X, Y, Z, V, M, R> {
+        return KvTree {
+            backend: self.backend,
+            root: None,
+            rank_strategy: self.rank_strategy,
+            phantom: PhantomData,
+        };
+    }
+
+    /// Create the configured tree from a backend that already stores a kv-tree (and nothing else), for example one that a previous process has [flushed](KvTree::flush). See [`KvTree::open`].
+    pub async fn open<X, Y, Z, V, M>(
+        self,
+    ) -> Result<KvTree<B, X, Y, Z, V, M, R>, OpenError<B::Error>>
+    where
+        B: BackEnd<KvNode<V, M>>,
+    {
+        let root = find_root(&self.backend).await?;
+
+        return Ok(KvTree {
+            backend: self.backend,
+            root,
+            rank_strategy: self.rank_strategy,
+            phantom: PhantomData,
+        });
+    }
+}
+
+#[cfg(feature = "redb-backend")]
+impl<V, C, R> KvTreeBuilder<crate::RedbBackEnd<V, C>, R> {
+    /// Store values in the redb backend with the given [`ValueCodec`](crate::ValueCodec) instead of its current one, discarding all mutations of the backend that have not been [flushed](BackEnd::flush).
+    #[allow(clippy::result_large_err)]
+    pub fn with_value_codec<C2>(
+        self,
+        codec: C2,
+    ) -> Result<KvTreeBuilder<crate::RedbBackEnd<V, C2>, R>, ::redb::Error> {
+        return Ok(KvTreeBuilder {
+            backend: crate::RedbBackEnd::with_codec(self.backend.into_database(), codec)?,
+            rank_strategy: self.rank_strategy,
+        });
+    }
+}
+
+/// The reasons why an operation on a [`KvTree`] can fail.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KvTreeError<E> {
+    /// The backend reported an error.
+    Backend(E),
+    /// The backend does not store a valid kv-tree at the given key: the key is not the key of a vertex, or a vertex refers to it as its child but the backend stores nothing under it. When [opening](KvTree::open) a tree, this is also reported if the backend did not find the least key of the greatest rank.
+    Corrupted(Vec<u8>),
+}
+
+/// The reasons why [`KvTree::open`] can fail.
+pub type OpenError<E> = KvTreeError<E>;
+
+impl<E: core::fmt::Display> core::fmt::Display for KvTreeError<E> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            KvTreeError::Backend(err) => return write!(f, "backend error: {}", err),
+            KvTreeError::Corrupted(key) => return write!(f, "corrupt kv-tree at key {:?}", key),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<E: std::error::Error + 'static> std::error::Error for KvTreeError<E> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            KvTreeError::Backend(err) => return Some(err),
+            KvTreeError::Corrupted(_) => return None,
+        }
+    }
+}
+
+// A point, its value, and its rank.
+type Item<X, Y, Z, V> = (Point3d<X, Y, Z>, V, Rank);
+
+// A rank, a point of that rank, and its value.
+type RankedItem<X, Y, Z, V> = (Rank, Point3d<X, Y, Z>, V);
+
+// A rank, and a point of that rank.
+type RankedPoint<X, Y, Z> = (Rank, Point3d<X, Y, Z>);
+
+// The key, count, and summary of the root of a subtree, or `None` if the subtree is empty.
+type SubtreeRoot<M> = Option<(Vec<u8>, usize, M)>;
+
+impl<B, X, Y, Z, V, M> KvTree<B, X, Y, Z, V, M> {
+    /// Create an empty tree with the [`HashRank`] strategy, stored in a backend that must not contain any kv pairs.
+    pub fn new(backend: B) -> Self {
+        return KvTreeBuilder::new(backend).build();
+    }
+
+    /// Create a tree with the [`HashRank`] strategy from a backend that already stores a kv-tree (and nothing else), for example one that a previous process has [flushed](Self::flush). An empty backend yields an empty tree.
+    ///
+    /// No root pointer needs to be stored: the root is the vertex of greatest rank, and among those the one with the least key. Since every key starts with the rank of its vertex, two lookups find it. Storing the root under a reserved key such as `"__root__"` instead would not work: in a backend of [`KvNode`]s, there is no value to store under it, and the key would lie among the keys of the vertices of rank 95 (the byte `b'_'`). Because nothing needs to be written for an empty tree, there is no separate `create` function either; [`new`](Self::new) creates an empty tree, and `open` recovers it later.
+    ///
+    /// Fails with [`KvTreeError::Corrupted`] if the backend contains keys that are not vertex keys.
+    pub async fn open(backend: B) -> Result<Self, OpenError<B::Error>>
+    where
+        B: BackEnd<KvNode<V, M>>,
+    {
+        return KvTreeBuilder::new(backend).open().await;
+    }
+}
+
+impl<B, X, Y, Z, V, M, R> KvTree<B, X, Y, Z, V, M, R> {
+    /// Return a reference to the backend in which the tree is stored.
+    pub fn backend(&self) -> &B {
+        return &self.backend;
+    }
+
+    /// Return the backend in which the tree is stored.
+    pub fn into_backend(self) -> B {
+        return self.backend;
+    }
+
+    /// Return the key of the root vertex, or `None` if the tree is empty.
+    pub fn root_key(&self) -> Option<&[u8]> {
+        return self.root.as_deref();
+    }
+
+    /// Return the [`RankStrategy`] of the tree.
+    pub fn rank_strategy(&self) -> &R {
+        return &self.rank_strategy;
+    }
+}
+
+impl<B, X, Y, Z, V, M> KvTree<B, X, Y, Z, V, M>
+where
+    B: BackEnd<KvNode<V, M>>,
+    X: Dimension + Clone,
+    Y: Dimension + Clone,
+    Z: Dimension + Clone,
+    V: Clone,
+    M: LiftingCommutativeMonoid<(Point3d<X, Y, Z>, V)> + Clone,
+{
+    /// Create a tree with the [`HashRank`] strategy from a sequence of points, associated values, and desired ranks, stored in a backend that must not contain any kv pairs. In case of duplicate points, the last occurrence (with its value and rank) wins.
+    ///
+    /// Computes the shape of the tree in memory, and then writes all vertices to the backend in a single pass in ascending order of keys. This is much faster than [inserting](Self::insert) the items one by one.
+    pub async fn bulk_load<I: Iterator<Item = Item<X, Y, Z, V>>>(
+        mut backend: B,
+        items: I,
+    ) -> Result<Self, B::Error> {
+        let items: Vec<_> = items.collect();
+
+        let mut nodes = vec![];
+        let root = build_subtree(dedup_last_wins(items), &mut nodes);
+
+        nodes.sort_by(|(key1, _), (key2, _)| key1.cmp(key2));
+        for (key, node) in nodes {
+            backend.insert(&key, node).await?;
+        }
+
+        return Ok(KvTree {
+            backend,
+            root: root.map(|(key, _, _)| key),
+            rank_strategy: HashRank,
+            phantom: PhantomData,
+        });
+    }
+}
+
+impl<B, X, Y, Z, V, M, R> KvTree<B, X, Y, Z, V, M, R>
+where
+    B: BackEnd<KvNode<V, M>>,
+    X: Dimension + Clone,
+    Y: Dimension + Clone,
+    Z: Dimension + Clone,
+    V: Clone,
+    M: LiftingCommutativeMonoid<(Point3d<X, Y, Z>, V)> + Clone,
+{
+    /// Get the value associated with the given point, if there is any.
+    ///
+    /// Fails with [`KvTreeError::Corrupted`] if a vertex on the way to the point is missing from the backend.
+    pub async fn get(&self, point: &Point3d<X, Y, Z>) -> Result<Option<V>, KvTreeError<B::Error>> {
+        let mut current = self.root.clone();
+
+        while let Some(key) = current {
+            let node = self.load(&key).await?;
+
+            // Comparing encodings is equivalent to comparing points in the ordering of the rank of the vertex.
+            match encode_tree_key(node.rank, point).cmp(&key) {
+                Ordering::Equal => return Ok(Some(node.value)),
+                Ordering::Less => current = node.left_key(),
+                Ordering::Greater => current = node.right_key(),
+            }
+        }
+
+        return Ok(None);
+    }
+
+    /// Insert a point with the given value and rank. Returns the old value for that point, if there was any. If the point was present with a different rank, its vertex moves to the position given by the new rank.
+    ///
+    /// If the point was present with the same rank, this only replaces its value, and updates the summaries of the vertex and its ancestors. If it was present with a different rank, its vertex is removed first, by zipping its two children together. The new vertex then takes the position of the first vertex on its search path that it takes precedence over: the subtree rooted there is unzipped along the new point into the two children of the new vertex, and the ancestors are updated. Only the vertices on the search path and on the zip and unzip paths are rewritten, the remaining vertices stay untouched.
+    ///
+    /// Fails with [`KvTreeError::Corrupted`] if a vertex on any of these paths, or a child of one, is missing from the backend or stored under a key that is not a vertex key. The backend might then have been partially modified.
+    pub async fn insert(
+        &mut self,
+        point: Point3d<X, Y, Z>,
+        value: V,
+        rank: Rank,
+    ) -> Result<Option<V>, KvTreeError<B::Error>> {
+        // Search for the point, remembering the path there, and the direction taken at each vertex.
+        let mut path = vec![];
+        let mut current = self.root.clone();
+        let mut old = None;
+
+        while let Some(key) = current.take() {
+            let mut node = self.load(&key).await?;
+
+            match encode_tree_key(node.rank, &point).cmp(&key) {
+                Ordering::Less => {
+                    current = node.left_key();
+                    path.push((key, node, true));
+                }
+                Ordering::Greater => {
+                    current = node.right_key();
+                    path.push((key, node, false));
+                }
+                Ordering::Equal if node.rank == rank => {
+                    // The shape does not depend on values, so only the summaries on the path change.
+                    let old = core::mem::replace(&mut node.value, value);
+                    let left = self.subtree_root(node.left_key()).await?;
+                    let right = self.subtree_root(node.right_key()).await?;
+                    let vertex = self.write_vertex(key, &point, node, left, right).await?;
+
+                    let root = self.rewrite_path(path, Some(vertex)).await?;
+                    self.root = root.map(|(key, _, _)| key);
+                    return Ok(Some(old));
+                }
+                Ordering::Equal => {
+                    // Replace the vertex by the union of its children.
+                    let left = self.subtree_root(node.left_key()).await?;
+                    let right = self.subtree_root(node.right_key()).await?;
+                    let merged = self.union(left, right).await?;
+                    self.backend
+                        .delete(&key)
+                        .await
+                        .map_err(KvTreeError::Backend)?;
+
+                    let root = self
+                        .rewrite_path(core::mem::take(&mut path), merged)
+                        .await?;
+                    self.root = root.map(|(key, _, _)| key);
+                    old = Some(node.value);
+                }
+            }
+        }
+
+        // Descend to the first vertex that the new vertex takes precedence over: the new rank is greater, or the ranks are equal and the new point is less in their ordering.
+        let mut path = vec![];
+        let mut current = self.root.clone();
+
+        while let Some(key) = current.take() {
+            let node_rank = match Rank::of_key(&key) {
+                Some(rank) => rank,
+                None => return Err(KvTreeError::Corrupted(key)),
+            };
+            let cmp = encode_tree_key(node_rank, &point).cmp(&key);
+
+            let new_wins = match rank.cmp(&node_rank) {
+                Ordering::Equal => cmp == Ordering::Less,
+                rank_cmp => rank_cmp == Ordering::Greater,
+            };
+            if new_wins {
+                current = Some(key);
+                break;
+            }
+
+            let node = self.load(&key).await?;
+            let went_left = cmp == Ordering::Less;
+            current = if went_left {
+                node.left_key()
+            } else {
+                node.right_key()
+            };
+            path.push((key, node, went_left));
+        }
+
+        // Unzip the subtree there into the children of the new vertex.
+        let current = self.subtree_root(current).await?;
+        let (less, greater) = self.split(current, &point, rank.ordering()).await?;
+
+        let node = KvNode {
+            rank,
+            value,
+            left: ChildRef::None,
+            left_point: vec![],
+            right: ChildRef::None,
+            right_point: vec![],
+            count: 0,
+            summary: M::NEUTRAL,
+        };
+        let key = encode_tree_key(rank, &point);
+        let vertex = self.write_vertex(key, &point, node, less, greater).await?;
+
+        let root = self.rewrite_path(path, Some(vertex)).await?;
+        self.root = root.map(|(key, _, _)| key);
+        return Ok(old);
+    }
+
+    /// Insert a point with the given value, at the rank that the [`RankStrategy`] of the tree assigns to them. Returns the old value for that point, if there was any.
+    pub async fn insert_with_strategy(
+        &mut self,
+        point: Point3d<X, Y, Z>,
+        value: V,
+    ) -> Result<Option<V>, KvTreeError<B::Error>>
+    where
+        R: RankStrategy<X, Y, Z, V>,
+    {
+        let rank = self.rank_strategy.rank(&point, &value);
+        return self.insert(point, value, rank).await;
+    }
+
+    /// Lazily iterate over all vertices of the tree, as triples of rank, point, and value, grouped by ascending rank. Within each rank, the points come in ascending order according to the [ordering of that rank](crate::ordering_for_rank).
+    ///
+    /// This scans the backend in key order. Since the rank is the first byte of every vertex key, the vertices naturally come grouped by rank.
+    ///
+    /// Yields [`KvTreeError::Corrupted`] for keys that are not vertex keys.
+    pub fn iter_by_rank(
+        &self,
+    ) -> impl Stream<Item = Result<RankedItem<X, Y, Z, V>, KvTreeError<B::Error>>> + '_ {
+        return self
+            .backend
+            .stream_range(Unbounded, Unbounded)
+            .map(|entry| {
+                let (key, node) = entry.map_err(KvTreeError::Backend)?;
+                let (rank, point) = decode_key(&key)?;
+                return Ok((rank, point, node.value));
+            });
+    }
+
+    /// Rebuild the tree with the ranks that its [`RankStrategy`] assigns to the stored point-value pairs, rewrite the backend accordingly, and [flush](Self::flush) it.
+    ///
+    /// This is a maintenance operation, meant to be run offline: it reads all vertices into memory, computes the canonical shape, deletes the vertices whose keys changed, and writes all vertices in ascending order of keys. Use it to repair the shape of a tree whose ranks were supplied via [`insert`](Self::insert) rather than by the strategy, or to clean up a backend after many mutations.
+    ///
+    /// Fails with [`KvTreeError::Corrupted`] if the backend contains keys that are not vertex keys, without modifying the backend.
+    pub async fn compact(&mut self) -> Result<(), KvTreeError<B::Error>>
+    where
+        R: RankStrategy<X, Y, Z, V>,
+    {
+        let mut old_keys = vec![];
+        let mut items = vec![];
+        {
+            let mut stream = pin!(self.backend.stream_range(Unbounded, Unbounded));
+            while let Some(entry) = stream.next().await {
+                let (key, node) = entry.map_err(KvTreeError::Backend)?;
+                let (_, point) = decode_key(&key)?;
+                let rank = self.rank_strategy.rank(&point, &node.value);
+
+                items.push((point, node.value, rank));
+                old_keys.push(key);
+            }
+        }
+
+        let mut nodes = vec![];
+        let root = build_subtree(items, &mut nodes);
+        nodes.sort_by(|(key1, _), (key2, _)| key1.cmp(key2));
+
+        // Vertices whose key did not change are overwritten below, all others are stale.
+        for key in old_keys {
+            if nodes.binary_search_by(|(new_key, _)| new_key.cmp(&key)).is_err() {
+                self.backend
+                    .delete(&key)
+                    .await
+                    .map_err(KvTreeError::Backend)?;
+            }
+        }
+
+        for (key, node) in nodes {
+            self.backend
+                .insert(&key, node)
+                .await
+                .map_err(KvTreeError::Backend)?;
+        }
+
+        self.root = root.map(|(key, _, _)| key);
+        return self.flush().await.map_err(KvTreeError::Backend);
+    }
+
+    /// Commit all mutations that have been performed so far to disk, see [`BackEnd::flush`].
+    pub async fn flush(&mut self) -> Result<(), B::Error> {
+        return self.backend.flush().await;
+    }
+
+    // Load the vertex stored under the given key, which some other vertex (or the tree itself) refers to.
+    async fn load(&self, key: &[u8]) -> Result<KvNode<V, M>, KvTreeError<B::Error>> {
+        match self.backend.get(key).await.map_err(KvTreeError::Backend)? {
+            Some(node) => return Ok(node),
+            None => return Err(KvTreeError::Corrupted(key.to_vec())),
+        }
+    }
+
+    // Return the key, count, and summary of the subtree rooted at the given key.
+    async fn subtree_root(
+        &self,
+        key: Option<Vec<u8>>,
+    ) -> Result<SubtreeRoot<M>, KvTreeError<B::Error>> {
+        match key {
+            None => return Ok(None),
+            Some(key) => {
+                let node = self.load(&key).await?;
+                return Ok(Some((key, node.count, node.summary)));
+            }
+        }
+    }
+
+    // Write the vertex of the given point under the given key, with the given subtrees as its children, and with count and summary computed from them. Returns the key, count, and summary of the vertex.
+    async fn write_vertex(
+        &mut self,
+        key: Vec<u8>,
+        point: &Point3d<X, Y, Z>,
+        mut node: KvNode<V, M>,
+        left: SubtreeRoot<M>,
+        right: SubtreeRoot<M>,
+    ) -> Result<(Vec<u8>, usize, M), KvTreeError<B::Error>> {
+        let (left_key, left_count, left_summary) = match left {
+            Some((key, count, summary)) => (Some(key), count, summary),
+            None => (None, 0, M::NEUTRAL),
+        };
+        let (right_key, right_count, right_summary) = match right {
+            Some((key, count, summary)) => (Some(key), count, summary),
+            None => (None, 0, M::NEUTRAL),
+        };
+
+        let own = M::lift(&(point.clone(), node.value.clone()));
+        node.count = 1 + left_count + right_count;
+        node.summary = M::combine_all([&own, &left_summary, &right_summary]);
+        (node.left, node.left_point) = split_child_key(left_key);
+        (node.right, node.right_point) = split_child_key(right_key);
+
+        let vertex = (key.clone(), node.count, node.summary.clone());
+        self.backend
+            .insert(&key, node)
+            .await
+            .map_err(KvTreeError::Backend)?;
+
+        return Ok(vertex);
+    }
+
+    // Update the child references and aggregates of the vertices on the given path, bottom-up, after the subtree at its end was replaced by `child`. Returns the new root of the tree.
+    async fn rewrite_path(
+        &mut self,
+        mut path: Vec<(Vec<u8>, KvNode<V, M>, bool)>,
+        mut child: SubtreeRoot<M>,
+    ) -> Result<SubtreeRoot<M>, KvTreeError<B::Error>> {
+        while let Some((key, node, went_left)) = path.pop() {
+            let (_, point) = decode_key(&key)?;
+            let (left, right) = if went_left {
+                (child, self.subtree_root(node.right_key()).await?)
+            } else {
+                (self.subtree_root(node.left_key()).await?, child)
+            };
+
+            child = Some(self.write_vertex(key, &point, node, left, right).await?);
+        }
+
+        return Ok(child);
+    }
+
+    // Split a subtree that does not contain `point` into the subtrees of all points less than `point` and all points greater than `point`, according to the given ordering. Rewrites the vertices on the unzip paths, and returns the roots of the two parts.
+    async fn split(
+        &mut self,
+        root: SubtreeRoot<M>,
+        point: &Point3d<X, Y, Z>,
+        ordering: Ordering3d,
+    ) -> Result<(SubtreeRoot<M>, SubtreeRoot<M>), KvTreeError<B::Error>> {
+        let key = match root {
+            None => return Ok((None, None)),
+            Some((key, _, _)) => key,
+        };
+        let node = self.load(&key).await?;
+        let (rank, key_point) = decode_key(&key)?;
+        let key_is_less = key_point.cmp_by(point, ordering) == Ordering::Less;
+
+        let left = self.subtree_root(node.left_key()).await?;
+        let right = self.subtree_root(node.right_key()).await?;
+
+        if rank.ordering() == ordering {
+            // The vertex separates its subtrees according to the ordering of the split, so only one subtree needs splitting.
+            if key_is_less {
+                let (right_less, right_greater) =
+                    Box::pin(self.split(right, point, ordering)).await?;
+                let less = self
+                    .write_vertex(key, &key_point, node, left, right_less)
+                    .await?;
+                return Ok((Some(less), right_greater));
+            } else {
+                let (left_less, left_greater) = Box::pin(self.split(left, point, ordering)).await?;
+                let greater = self
+                    .write_vertex(key, &key_point, node, left_greater, right)
+                    .await?;
+                return Ok((left_less, Some(greater)));
+            }
+        } else {
+            let (left_less, left_greater) = Box::pin(self.split(left, point, ordering)).await?;
+            let (right_less, right_greater) = Box::pin(self.split(right, point, ordering)).await?;
+
+            if key_is_less {
+                let less = self
+                    .write_vertex(key, &key_point, node, left_less, right_less)
+                    .await?;
+                let greater = Box::pin(self.union(left_greater, right_greater)).await?;
+                return Ok((Some(less), greater));
+            } else {
+                let less = Box::pin(self.union(left_less, right_less)).await?;
+                let greater = self
+                    .write_vertex(key, &key_point, node, left_greater, right_greater)
+                    .await?;
+                return Ok((less, Some(greater)));
+            }
+        }
+    }
+
+    // Zip two subtrees over disjoint sets of points into the subtree over the union of their points. Rewrites the vertices on the zip paths, and returns the root of the union.
+    //
+    // The root of the union is the root of higher rank (or, for equal ranks, the lesser root according to the ordering of that rank). The other subtree is split by that root, and the parts are recursively zipped with the children of the root.
+    async fn union(
+        &mut self,
+        a: SubtreeRoot<M>,
+        b: SubtreeRoot<M>,
+    ) -> Result<SubtreeRoot<M>, KvTreeError<B::Error>> {
+        let (a, b) = match (a, b) {
+            (None, b) => return Ok(b),
+            (a, None) => return Ok(a),
+            (Some(a), Some(b)) => (a, b),
+        };
+
+        let (a_rank, a_point) = decode_key(&a.0)?;
+        let (b_rank, b_point) = decode_key(&b.0)?;
+        let a_wins = match a_rank.cmp(&b_rank) {
+            Ordering::Equal => a_point.cmp_by(&b_point, a_rank.ordering()) == Ordering::Less,
+            rank_cmp => rank_cmp == Ordering::Greater,
+        };
+
+        let (winner, rank, point, loser) = if a_wins {
+            (a.0, a_rank, a_point, b)
+        } else {
+            (b.0, b_rank, b_point, a)
+        };
+
+        let node = self.load(&winner).await?;
+        let (loser_less, loser_greater) =
+            Box::pin(self.split(Some(loser), &point, rank.ordering())).await?;
+
+        let left = self.subtree_root(node.left_key()).await?;
+        let left = Box::pin(self.union(left, loser_less)).await?;
+        let right = self.subtree_root(node.right_key()).await?;
+        let right = Box::pin(self.union(right, loser_greater)).await?;
+
+        let vertex = self.write_vertex(winner, &point, node, left, right).await?;
+        return Ok(Some(vertex));
+    }
+}
+
+// Find the key of the root of the kv-tree stored in the backend: the least key of the greatest rank.
+async fn find_root<B, V, M>(backend: &B) -> Result<Option<Vec<u8>>, KvTreeError<B::Error>>
+where
+    B: BackEnd<KvNode<V, M>>,
+{
+    // Every vertex key starts with a rank less than `EMPTY_CHILD`, so it is less than the key consisting of `EMPTY_CHILD` alone.
+    let greatest = match backend
+        .find_lte(&[EMPTY_CHILD])
+        .await
+        .map_err(KvTreeError::Backend)?
+    {
+        None => return Ok(None),
+        Some((key, _)) => key,
+    };
+
+    let rank = match Rank::of_key(&greatest) {
+        Some(rank) => rank,
+        None => return Err(KvTreeError::Corrupted(greatest)),
+    };
+
+    match backend
+        .find_gte(&[rank.get()])
+        .await
+        .map_err(KvTreeError::Backend)?
+    {
+        Some((key, _)) if Rank::of_key(&key) == Some(rank) => return Ok(Some(key)),
+        _ => return Err(KvTreeError::Corrupted(greatest)),
+    }
+}
+
+// Return the key of the child with the given reference and point encoding, if there is such a child.
+fn child_key(child: ChildRef, point: &[u8]) -> Option<Vec<u8>> {
+    let rank = child.rank()?;
+
+    let mut key = Vec::with_capacity(1 + point.len());
+    key.push(rank.get());
+    key.extend_from_slice(point);
+    return Some(key);
+}
+
+// Split the key of a child (if any) into the reference to the child and the encoding of its point.
+fn split_child_key(key: Option<Vec<u8>>) -> (ChildRef, Vec<u8>) {
+    match key {
+        None => return (ChildRef::None, vec![]),
+        Some(mut key) => {
+            // The key was created by `encode_tree_key`, so it starts with a rank.
+            let rank = key.remove(0);
+            return (ChildRef::decode(rank), key);
+        }
+    }
+}
+
+// Decode the key of a vertex, reporting an invalid key as corruption.
+fn decode_key<X, Y, Z, E>(key: &[u8]) -> Result<RankedPoint<X, Y, Z>, KvTreeError<E>>
+where
+    X: Dimension,
+    Y: Dimension,
+    Z: Dimension,
+{
+    return decode_tree_key(key).map_err(|_| KvTreeError::Corrupted(key.to_vec()));
+}
+
+// Remove all but the last occurrence of each point.
+fn dedup_last_wins<X, Y, Z, V>(mut items: Vec<Item<X, Y, Z, V>>) -> Vec<Item<X, Y, Z, V>>
+where
+    X: Dimension,
+    Y: Dimension,
+    Z: Dimension,
+{
+    // The sort is stable, so the last occurrence of a point comes last among its equals.
+    items.sort_by(|(p1, _, _), (p2, _, _)| p1.cmp_xyz(p2));
+
+    let mut unique: Vec<Item<X, Y, Z, V>> = Vec::with_capacity(items.len());
+    for item in items {
+        match unique.last_mut() {
+            Some(last) if last.0 == item.0 => *last = item,
+            _ => unique.push(item),
+        }
+    }
+
+    return unique;
+}
+
+// Compute the vertices of the 3d-ish-zip-tree over the given items (which must have pairwise distinct points), and append them to `out`. Returns the key, count, and summary of the root, or `None` if there are no items.
+fn build_subtree<X, Y, Z, V, M>(
+    mut items: Vec<Item<X, Y, Z, V>>,
+    out: &mut Vec<(Vec<u8>, KvNode<V, M>)>,
+) -> SubtreeRoot<M>
+where
+    X: Dimension + Clone,
+    Y: Dimension + Clone,
+    Z: Dimension + Clone,
+    M: LiftingCommutativeMonoid<(Point3d<X, Y, Z>, V)> + Clone,
+{
+    if items.is_empty() {
+        return None;
+    }
+
+    // The root is the item of greatest rank, ties are broken in favor of the least point in the ordering of that rank.
+    let mut root_index = 0;
+    for (i, (point, _, rank)) in items.iter().enumerate().skip(1) {
+        let (root_point, _, root_rank) = &items[root_index];
+        if rank > root_rank
+            || (rank == root_rank && point.cmp_by(root_point, rank.ordering()) == Ordering::Less)
+        {
+            root_index = i;
+        }
+    }
+
+    let (point, value, rank) = items.swap_remove(root_index);
+    let ordering = rank.ordering();
+    let (left_items, right_items): (Vec<_>, Vec<_>) = items
+        .into_iter()
+        .partition(|(p, _, _)| p.cmp_by(&point, ordering) == Ordering::Less);
+
+    let left = build_subtree(left_items, out);
+    let right = build_subtree(right_items, out);
+
+    let (left_key, left_count, left_summary) = match left {
+        Some((key, count, summary)) => (Some(key), count, summary),
+        None => (None, 0, M::NEUTRAL),
+    };
+    let (right_key, right_count, right_summary) = match right {
+        Some((key, count, summary)) => (Some(key), count, summary),
+        None => (None, 0, M::NEUTRAL),
+    };
+
+    let key = encode_tree_key(rank, &point);
+    let kv_pair = (point, value);
+    let own = M::lift(&kv_pair);
+    let (_, value) = kv_pair;
+
+    let count = 1 + left_count + right_count;
+    let summary = M::combine_all([&own, &left_summary, &right_summary]);
+
+    let (left, left_point) = split_child_key(left_key);
+    let (right, right_point) = split_child_key(right_key);
+    out.push((
+        key.clone(),
+        KvNode {
+            rank,
+            value,
+            left,
+            left_point,
+            right,
+            right_point,
+            count,
+            summary: summary.clone(),
+        },
+    ));
+
+    return Some((key, count, summary));
+}
@@ -31,16 +31,82 @@
 //! To allow to efficiently answer certain queries, all our trees are [monoid trees](https://github.com/AljoschaMeyer/rbsr_short/blob/main/main.pdf), based off the [`LiftingCommutativeMonoid` trait](monoid::LiftingCommutativeMonoid). Monoids must be commutative, or things will randomly break. We always employ the counting monoid, plus an arbitrary user-specified monoid.
 //! 
 //! We work with monoid-3d-ish-zip-trees conceptually, but we do not implement them directly. Instead, we define for any tree a corresponding set of key-value pairs to store in the storage backend. All algorithms need to implemented in terms of these *kv-trees*. The precise definition of kv-trees and generic functionality is implemented in the `kv_tree` module.
+//!
+//! ## Features
+//!
+//! The crate supports `no_std` environments. The `std` feature (enabled by default) provides the APIs that work with `std::io`. The `alloc` feature (implied by `std`) provides everything that needs heap allocations, including the [`BackEnd`] trait and all kv-tree functionality. Without either feature, only the core [`Point3d`] and [`Dimension`] types (with slice-based encoding and decoding) and the monoids are available.
+//!
+//! The `arbitrary` feature provides implementations of the [`Arbitrary`](arbitrary::Arbitrary) trait, for fuzz testing. The `testing` feature provides utilities for checking implementations of the [`Dimension`] trait, such as the [`dimension_conformance_tests`] macro, and of the [`BackEnd`] trait, such as [`run_backend_conformance`]. The `bincode-codec` and `cbor-codec` features provide [`ValueCodec`]s that let persistent backends store any [serde](https://docs.rs/serde) type. The `uuid` feature implements [`Dimension`] for [`Uuid`](uuid::Uuid).
+#![cfg_attr(not(feature = "std"), no_std)]
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
 
 mod point3d;
 pub use point3d::*;
 
+mod dimensions;
+pub use dimensions::*;
+
+#[cfg(feature = "alloc")]
 mod backend;
+#[cfg(feature = "alloc")]
 pub use backend::*;
 
+#[cfg(feature = "alloc")]
+mod codec;
+#[cfg(feature = "alloc")]
+pub use codec::*;
+
+#[cfg(feature = "alloc")]
+mod backends;
+#[cfg(feature = "alloc")]
+pub use backends::*;
+
+#[cfg(feature = "alloc")]
+mod point_store;
+#[cfg(feature = "alloc")]
+pub use point_store::*;
+
 mod monoid;
 pub use monoid::*;
 
 mod kv_tree;
 pub use kv_tree::*;
+
+#[cfg(feature = "alloc")]
+mod rank;
+#[cfg(feature = "alloc")]
+pub use rank::*;
+
+#[cfg(feature = "testing")]
+mod testing;
+#[cfg(feature = "testing")]
+pub use testing::*;
+
+/// The reasons why decoding a [`Dimension`] or a [`Point3d`] can fail.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Hash)]
+pub enum DecodeError {
+    /// The input ended before the encoding was complete.
+    UnexpectedEof,
+    /// A variable-width encoding is not followed by the separator that must come after it.
+    BadSeparator,
+    /// The bytes are not the encoding of any value.
+    InvalidValue,
+    /// The input contains bytes after the encoding, where it must contain exactly one encoding.
+    TrailingBytes,
+}
+
+impl core::fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            DecodeError::UnexpectedEof => return write!(f, "unexpected end of input"),
+            DecodeError::BadSeparator => return write!(f, "missing separator after a variable-width encoding"),
+            DecodeError::InvalidValue => return write!(f, "invalid encoding"),
+            DecodeError::TrailingBytes => return write!(f, "trailing bytes after the encoding"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for DecodeError {}
@@ -1,37 +1,218 @@
-/// A commutative [monoid](https://en.wikipedia.org/wiki/Monoid), together with a function that lifts values of type `LiftingFrom` into the universe of the monoid. See the [range-based set reconciliation paper](https://github.com/AljoschaMeyer/rbsr_short/blob/main/main.pdf) for more context.
-pub trait LiftingCommutativeMonoid<LiftingFrom>: Sized + Eq {
+use core::cmp::Ordering;
+#[cfg(feature = "alloc")]
+use core::hash::Hasher;
+
+use crate::{Dimension, Point3d};
+#[cfg(feature = "alloc")]
+use crate::{Ordering3d, RankHasher};
+
+/// A commutative [monoid](https://en.wikipedia.org/wiki/Monoid): the part of a [`LiftingCommutativeMonoid`] that does not depend on the type of the values that are lifted into it.
+pub trait CommutativeMonoid: Sized + Eq {
     /// The neutral element of the monoid.
     const NEUTRAL: Self;
 
+    /// Combine two monoidal values. This function must be associative, commutative, and [`Self::NEUTRAL`] must be the neutral element of this function.
+    fn combine(a: &Self, b: &Self) -> Self;
+
+    /// Combine any number of monoidal values, starting from [`Self::NEUTRAL`].
+    fn combine_all<'a>(items: impl IntoIterator<Item = &'a Self>) -> Self
+    where
+        Self: 'a,
+    {
+        return items
+            .into_iter()
+            .fold(Self::NEUTRAL, |acc, item| Self::combine(&acc, item));
+    }
+}
+
+/// A commutative [monoid](https://en.wikipedia.org/wiki/Monoid), together with a function that lifts values of type `LiftingFrom` into the universe of the monoid. See the [range-based set reconciliation paper](https://github.com/AljoschaMeyer/rbsr_short/blob/main/main.pdf) for more context.
+pub trait LiftingCommutativeMonoid<LiftingFrom>: CommutativeMonoid {
     /// Lift a value into the monoid.
     fn lift(val: &LiftingFrom) -> Self;
 
-    /// Combine two monoidal values. This function must be associative, commutative, and [`Self::NEUTRAL`] must be the neutral element of this function.
-    fn combine(a: &Self, b: &Self) -> Self;
+    /// Lift all given values into the monoid, and combine the results.
+    fn lift_all(vals: &[LiftingFrom]) -> Self {
+        return vals
+            .iter()
+            .fold(Self::NEUTRAL, |acc, val| Self::combine(&acc, &Self::lift(val)));
+    }
 }
 
 /// The trivial monoid that performs no computation. Use this when you *have* to supply a monoid but you do not actually need one.
-impl<T> LiftingCommutativeMonoid<T> for () {
+impl CommutativeMonoid for () {
     const NEUTRAL: Self = ();
 
-    fn lift(_val: &T) -> Self {
+    fn combine(_a: &Self, _b: &Self) -> Self {
         return ();
     }
+}
 
-    fn combine(_a: &Self, _b: &Self) -> Self {
+impl<T> LiftingCommutativeMonoid<T> for () {
+    fn lift(_val: &T) -> Self {
         return ();
     }
 }
 
 /// The `LiftingCommutativeMonoid` implementation for `usize` performs counting: any value is lifted to `1`, and `combine` is addition.
-impl<T> LiftingCommutativeMonoid<T> for usize {
+impl CommutativeMonoid for usize {
     const NEUTRAL: Self = 0;
 
+    fn combine(a: &Self, b: &Self) -> Self {
+        return *a + *b;
+    }
+}
+
+impl<T> LiftingCommutativeMonoid<T> for usize {
     fn lift(_val: &T) -> Self {
         return 1;
     }
+}
+
+/// A monoid that tracks the least and the greatest point of a set of point-value pairs, according to the [xyz ordering](Point3d::cmp_xyz).
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Hash)]
+pub enum BoundsMonoid<X, Y, Z>
+where
+    X: Dimension,
+    Y: Dimension,
+    Z: Dimension,
+{
+    /// The bounds of the empty set, this is the neutral element.
+    Empty,
+    /// The least and greatest point of a non-empty set.
+    Bounds {
+        min: Point3d<X, Y, Z>,
+        max: Point3d<X, Y, Z>,
+    },
+}
+
+/// `combine` takes the lesser minimum and the greater maximum.
+impl<X, Y, Z> CommutativeMonoid for BoundsMonoid<X, Y, Z>
+where
+    X: Dimension + Clone,
+    Y: Dimension + Clone,
+    Z: Dimension + Clone,
+{
+    const NEUTRAL: Self = BoundsMonoid::Empty;
 
     fn combine(a: &Self, b: &Self) -> Self {
-        return *a + *b;
+        match (a, b) {
+            (BoundsMonoid::Empty, _) => return b.clone(),
+            (_, BoundsMonoid::Empty) => return a.clone(),
+            (
+                BoundsMonoid::Bounds {
+                    min: min_a,
+                    max: max_a,
+                },
+                BoundsMonoid::Bounds {
+                    min: min_b,
+                    max: max_b,
+                },
+            ) => {
+                let min = if min_a.cmp_xyz(min_b) == Ordering::Greater {
+                    min_b
+                } else {
+                    min_a
+                };
+                let max = if max_a.cmp_xyz(max_b) == Ordering::Less {
+                    max_b
+                } else {
+                    max_a
+                };
+
+                return BoundsMonoid::Bounds {
+                    min: min.clone(),
+                    max: max.clone(),
+                };
+            }
+        }
+    }
+}
+
+/// Any point-value pair is lifted to the bounds consisting only of its point.
+impl<X, Y, Z, V> LiftingCommutativeMonoid<(Point3d<X, Y, Z>, V)> for BoundsMonoid<X, Y, Z>
+where
+    X: Dimension + Clone,
+    Y: Dimension + Clone,
+    Z: Dimension + Clone,
+{
+    fn lift(val: &(Point3d<X, Y, Z>, V)) -> Self {
+        return BoundsMonoid::Bounds {
+            min: val.0.clone(),
+            max: val.0.clone(),
+        };
     }
-}
\ No newline at end of file
+}
+
+/// A monoid that fingerprints the set of points of a set of point-value pairs: the fingerprint is the bitwise xor of a 64 bit hash of every point. Values do not influence the fingerprint.
+///
+/// Equal sets have equal fingerprints, and distinct sets have distinct fingerprints with high probability. The fingerprints are not cryptographically secure: it is easy to construct distinct sets of equal fingerprint on purpose.
+///
+/// The hash of a point is a hash of its [xyz encoding](Point3d::encode_xyz), computed with the same deterministic hash function as [`rank_from_hash`](crate::rank_from_hash).
+#[cfg(feature = "alloc")]
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Hash, Default)]
+pub struct XorFingerprint(pub u64);
+
+/// `combine` is bitwise xor.
+#[cfg(feature = "alloc")]
+impl CommutativeMonoid for XorFingerprint {
+    const NEUTRAL: Self = XorFingerprint(0);
+
+    fn combine(a: &Self, b: &Self) -> Self {
+        return XorFingerprint(a.0 ^ b.0);
+    }
+}
+
+/// Any point-value pair is lifted to the hash of its point.
+#[cfg(feature = "alloc")]
+impl<X, Y, Z, V> LiftingCommutativeMonoid<(Point3d<X, Y, Z>, V)> for XorFingerprint
+where
+    X: Dimension,
+    Y: Dimension,
+    Z: Dimension,
+{
+    fn lift(val: &(Point3d<X, Y, Z>, V)) -> Self {
+        let mut hasher = RankHasher::new();
+        val.0.hash_encoded(&mut hasher, Ordering3d::Xyz);
+        return XorFingerprint(hasher.finish());
+    }
+}
+
+/// Combine the summaries of two *disjoint* ranges into the summary of their union.
+///
+/// This is simply [`combine`](CommutativeMonoid::combine), but spelling it out documents that the summaries may be reused. Combining the summaries of overlapping ranges counts the items of the overlap twice, which yields incorrect results for most monoids (e.g., for counting, or for [`XorFingerprint`]s, where the overlap cancels out).
+pub fn combine_range_summaries<M: CommutativeMonoid>(a: &M, b: &M) -> M {
+    return M::combine(a, b);
+}
+
+/// The summary of the items within the half-open range from `lo` (inclusive) to `hi` (exclusive).
+///
+/// Two range summaries can be [concatenated](Self::concat) if the first range ends where the second begins, to obtain the summary of the union of the ranges without recomputing it.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Hash)]
+pub struct RangeSummary<K, M> {
+    /// The inclusive lower bound of the range.
+    pub lo: K,
+    /// The exclusive upper bound of the range.
+    pub hi: K,
+    /// The summary of the items in the range.
+    pub summary: M,
+}
+
+impl<K: PartialEq, M> RangeSummary<K, M> {
+    /// Combine the summaries of two adjacent ranges into the summary of the range from `self.lo` to `other.hi`.
+    ///
+    /// Return both summaries unchanged if `self.hi != other.lo`. Since the ranges are half-open, adjacent ranges are disjoint, so their summaries can be [combined](combine_range_summaries) safely. If you encode unbounded ranges with a sentinel value, make sure the sentinels for the lower and the upper end differ, or the end of a range that is unbounded above would be adjacent to the start of a range that is unbounded below.
+    pub fn concat(self, other: Self) -> Result<Self, (Self, Self)>
+    where
+        M: CommutativeMonoid,
+    {
+        if self.hi != other.lo {
+            return Err((self, other));
+        }
+
+        return Ok(RangeSummary {
+            lo: self.lo,
+            hi: other.hi,
+            summary: combine_range_summaries(&self.summary, &other.summary),
+        });
+    }
+}
@@ -1,32 +1,164 @@
 use core::cmp::{Ordering, Ordering::*};
+use core::marker::PhantomData;
+#[cfg(feature = "alloc")]
+use core::hash::{Hash, Hasher};
+#[cfg(feature = "alloc")]
+use alloc::{string::String, vec, vec::Vec};
+#[cfg(feature = "std")]
+use std::io::{self, Read, Write};
 
+#[cfg(feature = "arbitrary")]
 use arbitrary::Arbitrary;
 
-// Testing: `fuzz/encoding.rs` contains extensive fuzz tests that check that the `Point3d` encodings are indeed homomorphic. Also contains a utility function for checking whether a type correctly implements `Dimension`. Client code can simply copy-paste that function.
+use crate::DecodeError;
+
+// Testing: `fuzz/encoding.rs` contains extensive fuzz tests that check that the `Point3d` encodings are indeed homomorphic. The utility function for checking whether a type correctly implements `Dimension` lives in the `testing` module, so that client code can use it as well.
 
 /// A type that can be used as a dimension of a [`Point3d`].
 ///
 /// Must be totally ordered, and must provide an order-homomorphic [encoding function](https://willowprotocol.org/specs/encodings/index.html#encoding_function), that is., comparing encodings lexicographically must coincide with the total order on the dimension.
+///
+/// # Empty encodings
+///
+/// Encodings may be empty, for example for the least value of a dimension of strings. The separator after a variable-width dimension is written and checked regardless, so a point whose leading dimensions have empty encodings still [decodes](Point3d::decode_xyz) unambiguously. The [decoding function](Self::homomorphic_decode) then must return a length of zero for the empty encoding even when more bytes follow it, such as the separator. The only caveat concerns reading points from an `io::Read`, see `Point3d::decode_xyz_from_reader`.
 pub trait Dimension: Ord + Sized {
     /// The maximum length of any [homomorphic encoding](Self::homomorphic_encode).
     const HOMOMORPHIC_ENCODING_MAX_LENGTH: usize;
 
-    /// Do the [homomorphic encodings](Self::homomorphic_encode) of all values have the same length? If this is `false`, then no encoding may contain two successive zero bytes (the combined encoding of a `3dPoint` will use two consecutive zero bytes to terminate variable-width encodings, so things will subtly break if the encodings contained consecutive zero bytes themselves).
+    /// Can the [homomorphic encodings](Self::homomorphic_encode) only be used as the last component of an encoding of a [`Point3d`]? This is `false` by default. Variable-width dimensions may set it to `true` to be exempt from the requirement that encodings contain no consecutive zero bytes, and that [decoding](Self::homomorphic_decode) stops before the separator that follows the encoding. Their decoding function may instead consume the whole slice it is given.
+    ///
+    /// Such a dimension can only be the `Z` dimension of points with [xyz encodings](Point3d::encode_xyz) (or `X` for yzx, or `Y` for zxy). Encoding a point in any other ordering panics in debug builds.
+    const MUST_BE_LAST: bool = false;
+
+    /// Do the [homomorphic encodings](Self::homomorphic_encode) of all values have the same length? If this is `false`, then no encoding may contain two successive zero bytes (the combined encoding of a `3dPoint` will use two consecutive zero bytes to terminate variable-width encodings, so things will subtly break if the encodings contained consecutive zero bytes themselves). The only exception are points that are solely encoded with [`encode_xyz_with_separator`](Point3d::encode_xyz_with_separator) and decoded with [`decode_xyz_with_separator`](Point3d::decode_xyz_with_separator) for some other [`Separator`]: then, the encodings must not contain the bytes of that separator instead.
     const IS_FIXED_WIDTH_ENCODING: bool;
 
+    /// Is no [homomorphic encoding](Self::homomorphic_encode) a strict prefix of another one? This is `false` by default, and only matters for variable-width dimensions. Setting it to `true` promises that the encodings are prefix-free, which in particular rules out an [empty encoding](Dimension#empty-encodings) alongside non-empty ones, and that [decoding](Self::homomorphic_decode) a strict prefix of an encoding yields an error rather than a panic.
+    ///
+    /// Reading a point from an `io::Read` requires this of the last dimension of the ordering, unless it is fixed-width, see `Point3d::decode_xyz_from_reader`. The promise cannot be checked at compile time, but `assert_dimension_works` from the `testing` feature checks it for the given values.
+    const IS_PREFIX_FREE: bool = false;
+
     /// Encode `self` into a slice of at least `Self::HOMOMORPHIC_ENCODING_LENGTH` many bytes, and return how long the produced encoding is. The [encoding](https://willowprotocol.org/specs/encodings/index.html#encoding_function) must be order-homomorphic, that is: for any two values `v1` and `v2` with `v1 <= v2`, the encoding of `v1` must be lexicographically less than or equal to the encoding of `v2`. Further, if [`IS_FIXED_WIDTH_ENCODING`](Self::IS_FIXED_WIDTH_ENCODING) is `false`, then no encoding may contain two consecutive zero bytes.
     ///
     /// If the encoding is longer than the given slice, this function must panic.
     fn homomorphic_encode(&self, buf: &mut [u8]) -> usize;
 
-    /// Decode the [homomorphic encoding](Self::homomorphic_encode) from a slice. On success, return the decoded value, and the number of bytes that were decoded.
-    fn homomorphic_decode(buf: &[u8]) -> Result<(Self, usize), ()>;
+    /// Decode the [homomorphic encoding](Self::homomorphic_encode) from a slice. On success, return the decoded value, and the number of bytes that were decoded. On failure, return the [`DecodeError`] that best describes why the slice does not start with an encoding.
+    fn homomorphic_decode(buf: &[u8]) -> Result<(Self, usize), DecodeError>;
+
+    /// Return the length of the [homomorphic encoding](Self::homomorphic_encode) of `self`.
+    ///
+    /// For fixed-width encodings, the default implementation returns [`HOMOMORPHIC_ENCODING_MAX_LENGTH`](Self::HOMOMORPHIC_ENCODING_MAX_LENGTH). Otherwise, it encodes `self` into a scratch buffer. Variable-width dimensions should override it if they can compute the length more cheaply. Without the `alloc` feature, the scratch buffer lives on the stack and holds at most 256 bytes, so using the default implementation for a variable-width dimension with a greater [`HOMOMORPHIC_ENCODING_MAX_LENGTH`](Self::HOMOMORPHIC_ENCODING_MAX_LENGTH) fails to compile.
+    fn encoded_len(&self) -> usize {
+        if Self::IS_FIXED_WIDTH_ENCODING {
+            return Self::HOMOMORPHIC_ENCODING_MAX_LENGTH;
+        }
+
+        #[cfg(feature = "alloc")]
+        {
+            let mut scratch = vec![0; Self::HOMOMORPHIC_ENCODING_MAX_LENGTH];
+            return self.homomorphic_encode(&mut scratch);
+        }
+
+        #[cfg(not(feature = "alloc"))]
+        {
+            const {
+                assert!(
+                    Self::IS_FIXED_WIDTH_ENCODING || Self::HOMOMORPHIC_ENCODING_MAX_LENGTH <= 256,
+                    "the default encoded_len supports at most 256 bytes without the alloc feature"
+                )
+            };
+            let mut scratch = [0; 256];
+            return self.homomorphic_encode(&mut scratch);
+        }
+    }
+}
+
+/// Check the associated constants of a [`Dimension`] for contract violations that are obvious without looking at any values, and return whether there are none. This is usable in const contexts, see [`check_dimension!`](crate::check_dimension!) for a compile-time check.
+///
+/// Currently, this rejects a [`HOMOMORPHIC_ENCODING_MAX_LENGTH`](Dimension::HOMOMORPHIC_ENCODING_MAX_LENGTH) of zero unless the encodings are [fixed-width](Dimension::IS_FIXED_WIDTH_ENCODING). Fixed-width encodings of length zero are fine for dimensions with a single value, such as `()`. A variable-width dimension whose encodings can only be empty makes no sense, and more likely states its maximum length incorrectly.
+pub const fn dimension_const_sanity<D: Dimension>() -> bool {
+    return D::HOMOMORPHIC_ENCODING_MAX_LENGTH > 0 || D::IS_FIXED_WIDTH_ENCODING;
+}
+
+/// Fail compilation if the given [`Dimension`] does not pass [`dimension_const_sanity`]. Place it next to the implementation of the trait.
+///
+/// ```
+/// # use kv_3d_storage::check_dimension;
+/// check_dimension!(u16);
+/// check_dimension!(());
+/// ```
+#[macro_export]
+macro_rules! check_dimension {
+    ($t:ty) => {
+        const _: () = assert!(
+            $crate::dimension_const_sanity::<$t>(),
+            "dimension_const_sanity failed: a variable-width Dimension must have a positive HOMOMORPHIC_ENCODING_MAX_LENGTH"
+        );
+    };
+}
+
+/// A [`Dimension`] with a least and a greatest value.
+///
+/// The [encoding](Dimension::homomorphic_encode) of [`MIN`](Self::MIN) must be lexicographically less than or equal to all other encodings, and the encoding of [`MAX`](Self::MAX) must be greater than or equal to all other encodings. This follows from order-homomorphism, so it holds automatically for correct implementations of [`Dimension`].
+pub trait BoundedDimension: Dimension {
+    /// The least value of the dimension.
+    const MIN: Self;
+
+    /// The greatest value of the dimension.
+    const MAX: Self;
+}
+
+/// A [`BoundedDimension`] in which every value but the greatest one has an immediate successor.
+///
+/// The kv-tree range code works with half-open ranges and uses none of the successor functions. To turn an inclusive upper bound into an exclusive one, use the canonical [`successor`](Self::successor): `None` then stands for a range without upper bound, as in [`tree_key_bounds`](crate::tree_key_bounds). Algorithms that need a value in any case can use [`successor_saturating`] or [`successor_wrapping`].
+pub trait DiscreteDimension: BoundedDimension {
+    /// Return the least value that is greater than `self`, or `None` if `self` is [`MAX`](BoundedDimension::MAX).
+    fn successor(&self) -> Option<Self>;
+}
+
+/// Return the [successor](DiscreteDimension::successor) of a value, or [`MAX`](BoundedDimension::MAX) if the value is the greatest one already.
+pub fn successor_saturating<D: DiscreteDimension>(d: &D) -> D {
+    return d.successor().unwrap_or(D::MAX);
+}
+
+/// Return the [successor](DiscreteDimension::successor) of a value, or [`MIN`](BoundedDimension::MIN) if the value is the greatest one.
+pub fn successor_wrapping<D: DiscreteDimension>(d: &D) -> D {
+    return d.successor().unwrap_or(D::MIN);
+}
+
+/// The two bytes that the encodings of a [`Point3d`] place after every variable-width dimension (except for the last one), see [`encode_xyz_with_separator`](Point3d::encode_xyz_with_separator).
+///
+/// The default is the [`ZeroSeparator`]. Other separators let dimensions whose encodings naturally contain two consecutive zero bytes forbid a different pair of bytes instead. For the encodings of points to stay order-homomorphic, the separator must be less than any two bytes by which an encoding of a variable-width dimension can continue to form another encoding; if no encoding is a strict prefix of another one, any separator works.
+///
+/// Separators only apply to [`encode_xyz_with_separator`](Point3d::encode_xyz_with_separator) and [`decode_xyz_with_separator`](Point3d::decode_xyz_with_separator). Everything else always uses the [`ZeroSeparator`]: the yzx and zxy encodings, the [`EncodedPointXyz`] view when it locates the dimensions of an encoding, and the functions that write points to an `io::Write` or read them from an `io::Read`.
+pub trait Separator {
+    /// The bytes of the separator.
+    const BYTES: [u8; 2];
+}
+
+/// The default [`Separator`]: two zero bytes, which are less than any continuation of an encoding. This is the separator of [`encode_xyz`](Point3d::encode_xyz) and all other encoding functions that do not take a separator.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Hash, Default)]
+pub struct ZeroSeparator;
+
+impl Separator for ZeroSeparator {
+    const BYTES: [u8; 2] = [0, 0];
+}
+
+/// One of the three total orderings on [`Point3d`]s: [xyz](Point3d::cmp_xyz), [yzx](Point3d::cmp_yzx), or [zxy](Point3d::cmp_zxy).
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Hash)]
+#[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
+pub enum Ordering3d {
+    Xyz,
+    Yzx,
+    Zxy,
 }
 
-/// A point in a 3d space. Note that this struct does *not* implement `Ord`. Instead it provides three functions for three possible choices of total orderings: [`cmp_xyz`](Self::cmp_xyz), [`cmp_yzx`](Self::cmp_yzx), and [`cmp_zxy`](Self::cmp_zxy). This is to make sure that any comparisons explicitly select an ordering.
+/// A point in a 3d space. Note that this struct does *not* implement `Ord`. Instead it provides three functions for three possible choices of total orderings: [`cmp_xyz`](Self::cmp_xyz), [`cmp_yzx`](Self::cmp_yzx), and [`cmp_zxy`](Self::cmp_zxy). This is to make sure that any comparisons explicitly select an ordering. To use points with APIs that require `Ord`, wrap them in [`Xyz`], [`Yzx`], or [`Zxy`].
 ///
 /// The three dimensions have types `X`, `Y`, and `Z`.
-#[derive(PartialEq, Eq, Clone, Copy, Debug, Hash, Arbitrary)]
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Hash)]
+#[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
 pub struct Point3d<X, Y, Z>
 where
     X: Dimension,
@@ -38,7 +170,121 @@ where
     pub z: Z,
 }
 
+/// Points are displayed as the tuple of their coordinates, `(x, y, z)`.
+impl<X, Y, Z> core::fmt::Display for Point3d<X, Y, Z>
+where
+    X: Dimension + core::fmt::Display,
+    Y: Dimension + core::fmt::Display,
+    Z: Dimension + core::fmt::Display,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        return write!(f, "({}, {}, {})", self.x, self.y, self.z);
+    }
+}
+
+impl<X: Dimension, Y: Dimension, Z: Dimension> From<(X, Y, Z)> for Point3d<X, Y, Z> {
+    /// Create a point from a tuple of its x, y, and z coordinates.
+    ///
+    /// ```
+    /// # use kv_3d_storage::Point3d;
+    /// let point = Point3d::from((1u32, 2u32, 3u32));
+    /// assert_eq!(point, Point3d { x: 1, y: 2, z: 3 });
+    ///
+    /// let (x, y, z) = point.into();
+    /// assert_eq!((x, y, z), (1, 2, 3));
+    /// ```
+    fn from((x, y, z): (X, Y, Z)) -> Self {
+        return Point3d { x, y, z };
+    }
+}
+
+impl<X: Dimension, Y: Dimension, Z: Dimension> From<Point3d<X, Y, Z>> for (X, Y, Z) {
+    /// Turn a point into a tuple of its x, y, and z coordinates.
+    fn from(point: Point3d<X, Y, Z>) -> Self {
+        return (point.x, point.y, point.z);
+    }
+}
+
+impl<X: BoundedDimension, Y: BoundedDimension, Z: BoundedDimension> Point3d<X, Y, Z> {
+    /// The point whose coordinates are the [least values](BoundedDimension::MIN) of their dimensions. It is the least point in all three orderings, so its encodings are the least ones, making it a substitute for `Bound::Unbounded` in range scans.
+    pub fn min() -> Self {
+        return Point3d {
+            x: X::MIN,
+            y: Y::MIN,
+            z: Z::MIN,
+        };
+    }
+
+    /// The point whose coordinates are the [greatest values](BoundedDimension::MAX) of their dimensions. It is the greatest point in all three orderings, so its encodings are the greatest ones, making it a substitute for `Bound::Unbounded` in range scans.
+    pub fn max() -> Self {
+        return Point3d {
+            x: X::MAX,
+            y: Y::MAX,
+            z: Z::MAX,
+        };
+    }
+}
+
 impl<X: Dimension, Y: Dimension, Z: Dimension> Point3d<X, Y, Z> {
+    /// Whether all three dimensions have [fixed-width encodings](Dimension::IS_FIXED_WIDTH_ENCODING). If so, all encodings of all points have the same length, and they contain no separators.
+    pub const IS_FIXED_WIDTH_ENCODING: bool =
+        X::IS_FIXED_WIDTH_ENCODING && Y::IS_FIXED_WIDTH_ENCODING && Z::IS_FIXED_WIDTH_ENCODING;
+
+    // The maximum encoding lengths are associated constants, so that an overflowing sum is a compile-time error rather than a silent wrap-around.
+    const MAX_ENCODING_LEN_XYZ: usize = max_encoding_len::<X, Y, Z>();
+    const MAX_ENCODING_LEN_YZX: usize = max_encoding_len::<Y, Z, X>();
+    const MAX_ENCODING_LEN_ZXY: usize = max_encoding_len::<Z, X, Y>();
+    const MAX_ENCODING_LEN_XYZ_LENGTH_PREFIXED: usize = checked_sum(
+        &[varint_len(Self::MAX_ENCODING_LEN_XYZ), Self::MAX_ENCODING_LEN_XYZ],
+    );
+    const MAX_ENCODING_LEN_XYZ_VERSIONED: usize = checked_sum(&[1, Self::MAX_ENCODING_LEN_XYZ]);
+
+    /// Return references to the x, y, and z coordinates as a tuple.
+    pub fn as_tuple(&self) -> (&X, &Y, &Z) {
+        return (&self.x, &self.y, &self.z);
+    }
+
+    /// Transform the x coordinate, possibly into a different dimension type, keeping the y and z coordinates.
+    pub fn map_x<X2: Dimension>(self, f: impl FnOnce(X) -> X2) -> Point3d<X2, Y, Z> {
+        return Point3d {
+            x: f(self.x),
+            y: self.y,
+            z: self.z,
+        };
+    }
+
+    /// Transform the y coordinate, possibly into a different dimension type, keeping the x and z coordinates.
+    pub fn map_y<Y2: Dimension>(self, f: impl FnOnce(Y) -> Y2) -> Point3d<X, Y2, Z> {
+        return Point3d {
+            x: self.x,
+            y: f(self.y),
+            z: self.z,
+        };
+    }
+
+    /// Transform the z coordinate, possibly into a different dimension type, keeping the x and y coordinates.
+    pub fn map_z<Z2: Dimension>(self, f: impl FnOnce(Z) -> Z2) -> Point3d<X, Y, Z2> {
+        return Point3d {
+            x: self.x,
+            y: self.y,
+            z: f(self.z),
+        };
+    }
+
+    /// Transform all three coordinates, possibly into different dimension types. Useful for adapting points from one schema to another.
+    pub fn map_all<X2: Dimension, Y2: Dimension, Z2: Dimension>(
+        self,
+        fx: impl FnOnce(X) -> X2,
+        fy: impl FnOnce(Y) -> Y2,
+        fz: impl FnOnce(Z) -> Z2,
+    ) -> Point3d<X2, Y2, Z2> {
+        return Point3d {
+            x: fx(self.x),
+            y: fy(self.y),
+            z: fz(self.z),
+        };
+    }
+
     /// Compare by x dimension first, using the y dimension as a tiebreaker, and using the z dimension as the final tiebreaker.
     pub fn cmp_xyz(&self, other: &Self) -> Ordering {
         let x_cmp = self.x.cmp(&other.x);
@@ -84,77 +330,316 @@ impl<X: Dimension, Y: Dimension, Z: Dimension> Point3d<X, Y, Z> {
         }
     }
 
+    /// Compare according to the given [`Ordering3d`].
+    pub fn cmp_by(&self, other: &Self, ordering: Ordering3d) -> Ordering {
+        match ordering {
+            Ordering3d::Xyz => return self.cmp_xyz(other),
+            Ordering3d::Yzx => return self.cmp_yzx(other),
+            Ordering3d::Zxy => return self.cmp_zxy(other),
+        }
+    }
+
+    /// Return whether both points agree in their y and z coordinates, regardless of their x coordinates.
+    pub fn eq_ignoring_x(&self, other: &Self) -> bool {
+        return self.y == other.y && self.z == other.z;
+    }
+
+    /// Return whether both points agree in their x and z coordinates, regardless of their y coordinates.
+    pub fn eq_ignoring_y(&self, other: &Self) -> bool {
+        return self.x == other.x && self.z == other.z;
+    }
+
+    /// Return whether both points agree in their x and y coordinates, regardless of their z coordinates.
+    pub fn eq_ignoring_z(&self, other: &Self) -> bool {
+        return self.x == other.x && self.y == other.y;
+    }
+
+    /// Compare by the [xyz encodings](Self::encode_xyz) of the two points, lexicographically as bytestrings. Since the encoding is homomorphic, this always agrees with [`cmp_xyz`](Self::cmp_xyz); it is a debugging aid for checking exactly that.
+    ///
+    /// Encodes into two stack buffers of [`CMP_VIA_ENCODING_BUFFER_LEN`] bytes, without allocating. Fails to compile if [`max_encoding_len_xyz`](Self::max_encoding_len_xyz) exceeds that length.
+    pub fn cmp_xyz_via_encoding(&self, other: &Self) -> Ordering {
+        const { assert!(Self::MAX_ENCODING_LEN_XYZ <= CMP_VIA_ENCODING_BUFFER_LEN) };
+
+        let mut buf_self = [0; CMP_VIA_ENCODING_BUFFER_LEN];
+        let mut buf_other = [0; CMP_VIA_ENCODING_BUFFER_LEN];
+        let len_self = self.encode_xyz(&mut buf_self);
+        let len_other = other.encode_xyz(&mut buf_other);
+
+        return buf_self[..len_self].cmp(&buf_other[..len_other]);
+    }
+
+    /// Restrict `self` to the closed interval `[lo, hi]` according to the [xyz ordering](Self::cmp_xyz): return a clone of `lo` if `self` is less than `lo`, a clone of `hi` if `self` is greater than `hi`, and `self` otherwise.
+    ///
+    /// Panics if `lo` is greater than `hi`.
+    pub fn clamp_xyz(self, lo: &Self, hi: &Self) -> Self
+    where
+        Self: Clone,
+    {
+        assert!(
+            lo.cmp_xyz(hi) != Greater,
+            "clamp_xyz requires lo to be less than or equal to hi"
+        );
+
+        if self.cmp_xyz(lo) == Less {
+            return lo.clone();
+        } else if self.cmp_xyz(hi) == Greater {
+            return hi.clone();
+        } else {
+            return self;
+        }
+    }
+
+    /// Restrict `self` to the closed interval `[lo, hi]` according to the [yzx ordering](Self::cmp_yzx): return a clone of `lo` if `self` is less than `lo`, a clone of `hi` if `self` is greater than `hi`, and `self` otherwise.
+    ///
+    /// Panics if `lo` is greater than `hi`.
+    pub fn clamp_yzx(self, lo: &Self, hi: &Self) -> Self
+    where
+        Self: Clone,
+    {
+        assert!(
+            lo.cmp_yzx(hi) != Greater,
+            "clamp_yzx requires lo to be less than or equal to hi"
+        );
+
+        if self.cmp_yzx(lo) == Less {
+            return lo.clone();
+        } else if self.cmp_yzx(hi) == Greater {
+            return hi.clone();
+        } else {
+            return self;
+        }
+    }
+
+    /// Restrict `self` to the closed interval `[lo, hi]` according to the [zxy ordering](Self::cmp_zxy): return a clone of `lo` if `self` is less than `lo`, a clone of `hi` if `self` is greater than `hi`, and `self` otherwise.
+    ///
+    /// Panics if `lo` is greater than `hi`.
+    pub fn clamp_zxy(self, lo: &Self, hi: &Self) -> Self
+    where
+        Self: Clone,
+    {
+        assert!(
+            lo.cmp_zxy(hi) != Greater,
+            "clamp_zxy requires lo to be less than or equal to hi"
+        );
+
+        if self.cmp_zxy(lo) == Less {
+            return lo.clone();
+        } else if self.cmp_zxy(hi) == Greater {
+            return hi.clone();
+        } else {
+            return self;
+        }
+    }
+
+    /// Return whether `self` lies within the half-open interval `[lo, hi_exclusive)` according to the [xyz ordering](Self::cmp_xyz). Returns `false` if `lo` is greater than or equal to `hi_exclusive`, since the interval is empty then.
+    pub fn in_range_xyz(&self, lo: &Self, hi_exclusive: &Self) -> bool {
+        return self.cmp_xyz(lo) != Less && self.cmp_xyz(hi_exclusive) == Less;
+    }
+
+    /// Return whether `self` lies within the half-open interval `[lo, hi_exclusive)` according to the [yzx ordering](Self::cmp_yzx). Returns `false` if `lo` is greater than or equal to `hi_exclusive`, since the interval is empty then.
+    pub fn in_range_yzx(&self, lo: &Self, hi_exclusive: &Self) -> bool {
+        return self.cmp_yzx(lo) != Less && self.cmp_yzx(hi_exclusive) == Less;
+    }
+
+    /// Return whether `self` lies within the half-open interval `[lo, hi_exclusive)` according to the [zxy ordering](Self::cmp_zxy). Returns `false` if `lo` is greater than or equal to `hi_exclusive`, since the interval is empty then.
+    pub fn in_range_zxy(&self, lo: &Self, hi_exclusive: &Self) -> bool {
+        return self.cmp_zxy(lo) != Less && self.cmp_zxy(hi_exclusive) == Less;
+    }
+
+    /// Return whether `self` lies within the half-open interval `[lo, hi_exclusive)` according to the given [`Ordering3d`].
+    pub fn in_range_by(&self, lo: &Self, hi_exclusive: &Self, ordering: Ordering3d) -> bool {
+        match ordering {
+            Ordering3d::Xyz => return self.in_range_xyz(lo, hi_exclusive),
+            Ordering3d::Yzx => return self.in_range_yzx(lo, hi_exclusive),
+            Ordering3d::Zxy => return self.in_range_zxy(lo, hi_exclusive),
+        }
+    }
+
     /// Return the maximum length of any [xyz-encoding](Self::encode_xyz).
+    ///
+    /// Fails to compile if the maximum length does not fit into a `usize`.
     pub const fn max_encoding_len_xyz() -> usize {
-        return X::HOMOMORPHIC_ENCODING_MAX_LENGTH
+        return Self::MAX_ENCODING_LEN_XYZ;
+    }
+
+    /// Return the length of the [xyz-encoding](Self::encode_xyz) of `self`, without encoding it. Useful for allocating buffers of exactly the right size.
+    pub fn encoded_len_xyz(&self) -> usize {
+        return self.x.encoded_len()
             + if X::IS_FIXED_WIDTH_ENCODING { 0 } else { 2 }
-            + Y::HOMOMORPHIC_ENCODING_MAX_LENGTH
+            + self.y.encoded_len()
             + if Y::IS_FIXED_WIDTH_ENCODING { 0 } else { 2 }
-            + Z::HOMOMORPHIC_ENCODING_MAX_LENGTH;
+            + self.z.encoded_len();
     }
 
     /// Encode a [`Point3d`](Self) with an encoding that is homomorphic to the [xyz ordering](Self::cmp_xyz), and return how long the produced encoding is.
     ///
     /// Panic if the encoding is longer than the given slice. To prevent this, ensure the slice has a length of at least [`max_encoding_len_xyz`](Self::max_encoding_len_xyz).
     pub fn encode_xyz(&self, buf: &mut [u8]) -> usize {
+        return self.encode_xyz_with_separator::<ZeroSeparator>(buf);
+    }
+
+    /// Encode a [`Point3d`](Self) like [`encode_xyz`](Self::encode_xyz), but place the bytes of the [`Separator`] `S` after variable-width dimensions instead of two zero bytes. The encoding has the same length either way, so [`max_encoding_len_xyz`](Self::max_encoding_len_xyz) and [`encoded_len_xyz`](Self::encoded_len_xyz) apply as well.
+    ///
+    /// Panic if the encoding is longer than the given slice.
+    pub fn encode_xyz_with_separator<S: Separator>(&self, buf: &mut [u8]) -> usize {
         let mut len = 0;
 
-        len += self.x.homomorphic_encode(&mut buf[len..]);
+        len += encode_checked(&self.x, &mut buf[len..], "x");
         if !X::IS_FIXED_WIDTH_ENCODING {
-            buf[len] = 0;
-            buf[len + 1] = 0;
+            debug_assert_not_last_only::<X>();
+            buf[len..len + 2].copy_from_slice(&S::BYTES);
             len += 2;
         }
 
-        len += self.y.homomorphic_encode(&mut buf[len..]);
+        len += encode_checked(&self.y, &mut buf[len..], "y");
         if !Y::IS_FIXED_WIDTH_ENCODING {
-            buf[len] = 0;
-            buf[len + 1] = 0;
+            debug_assert_not_last_only::<Y>();
+            buf[len..len + 2].copy_from_slice(&S::BYTES);
             len += 2;
         }
 
-        len += self.z.homomorphic_encode(&mut buf[len..]);
+        len += encode_checked(&self.z, &mut buf[len..], "z");
+
+        return len;
+    }
+
+    /// Encode a [`Point3d`](Self) whose dimensions are all fixed-width with the [xyz encoding](Self::encode_xyz), skipping all separator handling. Returns the length of the encoding, which is always [`max_encoding_len_xyz`](Self::max_encoding_len_xyz).
+    ///
+    /// Fails to compile unless [`IS_FIXED_WIDTH_ENCODING`](Self::IS_FIXED_WIDTH_ENCODING) is `true`. Panics if the given slice is shorter than the encoding.
+    pub fn encode_xyz_fixed(&self, buf: &mut [u8]) -> usize {
+        const { assert!(Self::IS_FIXED_WIDTH_ENCODING) };
+
+        let len = Self::max_encoding_len_xyz();
+        let (buf_x, rest) = buf[..len].split_at_mut(X::HOMOMORPHIC_ENCODING_MAX_LENGTH);
+        let (buf_y, buf_z) = rest.split_at_mut(Y::HOMOMORPHIC_ENCODING_MAX_LENGTH);
+
+        encode_checked(&self.x, buf_x, "x");
+        encode_checked(&self.y, buf_y, "y");
+        encode_checked(&self.z, buf_z, "z");
 
         return len;
     }
 
     /// Decode the [xyz encoding](Self::encode_xyz) from a slice. On success, return the decoded value, and the number of bytes that were decoded.
-    pub fn decode_xyz(buf: &[u8]) -> Result<(Self, usize), ()> {
+    pub fn decode_xyz(buf: &[u8]) -> Result<(Self, usize), DecodeError> {
+        return Self::decode_xyz_with_separator::<ZeroSeparator>(buf);
+    }
+
+    /// Decode an encoding produced by [`encode_xyz_with_separator`](Self::encode_xyz_with_separator) with the same [`Separator`] `S`. On success, return the decoded value, and the number of bytes that were decoded. Any other bytes where a separator belongs yield [`DecodeError::BadSeparator`].
+    pub fn decode_xyz_with_separator<S: Separator>(buf: &[u8]) -> Result<(Self, usize), DecodeError> {
         let mut offset = 0;
 
-        let (x, x_len) = X::homomorphic_decode(&buf[offset..])?;
+        let (x, x_len) = X::homomorphic_decode(buf.get(offset..).ok_or(DecodeError::UnexpectedEof)?)?;
         offset += x_len;
         if !X::IS_FIXED_WIDTH_ENCODING {
-            if buf[offset] != 0 || buf[offset + 1] != 0 {
-                return Err(());
-            } else {
-                offset += 2;
-            }
+            offset += check_separator::<S>(buf, offset)?;
         }
 
-        let (y, y_len) = Y::homomorphic_decode(&buf[offset..])?;
+        let (y, y_len) = Y::homomorphic_decode(buf.get(offset..).ok_or(DecodeError::UnexpectedEof)?)?;
         offset += y_len;
         if !Y::IS_FIXED_WIDTH_ENCODING {
-            if buf[offset] != 0 || buf[offset + 1] != 0 {
-                return Err(());
-            } else {
-                offset += 2;
-            }
+            offset += check_separator::<S>(buf, offset)?;
         }
 
-        let (z, z_len) = Z::homomorphic_decode(&buf[offset..])?;
+        let (z, z_len) = Z::homomorphic_decode(buf.get(offset..).ok_or(DecodeError::UnexpectedEof)?)?;
         offset += z_len;
 
         return Ok((Point3d { x, y, z }, offset));
     }
 
+    /// Decode the [xyz encoding](Self::encode_xyz) from a slice that must contain exactly the encoding and nothing else. Unlike [`decode_xyz`](Self::decode_xyz), this rejects buffers with trailing bytes.
+    pub fn decode_xyz_exact(buf: &[u8]) -> Result<Self, DecodeError> {
+        let (point, len) = Self::decode_xyz(buf)?;
+
+        if len == buf.len() {
+            return Ok(point);
+        } else {
+            return Err(DecodeError::TrailingBytes);
+        }
+    }
+
+    /// Return the maximum length of any [length-prefixed xyz-encoding](Self::encode_xyz_length_prefixed).
+    ///
+    /// Fails to compile if the maximum length does not fit into a `usize`.
+    pub const fn max_encoding_len_xyz_length_prefixed() -> usize {
+        return Self::MAX_ENCODING_LEN_XYZ_LENGTH_PREFIXED;
+    }
+
+    /// Encode a [`Point3d`](Self) with the [xyz encoding](Self::encode_xyz), preceded by the length of that encoding as a varint, and return how long the produced encoding is (including the prefix).
+    ///
+    /// Unlike the plain xyz encoding, the length-prefixed encoding is self-delimiting, so several of them can be concatenated and decoded again with [`decode_xyz_length_prefixed`](Self::decode_xyz_length_prefixed). It is *not* homomorphic to the xyz ordering.
+    ///
+    /// The varint stores the length in groups of seven bits, least significant group first, with the most significant bit of each byte indicating whether more bytes follow.
+    ///
+    /// Panic if the encoding is longer than the given slice. To prevent this, ensure the slice has a length of at least [`max_encoding_len_xyz_length_prefixed`](Self::max_encoding_len_xyz_length_prefixed).
+    pub fn encode_xyz_length_prefixed(&self, buf: &mut [u8]) -> usize {
+        let prefix_len = encode_varint(self.encoded_len_xyz(), buf);
+        return prefix_len + self.encode_xyz(&mut buf[prefix_len..]);
+    }
+
+    /// Decode a [length-prefixed xyz encoding](Self::encode_xyz_length_prefixed) from the start of a slice. On success, return the decoded value, and the number of bytes that were decoded (including the prefix).
+    ///
+    /// Fails if the bytes indicated by the prefix are not exactly the xyz encoding of a point.
+    pub fn decode_xyz_length_prefixed(buf: &[u8]) -> Result<(Self, usize), DecodeError> {
+        let (len, prefix_len) = decode_varint(buf)?;
+
+        if len > Self::max_encoding_len_xyz() {
+            return Err(DecodeError::InvalidValue);
+        } else if buf.len() - prefix_len < len {
+            return Err(DecodeError::UnexpectedEof);
+        }
+
+        let point = Self::decode_xyz_exact(&buf[prefix_len..prefix_len + len])?;
+        return Ok((point, prefix_len + len));
+    }
+
+    /// Return the maximum length of any [versioned xyz-encoding](Self::encode_xyz_versioned).
+    ///
+    /// Fails to compile if the maximum length does not fit into a `usize`.
+    pub const fn max_encoding_len_xyz_versioned() -> usize {
+        return Self::MAX_ENCODING_LEN_XYZ_VERSIONED;
+    }
+
+    /// Encode a [`Point3d`](Self) with the [xyz encoding](Self::encode_xyz), preceded by a single version byte, and return how long the produced encoding is (including the version byte). Pass [`POINT_ENCODING_VERSION`], the version of the encodings of this crate, as the version: [`decode_xyz_versioned`](Self::decode_xyz_versioned) rejects all other versions.
+    ///
+    /// Tagging stored encodings with a version allows detecting encodings of a different format (e.g., written by a future version of this crate) with [`decode_xyz_versioned`](Self::decode_xyz_versioned), instead of silently misinterpreting them. Since all encodings of the same version start with the same byte, the versioned encoding is still homomorphic to the xyz ordering.
+    ///
+    /// Panic if the encoding is longer than the given slice. To prevent this, ensure the slice has a length of at least [`max_encoding_len_xyz_versioned`](Self::max_encoding_len_xyz_versioned).
+    pub fn encode_xyz_versioned(&self, version: u8, buf: &mut [u8]) -> usize {
+        buf[0] = version;
+        return 1 + self.encode_xyz(&mut buf[1..]);
+    }
+
+    /// Decode a [versioned xyz encoding](Self::encode_xyz_versioned) from the start of a slice. On success, return the decoded value, and the number of bytes that were decoded (including the version byte).
+    ///
+    /// Fails with [`UnsupportedVersion`](VersionedDecodeError::UnsupportedVersion) if the version byte is not [`POINT_ENCODING_VERSION`], without looking at the remaining bytes.
+    pub fn decode_xyz_versioned(buf: &[u8]) -> Result<(Self, usize), VersionedDecodeError> {
+        let (version, encoding) = buf
+            .split_first()
+            .ok_or(VersionedDecodeError::Invalid(DecodeError::UnexpectedEof))?;
+
+        if *version != POINT_ENCODING_VERSION {
+            return Err(VersionedDecodeError::UnsupportedVersion(*version));
+        }
+
+        let (point, len) = Self::decode_xyz(encoding).map_err(VersionedDecodeError::Invalid)?;
+        return Ok((point, 1 + len));
+    }
+
     /// Return the maximum length of any [yzx-encoding](Self::encode_xyz).
+    ///
+    /// Fails to compile if the maximum length does not fit into a `usize`.
     pub const fn max_encoding_len_yzx() -> usize {
-        return Y::HOMOMORPHIC_ENCODING_MAX_LENGTH
+        return Self::MAX_ENCODING_LEN_YZX;
+    }
+
+    /// Return the length of the [yzx-encoding](Self::encode_yzx) of `self`, without encoding it. Useful for allocating buffers of exactly the right size.
+    pub fn encoded_len_yzx(&self) -> usize {
+        return self.y.encoded_len()
             + if Y::IS_FIXED_WIDTH_ENCODING { 0 } else { 2 }
-            + Z::HOMOMORPHIC_ENCODING_MAX_LENGTH
+            + self.z.encoded_len()
             + if Z::IS_FIXED_WIDTH_ENCODING { 0 } else { 2 }
-            + X::HOMOMORPHIC_ENCODING_MAX_LENGTH;
+            + self.x.encoded_len();
     }
 
     /// Encode a [`Point3d`](Self) with an encoding that is homomorphic to the [yzx ordering](Self::cmp_yzx), and return how long the produced encoding is.
@@ -163,62 +648,91 @@ impl<X: Dimension, Y: Dimension, Z: Dimension> Point3d<X, Y, Z> {
     pub fn encode_yzx(&self, buf: &mut [u8]) -> usize {
         let mut len = 0;
 
-        len += self.y.homomorphic_encode(&mut buf[len..]);
+        len += encode_checked(&self.y, &mut buf[len..], "y");
         if !Y::IS_FIXED_WIDTH_ENCODING {
+            debug_assert_not_last_only::<Y>();
             buf[len] = 0;
             buf[len + 1] = 0;
             len += 2;
         }
 
-        len += self.z.homomorphic_encode(&mut buf[len..]);
+        len += encode_checked(&self.z, &mut buf[len..], "z");
         if !Z::IS_FIXED_WIDTH_ENCODING {
+            debug_assert_not_last_only::<Z>();
             buf[len] = 0;
             buf[len + 1] = 0;
             len += 2;
         }
 
-        len += self.x.homomorphic_encode(&mut buf[len..]);
+        len += encode_checked(&self.x, &mut buf[len..], "x");
+
+        return len;
+    }
+
+    /// Encode a [`Point3d`](Self) whose dimensions are all fixed-width with the [yzx encoding](Self::encode_yzx), skipping all separator handling. Returns the length of the encoding, which is always [`max_encoding_len_yzx`](Self::max_encoding_len_yzx).
+    ///
+    /// Fails to compile unless [`IS_FIXED_WIDTH_ENCODING`](Self::IS_FIXED_WIDTH_ENCODING) is `true`. Panics if the given slice is shorter than the encoding.
+    pub fn encode_yzx_fixed(&self, buf: &mut [u8]) -> usize {
+        const { assert!(Self::IS_FIXED_WIDTH_ENCODING) };
+
+        let len = Self::max_encoding_len_yzx();
+        let (buf_y, rest) = buf[..len].split_at_mut(Y::HOMOMORPHIC_ENCODING_MAX_LENGTH);
+        let (buf_z, buf_x) = rest.split_at_mut(Z::HOMOMORPHIC_ENCODING_MAX_LENGTH);
+
+        encode_checked(&self.y, buf_y, "y");
+        encode_checked(&self.z, buf_z, "z");
+        encode_checked(&self.x, buf_x, "x");
 
         return len;
     }
 
     /// Decode the [yzx encoding](Self::encode_yzx) from a slice. On success, return the decoded value, and the number of bytes that were decoded.
-    pub fn decode_yzx(buf: &[u8]) -> Result<(Self, usize), ()> {
+    pub fn decode_yzx(buf: &[u8]) -> Result<(Self, usize), DecodeError> {
         let mut offset = 0;
 
-        let (y, y_len) = Y::homomorphic_decode(&buf[offset..])?;
+        let (y, y_len) = Y::homomorphic_decode(buf.get(offset..).ok_or(DecodeError::UnexpectedEof)?)?;
         offset += y_len;
         if !Y::IS_FIXED_WIDTH_ENCODING {
-            if buf[offset] != 0 || buf[offset + 1] != 0 {
-                return Err(());
-            } else {
-                offset += 2;
-            }
+            offset += check_separator::<ZeroSeparator>(buf, offset)?;
         }
 
-        let (z, z_len) = Z::homomorphic_decode(&buf[offset..])?;
+        let (z, z_len) = Z::homomorphic_decode(buf.get(offset..).ok_or(DecodeError::UnexpectedEof)?)?;
         offset += z_len;
         if !Z::IS_FIXED_WIDTH_ENCODING {
-            if buf[offset] != 0 || buf[offset + 1] != 0 {
-                return Err(());
-            } else {
-                offset += 2;
-            }
+            offset += check_separator::<ZeroSeparator>(buf, offset)?;
         }
 
-        let (x, x_len) = X::homomorphic_decode(&buf[offset..])?;
+        let (x, x_len) = X::homomorphic_decode(buf.get(offset..).ok_or(DecodeError::UnexpectedEof)?)?;
         offset += x_len;
 
         return Ok((Point3d { x, y, z }, offset));
     }
 
+    /// Decode the [yzx encoding](Self::encode_yzx) from a slice that must contain exactly the encoding and nothing else. Unlike [`decode_yzx`](Self::decode_yzx), this rejects buffers with trailing bytes.
+    pub fn decode_yzx_exact(buf: &[u8]) -> Result<Self, DecodeError> {
+        let (point, len) = Self::decode_yzx(buf)?;
+
+        if len == buf.len() {
+            return Ok(point);
+        } else {
+            return Err(DecodeError::TrailingBytes);
+        }
+    }
+
     /// Return the maximum length of any [xyz-encoding](Self::encode_xyz).
+    ///
+    /// Fails to compile if the maximum length does not fit into a `usize`.
     pub const fn max_encoding_len_zxy() -> usize {
-        return Z::HOMOMORPHIC_ENCODING_MAX_LENGTH
+        return Self::MAX_ENCODING_LEN_ZXY;
+    }
+
+    /// Return the length of the [zxy-encoding](Self::encode_zxy) of `self`, without encoding it. Useful for allocating buffers of exactly the right size.
+    pub fn encoded_len_zxy(&self) -> usize {
+        return self.z.encoded_len()
             + if Z::IS_FIXED_WIDTH_ENCODING { 0 } else { 2 }
-            + X::HOMOMORPHIC_ENCODING_MAX_LENGTH
+            + self.x.encoded_len()
             + if X::IS_FIXED_WIDTH_ENCODING { 0 } else { 2 }
-            + Y::HOMOMORPHIC_ENCODING_MAX_LENGTH;
+            + self.y.encoded_len();
     }
 
     /// Encode a [`Point3d`](Self) with an encoding that is homomorphic to the [zxy ordering](Self::cmp_zxy), and return how long the produced encoding is.
@@ -227,52 +741,747 @@ impl<X: Dimension, Y: Dimension, Z: Dimension> Point3d<X, Y, Z> {
     pub fn encode_zxy(&self, buf: &mut [u8]) -> usize {
         let mut len = 0;
 
-        len += self.z.homomorphic_encode(&mut buf[len..]);
+        len += encode_checked(&self.z, &mut buf[len..], "z");
         if !Z::IS_FIXED_WIDTH_ENCODING {
+            debug_assert_not_last_only::<Z>();
             buf[len] = 0;
             buf[len + 1] = 0;
             len += 2;
         }
 
-        len += self.x.homomorphic_encode(&mut buf[len..]);
+        len += encode_checked(&self.x, &mut buf[len..], "x");
         if !X::IS_FIXED_WIDTH_ENCODING {
+            debug_assert_not_last_only::<X>();
             buf[len] = 0;
             buf[len + 1] = 0;
             len += 2;
         }
 
-        len += self.y.homomorphic_encode(&mut buf[len..]);
+        len += encode_checked(&self.y, &mut buf[len..], "y");
+
+        return len;
+    }
+
+    /// Encode a [`Point3d`](Self) whose dimensions are all fixed-width with the [zxy encoding](Self::encode_zxy), skipping all separator handling. Returns the length of the encoding, which is always [`max_encoding_len_zxy`](Self::max_encoding_len_zxy).
+    ///
+    /// Fails to compile unless [`IS_FIXED_WIDTH_ENCODING`](Self::IS_FIXED_WIDTH_ENCODING) is `true`. Panics if the given slice is shorter than the encoding.
+    pub fn encode_zxy_fixed(&self, buf: &mut [u8]) -> usize {
+        const { assert!(Self::IS_FIXED_WIDTH_ENCODING) };
+
+        let len = Self::max_encoding_len_zxy();
+        let (buf_z, rest) = buf[..len].split_at_mut(Z::HOMOMORPHIC_ENCODING_MAX_LENGTH);
+        let (buf_x, buf_y) = rest.split_at_mut(X::HOMOMORPHIC_ENCODING_MAX_LENGTH);
+
+        encode_checked(&self.z, buf_z, "z");
+        encode_checked(&self.x, buf_x, "x");
+        encode_checked(&self.y, buf_y, "y");
 
         return len;
     }
 
     /// Decode the [zxy encoding](Self::encode_zxy) from a slice.  On success, return the decoded value, and the number of bytes that were decoded.
-    pub fn decode_zxy(buf: &[u8]) -> Result<(Self, usize), ()> {
+    pub fn decode_zxy(buf: &[u8]) -> Result<(Self, usize), DecodeError> {
         let mut offset = 0;
 
-        let (z, z_len) = Z::homomorphic_decode(&buf[offset..])?;
+        let (z, z_len) = Z::homomorphic_decode(buf.get(offset..).ok_or(DecodeError::UnexpectedEof)?)?;
         offset += z_len;
         if !Z::IS_FIXED_WIDTH_ENCODING {
-            if buf[offset] != 0 || buf[offset + 1] != 0 {
-                return Err(());
-            } else {
-                offset += 2;
-            }
+            offset += check_separator::<ZeroSeparator>(buf, offset)?;
         }
 
-        let (x, x_len) = X::homomorphic_decode(&buf[offset..])?;
+        let (x, x_len) = X::homomorphic_decode(buf.get(offset..).ok_or(DecodeError::UnexpectedEof)?)?;
         offset += x_len;
         if !X::IS_FIXED_WIDTH_ENCODING {
-            if buf[offset] != 0 || buf[offset + 1] != 0 {
-                return Err(());
-            } else {
-                offset += 2;
-            }
+            offset += check_separator::<ZeroSeparator>(buf, offset)?;
         }
 
-        let (y, y_len) = Y::homomorphic_decode(&buf[offset..])?;
+        let (y, y_len) = Y::homomorphic_decode(buf.get(offset..).ok_or(DecodeError::UnexpectedEof)?)?;
         offset += y_len;
 
         return Ok((Point3d { x, y, z }, offset));
     }
-}
\ No newline at end of file
+
+    /// Decode the [zxy encoding](Self::encode_zxy) from a slice that must contain exactly the encoding and nothing else. Unlike [`decode_zxy`](Self::decode_zxy), this rejects buffers with trailing bytes.
+    pub fn decode_zxy_exact(buf: &[u8]) -> Result<Self, DecodeError> {
+        let (point, len) = Self::decode_zxy(buf)?;
+
+        if len == buf.len() {
+            return Ok(point);
+        } else {
+            return Err(DecodeError::TrailingBytes);
+        }
+    }
+
+    /// Feed the homomorphic encoding of `self` in the given [`Ordering3d`] into a [`Hasher`], so that the hash depends only on the canonical encoding rather than on the layout of the fields. Points that are equal according to the ordering hash identically.
+    ///
+    /// This hashes the encoding as a byte slice would be hashed, i.e., the bytes are preceded by their length.
+    #[cfg(feature = "alloc")]
+    pub fn hash_encoded<H: Hasher>(&self, state: &mut H, ordering: Ordering3d) {
+        let len;
+        let mut buf;
+
+        match ordering {
+            Ordering3d::Xyz => {
+                buf = vec![0; Self::max_encoding_len_xyz()];
+                len = self.encode_xyz(&mut buf);
+            }
+            Ordering3d::Yzx => {
+                buf = vec![0; Self::max_encoding_len_yzx()];
+                len = self.encode_yzx(&mut buf);
+            }
+            Ordering3d::Zxy => {
+                buf = vec![0; Self::max_encoding_len_zxy()];
+                len = self.encode_zxy(&mut buf);
+            }
+        }
+
+        buf[..len].hash(state);
+    }
+
+    /// Compute the [xyz encoding](Self::encode_xyz), the [yzx encoding](Self::encode_yzx), and the [zxy encoding](Self::encode_zxy) at once. Each dimension is encoded only once, and the encodings are then assembled from the shared per-dimension encodings.
+    #[cfg(feature = "alloc")]
+    pub fn encode_all(&self) -> Point3dEncodings {
+        let x = encode_dimension(&self.x, "x");
+        let y = encode_dimension(&self.y, "y");
+        let z = encode_dimension(&self.z, "z");
+
+        let mut xyz = Vec::with_capacity(Self::max_encoding_len_xyz());
+        append_dimension::<X>(&mut xyz, &x, false);
+        append_dimension::<Y>(&mut xyz, &y, false);
+        append_dimension::<Z>(&mut xyz, &z, true);
+
+        let mut yzx = Vec::with_capacity(Self::max_encoding_len_yzx());
+        append_dimension::<Y>(&mut yzx, &y, false);
+        append_dimension::<Z>(&mut yzx, &z, false);
+        append_dimension::<X>(&mut yzx, &x, true);
+
+        let mut zxy = Vec::with_capacity(Self::max_encoding_len_zxy());
+        append_dimension::<Z>(&mut zxy, &z, false);
+        append_dimension::<X>(&mut zxy, &x, false);
+        append_dimension::<Y>(&mut zxy, &y, true);
+
+        return Point3dEncodings { xyz, yzx, zxy };
+    }
+
+    /// Append the [xyz encoding](Self::encode_xyz) to a [`Vec`], and return how many bytes were appended.
+    ///
+    /// This reserves [`max_encoding_len_xyz`](Self::max_encoding_len_xyz) bytes at the end of the vector, encodes into them, and truncates the vector to the actual end of the encoding. Bytes that already are in the vector are left untouched, so several encodings (and other data) can be appended one after the other.
+    #[cfg(feature = "alloc")]
+    pub fn encode_xyz_append(&self, buf: &mut Vec<u8>) -> usize {
+        let start = buf.len();
+        buf.resize(start + Self::max_encoding_len_xyz(), 0);
+
+        let len = self.encode_xyz(&mut buf[start..]);
+        buf.truncate(start + len);
+
+        return len;
+    }
+
+    /// Render the [xyz encoding](Self::encode_xyz) as hex bytes, for debugging. The encodings of the three dimensions are divided by ` | `, an empty encoding is rendered as `-`, and the separators after variable-width dimensions are enclosed in brackets. For example, a point with a `u16` x coordinate, a variable-width y coordinate, and a `u8` z coordinate might render as `00 2a | 61 62 [00 00] | ff`.
+    #[cfg(feature = "alloc")]
+    pub fn debug_encoding_xyz(&self) -> String {
+        let mut rendered = String::new();
+
+        push_hex_dimension::<X>(&mut rendered, &encode_dimension(&self.x, "x"), false);
+        rendered.push_str(" | ");
+        push_hex_dimension::<Y>(&mut rendered, &encode_dimension(&self.y, "y"), false);
+        rendered.push_str(" | ");
+        push_hex_dimension::<Z>(&mut rendered, &encode_dimension(&self.z, "z"), true);
+
+        return rendered;
+    }
+
+    /// Write the [xyz encoding](Self::encode_xyz) to a [`Write`], and return how many bytes were written.
+    ///
+    /// Unlike [`encode_xyz`](Self::encode_xyz), this does not require a buffer for the full encoding; it only uses a scratch buffer for encoding one dimension at a time.
+    #[cfg(feature = "std")]
+    pub fn encode_xyz_to_writer<W: Write>(&self, w: &mut W) -> io::Result<usize> {
+        let mut scratch = Self::new_scratch_buffer();
+        let mut len = 0;
+
+        len += write_dimension(&self.x, "x", false, &mut scratch, w)?;
+        len += write_dimension(&self.y, "y", false, &mut scratch, w)?;
+        len += write_dimension(&self.z, "z", true, &mut scratch, w)?;
+
+        return Ok(len);
+    }
+
+    /// Read an [xyz encoding](Self::encode_xyz) from a [`Read`], consuming exactly the bytes of the encoding. On success, return the decoded value, and the number of bytes that were read.
+    ///
+    /// Variable-width dimensions are read one byte at a time: all but the last one up to their separator, and the last one until its [decoding function](Dimension::homomorphic_decode) accepts the bytes read so far. Hence, the last dimension must either be [fixed-width](Dimension::IS_FIXED_WIDTH_ENCODING) or [prefix-free](Dimension::IS_PREFIX_FREE), otherwise this fails to compile. Failure to decode yields an error of kind [`io::ErrorKind::InvalidData`].
+    #[cfg(feature = "std")]
+    pub fn decode_xyz_from_reader<R: Read>(r: &mut R) -> io::Result<(Self, usize)> {
+        let mut scratch = vec![];
+        let mut len = 0;
+
+        let (x, x_len) = read_dimension(&mut scratch, r)?;
+        len += x_len;
+        let (y, y_len) = read_dimension(&mut scratch, r)?;
+        len += y_len;
+        let (z, z_len) = read_last_dimension(&mut scratch, r)?;
+        len += z_len;
+
+        return Ok((Point3d { x, y, z }, len));
+    }
+
+    /// Write the [yzx encoding](Self::encode_yzx) to a [`Write`], and return how many bytes were written.
+    ///
+    /// Unlike [`encode_yzx`](Self::encode_yzx), this does not require a buffer for the full encoding; it only uses a scratch buffer for encoding one dimension at a time.
+    #[cfg(feature = "std")]
+    pub fn encode_yzx_to_writer<W: Write>(&self, w: &mut W) -> io::Result<usize> {
+        let mut scratch = Self::new_scratch_buffer();
+        let mut len = 0;
+
+        len += write_dimension(&self.y, "y", false, &mut scratch, w)?;
+        len += write_dimension(&self.z, "z", false, &mut scratch, w)?;
+        len += write_dimension(&self.x, "x", true, &mut scratch, w)?;
+
+        return Ok(len);
+    }
+
+    /// Read a [yzx encoding](Self::encode_yzx) from a [`Read`], consuming exactly the bytes of the encoding. On success, return the decoded value, and the number of bytes that were read.
+    ///
+    /// See [`decode_xyz_from_reader`](Self::decode_xyz_from_reader) for the requirements on variable-width dimensions.
+    #[cfg(feature = "std")]
+    pub fn decode_yzx_from_reader<R: Read>(r: &mut R) -> io::Result<(Self, usize)> {
+        let mut scratch = vec![];
+        let mut len = 0;
+
+        let (y, y_len) = read_dimension(&mut scratch, r)?;
+        len += y_len;
+        let (z, z_len) = read_dimension(&mut scratch, r)?;
+        len += z_len;
+        let (x, x_len) = read_last_dimension(&mut scratch, r)?;
+        len += x_len;
+
+        return Ok((Point3d { x, y, z }, len));
+    }
+
+    /// Write the [zxy encoding](Self::encode_zxy) to a [`Write`], and return how many bytes were written.
+    ///
+    /// Unlike [`encode_zxy`](Self::encode_zxy), this does not require a buffer for the full encoding; it only uses a scratch buffer for encoding one dimension at a time.
+    #[cfg(feature = "std")]
+    pub fn encode_zxy_to_writer<W: Write>(&self, w: &mut W) -> io::Result<usize> {
+        let mut scratch = Self::new_scratch_buffer();
+        let mut len = 0;
+
+        len += write_dimension(&self.z, "z", false, &mut scratch, w)?;
+        len += write_dimension(&self.x, "x", false, &mut scratch, w)?;
+        len += write_dimension(&self.y, "y", true, &mut scratch, w)?;
+
+        return Ok(len);
+    }
+
+    /// Read a [zxy encoding](Self::encode_zxy) from a [`Read`], consuming exactly the bytes of the encoding. On success, return the decoded value, and the number of bytes that were read.
+    ///
+    /// See [`decode_xyz_from_reader`](Self::decode_xyz_from_reader) for the requirements on variable-width dimensions.
+    #[cfg(feature = "std")]
+    pub fn decode_zxy_from_reader<R: Read>(r: &mut R) -> io::Result<(Self, usize)> {
+        let mut scratch = vec![];
+        let mut len = 0;
+
+        let (z, z_len) = read_dimension(&mut scratch, r)?;
+        len += z_len;
+        let (x, x_len) = read_dimension(&mut scratch, r)?;
+        len += x_len;
+        let (y, y_len) = read_last_dimension(&mut scratch, r)?;
+        len += y_len;
+
+        return Ok((Point3d { x, y, z }, len));
+    }
+
+    // A buffer large enough to hold the encoding of any single dimension.
+    #[cfg(feature = "std")]
+    fn new_scratch_buffer() -> Vec<u8> {
+        let len = X::HOMOMORPHIC_ENCODING_MAX_LENGTH
+            .max(Y::HOMOMORPHIC_ENCODING_MAX_LENGTH)
+            .max(Z::HOMOMORPHIC_ENCODING_MAX_LENGTH);
+        return vec![0; len];
+    }
+}
+
+/// The three encodings of a [`Point3d`], as computed by [`Point3d::encode_all`].
+#[cfg(feature = "alloc")]
+#[derive(PartialEq, Eq, Clone, Debug, Hash)]
+pub struct Point3dEncodings {
+    /// The [xyz encoding](Point3d::encode_xyz).
+    pub xyz: Vec<u8>,
+    /// The [yzx encoding](Point3d::encode_yzx).
+    pub yzx: Vec<u8>,
+    /// The [zxy encoding](Point3d::encode_zxy).
+    pub zxy: Vec<u8>,
+}
+
+/// The size of the stack buffers that [`Point3d::cmp_xyz_via_encoding`] encodes into, and hence the maximum [xyz encoding](Point3d::encode_xyz) length it supports.
+pub const CMP_VIA_ENCODING_BUFFER_LEN: usize = 1024;
+
+/// The version of the point encodings of this crate, as written by [`Point3d::encode_xyz_versioned`]. This changes whenever the encoding of points changes in an incompatible way.
+pub const POINT_ENCODING_VERSION: u8 = 0;
+
+/// The reasons why [`Point3d::decode_xyz_versioned`] can fail.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Hash)]
+pub enum VersionedDecodeError {
+    /// The encoding has a version other than [`POINT_ENCODING_VERSION`].
+    UnsupportedVersion(u8),
+    /// The encoding has the supported version, but is not a valid encoding of a point, for the given reason. A missing version byte is an [`UnexpectedEof`](DecodeError::UnexpectedEof).
+    Invalid(DecodeError),
+}
+
+impl core::fmt::Display for VersionedDecodeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            VersionedDecodeError::UnsupportedVersion(version) => {
+                return write!(f, "unsupported point encoding version {}", version)
+            }
+            VersionedDecodeError::Invalid(err) => return write!(f, "invalid point encoding: {}", err),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for VersionedDecodeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            VersionedDecodeError::Invalid(err) => return Some(err),
+            VersionedDecodeError::UnsupportedVersion(_) => return None,
+        }
+    }
+}
+
+/// A [`Point3d`] that implements [`Ord`] via the [xyz ordering](Point3d::cmp_xyz). Use this to store points in ordered collections, or to sort them with [`slice::sort`].
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Hash)]
+#[repr(transparent)]
+pub struct Xyz<X: Dimension, Y: Dimension, Z: Dimension>(pub Point3d<X, Y, Z>);
+
+impl<X: Dimension, Y: Dimension, Z: Dimension> PartialOrd for Xyz<X, Y, Z> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        return Some(self.cmp(other));
+    }
+}
+
+impl<X: Dimension, Y: Dimension, Z: Dimension> Ord for Xyz<X, Y, Z> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        return self.0.cmp_xyz(&other.0);
+    }
+}
+
+/// Decodes the [xyz encoding](Point3d::encode_xyz) via [`decode_xyz_exact`](Point3d::decode_xyz_exact), so the slice must contain exactly one encoding and no trailing bytes.
+impl<X: Dimension, Y: Dimension, Z: Dimension> TryFrom<&[u8]> for Xyz<X, Y, Z> {
+    type Error = DecodeError;
+
+    fn try_from(buf: &[u8]) -> Result<Self, Self::Error> {
+        return Point3d::decode_xyz_exact(buf).map(Xyz);
+    }
+}
+
+/// A [`Point3d`] that implements [`Ord`] via the [yzx ordering](Point3d::cmp_yzx). Use this to store points in ordered collections, or to sort them with [`slice::sort`].
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Hash)]
+#[repr(transparent)]
+pub struct Yzx<X: Dimension, Y: Dimension, Z: Dimension>(pub Point3d<X, Y, Z>);
+
+impl<X: Dimension, Y: Dimension, Z: Dimension> PartialOrd for Yzx<X, Y, Z> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        return Some(self.cmp(other));
+    }
+}
+
+impl<X: Dimension, Y: Dimension, Z: Dimension> Ord for Yzx<X, Y, Z> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        return self.0.cmp_yzx(&other.0);
+    }
+}
+
+/// Decodes the [yzx encoding](Point3d::encode_yzx) via [`decode_yzx_exact`](Point3d::decode_yzx_exact), so the slice must contain exactly one encoding and no trailing bytes.
+impl<X: Dimension, Y: Dimension, Z: Dimension> TryFrom<&[u8]> for Yzx<X, Y, Z> {
+    type Error = DecodeError;
+
+    fn try_from(buf: &[u8]) -> Result<Self, Self::Error> {
+        return Point3d::decode_yzx_exact(buf).map(Yzx);
+    }
+}
+
+/// A [`Point3d`] that implements [`Ord`] via the [zxy ordering](Point3d::cmp_zxy). Use this to store points in ordered collections, or to sort them with [`slice::sort`].
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Hash)]
+#[repr(transparent)]
+pub struct Zxy<X: Dimension, Y: Dimension, Z: Dimension>(pub Point3d<X, Y, Z>);
+
+impl<X: Dimension, Y: Dimension, Z: Dimension> PartialOrd for Zxy<X, Y, Z> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        return Some(self.cmp(other));
+    }
+}
+
+impl<X: Dimension, Y: Dimension, Z: Dimension> Ord for Zxy<X, Y, Z> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        return self.0.cmp_zxy(&other.0);
+    }
+}
+
+/// Decodes the [zxy encoding](Point3d::encode_zxy) via [`decode_zxy_exact`](Point3d::decode_zxy_exact), so the slice must contain exactly one encoding and no trailing bytes.
+impl<X: Dimension, Y: Dimension, Z: Dimension> TryFrom<&[u8]> for Zxy<X, Y, Z> {
+    type Error = DecodeError;
+
+    fn try_from(buf: &[u8]) -> Result<Self, Self::Error> {
+        return Point3d::decode_zxy_exact(buf).map(Zxy);
+    }
+}
+
+/// A view into the [xyz encoding](Point3d::encode_xyz) of a point, which gives access to the encodings of the individual dimensions without decoding the point.
+///
+/// Creating the view locates the encodings of the three dimensions once. Fixed-width dimensions are located by their width, variable-width dimensions by scanning for the separator that follows them. Only if the separator is ambiguous (the encoding of a variable-width dimension may end with a zero byte, and the next encoding may start with zero bytes) is the dimension decoded to determine where its encoding ends.
+///
+/// The view only checks the structure of the encoding, not whether the encodings of the dimensions are valid. Decoding the sub-slices may still fail.
+#[derive(Debug)]
+pub struct EncodedPointXyz<'a, X, Y, Z> {
+    bytes: &'a [u8],
+    x_end: usize,
+    y_start: usize,
+    y_end: usize,
+    z_start: usize,
+    phantom: PhantomData<(X, Y, Z)>,
+}
+
+impl<'a, X: Dimension, Y: Dimension, Z: Dimension> EncodedPointXyz<'a, X, Y, Z> {
+    /// Create a view into the given slice, which must contain exactly the [xyz encoding](Point3d::encode_xyz) of a point and nothing else. Fail if the dimensions cannot be located in the slice.
+    pub fn new(bytes: &'a [u8]) -> Result<Self, DecodeError> {
+        let (x_end, y_start) = locate_dimension::<X>(bytes, 0)?;
+        let (y_end, z_start) = locate_dimension::<Y>(bytes, y_start)?;
+
+        if Z::IS_FIXED_WIDTH_ENCODING {
+            match (bytes.len() - z_start).cmp(&Z::HOMOMORPHIC_ENCODING_MAX_LENGTH) {
+                Less => return Err(DecodeError::UnexpectedEof),
+                Greater => return Err(DecodeError::TrailingBytes),
+                Equal => {}
+            }
+        }
+
+        return Ok(EncodedPointXyz {
+            bytes,
+            x_end,
+            y_start,
+            y_end,
+            z_start,
+            phantom: PhantomData,
+        });
+    }
+
+    /// The complete encoding of the point.
+    pub fn bytes(&self) -> &'a [u8] {
+        return self.bytes;
+    }
+
+    /// The [homomorphic encoding](Dimension::homomorphic_encode) of the `x` dimension, without the separator that follows it.
+    pub fn x_bytes(&self) -> &'a [u8] {
+        return &self.bytes[..self.x_end];
+    }
+
+    /// The [homomorphic encoding](Dimension::homomorphic_encode) of the `y` dimension, without the separator that follows it.
+    pub fn y_bytes(&self) -> &'a [u8] {
+        return &self.bytes[self.y_start..self.y_end];
+    }
+
+    /// The [homomorphic encoding](Dimension::homomorphic_encode) of the `z` dimension.
+    pub fn z_bytes(&self) -> &'a [u8] {
+        return &self.bytes[self.z_start..];
+    }
+}
+
+// Return the maximum length of the encoding of a point whose dimensions are encoded in the order `A`, `B`, `C`: the maximum lengths of the three dimensions, plus the separators after the first two if they are variable-width. Panics if the sum overflows.
+const fn max_encoding_len<A: Dimension, B: Dimension, C: Dimension>() -> usize {
+    return checked_sum(&[
+        A::HOMOMORPHIC_ENCODING_MAX_LENGTH,
+        if A::IS_FIXED_WIDTH_ENCODING { 0 } else { 2 },
+        B::HOMOMORPHIC_ENCODING_MAX_LENGTH,
+        if B::IS_FIXED_WIDTH_ENCODING { 0 } else { 2 },
+        C::HOMOMORPHIC_ENCODING_MAX_LENGTH,
+    ]);
+}
+
+// Sum up encoding lengths, panicking if the sum overflows.
+const fn checked_sum(lengths: &[usize]) -> usize {
+    let mut sum: usize = 0;
+
+    let mut i = 0;
+    while i < lengths.len() {
+        match sum.checked_add(lengths[i]) {
+            Some(new_sum) => sum = new_sum,
+            None => panic!("the maximum encoding length of a Point3d overflows usize, the HOMOMORPHIC_ENCODING_MAX_LENGTH of its dimensions are too large"),
+        }
+        i += 1;
+    }
+
+    return sum;
+}
+
+// Return the length of the varint encoding of `n`.
+pub(crate) const fn varint_len(mut n: usize) -> usize {
+    let mut len = 1;
+    while n >= 0x80 {
+        n >>= 7;
+        len += 1;
+    }
+
+    return len;
+}
+
+// Write `n` as a varint: seven bits per byte, least significant group first, with the most significant bit set on all but the last byte. Return the number of bytes written.
+pub(crate) fn encode_varint(mut n: usize, buf: &mut [u8]) -> usize {
+    let mut len = 0;
+    while n >= 0x80 {
+        buf[len] = (n as u8 & 0x7f) | 0x80;
+        n >>= 7;
+        len += 1;
+    }
+    buf[len] = n as u8;
+
+    return len + 1;
+}
+
+// Read a varint written by `encode_varint`, and return it together with the number of bytes read. Rejects non-canonical encodings (with superfluous trailing zero groups) and values that do not fit into a usize.
+pub(crate) fn decode_varint(buf: &[u8]) -> Result<(usize, usize), DecodeError> {
+    let mut n: usize = 0;
+
+    for (i, byte) in buf.iter().enumerate() {
+        let group = (*byte & 0x7f) as usize;
+        let shift = 7 * i as u32;
+
+        if shift >= usize::BITS || (group << shift) >> shift != group {
+            return Err(DecodeError::InvalidValue);
+        }
+        n |= group << shift;
+
+        if byte & 0x80 == 0 {
+            if i > 0 && group == 0 {
+                return Err(DecodeError::InvalidValue);
+            } else {
+                return Ok((n, i + 1));
+            }
+        }
+    }
+
+    return Err(DecodeError::UnexpectedEof);
+}
+
+// Check that the separator after a variable-width dimension starts at the given offset, and return its length.
+fn check_separator<S: Separator>(buf: &[u8], offset: usize) -> Result<usize, DecodeError> {
+    match buf.get(offset..offset + 2) {
+        None => return Err(DecodeError::UnexpectedEof),
+        Some(bytes) if bytes == S::BYTES => return Ok(2),
+        Some(_) => return Err(DecodeError::BadSeparator),
+    }
+}
+
+// Encode a single dimension (named `name` in panic messages) into the given slice. In debug builds, panics if a fixed-width dimension produces an encoding whose length differs from its declared width, since that would silently corrupt the encoding of the point.
+fn encode_checked<D: Dimension>(d: &D, buf: &mut [u8], name: &str) -> usize {
+    let len = d.homomorphic_encode(buf);
+
+    debug_assert!(
+        !D::IS_FIXED_WIDTH_ENCODING || len == D::HOMOMORPHIC_ENCODING_MAX_LENGTH,
+        "the {} dimension (of type {}) claims a fixed-width encoding of {} bytes, but its homomorphic_encode returned a length of {}",
+        name,
+        core::any::type_name::<D>(),
+        D::HOMOMORPHIC_ENCODING_MAX_LENGTH,
+        len
+    );
+
+    return len;
+}
+
+// Locate the encoding of a dimension that starts at `start` and is not the last one of the point. Return the end of the encoding and the start of the next one, which differ by the length of the separator for variable-width dimensions.
+fn locate_dimension<D: Dimension>(buf: &[u8], start: usize) -> Result<(usize, usize), DecodeError> {
+    if D::IS_FIXED_WIDTH_ENCODING {
+        let end = start + D::HOMOMORPHIC_ENCODING_MAX_LENGTH;
+
+        if end > buf.len() {
+            return Err(DecodeError::UnexpectedEof);
+        } else {
+            return Ok((end, end));
+        }
+    }
+
+    // The encoding contains no consecutive zero bytes, so the separator is part of the first pair of zero bytes.
+    let mut i = start;
+    loop {
+        match buf.get(i..i + 2) {
+            None => return Err(DecodeError::UnexpectedEof),
+            Some(&[0, 0]) => break,
+            Some(_) => i += 1,
+        }
+    }
+
+    if buf.get(i + 2) == Some(&0) {
+        // The encoding might end with a zero byte, or the next encoding might start with one.
+        let (_, len) = D::homomorphic_decode(&buf[start..])?;
+        let end = start + len;
+
+        return Ok((end, end + check_separator::<ZeroSeparator>(buf, end)?));
+    } else {
+        return Ok((i, i + 2));
+    }
+}
+
+// Panic in debug builds if the dimension must only be the last one of an encoding, for a dimension that is about to be followed by a separator.
+fn debug_assert_not_last_only<D: Dimension>() {
+    debug_assert!(
+        !D::MUST_BE_LAST,
+        "a dimension of type {} must only be the last dimension of an encoding, but is followed by further dimensions",
+        core::any::type_name::<D>()
+    );
+}
+
+// Return the encoding of a single dimension.
+#[cfg(feature = "alloc")]
+fn encode_dimension<D: Dimension>(d: &D, name: &str) -> Vec<u8> {
+    let mut buf = vec![0; D::HOMOMORPHIC_ENCODING_MAX_LENGTH];
+    let len = encode_checked(d, &mut buf, name);
+    buf.truncate(len);
+    return buf;
+}
+
+// Append the hex bytes of the encoding of a single dimension, followed by a bracketed separator if the dimension is variable-width and not the last one of the point.
+#[cfg(feature = "alloc")]
+fn push_hex_dimension<D: Dimension>(rendered: &mut String, encoding: &[u8], is_last: bool) {
+    use core::fmt::Write as _;
+
+    if encoding.is_empty() {
+        rendered.push('-');
+    }
+
+    for (i, byte) in encoding.iter().enumerate() {
+        if i > 0 {
+            rendered.push(' ');
+        }
+        // Writing to a `String` never fails.
+        write!(rendered, "{:02x}", byte).unwrap();
+    }
+
+    if !is_last && !D::IS_FIXED_WIDTH_ENCODING {
+        rendered.push_str(" [00 00]");
+    }
+}
+
+// Append the encoding of a single dimension, followed by a separator if the dimension is variable-width and not the last one of the point.
+#[cfg(feature = "alloc")]
+fn append_dimension<D: Dimension>(buf: &mut Vec<u8>, encoding: &[u8], is_last: bool) {
+    buf.extend_from_slice(encoding);
+    if !is_last && !D::IS_FIXED_WIDTH_ENCODING {
+        debug_assert_not_last_only::<D>();
+        buf.extend_from_slice(&[0, 0]);
+    }
+}
+
+// Write the encoding of a single dimension, followed by a separator if the dimension is variable-width and not the last one of the point.
+#[cfg(feature = "std")]
+fn write_dimension<D: Dimension, W: Write>(
+    d: &D,
+    name: &str,
+    is_last: bool,
+    scratch: &mut [u8],
+    w: &mut W,
+) -> io::Result<usize> {
+    let mut len = encode_checked(d, scratch, name);
+    w.write_all(&scratch[..len])?;
+
+    if !is_last && !D::IS_FIXED_WIDTH_ENCODING {
+        debug_assert_not_last_only::<D>();
+        w.write_all(&[0, 0])?;
+        len += 2;
+    }
+
+    return Ok(len);
+}
+
+// Read the encoding of a single dimension that is not the last one of the point, followed by a separator if the dimension is variable-width.
+#[cfg(feature = "std")]
+fn read_dimension<D: Dimension, R: Read>(
+    scratch: &mut Vec<u8>,
+    r: &mut R,
+) -> io::Result<(D, usize)> {
+    if D::IS_FIXED_WIDTH_ENCODING {
+        return read_fixed_width_dimension(scratch, r);
+    }
+
+    scratch.clear();
+
+    // Read up to a separator, so that an encoding which is a strict prefix of another one (in particular, an empty encoding) is not mistaken for the complete encoding. The encoding itself may end with a single zero byte, so the first separator candidate need not be the actual separator.
+    loop {
+        if scratch.ends_with(&[0, 0]) {
+            let encoding = &scratch[..scratch.len() - 2];
+            if let Ok((d, len)) = D::homomorphic_decode(encoding) {
+                if len == encoding.len() {
+                    return Ok((d, scratch.len()));
+                }
+            }
+        }
+
+        if scratch.len() >= D::HOMOMORPHIC_ENCODING_MAX_LENGTH + 2 {
+            return Err(invalid_encoding());
+        }
+
+        read_byte(scratch, r)?;
+    }
+}
+
+// Read the encoding of the last dimension of a point. Fails to compile unless the dimension is fixed-width or prefix-free.
+#[cfg(feature = "std")]
+fn read_last_dimension<D: Dimension, R: Read>(
+    scratch: &mut Vec<u8>,
+    r: &mut R,
+) -> io::Result<(D, usize)> {
+    const {
+        assert!(
+            D::IS_FIXED_WIDTH_ENCODING || D::IS_PREFIX_FREE,
+            "the last dimension of a Point3d must be fixed-width or prefix-free to be read from an io::Read"
+        )
+    };
+
+    if D::IS_FIXED_WIDTH_ENCODING {
+        return read_fixed_width_dimension(scratch, r);
+    }
+
+    scratch.clear();
+
+    // Nothing marks the end of the last dimension, so it ends where decoding first accepts the bytes read so far. Since the encodings are prefix-free, that is the actual end.
+    loop {
+        if let Ok((d, len)) = D::homomorphic_decode(scratch) {
+            if len == scratch.len() {
+                return Ok((d, len));
+            }
+        }
+
+        if scratch.len() >= D::HOMOMORPHIC_ENCODING_MAX_LENGTH {
+            return Err(invalid_encoding());
+        }
+
+        read_byte(scratch, r)?;
+    }
+}
+
+#[cfg(feature = "std")]
+fn read_fixed_width_dimension<D: Dimension, R: Read>(
+    scratch: &mut Vec<u8>,
+    r: &mut R,
+) -> io::Result<(D, usize)> {
+    scratch.clear();
+    scratch.resize(D::HOMOMORPHIC_ENCODING_MAX_LENGTH, 0);
+    r.read_exact(scratch)?;
+
+    match D::homomorphic_decode(scratch) {
+        Ok((d, len)) if len == scratch.len() => return Ok((d, len)),
+        _ => return Err(invalid_encoding()),
+    }
+}
+
+// Read a single byte and append it to `scratch`.
+#[cfg(feature = "std")]
+fn read_byte<R: Read>(scratch: &mut Vec<u8>, r: &mut R) -> io::Result<()> {
+    let mut byte = [0];
+    r.read_exact(&mut byte)?;
+    scratch.push(byte[0]);
+    return Ok(());
+}
+
+#[cfg(feature = "std")]
+fn invalid_encoding() -> io::Error {
+    return io::Error::new(io::ErrorKind::InvalidData, "invalid Point3d encoding");
+}
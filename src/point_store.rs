@@ -0,0 +1,93 @@
+use alloc::{vec, vec::Vec};
+use core::marker::PhantomData;
+use core::ops::Bound;
+
+use crate::{BackEnd, Dimension, Point3d};
+
+/// A thin wrapper around a [`BackEnd`] that uses [`Point3d`]s as keys instead of bytestrings.
+///
+/// Points are stored under their [xyz encoding](Point3d::encode_xyz), so all kv pairs are sorted according to the [xyz ordering](Point3d::cmp_xyz). The wrapped backend must not contain any keys other than those written by the `PointStore`.
+#[derive(Debug)]
+pub struct PointStore<B, X, Y, Z, V> {
+    inner: B,
+    phantom: PhantomData<(X, Y, Z, V)>,
+}
+
+impl<B, X, Y, Z, V> PointStore<B, X, Y, Z, V> {
+    /// Wrap a backend.
+    pub fn new(inner: B) -> Self {
+        return PointStore {
+            inner,
+            phantom: PhantomData,
+        };
+    }
+
+    /// Return the wrapped backend.
+    pub fn into_inner(self) -> B {
+        return self.inner;
+    }
+}
+
+impl<B, X, Y, Z, V> PointStore<B, X, Y, Z, V>
+where
+    B: BackEnd<V>,
+    X: Dimension,
+    Y: Dimension,
+    Z: Dimension,
+{
+    /// Get the value associated with the given point, if there is any.
+    pub async fn get(&self, point: &Point3d<X, Y, Z>) -> Result<Option<V>, B::Error> {
+        return self.inner.get(&encode_key(point)).await;
+    }
+
+    /// Insert a point-value pair. Returns the old value for that point, if there was any.
+    pub async fn insert(
+        &mut self,
+        point: &Point3d<X, Y, Z>,
+        value: V,
+    ) -> Result<Option<V>, B::Error> {
+        return self.inner.insert(&encode_key(point), value).await;
+    }
+
+    /// Delete a point-value pair. Returns the old value for that point, if there was any.
+    pub async fn delete(&mut self, point: &Point3d<X, Y, Z>) -> Result<Option<V>, B::Error> {
+        return self.inner.delete(&encode_key(point)).await;
+    }
+
+    /// Get all point-value pairs whose points lie within the given bounds according to the [xyz ordering](Point3d::cmp_xyz), in ascending xyz order.
+    pub async fn range_xyz(
+        &self,
+        lo: Bound<&Point3d<X, Y, Z>>,
+        hi: Bound<&Point3d<X, Y, Z>>,
+    ) -> Result<Vec<(Point3d<X, Y, Z>, V)>, B::Error> {
+        // Encodings are order-homomorphic, so bounds on points translate into bounds on their encodings.
+        let lo = lo.map(encode_key);
+        let hi = hi.map(encode_key);
+
+        let entries = self
+            .inner
+            .range(lo.as_ref().map(|k| k.as_slice()), hi.as_ref().map(|k| k.as_slice()))
+            .await?;
+
+        return Ok(entries
+            .map(|(key, value)| {
+                let (point, _) = Point3d::decode_xyz(&key)
+                    .expect("PointStore backend must only contain xyz encodings of points");
+                (point, value)
+            })
+            .collect());
+    }
+
+    /// Commit all mutations that have been performed so far to disk, see [`BackEnd::flush`].
+    pub async fn flush(&mut self) -> Result<(), B::Error> {
+        return self.inner.flush().await;
+    }
+}
+
+// The key under which to store a point.
+fn encode_key<X: Dimension, Y: Dimension, Z: Dimension>(point: &Point3d<X, Y, Z>) -> Vec<u8> {
+    let mut key = vec![0; Point3d::<X, Y, Z>::max_encoding_len_xyz()];
+    let len = point.encode_xyz(&mut key);
+    key.truncate(len);
+    return key;
+}
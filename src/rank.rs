@@ -0,0 +1,234 @@
+//! Zip-trees derive the rank of each vertex from a hash of its item, so that the shape of a tree depends only on its set of items, and is balanced with high probability.
+
+use alloc::vec;
+use core::hash::{Hash, Hasher};
+
+use crate::{ordering_for_rank, Dimension, Ordering3d, Point3d, EMPTY_CHILD};
+
+/// The rank of a kv-tree vertex: any byte except the reserved [`EMPTY_CHILD`], which stands for a missing child.
+///
+/// Working with `Rank`s rather than bare `u8`s rules out confusing the sentinel with a rank, and the arithmetic and comparison helpers never wrap around.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(
+    any(feature = "bincode-codec", feature = "cbor-codec"),
+    derive(serde::Serialize),
+    serde(transparent)
+)]
+pub struct Rank(u8);
+
+impl Rank {
+    /// The least rank.
+    pub const MIN: Rank = Rank(0);
+
+    /// The greatest rank, the byte just below [`EMPTY_CHILD`].
+    pub const MAX: Rank = Rank(EMPTY_CHILD - 1);
+
+    /// Return the given rank, or `None` if it is the reserved [`EMPTY_CHILD`].
+    pub const fn new(rank: u8) -> Option<Rank> {
+        if Self::is_sentinel(rank) {
+            return None;
+        } else {
+            return Some(Rank(rank));
+        }
+    }
+
+    /// Return the rank with which the key of a kv-tree vertex starts, or `None` if the key is empty or starts with the reserved [`EMPTY_CHILD`].
+    pub fn of_key(key: &[u8]) -> Option<Rank> {
+        return key.first().and_then(|byte| Rank::new(*byte));
+    }
+
+    /// Return whether the given byte is the reserved [`EMPTY_CHILD`] rather than a rank.
+    pub const fn is_sentinel(byte: u8) -> bool {
+        return byte == EMPTY_CHILD;
+    }
+
+    /// Return the rank as a byte.
+    pub const fn get(self) -> u8 {
+        return self.0;
+    }
+
+    /// Return the next greater rank, or `None` if `self` is [`MAX`](Self::MAX).
+    pub const fn successor(self) -> Option<Rank> {
+        return Rank::new(self.0 + 1);
+    }
+
+    /// Return the next smaller rank, or `None` if `self` is [`MIN`](Self::MIN).
+    pub const fn predecessor(self) -> Option<Rank> {
+        match self.0.checked_sub(1) {
+            Some(rank) => return Some(Rank(rank)),
+            None => return None,
+        }
+    }
+
+    /// Return the byte after the rank. Keys that consist of it alone are greater than the keys of all vertices of this rank, and less than or equal to the keys of all vertices of greater ranks. This is at most [`EMPTY_CHILD`], so it never wraps around.
+    pub const fn next_byte(self) -> u8 {
+        return self.0 + 1;
+    }
+
+    /// Return the [ordering that governs vertices of this rank](ordering_for_rank).
+    pub fn ordering(self) -> Ordering3d {
+        return ordering_for_rank(self.0);
+    }
+
+    /// Return whether a vertex of rank `child` can be the left child of a vertex of rank `self`, i.e., whether `child` is strictly less.
+    pub fn admits_left_child(self, child: Rank) -> bool {
+        return child < self;
+    }
+
+    /// Return whether a vertex of rank `child` can be the right child of a vertex of rank `self`, i.e., whether `child` is less than or equal.
+    pub fn admits_right_child(self, child: Rank) -> bool {
+        return child <= self;
+    }
+}
+
+impl From<Rank> for u8 {
+    fn from(rank: Rank) -> u8 {
+        return rank.0;
+    }
+}
+
+/// Deserializes a byte, and rejects the reserved [`EMPTY_CHILD`].
+#[cfg(any(feature = "bincode-codec", feature = "cbor-codec"))]
+impl<'de> serde::Deserialize<'de> for Rank {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let byte = u8::deserialize(deserializer)?;
+        return Rank::new(byte).ok_or_else(|| {
+            <D::Error as serde::de::Error>::custom("rank 255 is reserved for missing children")
+        });
+    }
+}
+
+/// Derive the rank of a point-value pair from a hash of the [xyz encoding](Point3d::encode_xyz) of the point and of the value.
+///
+/// The rank is the number of leading zero bits of a 64 bit hash, so `P(rank = k) = 2^-(k+1)`, and no rank exceeds 64. In particular, the rank is never the reserved [`EMPTY_CHILD`](crate::EMPTY_CHILD).
+///
+/// The hash function is deterministic across program runs. Because `Hash` implementations feed integers into the hasher in native byte order, the ranks of values whose `Hash` implementation does so may differ across platforms of different endianness.
+pub fn rank_from_hash<X, Y, Z, V>(point: &Point3d<X, Y, Z>, value: &V) -> u8
+where
+    X: Dimension,
+    Y: Dimension,
+    Z: Dimension,
+    V: Hash,
+{
+    return hash_rank(point, value).get();
+}
+
+/// Like [`rank_from_hash`], but lowers every rank greater than `max_rank` to `max_rank`.
+///
+/// Capping ranks trades shape for a bounded number of distinct ranks: all points whose hash calls for a greater rank collide at `max_rank`, and vertices of equal rank form chains of right children. In a tree of `n` points, about `n / 2^(max_rank + 1)` points share the cap rank, so a cap well below `log2(n)` makes the top of the tree wider and the paths through it longer. A cap of at least `64` changes nothing.
+pub fn rank_from_hash_capped<X, Y, Z, V>(point: &Point3d<X, Y, Z>, value: &V, max_rank: u8) -> u8
+where
+    X: Dimension,
+    Y: Dimension,
+    Z: Dimension,
+    V: Hash,
+{
+    return rank_from_hash(point, value).min(max_rank);
+}
+
+// The rank of `rank_from_hash`, which never exceeds 64.
+fn hash_rank<X, Y, Z, V>(point: &Point3d<X, Y, Z>, value: &V) -> Rank
+where
+    X: Dimension,
+    Y: Dimension,
+    Z: Dimension,
+    V: Hash,
+{
+    let mut buf = vec![0; Point3d::<X, Y, Z>::max_encoding_len_xyz()];
+    let len = point.encode_xyz(&mut buf);
+
+    let mut hasher = RankHasher::new();
+    hasher.write(&buf[..len]);
+    value.hash(&mut hasher);
+
+    return Rank(hasher.finish().leading_zeros() as u8);
+}
+
+/// A way of choosing the rank of a point-value pair when [inserting](crate::KvTree::insert_with_strategy) it into a [`KvTree`](crate::KvTree).
+///
+/// Any function or closure from a point and a value to a [`Rank`] is a rank strategy.
+pub trait RankStrategy<X, Y, Z, V>
+where
+    X: Dimension,
+    Y: Dimension,
+    Z: Dimension,
+{
+    /// Return the rank for the given point-value pair.
+    fn rank(&self, point: &Point3d<X, Y, Z>, value: &V) -> Rank;
+}
+
+/// The [`RankStrategy`] of [`rank_from_hash`], and the default strategy of every [`KvTree`](crate::KvTree).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct HashRank;
+
+impl<X, Y, Z, V> RankStrategy<X, Y, Z, V> for HashRank
+where
+    X: Dimension,
+    Y: Dimension,
+    Z: Dimension,
+    V: Hash,
+{
+    fn rank(&self, point: &Point3d<X, Y, Z>, value: &V) -> Rank {
+        return hash_rank(point, value);
+    }
+}
+
+/// The [`RankStrategy`] of [`rank_from_hash_capped`], which never assigns ranks greater than `max_rank`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CappedHashRank {
+    /// The greatest rank this strategy assigns.
+    pub max_rank: Rank,
+}
+
+impl<X, Y, Z, V> RankStrategy<X, Y, Z, V> for CappedHashRank
+where
+    X: Dimension,
+    Y: Dimension,
+    Z: Dimension,
+    V: Hash,
+{
+    fn rank(&self, point: &Point3d<X, Y, Z>, value: &V) -> Rank {
+        return hash_rank(point, value).min(self.max_rank);
+    }
+}
+
+impl<X, Y, Z, V, F> RankStrategy<X, Y, Z, V> for F
+where
+    X: Dimension,
+    Y: Dimension,
+    Z: Dimension,
+    F: Fn(&Point3d<X, Y, Z>, &V) -> Rank,
+{
+    fn rank(&self, point: &Point3d<X, Y, Z>, value: &V) -> Rank {
+        return self(point, value);
+    }
+}
+
+// FNV-1a, followed by the finalizer of MurmurHash3 to make the high bits of the hash depend on all input bits.
+// We do not use the std `DefaultHasher`, because its algorithm is not guaranteed to remain stable.
+pub(crate) struct RankHasher(u64);
+
+impl RankHasher {
+    pub(crate) fn new() -> Self {
+        return RankHasher(0xcbf29ce484222325);
+    }
+}
+
+impl Hasher for RankHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= *byte as u64;
+            self.0 = self.0.wrapping_mul(0x100000001b3);
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        let mut h = self.0;
+        h ^= h >> 33;
+        h = h.wrapping_mul(0xff51afd7ed558ccd);
+        h ^= h >> 33;
+        h = h.wrapping_mul(0xc4ceb9fe1a85ec53);
+        h ^= h >> 33;
+        return h;
+    }
+}
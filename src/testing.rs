@@ -0,0 +1,463 @@
+//! Utilities for testing implementations of the traits of this crate, available with the `testing` feature.
+
+use core::future::Future;
+use core::ops::Bound::{Excluded, Included, Unbounded};
+use core::pin::pin;
+use core::task::{Context, Poll};
+use std::fmt::Debug;
+use std::vec;
+use std::vec::Vec;
+
+use futures::task::noop_waker_ref;
+
+use crate::{BackEnd, Dimension, ReadBackEnd};
+
+/// Check that the encodings of two values of a dimension do not violate the contracts of the [`Dimension`] trait: encodings must have the claimed lengths, must not contain consecutive zero bytes if they are variable-width, must decode to the original value, must be order-homomorphic, and must be prefix-free if the dimension claims so. Panics with a detailed message otherwise.
+pub fn assert_dimension_works<D: Dimension + Debug>(v1: &D, v2: &D) {
+    let mut v1_buf = vec![0; D::HOMOMORPHIC_ENCODING_MAX_LENGTH];
+
+    let v1_encoding_len = v1.homomorphic_encode(&mut v1_buf);
+
+    assert_eq!(
+        v1.encoded_len(),
+        v1_encoding_len,
+        "\n\nencoded_len does not match the length of the actual encoding.
+value: {:?}
+encoding: {:?}\n\n",
+        v1,
+        &v1_buf[0..v1_encoding_len]
+    );
+
+    if D::IS_FIXED_WIDTH_ENCODING {
+        assert_eq!(
+            v1_encoding_len,
+            D::HOMOMORPHIC_ENCODING_MAX_LENGTH,
+            "\n\nDimension claims to produce fixed-width encodings, but got an encoding of length other than the claimed fixed width.
+value: {:?}
+encoding: {:?}
+actual encoding length: {:?}
+claimed fixed width (HOMOMORPHIC_ENCODING_MAX_LENGTH): {:?}\n\n", v1, &v1_buf[0..v1_encoding_len], v1_encoding_len, D::HOMOMORPHIC_ENCODING_MAX_LENGTH
+        );
+    } else {
+        assert!(
+            v1_encoding_len <= D::HOMOMORPHIC_ENCODING_MAX_LENGTH,
+            "\n\nOverlong encoding.
+value: {:?}
+encoding: {:?}
+encoding length: {:?}
+claimed maximum length (HOMOMORPHIC_ENCODING_MAX_LENGTH): {:?}\n\n",
+            v1,
+            &v1_buf[0..v1_encoding_len],
+            v1_encoding_len,
+            D::HOMOMORPHIC_ENCODING_MAX_LENGTH
+        );
+
+        for i in 0..v1_encoding_len {
+            if i > 0 && v1_buf[i] == 0 && v1_buf[i - 1] == 0 {
+                panic!(
+                    "A variable-width encoding must not contain consecutive zero bytes.
+value: {:?}
+encoding: {:?}
+index of first of the consecutive zero bytes: {:?}\n\n",
+                    v1,
+                    &v1_buf[0..v1_encoding_len],
+                    i - 1
+                );
+            }
+        }
+    }
+
+    let (v1_decoded, v1_num_decoded_bytes) = D::homomorphic_decode(&v1_buf).unwrap();
+
+    assert_eq!(
+        &v1_decoded,
+        v1,
+        "\n\nDecoding the encoding did not yield the original value.
+value: {:?}
+encoding: {:?}
+decoded: {:?}
+number of decoded bytes by the decoding function: {:?}\n\n",
+        v1,
+        &v1_buf[0..v1_encoding_len],
+        v1_decoded,
+        v1_num_decoded_bytes
+    );
+
+    assert_eq!(
+        v1_num_decoded_bytes,
+        v1_encoding_len,
+        "\n\nDecoding reported a different length than the encoding process.
+value: {:?}
+encoding: {:?}
+encoding length as reported by the encoding function: {:?}
+number of decoded bytes by the decoding function: {:?}\n\n",
+        v1,
+        &v1_buf[0..v1_encoding_len],
+        v1_encoding_len,
+        v1_num_decoded_bytes
+    );
+
+    let mut v2_buf = vec![0; D::HOMOMORPHIC_ENCODING_MAX_LENGTH];
+
+    let v2_encoding_len = v2.homomorphic_encode(&mut v2_buf);
+
+    // Test that the encoding is homomorphic.
+    assert_eq!(
+        v1.cmp(v2),
+        v1_buf[0..v1_encoding_len].cmp(&v2_buf[0..v2_encoding_len]),
+        "\n\nEncoding is not homomorphic:
+v1: {:?}
+v2: {:?}
+v1.cmp(v2): {:?}
+encoding of v1: {:?}
+encoding of v2: {:?}
+v1_enc.cmp(v2.enc): {:?}\n\n",
+        v1,
+        v2,
+        v1.cmp(v2),
+        &v1_buf[0..v1_encoding_len],
+        &v2_buf[0..v2_encoding_len],
+        v1_buf[0..v1_encoding_len].cmp(&v2_buf[0..v2_encoding_len])
+    );
+
+    if D::IS_PREFIX_FREE && !D::IS_FIXED_WIDTH_ENCODING {
+        assert!(
+            v1 == v2 || !v2_buf[0..v2_encoding_len].starts_with(&v1_buf[0..v1_encoding_len]),
+            "\n\nDimension claims prefix-free encodings, but the encoding of v1 is a strict prefix of that of v2.
+v1: {:?}
+v2: {:?}
+encoding of v1: {:?}
+encoding of v2: {:?}\n\n",
+            v1,
+            v2,
+            &v1_buf[0..v1_encoding_len],
+            &v2_buf[0..v2_encoding_len]
+        );
+
+        for len in 0..v1_encoding_len {
+            assert!(
+                D::homomorphic_decode(&v1_buf[0..len]).is_err(),
+                "\n\nDimension claims prefix-free encodings, but decoding a strict prefix of an encoding succeeded.
+value: {:?}
+encoding: {:?}
+decoded prefix: {:?}\n\n",
+                v1,
+                &v1_buf[0..v1_encoding_len],
+                &v1_buf[0..len]
+            );
+        }
+    }
+}
+
+/// Check that all given sample values of a dimension fulfil the contracts of the [`Dimension`] trait, by calling [`assert_dimension_works`] on every pair of samples.
+pub fn assert_dimension_conformance<D: Dimension + Debug>(samples: &[D]) {
+    for v1 in samples {
+        for v2 in samples {
+            assert_dimension_works(v1, v2);
+        }
+    }
+}
+
+/// Generate a `#[test]` function that checks whether a [`Dimension`] implementation fulfils all contracts of the trait, using [`assert_dimension_conformance`] on the given sample values.
+///
+/// By default, the test function is named like the type, which requires the type to be a plain identifier. For generic types, or for tuple structs (whose constructors already occupy the name), supply a name for the test function explicitly.
+///
+/// ```
+/// # use kv_3d_storage::{dimension_conformance_tests, Timestamp};
+/// dimension_conformance_tests!(u16, [0, 1, 255, 256, u16::MAX]);
+/// dimension_conformance_tests!(timestamp: Timestamp, [Timestamp(0), Timestamp(u64::MAX)]);
+/// ```
+#[macro_export]
+macro_rules! dimension_conformance_tests {
+    ($name:ident : $t:ty, [$($sample:expr),* $(,)?]) => {
+        #[test]
+        #[allow(non_snake_case)]
+        fn $name() {
+            let samples: &[$t] = &[$($sample),*];
+            $crate::assert_dimension_conformance(samples);
+        }
+    };
+    ($t:ident, [$($sample:expr),* $(,)?]) => {
+        $crate::dimension_conformance_tests!($t: $t, [$($sample),*]);
+    };
+}
+
+/// Check that a [`BackEnd`] implementation fulfils the contracts of the trait. This covers the return values of [`insert`](BackEnd::insert) and [`delete`](BackEnd::delete), reads that must reflect mutations which have not been flushed yet, [`get_many`](BackEnd::get_many), [`find_lte`](BackEnd::find_lte), [`find_gte`](BackEnd::find_gte), [`find_nearest`](BackEnd::find_nearest), and [`page_after`](BackEnd::page_after) at, between, and outside of the stored keys, [`delete_range`](BackEnd::delete_range) with inclusive, exclusive, and empty ranges, reads after a [`flush`](BackEnd::flush), and [snapshots](BackEnd::snapshot) that must not reflect later mutations. Panics if any check fails.
+///
+/// `make` must return a new, empty backend on every call.
+pub async fn run_backend_conformance<B: BackEnd<u64>>(make: impl Fn() -> B)
+where
+    B::Error: Debug,
+{
+    // Insertion, overwriting, and deletion.
+    let mut backend = make();
+    assert_eq!(backend.get(b"a").await.unwrap(), None);
+    assert_eq!(backend.insert(b"a", 1).await.unwrap(), None);
+    assert_eq!(backend.get(b"a").await.unwrap(), Some(1));
+    assert_eq!(backend.insert(b"a", 2).await.unwrap(), Some(1));
+    assert_eq!(backend.get(b"a").await.unwrap(), Some(2));
+    assert_eq!(backend.delete(b"a").await.unwrap(), Some(2));
+    assert_eq!(backend.get(b"a").await.unwrap(), None);
+    assert_eq!(backend.delete(b"a").await.unwrap(), None);
+    assert_eq!(backend.delete(b"never inserted").await.unwrap(), None);
+
+    // The empty key and keys that are prefixes of each other are distinct keys.
+    assert_eq!(backend.insert(b"", 10).await.unwrap(), None);
+    assert_eq!(backend.insert(b"b", 11).await.unwrap(), None);
+    assert_eq!(backend.insert(b"b\0", 12).await.unwrap(), None);
+    assert_eq!(backend.get(b"").await.unwrap(), Some(10));
+    assert_eq!(backend.get(b"b").await.unwrap(), Some(11));
+    assert_eq!(backend.get(b"b\0").await.unwrap(), Some(12));
+
+    // Searching an empty backend.
+    let backend = make();
+    assert_eq!(backend.find_lte(b"").await.unwrap(), None);
+    assert_eq!(backend.find_lte(b"m").await.unwrap(), None);
+    assert_eq!(backend.find_gte(b"").await.unwrap(), None);
+    assert_eq!(backend.find_gte(b"m").await.unwrap(), None);
+
+    // Searching, both before and after flushing.
+    let mut backend = make();
+    for (key, value) in [(&b"d"[..], 1), (b"f", 2), (b"f\0", 3), (b"h", 4)] {
+        backend.insert(key, value).await.unwrap();
+    }
+    assert_searches_work(&backend, &[(b"d", 1), (b"f", 2), (b"f\0", 3), (b"h", 4)]).await;
+
+    backend.flush().await.unwrap();
+    assert_searches_work(&backend, &[(b"d", 1), (b"f", 2), (b"f\0", 3), (b"h", 4)]).await;
+
+    // Mutations after a flush are visible immediately, mixed with the flushed kv pairs.
+    assert_eq!(backend.delete(b"f").await.unwrap(), Some(2));
+    assert_eq!(backend.insert(b"h", 5).await.unwrap(), Some(4));
+    assert_eq!(backend.insert(b"b", 6).await.unwrap(), None);
+    assert_searches_work(&backend, &[(b"b", 6), (b"d", 1), (b"f\0", 3), (b"h", 5)]).await;
+
+    // A snapshot of flushed kv pairs and pending mutations ignores all later mutations, flushed or not.
+    let snapshot = backend.snapshot().await.unwrap();
+    backend.flush().await.unwrap();
+    assert_searches_work(&backend, &[(b"b", 6), (b"d", 1), (b"f\0", 3), (b"h", 5)]).await;
+
+    assert_eq!(backend.insert(b"e", 7).await.unwrap(), None);
+    assert_eq!(backend.delete(b"d").await.unwrap(), Some(1));
+    backend.flush().await.unwrap();
+    assert_eq!(backend.insert(b"b", 8).await.unwrap(), Some(6));
+    assert_snapshot_works(&snapshot, &[(b"b", 6), (b"d", 1), (b"f\0", 3), (b"h", 5)]).await;
+    drop(snapshot);
+
+    assert_eq!(backend.insert(b"d", 1).await.unwrap(), None);
+    assert_eq!(backend.delete(b"e").await.unwrap(), Some(7));
+    assert_eq!(backend.insert(b"b", 6).await.unwrap(), Some(8));
+
+    // Flushing without pending mutations changes nothing.
+    backend.flush().await.unwrap();
+    assert_searches_work(&backend, &[(b"b", 6), (b"d", 1), (b"f\0", 3), (b"h", 5)]).await;
+
+    // Deleting ranges of flushed kv pairs and pending mutations alike.
+    let mut backend = make();
+    for (key, value) in [(&b"a"[..], 1), (b"b", 2), (b"c", 3), (b"d", 4), (b"e", 5)] {
+        backend.insert(key, value).await.unwrap();
+    }
+    backend.flush().await.unwrap();
+    assert_eq!(backend.insert(b"c\0", 6).await.unwrap(), None);
+    assert_eq!(backend.delete(b"e").await.unwrap(), Some(5));
+
+    assert_eq!(backend.delete_range(Included(&b"b"[..]), Included(&b"c\0"[..])).await.unwrap(), 3);
+    assert_searches_work(&backend, &[(b"a", 1), (b"d", 4)]).await;
+
+    assert_eq!(backend.insert(b"b", 7).await.unwrap(), None);
+    assert_eq!(backend.insert(b"c", 8).await.unwrap(), None);
+    assert_eq!(backend.delete_range(Excluded(&b"a"[..]), Excluded(&b"d"[..])).await.unwrap(), 2);
+    assert_searches_work(&backend, &[(b"a", 1), (b"d", 4)]).await;
+
+    // Empty ranges, and ranges without any stored keys.
+    assert_eq!(backend.delete_range(Included(&b"d"[..]), Excluded(&b"a"[..])).await.unwrap(), 0);
+    assert_eq!(backend.delete_range(Excluded(&b"a"[..]), Excluded(&b"a"[..])).await.unwrap(), 0);
+    assert_eq!(backend.delete_range(Included(&b"b"[..]), Included(&b"c"[..])).await.unwrap(), 0);
+    assert_searches_work(&backend, &[(b"a", 1), (b"d", 4)]).await;
+
+    backend.flush().await.unwrap();
+    assert_searches_work(&backend, &[(b"a", 1), (b"d", 4)]).await;
+    assert_eq!(backend.delete_range(Unbounded, Unbounded).await.unwrap(), 2);
+    backend.flush().await.unwrap();
+    assert_searches_work(&backend, &[]).await;
+}
+
+/// Check that cancelling a [`flush`](BackEnd::flush) leaves a [`BackEnd`] consistent, as the contract of `flush` demands. For each number of polls from zero to three, this polls the Future of a flush of some mutations that many times (or until it is done), drops it, and then checks that all reads still incorporate every mutation, both immediately and after a subsequent complete flush. Panics if any check fails.
+///
+/// `make` must return a new, empty backend on every call.
+pub async fn assert_flush_cancel_safe<B: BackEnd<u64>>(make: impl Fn() -> B)
+where
+    B::Error: Debug,
+{
+    for polls in 0..4 {
+        let mut backend = make();
+        for (key, value) in [(&b"a"[..], 1), (b"b", 2), (b"c", 3)] {
+            backend.insert(key, value).await.unwrap();
+        }
+        backend.flush().await.unwrap();
+
+        // Mutations that are pending when the flush is cancelled.
+        assert_eq!(backend.delete(b"b").await.unwrap(), Some(2));
+        assert_eq!(backend.insert(b"c", 4).await.unwrap(), Some(3));
+        assert_eq!(backend.insert(b"d", 5).await.unwrap(), None);
+
+        {
+            let mut flush = pin!(backend.flush());
+            let mut cx = Context::from_waker(noop_waker_ref());
+            for _ in 0..polls {
+                if let Poll::Ready(result) = flush.as_mut().poll(&mut cx) {
+                    result.unwrap();
+                    break;
+                }
+            }
+        }
+
+        // Whatever the cancelled flush did or did not persist, reads incorporate all mutations.
+        let expected: [(&[u8], u64); 3] = [(b"a", 1), (b"c", 4), (b"d", 5)];
+        assert_searches_work(&backend, &expected).await;
+
+        backend.flush().await.unwrap();
+        assert_searches_work(&backend, &expected).await;
+
+        // The backend remains usable.
+        assert_eq!(backend.insert(b"b", 6).await.unwrap(), None);
+        backend.flush().await.unwrap();
+        assert_searches_work(&backend, &[(b"a", 1), (b"b", 6), (b"c", 4), (b"d", 5)]).await;
+    }
+}
+
+// Check `get`, `find_lte`, `find_gte`, and `range` of a snapshot against the expected kv pairs (sorted by key).
+async fn assert_snapshot_works<S: ReadBackEnd<u64>>(snapshot: &S, expected: &[(&[u8], u64)])
+where
+    S::Error: Debug,
+{
+    let mut queries: Vec<Vec<u8>> = vec![vec![], b"z".to_vec()];
+    queries.extend(expected.iter().map(|(key, _)| key.to_vec()));
+    queries.extend(expected.iter().map(|(key, _)| [*key, &[0]].concat()));
+
+    for query in queries.iter() {
+        let get = expected
+            .iter()
+            .find(|(key, _)| *key == &query[..])
+            .map(|(_, value)| *value);
+        let lte = expected
+            .iter()
+            .rev()
+            .find(|(key, _)| *key <= &query[..])
+            .map(|(key, value)| (key.to_vec(), *value));
+        let gte = expected
+            .iter()
+            .find(|(key, _)| *key >= &query[..])
+            .map(|(key, value)| (key.to_vec(), *value));
+
+        assert_eq!(snapshot.get(query).await.unwrap(), get, "snapshot get {:?}", query);
+        assert_eq!(snapshot.find_lte(query).await.unwrap(), lte, "snapshot find_lte {:?}", query);
+        assert_eq!(snapshot.find_gte(query).await.unwrap(), gte, "snapshot find_gte {:?}", query);
+    }
+
+    for lo in queries.iter() {
+        for hi in queries.iter() {
+            let range: Vec<_> = expected
+                .iter()
+                .filter(|(key, _)| *key >= &lo[..] && *key < &hi[..])
+                .map(|(key, value)| (key.to_vec(), *value))
+                .collect();
+            let actual: Vec<_> = snapshot
+                .range(Included(&lo[..]), Excluded(&hi[..]))
+                .await
+                .unwrap()
+                .collect();
+            assert_eq!(actual, range, "snapshot range {:?}..{:?}", lo, hi);
+        }
+    }
+
+    let all: Vec<_> = expected.iter().map(|(key, value)| (key.to_vec(), *value)).collect();
+    assert_eq!(snapshot.range(Unbounded, Unbounded).await.unwrap().collect::<Vec<_>>(), all, "snapshot range ..");
+}
+
+// Check `get`, `get_many`, `find_lte`, `find_gte`, `find_nearest`, and `page_after` against the expected kv pairs (sorted by key), querying every stored key, the keys between them, and the keys outside of them.
+async fn assert_searches_work<B: BackEnd<u64>>(backend: &B, expected: &[(&[u8], u64)])
+where
+    B::Error: Debug,
+{
+    let mut queries: Vec<Vec<u8>> = vec![vec![], vec![0], b"a".to_vec(), b"z".to_vec()];
+    for (key, _) in expected {
+        queries.push(key.to_vec());
+
+        // Directly above and below (in the case of nonzero last bytes) the key.
+        let mut above = key.to_vec();
+        above.push(0);
+        queries.push(above);
+
+        if let Some((last, init)) = key.split_last() {
+            if *last > 0 {
+                let mut below = init.to_vec();
+                below.push(*last - 1);
+                below.push(0xff);
+                queries.push(below);
+            }
+        }
+    }
+
+    // Batched lookups of all queries at once, in query order.
+    let keys: Vec<&[u8]> = queries.iter().map(|query| query.as_slice()).collect();
+    let values: Vec<_> = keys
+        .iter()
+        .map(|key| expected.iter().find(|(k, _)| k == key).map(|(_, value)| *value))
+        .collect();
+    assert_eq!(backend.get_many(&keys).await.unwrap(), values, "get_many");
+
+    for query in queries {
+        let get = expected
+            .iter()
+            .find(|(key, _)| *key == &query[..])
+            .map(|(_, value)| *value);
+        let lte = expected
+            .iter()
+            .rev()
+            .find(|(key, _)| *key <= &query[..])
+            .map(|(key, value)| (key.to_vec(), *value));
+        let gte = expected
+            .iter()
+            .find(|(key, _)| *key >= &query[..])
+            .map(|(key, value)| (key.to_vec(), *value));
+
+        assert_eq!(backend.get(&query).await.unwrap(), get, "get({:?})", query);
+        assert_eq!(backend.find_lte(&query).await.unwrap(), lte, "find_lte({:?})", query);
+        assert_eq!(backend.find_gte(&query).await.unwrap(), gte, "find_gte({:?})", query);
+
+        // The nearest kv pair is the one whose key shares the longest prefix with the query, ties favour the lesser key.
+        let shared = |key: &[u8]| key.iter().zip(&query).take_while(|(a, b)| a == b).count();
+        let nearest = match (lte, gte) {
+            (Some(lte), Some(gte)) if shared(&gte.0) > shared(&lte.0) => Some(gte),
+            (Some(lte), _) => Some(lte),
+            (None, gte) => gte,
+        };
+        assert_eq!(backend.find_nearest(&query).await.unwrap(), nearest, "find_nearest({:?})", query);
+
+        let after: Vec<_> = expected
+            .iter()
+            .filter(|(key, _)| *key > &query[..])
+            .map(|(key, value)| (key.to_vec(), *value))
+            .collect();
+        for limit in [0, 1, 2, expected.len() + 1] {
+            let page = after.iter().take(limit).cloned().collect::<Vec<_>>();
+            assert_eq!(
+                backend.page_after(Some(&query), limit).await.unwrap(),
+                page,
+                "page_after({:?}, {})",
+                query,
+                limit
+            );
+        }
+    }
+
+    for limit in [0, 1, 2, expected.len() + 1] {
+        let page: Vec<_> = expected
+            .iter()
+            .take(limit)
+            .map(|(key, value)| (key.to_vec(), *value))
+            .collect();
+        assert_eq!(backend.page_after(None, limit).await.unwrap(), page, "page_after(None, {})", limit);
+    }
+}
@@ -0,0 +1,93 @@
+//! Checks that `Point3d::encode_xyz_append` appends encodings that can be decoded back one after the other.
+
+use std::fmt::Debug;
+
+use kv_3d_storage::{DecodeError, Dimension, Point3d};
+
+/// A variable-width dimension whose encodings end with a zero byte: `n` is encoded as `n` times the byte `0x01`, followed by a single `0x00`.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug)]
+struct Unary(u8);
+
+impl Dimension for Unary {
+    const HOMOMORPHIC_ENCODING_MAX_LENGTH: usize = 256;
+
+    const IS_FIXED_WIDTH_ENCODING: bool = false;
+
+    fn homomorphic_encode(&self, buf: &mut [u8]) -> usize {
+        let n = self.0 as usize;
+        buf[..n].fill(1);
+        buf[n] = 0;
+        return n + 1;
+    }
+
+    fn homomorphic_decode(buf: &[u8]) -> Result<(Self, usize), DecodeError> {
+        match buf.iter().position(|byte| *byte != 1) {
+            Some(n) if n < 256 && buf[n] == 0 => return Ok((Unary(n as u8), n + 1)),
+            _ => return Err(DecodeError::InvalidValue),
+        }
+    }
+}
+
+// Append all points, each preceded by a marker byte, then decode them back in order.
+fn assert_appends_decode<X, Y, Z>(points: &[Point3d<X, Y, Z>])
+where
+    X: Dimension + Debug + PartialEq,
+    Y: Dimension + Debug + PartialEq,
+    Z: Dimension + Debug + PartialEq,
+{
+    let mut buf = vec![];
+    for (i, point) in points.iter().enumerate() {
+        buf.push(i as u8);
+
+        let start = buf.len();
+        let len = point.encode_xyz_append(&mut buf);
+        assert_eq!(buf.len(), start + len);
+        assert_eq!(len, point.encoded_len_xyz());
+    }
+
+    let mut offset = 0;
+    for (i, point) in points.iter().enumerate() {
+        assert_eq!(buf[offset], i as u8);
+        offset += 1;
+
+        let (decoded, len) = Point3d::<X, Y, Z>::decode_xyz(&buf[offset..]).unwrap();
+        assert_eq!(&decoded, point);
+        offset += len;
+    }
+    assert_eq!(offset, buf.len());
+}
+
+#[test]
+fn fixed_width_points() {
+    assert_appends_decode(&[
+        Point3d::<u8, u16, u32>::from((1, 2, 3)),
+        Point3d::from((0, 0, 0)),
+        Point3d::from((u8::MAX, u16::MAX, u32::MAX)),
+    ]);
+}
+
+#[test]
+fn variable_width_points() {
+    assert_appends_decode(&[
+        Point3d::from((Unary(2), 7u16, Unary(0))),
+        Point3d::from((Unary(0), 0u16, Unary(5))),
+        Point3d::from((Unary(255), u16::MAX, Unary(255))),
+    ]);
+}
+
+#[test]
+fn appending_keeps_existing_bytes() {
+    let point = Point3d::from((Unary(3), 9u16, Unary(1)));
+
+    let mut buf = vec![0xaa, 0xbb];
+    let len = point.encode_xyz_append(&mut buf);
+
+    let mut expected = vec![0xaa, 0xbb];
+    let mut encoding = vec![0; Point3d::<Unary, u16, Unary>::max_encoding_len_xyz()];
+    let encoding_len = point.encode_xyz(&mut encoding);
+    encoding.truncate(encoding_len);
+    expected.extend_from_slice(&encoding);
+
+    assert_eq!(len, encoding.len());
+    assert_eq!(buf, expected);
+}
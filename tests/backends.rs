@@ -0,0 +1,363 @@
+//! Checks the `BackEnd` implementations of this crate with `run_backend_conformance`. Requires the `testing` feature: run `cargo test --features testing --test backends`.
+
+use std::ops::Bound::{Excluded, Included, Unbounded};
+use std::sync::Arc;
+use std::thread;
+
+use std::convert::Infallible;
+
+use kv_3d_storage::{
+    assert_flush_cancel_safe, run_backend_conformance, AutoFlush, BTreeMapBackEnd, BTreeMapSnapshot, BackEnd,
+    ReadBackEnd, SharedBTreeBackEnd, SharedBackEnd, SizeTracked,
+};
+
+#[test]
+fn btree_map_backend() {
+    pollster::block_on(run_backend_conformance(BTreeMapBackEnd::new));
+}
+
+#[test]
+fn shared_btree_backend() {
+    pollster::block_on(run_backend_conformance(SharedBTreeBackEnd::new));
+}
+
+#[test]
+fn size_tracked_backend() {
+    pollster::block_on(run_backend_conformance(|| SizeTracked::new(BTreeMapBackEnd::new())));
+}
+
+#[test]
+fn auto_flush_backend() {
+    pollster::block_on(run_backend_conformance(|| AutoFlush::new(BTreeMapBackEnd::new(), 3)));
+}
+
+#[test]
+fn flush_is_cancel_safe() {
+    pollster::block_on(assert_flush_cancel_safe(BTreeMapBackEnd::new));
+    pollster::block_on(assert_flush_cancel_safe(SharedBTreeBackEnd::new));
+    pollster::block_on(assert_flush_cancel_safe(|| AutoFlush::new(BTreeMapBackEnd::new(), 2)));
+}
+
+#[test]
+fn shared_btree_backend_concurrent_inserts() {
+    let backend = Arc::new(SharedBTreeBackEnd::new());
+
+    let handles: Vec<_> = (0..8u64)
+        .map(|task| {
+            let backend = Arc::clone(&backend);
+            thread::spawn(move || {
+                pollster::block_on(async {
+                    for i in 0..100u64 {
+                        let key = [task.to_be_bytes(), i.to_be_bytes()].concat();
+                        let old = SharedBackEnd::insert(&*backend, &key, task * 1000 + i).await;
+                        assert_eq!(old, Ok(None));
+                    }
+                });
+            })
+        })
+        .collect();
+
+    for handle in handles {
+        handle.join().unwrap();
+    }
+
+    let map = Arc::into_inner(backend).unwrap().into_map();
+    assert_eq!(map.len(), 800);
+    for (key, value) in map {
+        let task = u64::from_be_bytes(key[..8].try_into().unwrap());
+        let i = u64::from_be_bytes(key[8..].try_into().unwrap());
+        assert_eq!(value, task * 1000 + i);
+    }
+}
+
+#[test]
+fn find_nearest_prefers_longer_common_prefix() {
+    pollster::block_on(async {
+        let mut backend = BTreeMapBackEnd::new();
+        backend.insert(b"apple", 1).await.unwrap();
+        backend.insert(b"banana", 2).await.unwrap();
+
+        // Exactly present.
+        assert_eq!(backend.find_nearest(b"banana").await, Ok(Some((b"banana".to_vec(), 2))));
+        // Between two keys, sharing "ap" with the lesser one.
+        assert_eq!(backend.find_nearest(b"apricot").await, Ok(Some((b"apple".to_vec(), 1))));
+        // Between two keys, sharing "b" with the greater one.
+        assert_eq!(backend.find_nearest(b"b").await, Ok(Some((b"banana".to_vec(), 2))));
+        // Between two keys, sharing "a" with both, so the lesser one wins.
+        backend.insert(b"avocado", 3).await.unwrap();
+        assert_eq!(backend.find_nearest(b"ash").await, Ok(Some((b"apple".to_vec(), 1))));
+        // Before and after all keys.
+        assert_eq!(backend.find_nearest(b"").await, Ok(Some((b"apple".to_vec(), 1))));
+        assert_eq!(backend.find_nearest(b"cherry").await, Ok(Some((b"banana".to_vec(), 2))));
+
+        assert_eq!(BTreeMapBackEnd::<u64>::new().find_nearest(b"apple").await, Ok(None));
+    });
+}
+
+#[test]
+fn page_after_covers_all_entries() {
+    pollster::block_on(async {
+        let mut backend = BTreeMapBackEnd::new();
+        for i in 0..1000u64 {
+            backend.insert(&i.to_be_bytes(), i).await.unwrap();
+        }
+
+        let mut seen = vec![];
+        let mut cursor: Option<Vec<u8>> = None;
+        loop {
+            let page = backend.page_after(cursor.as_deref(), 100).await.unwrap();
+            if page.is_empty() {
+                break;
+            }
+
+            assert_eq!(page.len(), 100);
+            cursor = Some(page.last().unwrap().0.clone());
+            seen.extend(page.into_iter().map(|(_, value)| value));
+        }
+
+        // Every entry exactly once, in order.
+        assert_eq!(seen, (0..1000).collect::<Vec<_>>());
+    });
+}
+
+#[test]
+fn size_tracked_returns_to_zero() {
+    pollster::block_on(async {
+        let mut backend = SizeTracked::new(BTreeMapBackEnd::new());
+        assert_eq!(backend.approx_size(), 0);
+
+        backend.insert(b"a", vec![1, 2, 3]).await.unwrap();
+        assert_eq!(backend.approx_size(), 4);
+        backend.insert(b"bcd", vec![]).await.unwrap();
+        assert_eq!(backend.approx_size(), 7);
+
+        // Overwriting replaces the size of the old value.
+        backend.insert(b"a", vec![1]).await.unwrap();
+        assert_eq!(backend.approx_size(), 5);
+
+        // Deleting a missing key changes nothing.
+        backend.delete(b"zzz").await.unwrap();
+        assert_eq!(backend.approx_size(), 5);
+
+        // Updates go through insertion and deletion.
+        backend.update_with(b"a", |_| Some(vec![0; 10])).await.unwrap();
+        assert_eq!(backend.approx_size(), 14);
+
+        backend.delete(b"a").await.unwrap();
+        backend.update_with(b"bcd", |_| None).await.unwrap();
+        assert_eq!(backend.approx_size(), 0);
+        assert_eq!(backend.into_inner().into_map().len(), 0);
+    });
+}
+
+// An in-memory backend that counts how often it was flushed.
+#[derive(Default)]
+struct FlushCounter {
+    map: BTreeMapBackEnd<u64>,
+    flushes: usize,
+}
+
+impl BackEnd<u64> for FlushCounter {
+    type Error = Infallible;
+    type Snapshot = BTreeMapSnapshot<u64>;
+
+    async fn get(&self, key: &[u8]) -> Result<Option<u64>, Self::Error> {
+        return self.map.get(key).await;
+    }
+
+    async fn find_lte(&self, key: &[u8]) -> Result<Option<(Vec<u8>, u64)>, Self::Error> {
+        return self.map.find_lte(key).await;
+    }
+
+    async fn find_gte(&self, key: &[u8]) -> Result<Option<(Vec<u8>, u64)>, Self::Error> {
+        return self.map.find_gte(key).await;
+    }
+
+    async fn insert(&mut self, key: &[u8], value: u64) -> Result<Option<u64>, Self::Error> {
+        return self.map.insert(key, value).await;
+    }
+
+    async fn delete(&mut self, key: &[u8]) -> Result<Option<u64>, Self::Error> {
+        return self.map.delete(key).await;
+    }
+
+    async fn snapshot(&self) -> Result<Self::Snapshot, Self::Error> {
+        return self.map.snapshot().await;
+    }
+
+    async fn flush(&mut self) -> Result<(), Self::Error> {
+        self.flushes += 1;
+        return Ok(());
+    }
+}
+
+#[test]
+fn auto_flush_fires_every_threshold_mutations() {
+    pollster::block_on(async {
+        let mut backend = AutoFlush::new(FlushCounter::default(), 10);
+
+        for i in 0..25u64 {
+            backend.insert(&i.to_be_bytes(), i).await.unwrap();
+            assert_eq!(backend.inner().flushes, (i as usize + 1) / 10);
+            assert_eq!(backend.dirty(), (i as usize + 1) % 10);
+        }
+
+        // Reads are not mutations, deletions are (even of missing keys).
+        backend.get(&[0]).await.unwrap();
+        for i in 20..25u64 {
+            backend.delete(&i.to_be_bytes()).await.unwrap();
+        }
+        assert_eq!(backend.inner().flushes, 3);
+        assert_eq!(backend.dirty(), 0);
+        backend.delete(b"missing").await.unwrap();
+        assert_eq!(backend.dirty(), 1);
+
+        // Forcing a flush resets the count.
+        backend.force_flush().await.unwrap();
+        assert_eq!(backend.inner().flushes, 4);
+        assert_eq!(backend.dirty(), 0);
+        for i in 0..9u64 {
+            backend.delete(&i.to_be_bytes()).await.unwrap();
+        }
+        assert_eq!(backend.inner().flushes, 4);
+        backend.delete(&[9]).await.unwrap();
+        assert_eq!(backend.inner().flushes, 5);
+    });
+}
+
+#[test]
+fn get_many_is_positional() {
+    pollster::block_on(async {
+        let mut backend = BTreeMapBackEnd::new();
+        backend.insert(b"a", 1u64).await.unwrap();
+        backend.insert(b"c", 3).await.unwrap();
+
+        let keys: [&[u8]; 6] = [b"c", b"b", b"a", b"", b"c", b"a\0"];
+        let expected = vec![Some(3), None, Some(1), None, Some(3), None];
+        assert_eq!(backend.get_many(&keys).await.unwrap(), expected);
+
+        // The default implementation agrees.
+        let shared = SharedBTreeBackEnd::new();
+        SharedBackEnd::insert(&shared, b"a", 1u64).await.unwrap();
+        SharedBackEnd::insert(&shared, b"c", 3).await.unwrap();
+        assert_eq!(BackEnd::get_many(&shared, &keys).await.unwrap(), expected);
+
+        assert_eq!(backend.get_many(&[]).await.unwrap(), vec![]);
+    });
+}
+
+#[test]
+fn snapshot_ignores_later_mutations() {
+    pollster::block_on(async {
+        let mut backend = BTreeMapBackEnd::new();
+        backend.insert(b"a", 1u64).await.unwrap();
+        let snapshot = backend.snapshot().await.unwrap();
+
+        backend.insert(b"a", 2).await.unwrap();
+        backend.insert(b"b", 3).await.unwrap();
+        assert_eq!(snapshot.get(b"a").await.unwrap(), Some(1));
+        assert_eq!(snapshot.get(b"b").await.unwrap(), None);
+
+        let shared = SharedBTreeBackEnd::new();
+        SharedBackEnd::insert(&shared, b"a", 1u64).await.unwrap();
+        let snapshot = BackEnd::snapshot(&shared).await.unwrap();
+
+        SharedBackEnd::delete(&shared, b"a").await.unwrap();
+        assert_eq!(snapshot.find_gte(b"").await.unwrap(), Some((b"a".to_vec(), 1)));
+        assert_eq!(BackEnd::find_gte(&shared, b"").await.unwrap(), None);
+    });
+}
+
+#[test]
+fn get_or_insert_with_only_computes_absent_values() {
+    pollster::block_on(async {
+        let mut backend = SizeTracked::new(BTreeMapBackEnd::new());
+        let mut calls = 0;
+
+        let value = backend
+            .get_or_insert_with(b"a", || {
+                calls += 1;
+                return 1u64;
+            })
+            .await
+            .unwrap();
+        assert_eq!((value, calls), (1, 1));
+        assert_eq!(backend.get(b"a").await.unwrap(), Some(1));
+
+        // Present keys keep their value, and `f` does not run.
+        let value = backend
+            .get_or_insert_with(b"a", || {
+                calls += 1;
+                return 2;
+            })
+            .await
+            .unwrap();
+        assert_eq!((value, calls), (1, 1));
+
+        // Deleted keys are absent, even before a flush.
+        backend.flush().await.unwrap();
+        backend.delete(b"a").await.unwrap();
+        let value = backend
+            .get_or_insert_with(b"a", || {
+                calls += 1;
+                return 3;
+            })
+            .await
+            .unwrap();
+        assert_eq!((value, calls), (3, 2));
+        assert_eq!(backend.get(b"a").await.unwrap(), Some(3));
+        assert_eq!(backend.into_inner().into_map().len(), 1);
+    });
+}
+
+#[test]
+fn update_with_increments_a_counter() {
+    pollster::block_on(async {
+        let mut backend = SizeTracked::new(BTreeMapBackEnd::new());
+
+        for i in 1..=100u64 {
+            let new = backend
+                .update_with(b"counter", |old| Some(old.unwrap_or(0) + 1))
+                .await
+                .unwrap();
+            assert_eq!(new, Some(i));
+        }
+        assert_eq!(backend.get(b"counter").await.unwrap(), Some(100));
+
+        // Returning `None` deletes the counter.
+        let new = backend.update_with(b"counter", |_| None).await.unwrap();
+        assert_eq!(new, None);
+        assert_eq!(backend.get(b"counter").await.unwrap(), None);
+        assert_eq!(backend.into_inner().into_map().len(), 0);
+    });
+}
+
+#[test]
+fn btree_map_delete_range() {
+    pollster::block_on(async {
+        let mut backend = BTreeMapBackEnd::new();
+        for key in [&b"a"[..], b"b", b"b\0", b"c", b"d", b"e"] {
+            backend.insert(key, key.len() as u64).await.unwrap();
+        }
+        let keys = |backend: &BTreeMapBackEnd<u64>| {
+            return backend.clone().into_map().into_keys().collect::<Vec<_>>();
+        };
+
+        // Inclusive bounds delete the keys at the bounds.
+        assert_eq!(backend.delete_range(Included(&b"b"[..]), Included(&b"c"[..])).await.unwrap(), 3);
+        assert_eq!(keys(&backend), vec![b"a".to_vec(), b"d".to_vec(), b"e".to_vec()]);
+
+        // Exclusive bounds keep them.
+        assert_eq!(backend.delete_range(Excluded(&b"a"[..]), Excluded(&b"e"[..])).await.unwrap(), 1);
+        assert_eq!(keys(&backend), vec![b"a".to_vec(), b"e".to_vec()]);
+
+        // Empty ranges, and ranges without any stored keys, delete nothing.
+        assert_eq!(backend.delete_range(Included(&b"e"[..]), Excluded(&b"a"[..])).await.unwrap(), 0);
+        assert_eq!(backend.delete_range(Excluded(&b"a"[..]), Excluded(&b"a"[..])).await.unwrap(), 0);
+        assert_eq!(backend.delete_range(Included(&b"b"[..]), Included(&b"d"[..])).await.unwrap(), 0);
+        assert_eq!(keys(&backend), vec![b"a".to_vec(), b"e".to_vec()]);
+
+        assert_eq!(backend.delete_range(Unbounded, Unbounded).await.unwrap(), 2);
+        assert_eq!(keys(&backend), Vec::<Vec<u8>>::new());
+    });
+}
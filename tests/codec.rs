@@ -0,0 +1,231 @@
+//! Round-trips values through the `ValueCodec`s of this crate. Requires the `bincode-codec`, `cbor-codec`, `redb-backend`, and `testing` features: run `cargo test --features bincode-codec,cbor-codec,redb-backend,testing --test codec`.
+
+use core::ops::Bound::Unbounded;
+use std::fmt::Debug;
+
+use redb::{backends::InMemoryBackend, Database};
+use serde::{Deserialize, Serialize};
+
+use kv_3d_storage::{
+    assert_flush_cancel_safe, peek_child_ranks, run_backend_conformance, BackEnd, BincodeCodec,
+    BytesCodec, CborCodec, KvNodeCodec, KvTree, KvTreeBuilder, Point3d, Rank, ReadBackEnd,
+    RedbBackEnd, ValueCodec, EMPTY_CHILD,
+};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+enum Shape {
+    Point,
+    Circle { radius: f64 },
+    Polygon(Vec<(i32, i32)>),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Entry {
+    name: String,
+    tags: Vec<String>,
+    shape: Shape,
+    expires: Option<u64>,
+}
+
+fn sample_entries() -> Vec<Entry> {
+    return vec![
+        Entry {
+            name: String::new(),
+            tags: vec![],
+            shape: Shape::Point,
+            expires: None,
+        },
+        Entry {
+            name: "ring".to_string(),
+            tags: vec!["a".to_string(), "ü".to_string()],
+            shape: Shape::Circle { radius: 2.5 },
+            expires: Some(u64::MAX),
+        },
+        Entry {
+            name: "triangle".to_string(),
+            tags: vec!["\0".to_string()],
+            shape: Shape::Polygon(vec![(0, 0), (-1, 7), (i32::MIN, i32::MAX)]),
+            expires: Some(0),
+        },
+    ];
+}
+
+fn assert_round_trips<V: Debug + PartialEq, C: ValueCodec<V>>(codec: &C, values: &[V]) {
+    for value in values {
+        let bytes = codec.encode(value);
+        assert_eq!(&codec.decode(&bytes).unwrap(), value);
+    }
+}
+
+#[test]
+fn bytes_codec_round_trips() {
+    assert_round_trips(&BytesCodec, &[vec![], vec![0u8], vec![1, 2, 3, 0xff]]);
+}
+
+#[test]
+fn bincode_codec_round_trips() {
+    assert_round_trips(&BincodeCodec, &[0u64, 1, u64::MAX]);
+    assert_round_trips(&BincodeCodec, &["".to_string(), "hello".to_string()]);
+    assert_round_trips(&BincodeCodec, &sample_entries());
+
+    let result: Result<Entry, _> = BincodeCodec.decode(&[0xff; 3]);
+    assert!(result.is_err());
+}
+
+#[test]
+fn cbor_codec_round_trips() {
+    assert_round_trips(&CborCodec, &[0u64, 1, u64::MAX]);
+    assert_round_trips(&CborCodec, &["".to_string(), "hello".to_string()]);
+    assert_round_trips(&CborCodec, &sample_entries());
+
+    let result: Result<Entry, _> = CborCodec.decode(&[0xff; 3]);
+    assert!(result.is_err());
+}
+
+#[test]
+fn codecs_reject_the_reserved_rank() {
+    assert_round_trips(&BincodeCodec, &[Rank::MIN, Rank::MAX]);
+    assert_round_trips(&CborCodec, &[Rank::MIN, Rank::MAX]);
+
+    let bincode: Result<Rank, _> = BincodeCodec.decode(&BincodeCodec.encode(&EMPTY_CHILD));
+    assert!(bincode.is_err());
+    let cbor: Result<Rank, _> = CborCodec.decode(&CborCodec.encode(&EMPTY_CHILD));
+    assert!(cbor.is_err());
+}
+
+#[test]
+fn redb_backend_with_codec() {
+    pollster::block_on(async {
+        let db = Database::builder()
+            .create_with_backend(InMemoryBackend::new())
+            .unwrap();
+        let mut backend = RedbBackEnd::with_codec(db, CborCodec).unwrap();
+
+        for (i, entry) in sample_entries().into_iter().enumerate() {
+            backend.insert(&[i as u8], entry).await.unwrap();
+        }
+        backend.flush().await.unwrap();
+
+        for (i, entry) in sample_entries().into_iter().enumerate() {
+            assert_eq!(backend.get(&[i as u8]).await.unwrap(), Some(entry));
+        }
+
+        // Values that the codec cannot decode are reported as corruption.
+        let mut raw = RedbBackEnd::<Vec<u8>>::new(backend.into_database()).unwrap();
+        raw.insert(&[0], vec![0xff]).await.unwrap();
+        raw.flush().await.unwrap();
+
+        let backend = RedbBackEnd::<Entry, _>::with_codec(raw.into_database(), CborCodec).unwrap();
+        assert!(matches!(backend.get(&[0]).await, Err(redb::Error::Corrupted(_))));
+        assert_eq!(backend.get(&[1]).await.unwrap(), Some(sample_entries()[1].clone()));
+    });
+}
+
+#[test]
+fn redb_backend_flush_is_cancel_safe() {
+    pollster::block_on(assert_flush_cancel_safe(|| {
+        let db = Database::builder()
+            .create_with_backend(InMemoryBackend::new())
+            .unwrap();
+        return RedbBackEnd::with_codec(db, BincodeCodec).unwrap();
+    }));
+}
+
+#[test]
+fn redb_backend_conformance() {
+    pollster::block_on(run_backend_conformance(|| {
+        let db = Database::builder()
+            .create_with_backend(InMemoryBackend::new())
+            .unwrap();
+        return RedbBackEnd::with_codec(db, BincodeCodec).unwrap();
+    }));
+}
+
+#[test]
+fn redb_snapshot_ignores_later_flushes() {
+    pollster::block_on(async {
+        let db = Database::builder()
+            .create_with_backend(InMemoryBackend::new())
+            .unwrap();
+        let mut backend = RedbBackEnd::with_codec(db, CborCodec).unwrap();
+        let entries = sample_entries();
+
+        backend.insert(b"flushed", entries[0].clone()).await.unwrap();
+        backend.flush().await.unwrap();
+        backend.insert(b"pending", entries[1].clone()).await.unwrap();
+        let snapshot = backend.snapshot().await.unwrap();
+
+        backend.delete(b"flushed").await.unwrap();
+        backend.insert(b"pending", entries[2].clone()).await.unwrap();
+        backend.flush().await.unwrap();
+
+        assert_eq!(snapshot.get(b"flushed").await.unwrap(), Some(entries[0].clone()));
+        assert_eq!(snapshot.get(b"pending").await.unwrap(), Some(entries[1].clone()));
+        assert_eq!(backend.get(b"flushed").await.unwrap(), None);
+        assert_eq!(backend.get(b"pending").await.unwrap(), Some(entries[2].clone()));
+    });
+}
+
+#[test]
+fn kv_tree_on_redb_backend() {
+    pollster::block_on(async {
+        let db = Database::builder()
+            .create_with_backend(InMemoryBackend::new())
+            .unwrap();
+        let mut tree: KvTree<_, u8, u8, u8, String, usize, _> =
+            KvTreeBuilder::new(RedbBackEnd::new(db).unwrap())
+                .with_value_codec(BincodeCodec)
+                .unwrap()
+                .build();
+
+        for i in 0..50u8 {
+            let point = Point3d::from((i, i / 7, 255 - i));
+            tree.insert_with_strategy(point, i.to_string())
+                .await
+                .unwrap();
+        }
+        tree.flush().await.unwrap();
+
+        for i in 0..50u8 {
+            let point = Point3d::from((i, i / 7, 255 - i));
+            assert_eq!(tree.get(&point).await.unwrap(), Some(i.to_string()));
+        }
+    });
+}
+
+#[test]
+fn kv_tree_on_redb_backend_with_kv_node_codec() {
+    pollster::block_on(async {
+        let db = Database::builder()
+            .create_with_backend(InMemoryBackend::new())
+            .unwrap();
+        let mut tree: KvTree<_, u8, u8, u8, String, usize, _> =
+            KvTreeBuilder::new(RedbBackEnd::new(db).unwrap())
+                .with_value_codec(KvNodeCodec::new(BincodeCodec, BincodeCodec))
+                .unwrap()
+                .build();
+
+        for i in 0..50u8 {
+            let point = Point3d::from((i, i / 7, 255 - i));
+            tree.insert_with_strategy(point, i.to_string())
+                .await
+                .unwrap();
+        }
+        tree.flush().await.unwrap();
+
+        for i in 0..50u8 {
+            let point = Point3d::from((i, i / 7, 255 - i));
+            assert_eq!(tree.get(&point).await.unwrap(), Some(i.to_string()));
+        }
+
+        // The stored bytes start with the rank of the vertex, which is also the first byte of its key.
+        let raw =
+            RedbBackEnd::<Vec<u8>, _>::with_codec(tree.into_backend().into_database(), BytesCodec)
+                .unwrap();
+        let stored: Vec<_> = raw.range(Unbounded, Unbounded).await.unwrap().collect();
+        assert_eq!(stored.len(), 50);
+        for (key, bytes) in stored {
+            assert_eq!(peek_child_ranks(&bytes).unwrap().0.get(), key[0]);
+        }
+    });
+}
@@ -0,0 +1,7 @@
+//! Checks that misuses of the API are rejected at compile time. The expected compiler output lives next to each test case in `tests/ui`; regenerate it with `TRYBUILD=overwrite cargo test --test compile_fail`.
+
+#[test]
+fn compile_fail() {
+    let cases = trybuild::TestCases::new();
+    cases.compile_fail("tests/ui/*.rs");
+}
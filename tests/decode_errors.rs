@@ -0,0 +1,90 @@
+//! Checks that decoding reports the specific `DecodeError` for each way in which an encoding can be invalid.
+
+use kv_3d_storage::{DecodeError, Dimension, EncodedPointXyz, OrderedF64, Point3d, TailBytes};
+
+// A point whose first dimension is variable-width, so that its xyz encoding contains a separator.
+type P = Point3d<(TailBytes, u8), u16, u8>;
+
+fn encode_xyz(point: &P) -> Vec<u8> {
+    let mut buf = vec![0; P::max_encoding_len_xyz()];
+    let len = point.encode_xyz(&mut buf);
+    buf.truncate(len);
+    return buf;
+}
+
+fn point() -> P {
+    return Point3d {
+        x: (TailBytes(vec![1]), 7),
+        y: 0x0203,
+        z: 4,
+    };
+}
+
+#[test]
+fn truncated_encodings_are_unexpected_eof() {
+    assert_eq!(u16::homomorphic_decode(&[1]), Err(DecodeError::UnexpectedEof));
+
+    let encoding = encode_xyz(&point());
+    for len in 0..encoding.len() {
+        assert_eq!(
+            P::decode_xyz(&encoding[..len]),
+            Err(DecodeError::UnexpectedEof),
+            "{:?}",
+            &encoding[..len]
+        );
+    }
+
+    assert_eq!(
+        EncodedPointXyz::<(TailBytes, u8), u16, u8>::new(&encoding[..encoding.len() - 1]).unwrap_err(),
+        DecodeError::UnexpectedEof
+    );
+}
+
+#[test]
+fn missing_separators_are_bad_separators() {
+    let mut encoding = encode_xyz(&point());
+    assert_eq!(&encoding[..6], &[1, 0, 1, 7, 0, 0]);
+
+    // The separator after the x dimension.
+    encoding[5] = 5;
+    assert_eq!(P::decode_xyz(&encoding), Err(DecodeError::BadSeparator));
+
+    // The separator between the components of the pair.
+    assert_eq!(
+        <(TailBytes, u8)>::homomorphic_decode(&[1, 0, 3, 7]),
+        Err(DecodeError::BadSeparator)
+    );
+}
+
+#[test]
+fn invalid_values_are_invalid_values() {
+    // A NaN other than the canonical one.
+    let mut nan = [0; 8];
+    OrderedF64(f64::NAN).homomorphic_encode(&mut nan);
+    nan[7] = 1;
+    assert_eq!(OrderedF64::homomorphic_decode(&nan), Err(DecodeError::InvalidValue));
+
+    // A length prefix with a superfluous zero group, and a length prefix that exceeds the maximum encoding length.
+    assert_eq!(
+        P::decode_xyz_length_prefixed(&[0x80, 0x00]),
+        Err(DecodeError::InvalidValue)
+    );
+    assert_eq!(
+        Point3d::<u8, u8, u8>::decode_xyz_length_prefixed(&[4, 0, 0, 0, 0]),
+        Err(DecodeError::InvalidValue)
+    );
+}
+
+#[test]
+fn trailing_bytes_are_trailing_bytes() {
+    let mut encoding = encode_xyz(&point());
+    assert_eq!(P::decode_xyz_exact(&encoding), Ok(point()));
+
+    encoding.push(0);
+    assert_eq!(P::decode_xyz(&encoding), Ok((point(), encoding.len() - 1)));
+    assert_eq!(P::decode_xyz_exact(&encoding), Err(DecodeError::TrailingBytes));
+    assert_eq!(
+        EncodedPointXyz::<(TailBytes, u8), u16, u8>::new(&encoding).unwrap_err(),
+        DecodeError::TrailingBytes
+    );
+}
@@ -0,0 +1,463 @@
+//! Checks the `Dimension` implementations of this crate with the `dimension_conformance_tests` macro. Requires the `testing` feature: run `cargo test --features testing --test dimensions`.
+
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+use kv_3d_storage::{
+    assert_dimension_works, dimension_conformance_tests, successor_saturating, successor_wrapping,
+    DecodeError, Dimension, DiscreteDimension, Fixed, OrderedF32, OrderedF64, PadFixed, Point3d,
+    Rev, TailBytes, Timestamp,
+};
+
+dimension_conformance_tests!(u8, [0, 1, 0x7f, 0x80, u8::MAX]);
+dimension_conformance_tests!(u16, [0, 1, 0xff, 0x100, u16::MAX]);
+dimension_conformance_tests!(u32, [0, 1, 0xffff, 0x1_0000, u32::MAX]);
+dimension_conformance_tests!(u64, [0, 1, 0xffff_ffff, 0x1_0000_0000, u64::MAX]);
+dimension_conformance_tests!(u128, [0, 1, u64::MAX as u128, u64::MAX as u128 + 1, u128::MAX]);
+
+dimension_conformance_tests!(i8, [i8::MIN, -1, 0, 1, i8::MAX]);
+dimension_conformance_tests!(i16, [i16::MIN, -256, -1, 0, 1, 256, i16::MAX]);
+dimension_conformance_tests!(i32, [i32::MIN, -65536, -1, 0, 1, 65536, i32::MAX]);
+dimension_conformance_tests!(i64, [i64::MIN, -1, 0, 1, i64::MAX]);
+dimension_conformance_tests!(i128, [i128::MIN, -1, 0, 1, i128::MAX]);
+
+dimension_conformance_tests!(unit: (), [()]);
+
+dimension_conformance_tests!(
+    Ipv4Addr,
+    [
+        Ipv4Addr::UNSPECIFIED,
+        Ipv4Addr::new(0, 0, 0, 1),
+        Ipv4Addr::new(10, 0, 0, 255),
+        Ipv4Addr::new(10, 1, 0, 0),
+        Ipv4Addr::LOCALHOST,
+        Ipv4Addr::BROADCAST,
+    ]
+);
+dimension_conformance_tests!(
+    Ipv6Addr,
+    [
+        Ipv6Addr::UNSPECIFIED,
+        Ipv6Addr::LOCALHOST,
+        Ipv4Addr::new(192, 0, 2, 1).to_ipv6_mapped(),
+        Ipv4Addr::BROADCAST.to_ipv6_mapped(),
+        Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 1),
+        Ipv6Addr::new(0xffff, 0xffff, 0xffff, 0xffff, 0xffff, 0xffff, 0xffff, 0xffff),
+    ]
+);
+
+dimension_conformance_tests!(
+    timestamp: Timestamp,
+    [Timestamp(0), Timestamp(1), Timestamp(1_700_000_000_000_000), Timestamp(u64::MAX)]
+);
+
+dimension_conformance_tests!(
+    fixed: Fixed<2>,
+    [
+        Fixed::from_f64_rounded(-2.25).unwrap(),
+        Fixed::from_f64_rounded(0.0).unwrap(),
+        Fixed::from_f64_rounded(1.5).unwrap(),
+        Fixed(i64::MIN),
+        Fixed(i64::MAX),
+    ]
+);
+
+#[test]
+fn fixed_from_f64_rounded() {
+    assert_eq!(Fixed::<2>::from_f64_rounded(1.5), Some(Fixed(150)));
+    assert_eq!(Fixed::<2>::from_f64_rounded(-2.25), Some(Fixed(-225)));
+    assert_eq!(Fixed::<2>::from_f64_rounded(0.0), Some(Fixed(0)));
+    assert_eq!(Fixed::<2>::from_f64_rounded(0.005), Some(Fixed(1)));
+    assert_eq!(Fixed::<2>::from_f64_rounded(f64::NAN), None);
+    assert_eq!(Fixed::<2>::from_f64_rounded(1e18), None);
+    assert_eq!(Fixed::<2>(-225).to_f64(), -2.25);
+}
+
+dimension_conformance_tests!(
+    ordered_f64: OrderedF64,
+    [
+        OrderedF64(f64::NEG_INFINITY),
+        OrderedF64(f64::MIN),
+        OrderedF64(-1.0),
+        OrderedF64(-f64::MIN_POSITIVE),
+        OrderedF64(-0.0),
+        OrderedF64(0.0),
+        OrderedF64(f64::from_bits(1)),
+        OrderedF64(1.0),
+        OrderedF64(f64::MAX),
+        OrderedF64(f64::INFINITY),
+        OrderedF64(f64::NAN),
+        OrderedF64(-f64::NAN),
+    ]
+);
+
+dimension_conformance_tests!(
+    ordered_f32: OrderedF32,
+    [
+        OrderedF32(f32::NEG_INFINITY),
+        OrderedF32(-1.5),
+        OrderedF32(-0.0),
+        OrderedF32(0.0),
+        OrderedF32(1.5),
+        OrderedF32(f32::INFINITY),
+        OrderedF32(f32::NAN),
+    ]
+);
+
+#[test]
+fn ordered_floats_sort_like_their_encodings() {
+    let sorted = [
+        OrderedF64(f64::NEG_INFINITY),
+        OrderedF64(-1.0),
+        OrderedF64(-0.0),
+        OrderedF64(0.0),
+        OrderedF64(1.0),
+        OrderedF64(f64::INFINITY),
+        OrderedF64(f64::NAN),
+    ];
+
+    let encode = |value: &OrderedF64| {
+        let mut buf = [0; 8];
+        value.homomorphic_encode(&mut buf);
+        return buf;
+    };
+
+    for pair in sorted.windows(2) {
+        assert!(pair[0] < pair[1], "{:?}", pair);
+        assert!(encode(&pair[0]) < encode(&pair[1]), "{:?}", pair);
+    }
+
+    // All NaNs are equal, and share the encoding of the canonical NaN.
+    let payload_nan = OrderedF64(f64::from_bits(f64::NAN.to_bits() | 1));
+    assert_eq!(OrderedF64(-f64::NAN), OrderedF64(f64::NAN));
+    assert_eq!(payload_nan, OrderedF64(f64::NAN));
+    assert_eq!(encode(&payload_nan), encode(&OrderedF64(f64::NAN)));
+    assert_eq!(encode(&OrderedF64(-f64::NAN)), encode(&OrderedF64(f64::NAN)));
+
+    // Encodings of non-canonical NaNs are rejected.
+    let mut non_canonical = encode(&OrderedF64(f64::NAN));
+    non_canonical[7] = 1;
+    assert_eq!(OrderedF64::homomorphic_decode(&non_canonical), Err(DecodeError::InvalidValue));
+}
+
+dimension_conformance_tests!(
+    pair_fixed_fixed: (u8, u16),
+    [(0, 0), (0, 1), (0, 0x100), (1, 0), (0xff, u16::MAX)]
+);
+
+dimension_conformance_tests!(
+    pair_fixed_variable: (u16, TailBytes),
+    [
+        (0, TailBytes(vec![])),
+        (0, TailBytes(vec![0])),
+        (0, TailBytes(vec![0, 0])),
+        (0, TailBytes(vec![0, 1])),
+        (0, TailBytes(vec![1])),
+        (1, TailBytes(vec![])),
+        (0x100, TailBytes(vec![0, 0, 0])),
+        (u16::MAX, TailBytes(vec![0xff; 256])),
+    ]
+);
+
+dimension_conformance_tests!(
+    pair_variable_variable: (TailBytes, TailBytes),
+    [
+        (TailBytes(vec![]), TailBytes(vec![])),
+        (TailBytes(vec![]), TailBytes(vec![0])),
+        (TailBytes(vec![]), TailBytes(vec![1])),
+        (TailBytes(vec![0]), TailBytes(vec![])),
+        (TailBytes(vec![0]), TailBytes(vec![0, 0])),
+        (TailBytes(vec![0, 0]), TailBytes(vec![])),
+        (TailBytes(vec![0, 1]), TailBytes(vec![0])),
+        (TailBytes(vec![1]), TailBytes(vec![])),
+        (TailBytes(vec![1, 0]), TailBytes(vec![2])),
+        (TailBytes(vec![2]), TailBytes(vec![])),
+    ]
+);
+
+dimension_conformance_tests!(
+    pair_variable_fixed: (TailBytes, u8),
+    [
+        (TailBytes(vec![]), 0),
+        (TailBytes(vec![]), 0xff),
+        (TailBytes(vec![0]), 0),
+        (TailBytes(vec![0, 0]), 1),
+        (TailBytes(vec![1]), 0),
+    ]
+);
+
+#[test]
+fn pairs_as_point_dimensions() {
+    type Pair = Point3d<(u32, u32), Timestamp, u8>;
+    const { assert!(<(u32, u32)>::IS_FIXED_WIDTH_ENCODING) };
+    assert_eq!(Pair::max_encoding_len_xyz(), 8 + 8 + 1);
+
+    let p1: Pair = Point3d { x: (1, 2), y: Timestamp(3), z: 4 };
+    let p2: Pair = Point3d { x: (1, 3), y: Timestamp(0), z: 0 };
+    let p3: Pair = Point3d { x: (2, 0), y: Timestamp(0), z: 0 };
+    assert_eq!(p1.cmp_xyz_via_encoding(&p2), p1.cmp_xyz(&p2));
+    assert_eq!(p2.cmp_xyz_via_encoding(&p3), p2.cmp_xyz(&p3));
+    assert_eq!(p1.cmp_xyz(&p3), core::cmp::Ordering::Less);
+
+    // Pairs with a variable-width component are variable-width dimensions themselves, and can precede other dimensions.
+    type VariablePair = Point3d<(TailBytes, u8), (u16, TailBytes), u8>;
+    let point: VariablePair = Point3d {
+        x: (TailBytes(vec![0, 0, 7]), 0),
+        y: (0, TailBytes(vec![0])),
+        z: 9,
+    };
+    let mut buf = vec![0; VariablePair::max_encoding_len_xyz()];
+    let len = point.encode_xyz(&mut buf);
+    assert_eq!(VariablePair::decode_xyz_exact(&buf[..len]), Ok(point));
+}
+
+dimension_conformance_tests!(rev_u32: Rev<u32>, [Rev(0), Rev(1), Rev(0xff), Rev(0x100), Rev(u32::MAX)]);
+
+#[test]
+fn rev_sorts_descending() {
+    let mut values = vec![Rev(3u32), Rev(1000), Rev(0), Rev(u32::MAX), Rev(17)];
+    values.sort();
+    assert_eq!(values, vec![Rev(u32::MAX), Rev(1000), Rev(17), Rev(3), Rev(0)]);
+
+    let encodings: Vec<_> = values
+        .iter()
+        .map(|value| {
+            let mut buf = [0; 4];
+            assert_eq!(value.homomorphic_encode(&mut buf), 4);
+            buf
+        })
+        .collect();
+    assert!(encodings.windows(2).all(|pair| pair[0] < pair[1]));
+    assert_eq!(encodings[0], [0, 0, 0, 0]);
+}
+
+// A variable-width dimension: `n` is encoded as `n` bytes `0x02`, followed by a byte `0x01`.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug)]
+struct Unary(u8);
+
+impl Dimension for Unary {
+    const HOMOMORPHIC_ENCODING_MAX_LENGTH: usize = 256;
+
+    const IS_FIXED_WIDTH_ENCODING: bool = false;
+
+    const IS_PREFIX_FREE: bool = true;
+
+    fn homomorphic_encode(&self, buf: &mut [u8]) -> usize {
+        let n = self.0 as usize;
+        buf[..n].fill(2);
+        buf[n] = 1;
+        return n + 1;
+    }
+
+    fn homomorphic_decode(buf: &[u8]) -> Result<(Self, usize), DecodeError> {
+        let n = buf.iter().take_while(|byte| **byte == 2).count();
+        match buf.get(n) {
+            Some(1) if n <= 255 => return Ok((Unary(n as u8), n + 1)),
+            _ => return Err(DecodeError::InvalidValue),
+        }
+    }
+}
+
+dimension_conformance_tests!(unary: Unary, [Unary(0), Unary(1), Unary(7), Unary(255)]);
+
+dimension_conformance_tests!(
+    pad_fixed: PadFixed<Unary, 8>,
+    [PadFixed(Unary(0)), PadFixed(Unary(1)), PadFixed(Unary(6)), PadFixed(Unary(7))]
+);
+
+#[test]
+fn pad_fixed_pads_at_the_front() {
+    let mut buf = [0xff; 4];
+    assert_eq!(PadFixed::<_, 4>(Unary(1)).homomorphic_encode(&mut buf), 4);
+    assert_eq!(buf, [0, 0, 2, 1]);
+
+    // Padding that is not followed by exactly one encoding is rejected.
+    assert_eq!(PadFixed::<Unary, 4>::homomorphic_decode(&[0, 2, 1, 1]), Err(DecodeError::InvalidValue));
+    assert_eq!(PadFixed::<Unary, 4>::homomorphic_decode(&[0, 0, 0, 0]), Err(DecodeError::InvalidValue));
+    assert_eq!(PadFixed::<Unary, 4>::homomorphic_decode(&[0, 0, 1]), Err(DecodeError::UnexpectedEof));
+}
+
+#[test]
+#[should_panic(expected = "more than the 4 bytes to pad to")]
+fn pad_fixed_rejects_long_encodings() {
+    let mut buf = [0; 4];
+    PadFixed::<_, 4>(Unary(4)).homomorphic_encode(&mut buf);
+}
+
+// A variable-width dimension whose least value has an empty encoding: `n` is encoded as `n` bytes `0x01`.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug)]
+struct Tally(u8);
+
+impl Dimension for Tally {
+    const HOMOMORPHIC_ENCODING_MAX_LENGTH: usize = 255;
+
+    const IS_FIXED_WIDTH_ENCODING: bool = false;
+
+    fn homomorphic_encode(&self, buf: &mut [u8]) -> usize {
+        let n = self.0 as usize;
+        buf[..n].fill(1);
+        return n;
+    }
+
+    fn homomorphic_decode(buf: &[u8]) -> Result<(Self, usize), DecodeError> {
+        let n = buf.iter().take(255).take_while(|byte| **byte == 1).count();
+        return Ok((Tally(n as u8), n));
+    }
+}
+
+dimension_conformance_tests!(tally: Tally, [Tally(0), Tally(1), Tally(2), Tally(255)]);
+
+#[test]
+fn empty_encodings_round_trip_within_points() {
+    let values = [Tally(0), Tally(1), Tally(3)];
+    let mut buf = vec![0; Point3d::<Tally, Tally, u8>::max_encoding_len_xyz()];
+
+    for x in values {
+        for y in values {
+            let point = Point3d { x, y, z: 7u8 };
+
+            let len = point.encode_xyz(&mut buf);
+            assert_eq!(len, point.encoded_len_xyz());
+            assert_eq!(Point3d::decode_xyz(&buf[..len]), Ok((point, len)));
+
+            // Trailing bytes are not mistaken for part of the point.
+            buf[len] = 1;
+            assert_eq!(Point3d::decode_xyz(&buf[..len + 1]), Ok((point, len)));
+
+            // The separator after an empty encoding is still required.
+            if x == Tally(0) {
+                assert_eq!(&buf[..2], &[0, 0]);
+                assert_eq!(
+                    Point3d::<Tally, Tally, u8>::decode_xyz(&buf[2..len]),
+                    Err(DecodeError::UnexpectedEof)
+                );
+            }
+
+            let mut written = vec![];
+            assert_eq!(point.encode_xyz_to_writer(&mut written).unwrap(), len);
+            assert_eq!(&written[..], &buf[..len]);
+            assert_eq!(
+                Point3d::decode_xyz_from_reader(&mut &written[..]).unwrap(),
+                (point, len)
+            );
+
+            // The other orderings, where the empty encodings are also in the middle and at the end.
+            let len = point.encode_yzx(&mut buf);
+            assert_eq!(Point3d::decode_yzx(&buf[..len]), Ok((point, len)));
+            let len = point.encode_zxy(&mut buf);
+            assert_eq!(Point3d::decode_zxy(&buf[..len]), Ok((point, len)));
+        }
+    }
+
+    // Empty encodings sort first.
+    let encodings: Vec<Vec<u8>> = values
+        .iter()
+        .map(|x| {
+            let len = Point3d { x: *x, y: Tally(1), z: 0u8 }.encode_xyz(&mut buf);
+            buf[..len].to_vec()
+        })
+        .collect();
+    assert!(encodings.windows(2).all(|pair| pair[0] < pair[1]));
+    assert_eq!(encodings[0], [0, 0, 1, 0, 0, 0]);
+}
+
+// Wraps `Tally`, but wrongly claims that its encodings are prefix-free.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug)]
+struct PrefixFreeTally(Tally);
+
+impl Dimension for PrefixFreeTally {
+    const HOMOMORPHIC_ENCODING_MAX_LENGTH: usize = Tally::HOMOMORPHIC_ENCODING_MAX_LENGTH;
+
+    const IS_FIXED_WIDTH_ENCODING: bool = false;
+
+    const IS_PREFIX_FREE: bool = true;
+
+    fn homomorphic_encode(&self, buf: &mut [u8]) -> usize {
+        return self.0.homomorphic_encode(buf);
+    }
+
+    fn homomorphic_decode(buf: &[u8]) -> Result<(Self, usize), DecodeError> {
+        let (tally, len) = Tally::homomorphic_decode(buf)?;
+        return Ok((PrefixFreeTally(tally), len));
+    }
+}
+
+#[test]
+#[should_panic(expected = "Dimension claims prefix-free encodings")]
+fn conformance_detects_false_prefix_freedom() {
+    // The encoding of `Tally(1)` is a strict prefix of that of `Tally(2)`.
+    assert_dimension_works(&PrefixFreeTally(Tally(1)), &PrefixFreeTally(Tally(2)));
+}
+
+#[test]
+fn ip_addresses_encode_as_octets() {
+    let mut buf = [0; 16];
+
+    assert_eq!(Ipv4Addr::new(192, 168, 0, 1).homomorphic_encode(&mut buf), 4);
+    assert_eq!(buf[..4], [192, 168, 0, 1]);
+    assert_eq!(Ipv4Addr::homomorphic_decode(&[192, 168, 0]), Err(DecodeError::UnexpectedEof));
+
+    // IPv4-mapped IPv6 addresses are `::ffff:a.b.c.d`.
+    let mapped = Ipv4Addr::new(192, 168, 0, 1).to_ipv6_mapped();
+    assert_eq!(mapped.homomorphic_encode(&mut buf), 16);
+    assert_eq!(buf, [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0xff, 0xff, 192, 168, 0, 1]);
+    assert_eq!(Ipv6Addr::homomorphic_decode(&buf), Ok((mapped, 16)));
+    assert_eq!(Ipv6Addr::homomorphic_decode(&buf[..15]), Err(DecodeError::UnexpectedEof));
+}
+
+#[test]
+fn unit_dimension_encodes_like_a_2d_point() {
+    let point = Point3d { x: 0x0102_0304u32, y: 0x0506_0708u32, z: () };
+    assert_eq!(Point3d::<u32, u32, ()>::max_encoding_len_xyz(), 8);
+
+    let mut buf = [0; 8];
+    let mut expected = [0; 8];
+    point.x.homomorphic_encode(&mut expected[..4]);
+    point.y.homomorphic_encode(&mut expected[4..]);
+
+    assert_eq!(point.encode_xyz(&mut buf), 8);
+    assert_eq!(buf, expected);
+    assert_eq!(Point3d::decode_xyz_exact(&buf), Ok(point));
+
+    // The other orderings concatenate the two real dimensions as well, in their respective order.
+    assert_eq!(point.encode_yzx(&mut buf), 8);
+    assert_eq!(buf, [5, 6, 7, 8, 1, 2, 3, 4]);
+    assert_eq!(point.encode_zxy(&mut buf), 8);
+    assert_eq!(buf, expected);
+}
+
+// Check the three successor functions below and at the greatest value of a dimension.
+fn assert_successors<D: DiscreteDimension + std::fmt::Debug>(below_max: D) {
+    assert_eq!(below_max.successor(), Some(D::MAX));
+    assert_eq!(successor_saturating(&below_max), D::MAX);
+    assert_eq!(successor_wrapping(&below_max), D::MAX);
+
+    assert_eq!(D::MAX.successor(), None);
+    assert_eq!(successor_saturating(&D::MAX), D::MAX);
+    assert_eq!(successor_wrapping(&D::MAX), D::MIN);
+}
+
+#[test]
+fn successors_at_the_maximum() {
+    assert_successors(u8::MAX - 1);
+    assert_successors(u128::MAX - 1);
+    assert_successors(i8::MAX - 1);
+    assert_successors(i64::MAX - 1);
+    assert_successors(Timestamp(u64::MAX - 1));
+    assert_successors(Fixed::<2>(i64::MAX - 1));
+    assert_successors(Ipv4Addr::new(255, 255, 255, 254));
+    assert_successors(Ipv6Addr::from_bits(u128::MAX - 1));
+
+    // The unit dimension has a single value, which is its own saturating and wrapping successor.
+    assert_eq!(().successor(), None);
+    assert_eq!(successor_saturating(&()), ());
+    assert_eq!(successor_wrapping(&()), ());
+}
+
+#[test]
+fn successors_below_the_maximum() {
+    assert_eq!(0u16.successor(), Some(1));
+    assert_eq!((-1i32).successor(), Some(0));
+    assert_eq!(successor_wrapping(&i32::MIN), i32::MIN + 1);
+    assert_eq!(Fixed::<3>(999).successor(), Some(Fixed(1000)));
+    assert_eq!(Ipv4Addr::new(10, 0, 0, 255).successor(), Some(Ipv4Addr::new(10, 0, 1, 0)));
+}
@@ -0,0 +1,62 @@
+//! Checks the human-readable renderings of points and their encodings.
+
+use kv_3d_storage::{DecodeError, Dimension, Point3d, Timestamp};
+
+// A variable-width dimension of byte strings without zero bytes, encoded as the bytes themselves.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Debug)]
+struct Name(Vec<u8>);
+
+impl Dimension for Name {
+    const HOMOMORPHIC_ENCODING_MAX_LENGTH: usize = 16;
+
+    const IS_FIXED_WIDTH_ENCODING: bool = false;
+
+    fn homomorphic_encode(&self, buf: &mut [u8]) -> usize {
+        buf[..self.0.len()].copy_from_slice(&self.0);
+        return self.0.len();
+    }
+
+    fn homomorphic_decode(buf: &[u8]) -> Result<(Self, usize), DecodeError> {
+        let len = buf.iter().take_while(|byte| **byte != 0).count().min(16);
+        return Ok((Name(buf[..len].to_vec()), len));
+    }
+}
+
+#[test]
+fn display_renders_coordinates() {
+    let point = Point3d {
+        x: 42u16,
+        y: -7i32,
+        z: (),
+    };
+
+    assert_eq!(format!("{}", point.map_z(|()| 3u8)), "(42, -7, 3)");
+}
+
+#[test]
+fn debug_encoding_xyz_marks_dimensions_and_separators() {
+    let point = Point3d {
+        x: 42u16,
+        y: Name(b"ab".to_vec()),
+        z: 0xffu8,
+    };
+    assert_eq!(point.debug_encoding_xyz(), "00 2a | 61 62 [00 00] | ff");
+
+    // Empty encodings, and no separator after a variable-width last dimension.
+    let point = Point3d {
+        x: Name(vec![]),
+        y: (),
+        z: Name(b"z".to_vec()),
+    };
+    assert_eq!(point.debug_encoding_xyz(), "- [00 00] | - | 7a");
+
+    let point = Point3d {
+        x: Timestamp(1),
+        y: 1u8,
+        z: -1i8,
+    };
+    assert_eq!(
+        point.debug_encoding_xyz(),
+        "00 00 00 00 00 00 00 01 | 01 | 7f"
+    );
+}
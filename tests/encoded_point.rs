@@ -0,0 +1,87 @@
+//! Checks that `EncodedPointXyz` locates the encodings of the dimensions of a point.
+
+use std::fmt::Debug;
+
+use kv_3d_storage::{DecodeError, Dimension, EncodedPointXyz, Point3d};
+
+/// A variable-width dimension whose encodings end with a zero byte: `n` is encoded as `n` times the byte `0x01`, followed by a single `0x00`.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug)]
+struct Unary(u8);
+
+impl Dimension for Unary {
+    const HOMOMORPHIC_ENCODING_MAX_LENGTH: usize = 256;
+
+    const IS_FIXED_WIDTH_ENCODING: bool = false;
+
+    fn homomorphic_encode(&self, buf: &mut [u8]) -> usize {
+        let n = self.0 as usize;
+        buf[..n].fill(1);
+        buf[n] = 0;
+        return n + 1;
+    }
+
+    fn homomorphic_decode(buf: &[u8]) -> Result<(Self, usize), DecodeError> {
+        match buf.iter().position(|byte| *byte != 1) {
+            Some(n) if n < 256 && buf[n] == 0 => return Ok((Unary(n as u8), n + 1)),
+            _ => return Err(DecodeError::InvalidValue),
+        }
+    }
+}
+
+fn encode_xyz<X: Dimension, Y: Dimension, Z: Dimension>(point: &Point3d<X, Y, Z>) -> Vec<u8> {
+    let mut buf = vec![0; Point3d::<X, Y, Z>::max_encoding_len_xyz()];
+    let len = point.encode_xyz(&mut buf);
+    buf.truncate(len);
+    return buf;
+}
+
+fn assert_located<X, Y, Z>(point: Point3d<X, Y, Z>)
+where
+    X: Dimension + Debug + Copy,
+    Y: Dimension + Debug + Copy,
+    Z: Dimension + Debug + Copy,
+{
+    let enc = encode_xyz(&point);
+    let view = EncodedPointXyz::<X, Y, Z>::new(&enc).unwrap();
+
+    assert_eq!(view.bytes(), &enc[..]);
+    assert_eq!(X::homomorphic_decode(view.x_bytes()), Ok((point.x, view.x_bytes().len())));
+    assert_eq!(Y::homomorphic_decode(view.y_bytes()), Ok((point.y, view.y_bytes().len())));
+    assert_eq!(Z::homomorphic_decode(view.z_bytes()), Ok((point.z, view.z_bytes().len())));
+
+    assert_eq!(Point3d::<X, Y, Z>::decode_xyz(&enc), Ok((point, enc.len())));
+}
+
+#[test]
+fn fixed_width_dimensions_are_located() {
+    for x in [0u8, 1, 0xff] {
+        for y in [0u16, 0x100, u16::MAX] {
+            for z in [0u32, 7, u32::MAX] {
+                assert_located(Point3d { x, y, z });
+            }
+        }
+    }
+}
+
+#[test]
+fn variable_width_dimensions_are_located() {
+    for x in [0, 1, 5] {
+        for y in [0u16, 1, 0x100, u16::MAX] {
+            for z in [0, 1, 3] {
+                assert_located(Point3d { x: Unary(x), y, z: Unary(z) });
+                assert_located(Point3d { x: y, y: Unary(x), z: Unary(z) });
+                assert_located(Point3d { x: Unary(x), y: Unary(z), z: y });
+            }
+        }
+    }
+}
+
+#[test]
+fn malformed_encodings_are_rejected() {
+    // Missing separator after a variable-width dimension.
+    assert!(EncodedPointXyz::<Unary, u8, u8>::new(&[1, 0, 7, 7]).is_err());
+    // Trailing bytes after a fixed-width last dimension.
+    assert!(EncodedPointXyz::<u8, u8, u8>::new(&[1, 2, 3, 4]).is_err());
+    // Too short for the fixed-width dimensions.
+    assert!(EncodedPointXyz::<u8, u16, u8>::new(&[1, 2]).is_err());
+}
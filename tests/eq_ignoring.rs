@@ -0,0 +1,39 @@
+//! Checks comparing points for equality while ignoring one of their dimensions.
+
+use kv_3d_storage::Point3d;
+
+type P = Point3d<u8, u16, i32>;
+
+#[test]
+fn differences_in_the_ignored_dimension_do_not_matter() {
+    let point: P = Point3d::from((1, 2, 3));
+
+    let other_x: P = Point3d::from((9, 2, 3));
+    assert!(point.eq_ignoring_x(&other_x));
+    assert!(!point.eq_ignoring_y(&other_x));
+    assert!(!point.eq_ignoring_z(&other_x));
+
+    let other_y: P = Point3d::from((1, 9, 3));
+    assert!(!point.eq_ignoring_x(&other_y));
+    assert!(point.eq_ignoring_y(&other_y));
+    assert!(!point.eq_ignoring_z(&other_y));
+
+    let other_z: P = Point3d::from((1, 2, -9));
+    assert!(!point.eq_ignoring_x(&other_z));
+    assert!(!point.eq_ignoring_y(&other_z));
+    assert!(point.eq_ignoring_z(&other_z));
+}
+
+#[test]
+fn equal_points_are_equal_ignoring_anything() {
+    let point: P = Point3d::from((1, 2, 3));
+    assert!(point.eq_ignoring_x(&point));
+    assert!(point.eq_ignoring_y(&point));
+    assert!(point.eq_ignoring_z(&point));
+
+    // Differences in two dimensions are never ignored.
+    let other: P = Point3d::from((9, 9, 3));
+    assert!(!point.eq_ignoring_x(&other));
+    assert!(!point.eq_ignoring_y(&other));
+    assert!(!point.eq_ignoring_z(&other));
+}
@@ -0,0 +1,130 @@
+//! Checks the encoding of kv-tree vertices, and that reading the ranks from its header agrees with fully decoding it.
+
+use kv_3d_storage::{
+    decode_kv_node, encode_kv_node, peek_child_ranks, rank_from_hash, BytesCodec, ChildRef,
+    CodecError, KvNode, KvNodeCodec, Point3d, Rank, ValueCodec, EMPTY_CHILD,
+};
+
+// Encodes summaries as eight big-endian bytes.
+struct U64Codec;
+
+impl ValueCodec<u64> for U64Codec {
+    fn encode(&self, v: &u64) -> Vec<u8> {
+        return v.to_be_bytes().to_vec();
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<u64, CodecError> {
+        let bytes: [u8; 8] = bytes
+            .try_into()
+            .map_err(|_| CodecError::new("expected eight bytes"))?;
+        return Ok(u64::from_be_bytes(bytes));
+    }
+}
+
+#[test]
+fn peek_child_ranks_agrees_with_decode() {
+    let children = [
+        (ChildRef::None, vec![]),
+        (ChildRef::Child(Rank::MIN), vec![]),
+        (ChildRef::Child(Rank::new(3).unwrap()), vec![1, 2, 3]),
+        (ChildRef::Child(Rank::MAX), vec![254; 200]),
+    ];
+
+    for rank in [0, 1, 7, 254].map(|rank| Rank::new(rank).unwrap()) {
+        for left in children.iter() {
+            for right in children.iter() {
+                let node = KvNode {
+                    rank,
+                    value: vec![rank.get(), 42],
+                    left: left.0,
+                    left_point: left.1.clone(),
+                    right: right.0,
+                    right_point: right.1.clone(),
+                    count: 300 * rank.get() as usize,
+                    summary: 17u64 << (rank.get() % 64),
+                };
+
+                let bytes = encode_kv_node(&node, &BytesCodec, &U64Codec);
+                let decoded: KvNode<Vec<u8>, u64> =
+                    decode_kv_node(&bytes, &BytesCodec, &U64Codec).unwrap();
+                assert_eq!(decoded, node);
+
+                assert_eq!(
+                    peek_child_ranks(&bytes),
+                    Ok((decoded.rank, decoded.left.rank(), decoded.right.rank()))
+                );
+
+                let codec = KvNodeCodec::new(BytesCodec, U64Codec);
+                assert_eq!(codec.encode(&node), bytes);
+                assert_eq!(codec.decode(&bytes), Ok(node));
+            }
+        }
+    }
+}
+
+#[test]
+fn decode_rejects_truncated_nodes() {
+    let node = KvNode {
+        rank: Rank::new(1).unwrap(),
+        value: vec![1, 2, 3],
+        left: ChildRef::Child(Rank::MIN),
+        left_point: vec![9, 9],
+        right: ChildRef::None,
+        right_point: vec![],
+        count: 2,
+        summary: 5u64,
+    };
+    let bytes = encode_kv_node(&node, &BytesCodec, &U64Codec);
+
+    for len in 0..bytes.len() {
+        let decoded: Result<KvNode<Vec<u8>, u64>, _> =
+            decode_kv_node(&bytes[..len], &BytesCodec, &U64Codec);
+        assert!(decoded.is_err());
+        assert_eq!(peek_child_ranks(&bytes[..len]).is_err(), len < 3);
+    }
+}
+
+#[test]
+fn child_ref_round_trips() {
+    assert_eq!(ChildRef::None.encode(), EMPTY_CHILD);
+    assert_eq!(ChildRef::decode(EMPTY_CHILD), ChildRef::None);
+
+    for byte in 0..EMPTY_CHILD {
+        let rank = Rank::new(byte).unwrap();
+        assert_eq!(ChildRef::Child(rank).encode(), byte);
+        assert_eq!(ChildRef::decode(byte), ChildRef::Child(rank));
+        assert_eq!(ChildRef::decode(byte).rank(), Some(rank));
+    }
+}
+
+#[test]
+fn decode_rejects_reserved_rank() {
+    let node = KvNode {
+        rank: Rank::MAX,
+        value: vec![],
+        left: ChildRef::None,
+        left_point: vec![],
+        right: ChildRef::None,
+        right_point: vec![],
+        count: 1,
+        summary: 0u64,
+    };
+    let mut bytes = encode_kv_node(&node, &BytesCodec, &U64Codec);
+    bytes[0] = EMPTY_CHILD;
+
+    let decoded: Result<KvNode<Vec<u8>, u64>, _> = decode_kv_node(&bytes, &BytesCodec, &U64Codec);
+    assert!(decoded.is_err());
+    assert!(peek_child_ranks(&bytes).is_err());
+}
+
+#[test]
+fn rank_from_hash_never_returns_reserved_rank() {
+    for x in 0..64u16 {
+        for y in 0..64u16 {
+            for value in 0..8u8 {
+                let point = Point3d::from((x, y, x ^ y));
+                assert_ne!(rank_from_hash(&point, &value), EMPTY_CHILD);
+            }
+        }
+    }
+}
@@ -0,0 +1,335 @@
+//! Checks the configuration of `KvTree`s via the `KvTreeBuilder`.
+
+use std::collections::BTreeMap;
+
+use futures::StreamExt;
+use pollster::block_on;
+
+use kv_3d_storage::{
+    rank_from_hash, rank_from_hash_capped, BTreeMapBackEnd, BackEnd, CappedHashRank, ChildRef,
+    HashRank, KvNode, KvTree, KvTreeBuilder, KvTreeError, OpenError, Point3d, Rank, RankStrategy,
+    EMPTY_CHILD,
+};
+
+type P = Point3d<u16, u8, u16>;
+type Backend = BTreeMapBackEnd<KvNode<u32, usize>>;
+
+fn points() -> Vec<(P, u32)> {
+    return (0..200u16)
+        .map(|i| (Point3d::from((i % 13, (i % 5) as u8, i / 3)), i as u32))
+        .collect();
+}
+
+// Insert all points via the rank strategy of the tree, and return the kv pairs of the backend.
+fn fill<R>(
+    mut tree: KvTree<Backend, u16, u8, u16, u32, usize, R>,
+) -> BTreeMap<Vec<u8>, KvNode<u32, usize>>
+where
+    R: RankStrategy<u16, u8, u16, u32>,
+{
+    for (point, value) in points() {
+        block_on(tree.insert_with_strategy(point, value)).unwrap();
+    }
+
+    for (point, value) in points() {
+        assert_eq!(block_on(tree.get(&point)).unwrap(), Some(value));
+    }
+
+    return tree.into_backend().into_map();
+}
+
+#[test]
+fn default_and_explicit_configuration_agree() {
+    let defaults = fill(KvTree::new(BTreeMapBackEnd::new()));
+    let built = fill(KvTreeBuilder::new(BTreeMapBackEnd::new()).build());
+    let explicit = fill(
+        KvTreeBuilder::new(BTreeMapBackEnd::new())
+            .with_rank_strategy(HashRank)
+            .build(),
+    );
+    let closure = fill(
+        KvTreeBuilder::new(BTreeMapBackEnd::new())
+            .with_rank_strategy(|point: &P, value: &u32| {
+                Rank::new(rank_from_hash(point, value)).unwrap()
+            })
+            .build(),
+    );
+
+    assert_eq!(defaults, built);
+    assert_eq!(defaults, explicit);
+    assert_eq!(defaults, closure);
+}
+
+#[test]
+fn custom_rank_strategy() {
+    let mut tree: KvTree<_, u16, u8, u16, u32, usize, _> =
+        KvTreeBuilder::new(BTreeMapBackEnd::new())
+            .with_rank_strategy(|_: &P, value: &u32| Rank::new((value % 4) as u8).unwrap())
+            .build();
+
+    for (point, value) in points() {
+        block_on(tree.insert_with_strategy(point, value)).unwrap();
+    }
+
+    for key in tree.backend().clone().into_map().keys() {
+        assert!(key[0] < 4);
+    }
+    assert_eq!(tree.root_key().unwrap()[0], 3);
+}
+
+#[test]
+fn max_rank_caps_hash_ranks() {
+    let capped = fill(
+        KvTreeBuilder::new(BTreeMapBackEnd::new())
+            .with_max_rank(Rank::new(1).unwrap())
+            .build(),
+    );
+    assert!(capped.keys().all(|key| key[0] <= 1));
+    assert!(capped.keys().any(|key| key[0] == 1));
+
+    // Same as capping explicitly.
+    let explicit = fill(
+        KvTreeBuilder::new(BTreeMapBackEnd::new())
+            .with_rank_strategy(|point: &P, value: &u32| {
+                Rank::new(rank_from_hash_capped(point, value, 1)).unwrap()
+            })
+            .build(),
+    );
+    assert_eq!(capped, explicit);
+
+    // A cap above all hash ranks changes nothing.
+    let uncapped = fill(
+        KvTreeBuilder::new(BTreeMapBackEnd::new())
+            .with_rank_strategy(CappedHashRank {
+                max_rank: Rank::new(64).unwrap(),
+            })
+            .build(),
+    );
+    assert_eq!(uncapped, fill(KvTree::new(BTreeMapBackEnd::new())));
+}
+
+#[test]
+fn reopened_tree_finds_all_points() {
+    let mut tree: KvTree<Backend, u16, u8, u16, u32, usize> = KvTree::new(BTreeMapBackEnd::new());
+    let mut roots = vec![];
+    for (point, value) in points() {
+        block_on(tree.insert_with_strategy(point, value)).unwrap();
+        block_on(tree.flush()).unwrap();
+
+        // Reopening recovers the root after every insertion, whether or not it changed.
+        let root = tree.root_key().map(|key| key.to_vec());
+        let reopened: KvTree<Backend, u16, u8, u16, u32, usize> =
+            block_on(KvTree::open(tree.into_backend())).unwrap();
+        assert_eq!(reopened.root_key(), root.as_deref());
+        roots.push(root);
+        tree = reopened;
+    }
+    assert!(roots.windows(2).any(|pair| pair[0] != pair[1]));
+
+    let backend = tree.into_backend();
+    let reopened: KvTree<Backend, u16, u8, u16, u32, usize, CappedHashRank> = block_on(
+        KvTreeBuilder::new(backend)
+            .with_max_rank(Rank::new(3).unwrap())
+            .open(),
+    )
+    .unwrap();
+    for (point, value) in points() {
+        assert_eq!(block_on(reopened.get(&point)).unwrap(), Some(value));
+    }
+
+    // So do trees that were bulk-loaded.
+    let items = points().into_iter().map(|(point, value)| {
+        let rank = Rank::new(rank_from_hash(&point, &value)).unwrap();
+        return (point, value, rank);
+    });
+    let loaded: KvTree<Backend, u16, u8, u16, u32, usize> =
+        block_on(KvTree::bulk_load(BTreeMapBackEnd::new(), items)).unwrap();
+    let root = loaded.root_key().map(|key| key.to_vec());
+    let reopened: KvTree<Backend, u16, u8, u16, u32, usize> =
+        block_on(KvTree::open(loaded.into_backend())).unwrap();
+    assert_eq!(reopened.root_key(), root.as_deref());
+}
+
+#[test]
+fn opening_an_empty_backend_yields_an_empty_tree() {
+    let mut tree: KvTree<Backend, u16, u8, u16, u32, usize> =
+        block_on(KvTree::open(BTreeMapBackEnd::new())).unwrap();
+    assert_eq!(tree.root_key(), None);
+
+    let (point, value) = points()[0];
+    block_on(tree.insert(point, value, Rank::new(7).unwrap())).unwrap();
+    assert_eq!(block_on(tree.get(&point)).unwrap(), Some(value));
+}
+
+#[test]
+fn opening_a_backend_with_a_reserved_rank_key_fails() {
+    let node = KvNode {
+        rank: Rank::MAX,
+        value: 0,
+        left: ChildRef::None,
+        left_point: vec![],
+        right: ChildRef::None,
+        right_point: vec![],
+        count: 1,
+        summary: 0,
+    };
+    let mut backend: Backend = BTreeMapBackEnd::new();
+    block_on(backend.insert(&[EMPTY_CHILD], node)).unwrap();
+
+    let opened: Result<KvTree<Backend, u16, u8, u16, u32, usize>, _> =
+        block_on(KvTree::open(backend));
+    assert_eq!(opened.err(), Some(OpenError::Corrupted(vec![EMPTY_CHILD])));
+}
+
+#[test]
+fn compact_rebuilds_the_canonical_tree() {
+    let mut tree: KvTree<Backend, u16, u8, u16, u32, usize> = KvTree::new(BTreeMapBackEnd::new());
+    for (point, value) in points() {
+        // Externally supplied ranks, which the hash rank strategy does not agree with.
+        block_on(tree.insert(point, value, Rank::new((value % 7) as u8).unwrap())).unwrap();
+    }
+    // A point that was reinserted with a different rank and value.
+    let (point, value) = points()[0];
+    block_on(tree.insert(point, value + 1, Rank::new(9).unwrap())).unwrap();
+
+    block_on(tree.compact()).unwrap();
+
+    let mut expected_points = points();
+    expected_points[0].1 += 1;
+    for (point, value) in expected_points.iter() {
+        assert_eq!(block_on(tree.get(point)).unwrap(), Some(*value));
+    }
+
+    // The result is exactly the tree that bulk-loading with hash ranks produces.
+    let items = expected_points.into_iter().map(|(point, value)| {
+        let rank = Rank::new(rank_from_hash(&point, &value)).unwrap();
+        return (point, value, rank);
+    });
+    let loaded: KvTree<Backend, u16, u8, u16, u32, usize> =
+        block_on(KvTree::bulk_load(BTreeMapBackEnd::new(), items)).unwrap();
+    assert_eq!(tree.root_key(), loaded.root_key());
+    assert_eq!(
+        tree.into_backend().into_map(),
+        loaded.into_backend().into_map()
+    );
+}
+
+#[test]
+fn compacting_an_empty_tree_keeps_it_empty() {
+    let mut tree: KvTree<Backend, u16, u8, u16, u32, usize> = KvTree::new(BTreeMapBackEnd::new());
+    block_on(tree.compact()).unwrap();
+
+    assert_eq!(tree.root_key(), None);
+    assert!(tree.into_backend().into_map().is_empty());
+}
+
+#[test]
+fn missing_vertices_are_reported_as_corruption() {
+    let mut tree: KvTree<Backend, u16, u8, u16, u32, usize> = KvTree::new(BTreeMapBackEnd::new());
+    for (point, value) in points() {
+        block_on(tree.insert_with_strategy(point, value)).unwrap();
+    }
+
+    // Remove a child of the root, so that the root refers to a missing vertex.
+    let root = tree.root_key().unwrap().to_vec();
+    let mut map = tree.into_backend().into_map();
+    let missing = map[&root].left_key().or(map[&root].right_key()).unwrap();
+    map.remove(&missing);
+
+    let mut tree: KvTree<Backend, u16, u8, u16, u32, usize> =
+        block_on(KvTree::open(BTreeMapBackEnd::from(map))).unwrap();
+    let results: Vec<_> = points()
+        .into_iter()
+        .map(|(point, _)| block_on(tree.get(&point)))
+        .collect();
+    assert!(results.contains(&Err(KvTreeError::Corrupted(missing.clone()))));
+    assert!(results.iter().all(|result| match result {
+        Ok(_) => true,
+        Err(err) => *err == KvTreeError::Corrupted(missing.clone()),
+    }));
+
+    let inserted: Vec<_> = points()
+        .into_iter()
+        .map(|(point, value)| block_on(tree.insert_with_strategy(point, value)))
+        .collect();
+    assert!(inserted.contains(&Err(KvTreeError::Corrupted(missing))));
+}
+
+#[test]
+fn invalid_keys_are_reported_as_corruption() {
+    let mut tree: KvTree<Backend, u16, u8, u16, u32, usize> = KvTree::new(BTreeMapBackEnd::new());
+    for (point, value) in points() {
+        block_on(tree.insert_with_strategy(point, value)).unwrap();
+    }
+
+    // A key of rank zero that is too short to hold the encoding of a point.
+    let invalid = vec![0, 1];
+    let mut map = tree.into_backend().into_map();
+    let node = map.values().next().unwrap().clone();
+    map.insert(invalid.clone(), node);
+
+    let mut tree: KvTree<Backend, u16, u8, u16, u32, usize> =
+        block_on(KvTree::open(BTreeMapBackEnd::from(map.clone()))).unwrap();
+    let by_rank: Vec<_> = block_on(tree.iter_by_rank().collect::<Vec<_>>());
+    assert!(by_rank.contains(&Err(KvTreeError::Corrupted(invalid.clone()))));
+
+    // Compaction fails before it modifies the backend.
+    assert_eq!(
+        block_on(tree.compact()),
+        Err(KvTreeError::Corrupted(invalid))
+    );
+    assert_eq!(tree.into_backend().into_map(), map);
+}
+
+#[test]
+fn insert_only_rewrites_the_vertices_on_its_paths() {
+    let mut tree: KvTree<Backend, u16, u8, u16, u32, usize> = KvTree::new(BTreeMapBackEnd::new());
+    for (point, value) in points().into_iter().skip(1) {
+        block_on(tree.insert_with_strategy(point, value)).unwrap();
+    }
+    let before = tree.into_backend().into_map();
+
+    // Insert a new point, then move it to a different rank, then change only its value.
+    let (point, value) = points()[0];
+    let mut tree: KvTree<Backend, u16, u8, u16, u32, usize> =
+        block_on(KvTree::open(BTreeMapBackEnd::from(before.clone()))).unwrap();
+    assert_eq!(
+        block_on(tree.insert_with_strategy(point, value)).unwrap(),
+        None
+    );
+    assert_eq!(
+        block_on(tree.insert(point, value, Rank::MAX)).unwrap(),
+        Some(value)
+    );
+    assert_eq!(
+        block_on(tree.insert(point, value + 1, Rank::MAX)).unwrap(),
+        Some(value)
+    );
+
+    // The result is exactly the tree that bulk-loading produces.
+    let items = points().into_iter().map(|(point, value)| {
+        return (
+            point,
+            value,
+            Rank::new(rank_from_hash(&point, &value)).unwrap(),
+        );
+    });
+    let items = items.skip(1).chain([(point, value + 1, Rank::MAX)]);
+    let loaded: KvTree<Backend, u16, u8, u16, u32, usize> =
+        block_on(KvTree::bulk_load(BTreeMapBackEnd::new(), items)).unwrap();
+    assert_eq!(tree.root_key(), loaded.root_key());
+    let after = tree.into_backend().into_map();
+    assert_eq!(after, loaded.into_backend().into_map());
+
+    // Most vertices lie on none of the paths, and are left as they were.
+    let changed = after
+        .iter()
+        .filter(|(key, node)| before.get(*key) != Some(*node))
+        .count();
+    assert!(
+        changed < after.len() / 4,
+        "{} of {} vertices changed",
+        changed,
+        after.len()
+    );
+}
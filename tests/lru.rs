@@ -0,0 +1,204 @@
+//! Checks that the `LruCached` backend answers reads from its cache and invalidates it on mutations. Requires the `lru` and `testing` features: run `cargo test --features lru,testing --test lru`.
+
+use core::future::Future;
+use core::num::NonZeroUsize;
+use core::ops::Bound::{self, Excluded, Included, Unbounded};
+use std::cell::Cell;
+
+use pollster::block_on;
+
+use kv_3d_storage::{
+    run_backend_conformance, BTreeMapBackEnd, BTreeMapSnapshot, BackEnd, LruCached,
+};
+
+/// A `BTreeMapBackEnd` that counts how many keys were read through `get` and `get_many`.
+#[derive(Default)]
+struct TracingBackEnd {
+    inner: BTreeMapBackEnd<u64>,
+    reads: Cell<usize>,
+}
+
+impl BackEnd<u64> for TracingBackEnd {
+    type Error = core::convert::Infallible;
+    type Snapshot = BTreeMapSnapshot<u64>;
+
+    fn get(&self, key: &[u8]) -> impl Future<Output = Result<Option<u64>, Self::Error>> {
+        self.reads.set(self.reads.get() + 1);
+        return self.inner.get(key);
+    }
+
+    fn get_many(
+        &self,
+        keys: &[&[u8]],
+    ) -> impl Future<Output = Result<Vec<Option<u64>>, Self::Error>> {
+        self.reads.set(self.reads.get() + keys.len());
+        return self.inner.get_many(keys);
+    }
+
+    fn find_lte(
+        &self,
+        key: &[u8],
+    ) -> impl Future<Output = Result<Option<(Vec<u8>, u64)>, Self::Error>> {
+        return self.inner.find_lte(key);
+    }
+
+    fn find_gte(
+        &self,
+        key: &[u8],
+    ) -> impl Future<Output = Result<Option<(Vec<u8>, u64)>, Self::Error>> {
+        return self.inner.find_gte(key);
+    }
+
+    fn insert(
+        &mut self,
+        key: &[u8],
+        value: u64,
+    ) -> impl Future<Output = Result<Option<u64>, Self::Error>> {
+        return self.inner.insert(key, value);
+    }
+
+    fn delete(&mut self, key: &[u8]) -> impl Future<Output = Result<Option<u64>, Self::Error>> {
+        return self.inner.delete(key);
+    }
+
+    fn delete_range(
+        &mut self,
+        lo: Bound<&[u8]>,
+        hi: Bound<&[u8]>,
+    ) -> impl Future<Output = Result<usize, Self::Error>> {
+        return self.inner.delete_range(lo, hi);
+    }
+
+    fn snapshot(&self) -> impl Future<Output = Result<Self::Snapshot, Self::Error>> {
+        return self.inner.snapshot();
+    }
+
+    fn flush(&mut self) -> impl Future<Output = Result<(), Self::Error>> {
+        return self.inner.flush();
+    }
+}
+
+fn cached(capacity: usize) -> LruCached<TracingBackEnd, u64> {
+    let mut inner = TracingBackEnd::default();
+    for (key, value) in [(&b"a"[..], 1), (b"b", 2), (b"c", 3), (b"d", 4)] {
+        block_on(inner.insert(key, value)).unwrap();
+    }
+    return LruCached::new(inner, NonZeroUsize::new(capacity).unwrap());
+}
+
+fn reads(backend: &LruCached<TracingBackEnd, u64>) -> usize {
+    return backend.inner().reads.get();
+}
+
+#[test]
+fn lru_cached_conformance() {
+    block_on(run_backend_conformance(|| {
+        LruCached::new(BTreeMapBackEnd::new(), NonZeroUsize::new(2).unwrap())
+    }));
+}
+
+#[test]
+fn repeated_gets_hit_the_cache() {
+    let backend = cached(8);
+
+    for _ in 0..3 {
+        assert_eq!(block_on(backend.get(b"a")).unwrap(), Some(1));
+    }
+    assert_eq!(reads(&backend), 1);
+    assert_eq!(backend.cached(), 1);
+
+    // Absent keys are not cached.
+    for _ in 0..3 {
+        assert_eq!(block_on(backend.get(b"z")).unwrap(), None);
+    }
+    assert_eq!(reads(&backend), 4);
+    assert_eq!(backend.cached(), 1);
+
+    // Other reads bypass the cache without affecting it.
+    assert_eq!(
+        block_on(backend.find_gte(b"a\0")).unwrap(),
+        Some((b"b".to_vec(), 2))
+    );
+    assert_eq!(backend.cached(), 1);
+}
+
+#[test]
+fn get_many_only_reads_uncached_keys() {
+    let backend = cached(8);
+    assert_eq!(block_on(backend.get(b"b")).unwrap(), Some(2));
+
+    let values = block_on(backend.get_many(&[&b"a"[..], b"b", b"z", b"a"])).unwrap();
+    assert_eq!(values, vec![Some(1), Some(2), None, Some(1)]);
+    assert_eq!(reads(&backend), 1 + 3);
+
+    let values = block_on(backend.get_many(&[&b"a"[..], b"b"])).unwrap();
+    assert_eq!(values, vec![Some(1), Some(2)]);
+    assert_eq!(reads(&backend), 1 + 3);
+}
+
+#[test]
+fn least_recently_used_keys_are_evicted() {
+    let backend = cached(2);
+    assert_eq!(backend.capacity().get(), 2);
+
+    block_on(backend.get(b"a")).unwrap();
+    block_on(backend.get(b"b")).unwrap();
+    block_on(backend.get(b"a")).unwrap();
+    block_on(backend.get(b"c")).unwrap();
+    assert_eq!(backend.cached(), 2);
+    assert_eq!(reads(&backend), 3);
+
+    // `b` was used least recently, so it was evicted when caching `c`.
+    block_on(backend.get(b"a")).unwrap();
+    block_on(backend.get(b"c")).unwrap();
+    assert_eq!(reads(&backend), 3);
+    block_on(backend.get(b"b")).unwrap();
+    assert_eq!(reads(&backend), 4);
+}
+
+#[test]
+fn mutations_invalidate_the_cache() {
+    let mut backend = cached(8);
+    for key in [b"a", b"b", b"c", b"d"] {
+        block_on(backend.get(key)).unwrap();
+    }
+    assert_eq!(backend.cached(), 4);
+
+    assert_eq!(block_on(backend.insert(b"a", 10)).unwrap(), Some(1));
+    assert_eq!(block_on(backend.get(b"a")).unwrap(), Some(10));
+
+    assert_eq!(
+        block_on(backend.update_with(b"a", |old| old.map(|v| v + 1))).unwrap(),
+        Some(11)
+    );
+    assert_eq!(block_on(backend.get(b"a")).unwrap(), Some(11));
+
+    assert_eq!(block_on(backend.delete(b"b")).unwrap(), Some(2));
+    assert_eq!(block_on(backend.get(b"b")).unwrap(), None);
+
+    assert_eq!(
+        block_on(backend.delete_range(Excluded(&b"a"[..]), Included(&b"c"[..]))).unwrap(),
+        1
+    );
+    assert_eq!(block_on(backend.get(b"c")).unwrap(), None);
+    assert_eq!(block_on(backend.get(b"d")).unwrap(), Some(4));
+    assert_eq!(backend.cached(), 2);
+
+    assert_eq!(
+        block_on(backend.delete_range(Unbounded, Unbounded)).unwrap(),
+        2
+    );
+    assert_eq!(backend.cached(), 0);
+    assert_eq!(block_on(backend.get(b"a")).unwrap(), None);
+}
+
+#[test]
+fn clearing_the_cache_keeps_the_kv_pairs() {
+    let mut backend = cached(8);
+    block_on(backend.get(b"a")).unwrap();
+
+    backend.clear_cache();
+    assert_eq!(backend.cached(), 0);
+    assert_eq!(block_on(backend.get(b"a")).unwrap(), Some(1));
+    assert_eq!(reads(&backend), 2);
+}
@@ -0,0 +1,42 @@
+//! Checks transforming the dimension types of points with `map_x`, `map_y`, `map_z`, and `map_all`.
+
+use kv_3d_storage::{Point3d, Rev, Timestamp};
+
+#[test]
+fn map_all_changes_types_and_encodings() {
+    let point: Point3d<u32, u32, u32> = Point3d::from((1, 2, 3));
+
+    let mapped: Point3d<Timestamp, Rev<u8>, u64> = point.map_all(
+        |x| Timestamp(x as u64 * 1000),
+        |y| Rev(y as u8),
+        |z| z as u64,
+    );
+    assert_eq!(mapped, Point3d::from((Timestamp(1000), Rev(2), 3u64)));
+
+    let mut buf = [0; Point3d::<Timestamp, Rev<u8>, u64>::max_encoding_len_xyz()];
+    let len = mapped.encode_xyz(&mut buf);
+    assert_eq!(len, 8 + 1 + 8);
+    assert_eq!(buf[..8], 1000u64.to_be_bytes());
+    assert_eq!(buf[8], !2);
+    assert_eq!(buf[9..len], 3u64.to_be_bytes());
+
+    // The original point encodes differently.
+    let mut original = [0; Point3d::<u32, u32, u32>::max_encoding_len_xyz()];
+    let original_len = point.encode_xyz(&mut original);
+    assert_ne!(&original[..original_len], &buf[..len]);
+}
+
+#[test]
+fn map_single_dimensions() {
+    let point: Point3d<u32, u32, u32> = Point3d::from((1, 2, 3));
+
+    assert_eq!(point.map_x(|x| x as u8), Point3d::from((1u8, 2u32, 3u32)));
+    assert_eq!(point.map_y(Rev), Point3d::from((1u32, Rev(2u32), 3u32)));
+    assert_eq!(point.map_z(|z| -(z as i64)), Point3d::from((1u32, 2u32, -3i64)));
+
+    // Mapping each dimension in turn is the same as mapping all at once.
+    assert_eq!(
+        point.map_x(|x| x + 1).map_y(|y| y * 2).map_z(Rev),
+        point.map_all(|x| x + 1, |y| y * 2, Rev)
+    );
+}
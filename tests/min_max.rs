@@ -0,0 +1,66 @@
+//! Checks that `Point3d::min` and `Point3d::max` encode to the least and greatest encodings.
+
+use std::net::Ipv4Addr;
+
+use kv_3d_storage::{Point3d, Rev};
+
+type Point = Point3d<u16, Rev<i32>, Ipv4Addr>;
+
+// A deterministic stream of pseudo-random points (splitmix64).
+fn pseudo_random_points(count: usize) -> Vec<Point> {
+    let mut state: u64 = 0x2545_f491_4f6c_dd1d;
+    let mut next = move || {
+        state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        return z ^ (z >> 31);
+    };
+
+    return (0..count)
+        .map(|_| {
+            let bits = next();
+            return Point3d {
+                x: bits as u16,
+                y: Rev((bits >> 16) as i32),
+                z: Ipv4Addr::from(next() as u32),
+            };
+        })
+        .collect();
+}
+
+fn encode_xyz(point: &Point) -> Vec<u8> {
+    let mut buf = [0; Point::max_encoding_len_xyz()];
+    let len = point.encode_xyz(&mut buf);
+    return buf[..len].to_vec();
+}
+
+#[test]
+fn min_has_least_xyz_encoding() {
+    let min = encode_xyz(&Point::min());
+
+    for point in pseudo_random_points(500) {
+        assert!(min <= encode_xyz(&point), "{:?}", point);
+    }
+
+    assert_eq!(
+        Point::min(),
+        Point3d {
+            x: 0,
+            y: Rev(i32::MAX),
+            z: Ipv4Addr::UNSPECIFIED,
+        }
+    );
+    assert!(min.iter().all(|byte| *byte == 0));
+}
+
+#[test]
+fn max_has_greatest_xyz_encoding() {
+    let max = encode_xyz(&Point::max());
+
+    for point in pseudo_random_points(500) {
+        assert!(max >= encode_xyz(&point), "{:?}", point);
+    }
+
+    assert!(max.iter().all(|byte| *byte == 0xff));
+}
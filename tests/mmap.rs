@@ -0,0 +1,223 @@
+//! Builds sorted files for the `MmapSortedBackEnd` and queries them. Requires the `mmap` feature: run `cargo test --features mmap --test mmap`.
+
+use core::ops::Bound::{Excluded, Included, Unbounded};
+use std::path::PathBuf;
+
+use pollster::block_on;
+
+use kv_3d_storage::{BackEnd, MmapError, MmapSortedBackEnd, ReadBackEnd};
+
+// A fresh path in the temporary directory, unique to the given test.
+fn temp_path(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!(
+        "kv_3d_storage_mmap_{}_{}",
+        std::process::id(),
+        name
+    ));
+    let _ = std::fs::remove_file(&path);
+    return path;
+}
+
+// The keys 10, 20, ..., 90 (as single bytes), each mapping to the key twice.
+fn entries() -> Vec<(Vec<u8>, Vec<u8>)> {
+    return (1..10u8)
+        .map(|i| (vec![i * 10], vec![i * 10, i * 10]))
+        .collect();
+}
+
+// Safety: no test modifies a file after building it.
+fn build(name: &str) -> MmapSortedBackEnd<Vec<u8>> {
+    return unsafe { MmapSortedBackEnd::build_from_sorted(temp_path(name), entries()) }.unwrap();
+}
+
+fn entry(key: u8) -> (Vec<u8>, Vec<u8>) {
+    return (vec![key], vec![key, key]);
+}
+
+#[test]
+fn get_finds_exactly_the_stored_keys() {
+    let backend = build("get");
+    assert_eq!(backend.len(), 9);
+
+    for key in 0..=u8::MAX {
+        let expected = if key % 10 == 0 && (10..=90).contains(&key) {
+            Some(vec![key, key])
+        } else {
+            None
+        };
+        assert_eq!(block_on(backend.get(&[key])).unwrap(), expected);
+    }
+
+    // Keys that extend or shorten stored keys are not stored.
+    assert_eq!(block_on(backend.get(&[10, 0])).unwrap(), None);
+    assert_eq!(block_on(backend.get(&[])).unwrap(), None);
+}
+
+#[test]
+fn find_lte_and_find_gte_between_records() {
+    let backend = build("find");
+
+    // Exactly on a record.
+    assert_eq!(block_on(backend.find_lte(&[30])).unwrap(), Some(entry(30)));
+    assert_eq!(block_on(backend.find_gte(&[30])).unwrap(), Some(entry(30)));
+
+    // Between two records.
+    assert_eq!(block_on(backend.find_lte(&[35])).unwrap(), Some(entry(30)));
+    assert_eq!(block_on(backend.find_gte(&[35])).unwrap(), Some(entry(40)));
+    assert_eq!(
+        block_on(backend.find_lte(&[30, 0])).unwrap(),
+        Some(entry(30))
+    );
+    assert_eq!(
+        block_on(backend.find_gte(&[30, 0])).unwrap(),
+        Some(entry(40))
+    );
+
+    // Before the first and after the last record.
+    assert_eq!(block_on(backend.find_lte(&[5])).unwrap(), None);
+    assert_eq!(block_on(backend.find_gte(&[5])).unwrap(), Some(entry(10)));
+    assert_eq!(block_on(backend.find_lte(&[95])).unwrap(), Some(entry(90)));
+    assert_eq!(block_on(backend.find_gte(&[95])).unwrap(), None);
+}
+
+#[test]
+fn ranges() {
+    let backend = build("range");
+
+    let range: Vec<_> = block_on(backend.range(Included(&[25][..]), Excluded(&[50][..])))
+        .unwrap()
+        .collect();
+    assert_eq!(range, vec![entry(30), entry(40)]);
+
+    let range: Vec<_> = block_on(backend.range(Excluded(&[30][..]), Included(&[50][..])))
+        .unwrap()
+        .collect();
+    assert_eq!(range, vec![entry(40), entry(50)]);
+
+    let all: Vec<_> = block_on(backend.range(Unbounded, Unbounded))
+        .unwrap()
+        .collect();
+    assert_eq!(all, entries());
+
+    let rev: Vec<_> = block_on(backend.range_rev(Unbounded, Unbounded))
+        .unwrap()
+        .collect();
+    assert_eq!(rev, entries().into_iter().rev().collect::<Vec<_>>());
+
+    // Bounds that describe an empty range.
+    assert_eq!(
+        block_on(backend.range(Included(&[60][..]), Excluded(&[20][..])))
+            .unwrap()
+            .count(),
+        0
+    );
+    assert_eq!(
+        block_on(backend.range(Excluded(&[50][..]), Excluded(&[50][..])))
+            .unwrap()
+            .count(),
+        0
+    );
+
+    let page = block_on(backend.page_after(Some(&[40]), 2)).unwrap();
+    assert_eq!(page, vec![entry(50), entry(60)]);
+
+    let snapshot = block_on(backend.snapshot()).unwrap();
+    let range: Vec<_> = block_on(ReadBackEnd::range(
+        &snapshot,
+        Included(&[85][..]),
+        Unbounded,
+    ))
+    .unwrap()
+    .collect();
+    assert_eq!(range, vec![entry(90)]);
+}
+
+#[test]
+fn writes_are_rejected() {
+    let mut backend = build("writes");
+
+    assert!(matches!(
+        block_on(backend.insert(&[15], vec![])),
+        Err(MmapError::ReadOnly)
+    ));
+    assert!(matches!(
+        block_on(backend.delete(&[10])),
+        Err(MmapError::ReadOnly)
+    ));
+    block_on(backend.flush()).unwrap();
+
+    assert_eq!(block_on(backend.get(&[10])).unwrap(), Some(vec![10, 10]));
+    assert_eq!(block_on(backend.get(&[15])).unwrap(), None);
+}
+
+#[test]
+fn reopening_and_empty_files() {
+    let path = temp_path("reopen");
+    drop(unsafe { MmapSortedBackEnd::build_from_sorted(&path, entries()) }.unwrap());
+
+    let reopened = unsafe { MmapSortedBackEnd::<Vec<u8>>::open(&path) }.unwrap();
+    assert_eq!(
+        block_on(reopened.range(Unbounded, Unbounded))
+            .unwrap()
+            .collect::<Vec<_>>(),
+        entries()
+    );
+
+    let empty = unsafe {
+        MmapSortedBackEnd::<Vec<u8>>::build_from_sorted(
+            temp_path("empty"),
+            Vec::<(Vec<u8>, _)>::new(),
+        )
+    }
+    .unwrap();
+    assert!(empty.is_empty());
+    assert_eq!(block_on(empty.find_lte(&[0xff])).unwrap(), None);
+    assert_eq!(block_on(empty.find_gte(&[])).unwrap(), None);
+}
+
+#[test]
+fn truncated_files_are_corrupted() {
+    let path = temp_path("truncated");
+    std::fs::write(&path, [1, 2, 3]).unwrap();
+    assert!(matches!(
+        unsafe { MmapSortedBackEnd::<Vec<u8>>::open(&path) },
+        Err(MmapError::Corrupted(_))
+    ));
+
+    // A record count that the file is too short for.
+    std::fs::write(&path, 5u64.to_le_bytes()).unwrap();
+    assert!(matches!(
+        unsafe { MmapSortedBackEnd::<Vec<u8>>::open(&path) },
+        Err(MmapError::Corrupted(_))
+    ));
+}
+
+#[test]
+#[should_panic(expected = "strictly ascending")]
+fn unsorted_input_panics() {
+    let _ = unsafe {
+        MmapSortedBackEnd::build_from_sorted(
+            temp_path("unsorted"),
+            vec![(vec![2], vec![]), (vec![1], vec![])],
+        )
+    };
+}
+
+#[test]
+fn unsorted_input_leaves_existing_file_intact() {
+    let path = temp_path("unsorted_existing");
+    drop(unsafe { MmapSortedBackEnd::build_from_sorted(&path, entries()) }.unwrap());
+    let before = std::fs::read(&path).unwrap();
+
+    let result = std::panic::catch_unwind(|| {
+        let _ = unsafe {
+            MmapSortedBackEnd::build_from_sorted(&path, vec![(vec![2], vec![]), (vec![1], vec![])])
+        };
+    });
+    assert!(result.is_err());
+
+    assert_eq!(std::fs::read(&path).unwrap(), before);
+    let mut temp = path.clone().into_os_string();
+    temp.push(".tmp");
+    assert!(!PathBuf::from(temp).exists());
+}
@@ -0,0 +1,26 @@
+//! Checks that the core API can be used from a `no_std` crate. Run `cargo test --no-default-features --test no_std` to also build the library itself without `std` and `alloc`.
+#![no_std]
+
+use kv_3d_storage::{Dimension, Point3d, Rev};
+
+#[test]
+fn slice_based_encoding_works_without_std() {
+    let point = Point3d {
+        x: 17u8,
+        y: -3i32,
+        z: 0x0102_0304u64,
+    };
+
+    let mut buf = [0; Point3d::<u8, i32, u64>::max_encoding_len_xyz()];
+    let len = point.encode_xyz(&mut buf);
+    assert_eq!(len, 13);
+    assert_eq!(Point3d::<u8, i32, u64>::decode_xyz(&buf), Ok((point, len)));
+
+    let mut dimension_buf = [0; 4];
+    assert_eq!((-3i32).homomorphic_encode(&mut dimension_buf), 4);
+    assert_eq!(i32::homomorphic_decode(&dimension_buf), Ok((-3, 4)));
+
+    // `Rev` decodes via a stack buffer without `alloc`.
+    assert_eq!(Rev(7u32).homomorphic_encode(&mut dimension_buf), 4);
+    assert_eq!(Rev::<u32>::homomorphic_decode(&dimension_buf), Ok((Rev(7), 4)));
+}
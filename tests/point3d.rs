@@ -0,0 +1,127 @@
+//! Checks that encoding a `Point3d` catches `Dimension` implementations which violate the contracts of the trait. These checks are `debug_assert`s, so the tests only run with debug assertions enabled. Also checks that points round-trip through `io::Write` and `io::Read`.
+
+#[cfg(feature = "std")]
+use std::io::Cursor;
+
+use kv_3d_storage::{DecodeError, Dimension, Point3d};
+
+// A dimension that claims a fixed width of two bytes, but only ever writes one.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug)]
+struct WrongWidth(u8);
+
+impl Dimension for WrongWidth {
+    const HOMOMORPHIC_ENCODING_MAX_LENGTH: usize = 2;
+
+    const IS_FIXED_WIDTH_ENCODING: bool = true;
+
+    fn homomorphic_encode(&self, buf: &mut [u8]) -> usize {
+        buf[0] = self.0;
+        return 1;
+    }
+
+    fn homomorphic_decode(buf: &[u8]) -> Result<(Self, usize), DecodeError> {
+        match buf.first() {
+            Some(byte) => return Ok((WrongWidth(*byte), 1)),
+            None => return Err(DecodeError::UnexpectedEof),
+        }
+    }
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "the y dimension (of type point3d::WrongWidth) claims a fixed-width encoding of 2 bytes")]
+fn encode_xyz_detects_wrong_fixed_width() {
+    let point = Point3d {
+        x: 0u8,
+        y: WrongWidth(1),
+        z: 2u8,
+    };
+
+    let mut buf = [0; Point3d::<u8, WrongWidth, u8>::max_encoding_len_xyz()];
+    point.encode_xyz(&mut buf);
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "the z dimension (of type point3d::WrongWidth) claims a fixed-width encoding of 2 bytes")]
+fn encode_yzx_fixed_detects_wrong_fixed_width() {
+    let point = Point3d {
+        x: 0u8,
+        y: 1u8,
+        z: WrongWidth(2),
+    };
+
+    let mut buf = [0; Point3d::<u8, u8, WrongWidth>::max_encoding_len_yzx()];
+    point.encode_yzx_fixed(&mut buf);
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "the x dimension (of type point3d::WrongWidth) claims a fixed-width encoding of 2 bytes")]
+fn encode_all_detects_wrong_fixed_width() {
+    let point = Point3d {
+        x: WrongWidth(0),
+        y: 1u8,
+        z: 2u8,
+    };
+
+    point.encode_all();
+}
+
+// A variable-width dimension whose encodings are prefix-free: `n` is encoded as `n` bytes `0x02`, followed by a single `0x01`.
+#[cfg(feature = "std")]
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug)]
+struct Terminated(u8);
+
+#[cfg(feature = "std")]
+impl Dimension for Terminated {
+    const HOMOMORPHIC_ENCODING_MAX_LENGTH: usize = 256;
+
+    const IS_FIXED_WIDTH_ENCODING: bool = false;
+
+    const IS_PREFIX_FREE: bool = true;
+
+    fn homomorphic_encode(&self, buf: &mut [u8]) -> usize {
+        let n = self.0 as usize;
+        buf[..n].fill(2);
+        buf[n] = 1;
+        return n + 1;
+    }
+
+    fn homomorphic_decode(buf: &[u8]) -> Result<(Self, usize), DecodeError> {
+        match buf.iter().position(|byte| *byte != 2) {
+            Some(n) if n < 256 && buf[n] == 1 => return Ok((Terminated(n as u8), n + 1)),
+            Some(_) => return Err(DecodeError::InvalidValue),
+            None => return Err(DecodeError::UnexpectedEof),
+        }
+    }
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn streaming_round_trips_through_a_cursor() {
+    let points: Vec<Point3d<Terminated, u16, Terminated>> = (0..1000u32)
+        .map(|i| {
+            Point3d::from((
+                Terminated((i % 7) as u8),
+                (i * 31) as u16,
+                Terminated((i / 4) as u8),
+            ))
+        })
+        .collect();
+
+    let mut cursor = Cursor::new(vec![]);
+    for point in points.iter() {
+        point.encode_xyz_to_writer(&mut cursor).unwrap();
+        point.encode_yzx_to_writer(&mut cursor).unwrap();
+        point.encode_zxy_to_writer(&mut cursor).unwrap();
+    }
+
+    cursor.set_position(0);
+    for point in points.iter() {
+        assert_eq!(&Point3d::decode_xyz_from_reader(&mut cursor).unwrap().0, point);
+        assert_eq!(&Point3d::decode_yzx_from_reader(&mut cursor).unwrap().0, point);
+        assert_eq!(&Point3d::decode_zxy_from_reader(&mut cursor).unwrap().0, point);
+    }
+    assert_eq!(cursor.position(), cursor.get_ref().len() as u64);
+}
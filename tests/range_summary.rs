@@ -0,0 +1,85 @@
+//! Checks that concatenating the summaries of adjacent ranges yields the summary of the whole range.
+
+use core::cmp::Ordering;
+
+use kv_3d_storage::{BoundsMonoid, LiftingCommutativeMonoid, Point3d, RangeSummary};
+
+type Point = Point3d<u8, u8, u8>;
+type Item = (Point, u8);
+
+fn items() -> Vec<Item> {
+    let mut items = vec![];
+    for x in [0u8, 3, 200] {
+        for y in [1u8, 7] {
+            for z in [0u8, 42, 255] {
+                items.push((Point3d { x, y, z }, x ^ z));
+            }
+        }
+    }
+    return items;
+}
+
+// The summary of all items whose points lie in the half-open range from `lo` to `hi` in the xyz ordering.
+fn summarize<M: LiftingCommutativeMonoid<Item>>(items: &[Item], lo: Point, hi: Point) -> RangeSummary<Point, M> {
+    let in_range: Vec<Item> = items
+        .iter()
+        .filter(|(point, _)| point.cmp_xyz(&lo) != Ordering::Less && point.cmp_xyz(&hi) == Ordering::Less)
+        .copied()
+        .collect();
+
+    return RangeSummary {
+        lo,
+        hi,
+        summary: M::lift_all(&in_range),
+    };
+}
+
+fn assert_concat_matches_whole<M>()
+where
+    M: LiftingCommutativeMonoid<Item> + core::fmt::Debug,
+{
+    let items = items();
+    let bounds = [
+        Point3d { x: 0, y: 0, z: 0 },
+        Point3d { x: 0, y: 1, z: 42 },
+        Point3d { x: 3, y: 0, z: 0 },
+        Point3d { x: 3, y: 7, z: 0 },
+        Point3d { x: 200, y: 7, z: 255 },
+        Point3d { x: 255, y: 255, z: 255 },
+    ];
+
+    for lo in 0..bounds.len() {
+        for mid in lo..bounds.len() {
+            for hi in mid..bounds.len() {
+                let left = summarize::<M>(&items, bounds[lo], bounds[mid]);
+                let right = summarize::<M>(&items, bounds[mid], bounds[hi]);
+                let whole = summarize::<M>(&items, bounds[lo], bounds[hi]);
+
+                assert_eq!(left.concat(right), Ok(whole));
+            }
+        }
+    }
+}
+
+#[test]
+fn adjacent_counts_concatenate() {
+    assert_concat_matches_whole::<usize>();
+}
+
+#[test]
+fn adjacent_bounds_concatenate() {
+    assert_concat_matches_whole::<BoundsMonoid<u8, u8, u8>>();
+}
+
+#[test]
+fn non_adjacent_ranges_do_not_concatenate() {
+    let items = items();
+    let a = Point3d { x: 0, y: 0, z: 0 };
+    let b = Point3d { x: 3, y: 0, z: 0 };
+    let c = Point3d { x: 200, y: 0, z: 0 };
+
+    let left = summarize::<usize>(&items, a, b);
+    let right = summarize::<usize>(&items, a, c);
+
+    assert_eq!(left.concat(right), Err((left, right)));
+}
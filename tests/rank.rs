@@ -0,0 +1,100 @@
+//! Checks that `Rank` keeps the reserved `EMPTY_CHILD` byte apart from the ranks of vertices, and that `rank_from_hash` yields ranks with the geometric distribution of a zip-tree.
+
+use kv_3d_storage::{
+    encode_tree_key, ordering_for_rank, rank_from_hash, tree_key_bounds, Point3d, Rank, EMPTY_CHILD,
+};
+
+#[test]
+fn the_sentinel_is_not_a_rank() {
+    assert_eq!(Rank::new(EMPTY_CHILD), None);
+    assert!(Rank::is_sentinel(EMPTY_CHILD));
+
+    for byte in 0..EMPTY_CHILD {
+        let rank = Rank::new(byte).unwrap();
+        assert_eq!(rank.get(), byte);
+        assert_eq!(u8::from(rank), byte);
+        assert!(!Rank::is_sentinel(byte));
+        assert_eq!(rank.ordering(), ordering_for_rank(byte));
+    }
+
+    assert_eq!(Rank::new(0), Some(Rank::MIN));
+    assert_eq!(Rank::new(254), Some(Rank::MAX));
+}
+
+#[test]
+fn arithmetic_stops_at_the_boundaries() {
+    assert_eq!(Rank::MAX.successor(), None);
+    assert_eq!(Rank::MIN.predecessor(), None);
+    assert_eq!(Rank::new(253).unwrap().successor(), Some(Rank::MAX));
+    assert_eq!(Rank::new(1).unwrap().predecessor(), Some(Rank::MIN));
+
+    // The byte after the greatest rank is the sentinel, rather than a wrapped-around zero.
+    assert_eq!(Rank::MAX.next_byte(), EMPTY_CHILD);
+    assert_eq!(Rank::MIN.next_byte(), 1);
+}
+
+#[test]
+fn child_ranks() {
+    let rank = Rank::new(7).unwrap();
+    let lower = rank.predecessor().unwrap();
+    let higher = rank.successor().unwrap();
+
+    assert!(rank.admits_left_child(lower));
+    assert!(!rank.admits_left_child(rank));
+    assert!(!rank.admits_left_child(higher));
+
+    assert!(rank.admits_right_child(lower));
+    assert!(rank.admits_right_child(rank));
+    assert!(!rank.admits_right_child(higher));
+
+    assert!(!Rank::MIN.admits_left_child(Rank::MIN));
+    assert!(Rank::MAX.admits_right_child(Rank::MAX));
+}
+
+#[test]
+fn ranks_of_keys() {
+    let point = Point3d::from((1u8, 2u8, 3u8));
+
+    assert_eq!(Rank::of_key(&encode_tree_key(Rank::new(254).unwrap(), &point)), Some(Rank::MAX));
+    assert_eq!(Rank::of_key(&[EMPTY_CHILD, 1, 2, 3]), None);
+    assert_eq!(Rank::of_key(&[]), None);
+}
+
+#[test]
+fn bounds_of_the_greatest_rank() {
+    let point = Point3d::from((1u8, 2u8, 3u8));
+
+    // The upper bound of the greatest rank is the sentinel key, which exceeds the keys of all vertices.
+    let (lower, upper) = tree_key_bounds(Rank::MAX, &point, None);
+    assert_eq!(upper, vec![EMPTY_CHILD]);
+    assert!(lower < upper);
+    assert!(encode_tree_key(Rank::MAX, &Point3d::from((255u8, 255u8, 255u8))) < upper);
+}
+
+#[test]
+fn hashed_ranks_are_geometrically_distributed() {
+    const POINTS: u32 = 100_000;
+
+    let mut counts = [0u32; 5];
+    for i in 0..POINTS {
+        let point = Point3d::from((i, (i % 7) as u8, (i % 11) as u8));
+        let rank = rank_from_hash(&point, &());
+        if let Some(count) = counts.get_mut(rank as usize) {
+            *count += 1;
+        }
+    }
+
+    // Each rank k occurs with probability 2^-(k+1); allow five standard deviations of the binomial distribution.
+    for (k, count) in counts.iter().enumerate() {
+        let p = 0.5f64.powi(k as i32 + 1);
+        let expected = POINTS as f64 * p;
+        let deviation = (POINTS as f64 * p * (1.0 - p)).sqrt();
+        assert!(
+            (*count as f64 - expected).abs() <= 5.0 * deviation,
+            "rank {} occurred {} times, expected about {}",
+            k,
+            count,
+            expected
+        );
+    }
+}
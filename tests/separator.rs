@@ -0,0 +1,149 @@
+//! Checks that `Point3d` encodings with a non-default `Separator` round-trip and stay homomorphic.
+
+use kv_3d_storage::{DecodeError, Dimension, Point3d, Separator, ZeroSeparator};
+
+/// A variable-width dimension of up to four non-zero `u16`s. Each is encoded as two big-endian bytes, and the encoding is terminated by two zero bytes. So encodings naturally contain two consecutive zero bytes (e.g., `[0x0100, 0x0001]` is encoded as `01 00 00 01 00 00`), but no encoding is a strict prefix of another one.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Debug)]
+struct Words(Vec<u16>);
+
+impl Dimension for Words {
+    const HOMOMORPHIC_ENCODING_MAX_LENGTH: usize = 2 * 4 + 2;
+
+    const IS_FIXED_WIDTH_ENCODING: bool = false;
+
+    fn homomorphic_encode(&self, buf: &mut [u8]) -> usize {
+        let mut len = 0;
+        for word in self.0.iter() {
+            buf[len..len + 2].copy_from_slice(&word.to_be_bytes());
+            len += 2;
+        }
+        buf[len..len + 2].copy_from_slice(&[0, 0]);
+        return len + 2;
+    }
+
+    fn homomorphic_decode(buf: &[u8]) -> Result<(Self, usize), DecodeError> {
+        let mut words = vec![];
+
+        for chunk in buf.chunks(2) {
+            let word = match chunk {
+                [hi, lo] => u16::from_be_bytes([*hi, *lo]),
+                _ => return Err(DecodeError::UnexpectedEof),
+            };
+
+            if word == 0 {
+                let len = 2 * words.len() + 2;
+                return Ok((Words(words), len));
+            } else if words.len() == 4 {
+                return Err(DecodeError::InvalidValue);
+            } else {
+                words.push(word);
+            }
+        }
+
+        return Err(DecodeError::UnexpectedEof);
+    }
+}
+
+/// A separator other than the default one. Since no encoding of `Words` is a strict prefix of another one, any separator keeps the encodings of points homomorphic.
+struct OneSeparator;
+
+impl Separator for OneSeparator {
+    const BYTES: [u8; 2] = [0x00, 0x01];
+}
+
+type P = Point3d<Words, u8, Words>;
+
+fn samples() -> Vec<P> {
+    let words = [
+        vec![],
+        vec![1],
+        vec![0x0100],
+        vec![0x0100, 0x0001],
+        vec![0x0100, 0x0100, 0x0100, 0x0100],
+        vec![0xffff],
+        vec![1, 2, 3],
+    ];
+
+    let mut points = vec![];
+    for x in words.iter() {
+        for y in [0, 7] {
+            for z in words.iter().step_by(2) {
+                points.push(Point3d::from((Words(x.clone()), y, Words(z.clone()))));
+            }
+        }
+    }
+    return points;
+}
+
+fn encode<S: Separator>(point: &P) -> Vec<u8> {
+    let mut buf = vec![0; P::max_encoding_len_xyz()];
+    let len = point.encode_xyz_with_separator::<S>(&mut buf);
+    buf.truncate(len);
+    return buf;
+}
+
+#[test]
+fn non_default_separator_round_trips() {
+    for point in samples() {
+        let enc = encode::<OneSeparator>(&point);
+        assert_eq!(enc.len(), point.encoded_len_xyz());
+
+        // The separator follows the encoding of the x dimension.
+        let x_len = point.x.encoded_len();
+        assert_eq!(enc[x_len..x_len + 2], OneSeparator::BYTES);
+
+        assert_eq!(
+            P::decode_xyz_with_separator::<OneSeparator>(&enc),
+            Ok((point.clone(), enc.len()))
+        );
+    }
+}
+
+#[test]
+fn non_default_separator_is_homomorphic() {
+    for p in samples() {
+        for q in samples() {
+            assert_eq!(
+                encode::<OneSeparator>(&p).cmp(&encode::<OneSeparator>(&q)),
+                p.cmp_xyz(&q),
+                "{:?} {:?}",
+                p,
+                q
+            );
+        }
+    }
+}
+
+#[test]
+fn separators_must_match() {
+    let point = Point3d::from((Words(vec![0x0100]), 3, Words(vec![])));
+
+    let enc = encode::<OneSeparator>(&point);
+    assert_eq!(P::decode_xyz(&enc), Err(DecodeError::BadSeparator));
+
+    let enc = encode::<ZeroSeparator>(&point);
+    assert_eq!(
+        P::decode_xyz_with_separator::<OneSeparator>(&enc),
+        Err(DecodeError::BadSeparator)
+    );
+
+    // The zero separator is the default one.
+    let mut buf = vec![0; P::max_encoding_len_xyz()];
+    let len = point.encode_xyz(&mut buf);
+    assert_eq!(&buf[..len], &enc[..]);
+}
+
+#[test]
+fn fixed_width_points_ignore_the_separator() {
+    let point = Point3d::<u8, u16, Words>::from((1, 2, Words(vec![3])));
+    let mut with = vec![0; Point3d::<u8, u16, Words>::max_encoding_len_xyz()];
+    let mut without = with.clone();
+
+    let len = point.encode_xyz_with_separator::<OneSeparator>(&mut with);
+    assert_eq!(point.encode_xyz(&mut without), len);
+    assert_eq!(with, without);
+    assert_eq!(
+        Point3d::<u8, u16, Words>::decode_xyz_with_separator::<OneSeparator>(&with[..len]),
+        Ok((point, len))
+    );
+}
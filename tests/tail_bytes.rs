@@ -0,0 +1,91 @@
+//! Checks that `TailBytes` yields order-homomorphic xyz encodings when used as the last dimension.
+
+use kv_3d_storage::{Point3d, TailBytes};
+
+type Point = Point3d<u8, u16, TailBytes>;
+
+fn tails() -> Vec<TailBytes> {
+    return vec![
+        TailBytes(vec![]),
+        TailBytes(vec![0]),
+        TailBytes(vec![0, 0]),
+        TailBytes(vec![0, 0, 0]),
+        TailBytes(vec![0, 0, 1]),
+        TailBytes(vec![0, 1]),
+        TailBytes(vec![1]),
+        TailBytes(vec![1, 0, 0, 2]),
+        TailBytes(vec![1, 0, 0, 2, 0]),
+        TailBytes(vec![0xff, 0xff]),
+        TailBytes(vec![0x42; 256]),
+    ];
+}
+
+fn points() -> Vec<Point> {
+    let mut points = vec![];
+
+    for x in [0, 1, 0xff] {
+        for y in [0, 0xff, 0x100] {
+            for z in tails() {
+                points.push(Point3d { x, y, z });
+            }
+        }
+    }
+
+    return points;
+}
+
+fn encode_xyz(point: &Point) -> Vec<u8> {
+    let mut buf = vec![0; Point::max_encoding_len_xyz()];
+    let len = point.encode_xyz(&mut buf);
+    buf.truncate(len);
+    return buf;
+}
+
+#[test]
+fn xyz_encodings_compare_like_points() {
+    let points = points();
+
+    for p1 in &points {
+        for p2 in &points {
+            assert_eq!(
+                p1.cmp_xyz(p2),
+                encode_xyz(p1).cmp(&encode_xyz(p2)),
+                "{:?} {:?}",
+                p1,
+                p2
+            );
+        }
+    }
+}
+
+#[test]
+fn xyz_encodings_round_trip() {
+    for point in points() {
+        let enc = encode_xyz(&point);
+        assert_eq!(enc.len(), point.encoded_len_xyz());
+        assert_eq!(Point::decode_xyz_exact(&enc), Ok(point));
+    }
+}
+
+#[test]
+fn tail_bytes_are_encoded_raw() {
+    let point = Point3d {
+        x: 7u8,
+        y: 0x0102u16,
+        z: TailBytes(vec![0, 0, 3]),
+    };
+    assert_eq!(encode_xyz(&point), vec![7, 1, 2, 0, 0, 3]);
+}
+
+#[test]
+#[should_panic(expected = "must only be the last dimension")]
+#[cfg(debug_assertions)]
+fn tail_bytes_in_yzx_order_panics() {
+    let point = Point3d {
+        x: 0u8,
+        y: 0u16,
+        z: TailBytes(vec![1, 2]),
+    };
+    let mut buf = vec![0; Point::max_encoding_len_yzx()];
+    point.encode_yzx(&mut buf);
+}
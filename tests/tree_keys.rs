@@ -0,0 +1,94 @@
+//! Checks that `tree_key_bounds` selects exactly the kv-tree vertices of a rank whose points lie within the given bounds.
+
+use std::collections::BTreeMap;
+use std::ops::Bound::{Excluded, Included};
+
+use pollster::block_on;
+
+use kv_3d_storage::{
+    decode_tree_key, encode_tree_key, tree_key_bounds, BTreeMapBackEnd, BackEnd, DecodeError,
+    Point3d, Rank, EMPTY_CHILD,
+};
+
+type P = Point3d<u8, u8, u16>;
+
+fn points() -> Vec<P> {
+    return (0..120u16)
+        .map(|i| Point3d::from(((i % 7) as u8, (i % 4) as u8, i / 5)))
+        .collect();
+}
+
+fn ranks() -> impl Iterator<Item = Rank> {
+    return (0..4).map(|rank| Rank::new(rank).unwrap());
+}
+
+// Store every point at ranks 0 to 3, so that the scans must exclude the neighbouring ranks.
+fn backend() -> BTreeMapBackEnd<()> {
+    let mut map = BTreeMap::new();
+    for rank in ranks() {
+        for point in points() {
+            map.insert(encode_tree_key(rank, &point), ());
+        }
+    }
+
+    return BTreeMapBackEnd::from(map);
+}
+
+fn scan(backend: &BTreeMapBackEnd<()>, rank: Rank, lo: &P, hi: Option<&P>) -> Vec<P> {
+    let (lower, upper) = tree_key_bounds(rank, lo, hi);
+    let entries = block_on(backend.range(Included(&lower[..]), Excluded(&upper[..]))).unwrap();
+
+    return entries
+        .map(|(key, ())| {
+            let (key_rank, point) = decode_tree_key(&key).unwrap();
+            assert_eq!(key_rank, rank);
+            return point;
+        })
+        .collect();
+}
+
+fn expected(rank: Rank, lo: &P, hi: Option<&P>) -> Vec<P> {
+    let ordering = rank.ordering();
+    let mut selected: Vec<P> = points()
+        .into_iter()
+        .filter(|point| point.cmp_by(lo, ordering).is_ge())
+        .filter(|point| hi.is_none_or(|hi| point.cmp_by(hi, ordering).is_lt()))
+        .collect();
+    selected.sort_by(|a, b| a.cmp_by(b, ordering));
+
+    return selected;
+}
+
+#[test]
+fn bounds_select_exactly_the_points_in_range() {
+    let backend = backend();
+    let samples: Vec<P> = points().into_iter().step_by(11).collect();
+
+    for rank in ranks() {
+        for lo in samples.iter() {
+            for hi in samples.iter() {
+                assert_eq!(scan(&backend, rank, lo, Some(hi)), expected(rank, lo, Some(hi)));
+            }
+
+            assert_eq!(scan(&backend, rank, lo, None), expected(rank, lo, None));
+        }
+    }
+}
+
+#[test]
+fn unbounded_scan_covers_the_whole_rank() {
+    let backend = backend();
+    let least = Point3d::min();
+
+    for rank in ranks() {
+        assert_eq!(scan(&backend, rank, &least, None).len(), points().len());
+    }
+}
+
+#[test]
+fn keys_with_the_reserved_rank_do_not_decode() {
+    let mut key = encode_tree_key(Rank::MAX, &Point3d::from((0u8, 0u8, 0u16)));
+    key[0] = EMPTY_CHILD;
+
+    assert_eq!(decode_tree_key::<u8, u8, u16>(&key), Err(DecodeError::InvalidValue));
+}
@@ -0,0 +1,69 @@
+//! Checks decoding points via the `TryFrom<&[u8]>` implementations of the ordering newtypes.
+
+use kv_3d_storage::{DecodeError, Point3d, Xyz, Yzx, Zxy};
+
+type P = Point3d<u8, i16, u32>;
+
+fn points() -> Vec<P> {
+    return vec![
+        Point3d::from((0, 0, 0)),
+        Point3d::from((1, -2, 3)),
+        Point3d::from((u8::MAX, i16::MIN, u32::MAX)),
+    ];
+}
+
+#[test]
+fn try_from_matches_decoders() {
+    for point in points() {
+        let mut buf = [0; P::max_encoding_len_xyz()];
+
+        let len = point.encode_xyz(&mut buf);
+        assert_eq!(Xyz::try_from(&buf[..len]), Ok(Xyz(point)));
+        assert_eq!(Point3d::decode_xyz(&buf[..len]), Ok((point, len)));
+
+        let len = point.encode_yzx(&mut buf);
+        assert_eq!(Yzx::try_from(&buf[..len]), Ok(Yzx(point)));
+        assert_eq!(Point3d::decode_yzx(&buf[..len]), Ok((point, len)));
+
+        let len = point.encode_zxy(&mut buf);
+        assert_eq!(Zxy::try_from(&buf[..len]), Ok(Zxy(point)));
+        assert_eq!(Point3d::decode_zxy(&buf[..len]), Ok((point, len)));
+    }
+}
+
+#[test]
+fn try_from_rejects_trailing_and_missing_bytes() {
+    let point: P = Point3d::from((1, -2, 3));
+    let mut buf = [0; P::max_encoding_len_xyz() + 1];
+    let len = point.encode_xyz(&mut buf);
+
+    assert_eq!(Xyz::<u8, i16, u32>::try_from(&buf[..len + 1]), Err(DecodeError::TrailingBytes));
+    assert_eq!(Xyz::<u8, i16, u32>::try_from(&buf[..len - 1]), Err(DecodeError::UnexpectedEof));
+}
+
+#[test]
+fn try_from_collects() {
+    let encodings: Vec<Vec<u8>> = points()
+        .iter()
+        .map(|point| {
+            let mut buf = vec![0; P::max_encoding_len_xyz()];
+            let len = point.encode_xyz(&mut buf);
+            buf.truncate(len);
+            buf
+        })
+        .collect();
+
+    let decoded: Result<Vec<Xyz<u8, i16, u32>>, DecodeError> = encodings
+        .iter()
+        .map(|encoding| Xyz::try_from(encoding.as_slice()))
+        .collect();
+    assert_eq!(decoded, Ok(points().into_iter().map(Xyz).collect()));
+
+    let mut corrupted = encodings.clone();
+    corrupted[1].push(0);
+    let decoded: Result<Vec<Xyz<u8, i16, u32>>, DecodeError> = corrupted
+        .iter()
+        .map(|encoding| Xyz::try_from(encoding.as_slice()))
+        .collect();
+    assert_eq!(decoded, Err(DecodeError::TrailingBytes));
+}
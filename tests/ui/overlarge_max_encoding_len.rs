@@ -0,0 +1,26 @@
+use kv_3d_storage::{DecodeError, Dimension, Point3d};
+
+// A variable-width dimension that claims encodings of up to `usize::MAX` bytes.
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+struct Huge;
+
+impl Dimension for Huge {
+    const HOMOMORPHIC_ENCODING_MAX_LENGTH: usize = usize::MAX;
+
+    const IS_FIXED_WIDTH_ENCODING: bool = false;
+
+    fn homomorphic_encode(&self, buf: &mut [u8]) -> usize {
+        buf[0] = 1;
+        return 1;
+    }
+
+    fn homomorphic_decode(_buf: &[u8]) -> Result<(Self, usize), DecodeError> {
+        return Ok((Huge, 1));
+    }
+}
+
+const LEN: usize = Point3d::<u8, Huge, u8>::max_encoding_len_xyz();
+
+fn main() {
+    println!("{}", LEN);
+}
@@ -0,0 +1,26 @@
+use kv_3d_storage::{check_dimension, DecodeError, Dimension};
+
+// A variable-width dimension that claims all its encodings are empty.
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+struct Nothing(u8);
+
+impl Dimension for Nothing {
+    const HOMOMORPHIC_ENCODING_MAX_LENGTH: usize = 0;
+
+    const IS_FIXED_WIDTH_ENCODING: bool = false;
+
+    fn homomorphic_encode(&self, _buf: &mut [u8]) -> usize {
+        return 0;
+    }
+
+    fn homomorphic_decode(_buf: &[u8]) -> Result<(Self, usize), DecodeError> {
+        return Ok((Nothing(0), 0));
+    }
+}
+
+check_dimension!(Nothing);
+
+// The unit dimension has empty encodings as well, but they are fixed-width.
+check_dimension!(());
+
+fn main() {}
@@ -0,0 +1,68 @@
+//! Checks the `Dimension` implementation for `Uuid`. Requires the `testing` and `uuid` features: run `cargo test --features testing,uuid --test uuid`.
+
+use kv_3d_storage::{dimension_conformance_tests, DecodeError, Dimension};
+use uuid::Uuid;
+
+const MAX: Uuid = Uuid::from_bytes([0xff; 16]);
+
+dimension_conformance_tests!(
+    uuid: Uuid,
+    [
+        Uuid::nil(),
+        Uuid::from_u128(1),
+        Uuid::from_u128(0x0100_0000_0000_0000_0000_0000_0000_0000),
+        Uuid::from_u128(0x67e5_5044_10b1_426f_9247_bb68_0e5f_e0c8),
+        MAX,
+    ]
+);
+
+// A deterministic stream of pseudo-random UUIDs (splitmix64).
+fn pseudo_random_uuids(count: usize) -> Vec<Uuid> {
+    let mut state: u64 = 0x2545_f491_4f6c_dd1d;
+    let mut next = move || {
+        state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        return z ^ (z >> 31);
+    };
+
+    return (0..count)
+        .map(|_| Uuid::from_u64_pair(next(), next()))
+        .collect();
+}
+
+fn encode(uuid: &Uuid) -> [u8; 16] {
+    let mut buf = [0; 16];
+    assert_eq!(uuid.homomorphic_encode(&mut buf), 16);
+    return buf;
+}
+
+#[test]
+fn uuid_encoding_preserves_order() {
+    let mut uuids = pseudo_random_uuids(200);
+    uuids.push(Uuid::nil());
+    uuids.push(MAX);
+
+    for a in uuids.iter() {
+        for b in uuids.iter() {
+            assert_eq!(a.cmp(b), encode(a).cmp(&encode(b)), "{} vs {}", a, b);
+        }
+    }
+}
+
+#[test]
+fn uuid_decode_rejects_short_buffers() {
+    let encoding = encode(&Uuid::from_u128(0x67e5_5044_10b1_426f_9247_bb68_0e5f_e0c8));
+
+    for len in 0..16 {
+        assert_eq!(Uuid::homomorphic_decode(&encoding[..len]), Err(DecodeError::UnexpectedEof));
+    }
+    assert_eq!(
+        Uuid::homomorphic_decode(&[encoding.as_slice(), &[7]].concat()),
+        Ok((
+            Uuid::from_u128(0x67e5_5044_10b1_426f_9247_bb68_0e5f_e0c8),
+            16
+        ))
+    );
+}
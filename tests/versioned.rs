@@ -0,0 +1,42 @@
+//! Checks the versioned xyz encoding of points.
+
+use kv_3d_storage::{DecodeError, Point3d, VersionedDecodeError, POINT_ENCODING_VERSION};
+
+type P = Point3d<u8, i16, u32>;
+
+#[test]
+fn matching_version_decodes() {
+    let point: P = Point3d::from((7, -300, 123_456));
+    let mut buf = [0; P::max_encoding_len_xyz_versioned() + 2];
+
+    let len = point.encode_xyz_versioned(POINT_ENCODING_VERSION, &mut buf);
+    assert_eq!(buf[0], POINT_ENCODING_VERSION);
+    assert_eq!(len, 1 + point.encoded_len_xyz());
+
+    // Trailing bytes are not part of the encoding.
+    assert_eq!(P::decode_xyz_versioned(&buf), Ok((point, len)));
+}
+
+#[test]
+fn mismatching_version_is_rejected() {
+    let point: P = Point3d::from((7, -300, 123_456));
+    let mut buf = [0; P::max_encoding_len_xyz_versioned()];
+
+    let len = point.encode_xyz_versioned(POINT_ENCODING_VERSION + 1, &mut buf);
+    assert_eq!(
+        P::decode_xyz_versioned(&buf[..len]),
+        Err(VersionedDecodeError::UnsupportedVersion(POINT_ENCODING_VERSION + 1))
+    );
+}
+
+#[test]
+fn invalid_encodings_are_rejected() {
+    assert_eq!(
+        P::decode_xyz_versioned(&[]),
+        Err(VersionedDecodeError::Invalid(DecodeError::UnexpectedEof))
+    );
+    assert_eq!(
+        P::decode_xyz_versioned(&[POINT_ENCODING_VERSION, 1, 2]),
+        Err(VersionedDecodeError::Invalid(DecodeError::UnexpectedEof))
+    );
+}